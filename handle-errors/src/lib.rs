@@ -5,7 +5,7 @@ use tracing::{event, instrument, Level};
 use warp::{
     filters::{body::BodyDeserializeError, cors::CorsForbidden},
     http::StatusCode,
-    reject::Reject,
+    reject::{LengthRequired, PayloadTooLarge, Reject},
     Rejection, Reply,
 };
 
@@ -13,6 +13,7 @@ use warp::{
 pub enum Error {
     ParseError(std::num::ParseIntError),
     MissingParameters,
+    InvalidParameter(String),
     WrongPassword,
     CannotDecryptToken,
     Unauthorized,
@@ -24,6 +25,20 @@ pub enum Error {
     ClientError(APILayerError),
     ServerError(APILayerError),
     EnvironmentError(std::env::VarError),
+    QuestionHasAnswers(i64),
+    InvalidTag(String),
+    TooManyTags(usize),
+    TagInUse(String),
+    SelfVote,
+    InvalidComment(usize),
+    NotFound,
+    PreconditionFailed,
+    InvalidDump(String),
+    InvalidRefreshToken,
+    InvalidKeyring(String),
+    InvalidVerificationToken,
+    EmailNotVerified,
+    MailError(String),
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +60,7 @@ impl std::fmt::Display for Error {
                 write!(f, "Cannot parse parameter: {}", err)
             }
             Error::MissingParameters => write!(f, "Missing parameter"),
+            Error::InvalidParameter(name) => write!(f, "Invalid parameter: {}", name),
             Error::WrongPassword => write!(f, "Wrong password"),
             Error::CannotDecryptToken => write!(f, "Cannot decrypt error"),
             Error::Unauthorized => write!(f, "No permission to change the underlying resource"),
@@ -70,15 +86,57 @@ impl std::fmt::Display for Error {
             Error::EnvironmentError(err) => {
                 write!(f, "Environment variable error: {}", err)
             }
+            Error::QuestionHasAnswers(count) => {
+                write!(
+                    f,
+                    "Question has {} answer(s); pass cascade=true to delete them as well",
+                    count
+                )
+            }
+            Error::InvalidTag(tag) => write!(
+                f,
+                "Invalid tag: {:?}; tags may contain letters, digits, spaces and + # . -",
+                tag
+            ),
+            Error::TooManyTags(max) => write!(f, "A question can have at most {} tags", max),
+            Error::TagInUse(tag) => write!(
+                f,
+                "Tag {} is used by questions and cannot become a synonym",
+                tag
+            ),
+            Error::SelfVote => write!(f, "Cannot vote on your own post"),
+            Error::NotFound => write!(f, "Resource not found"),
+            Error::PreconditionFailed => {
+                write!(f, "Resource was changed since it was read")
+            }
+            Error::InvalidComment(max) => {
+                write!(f, "A comment must have between 1 and {} characters", max)
+            }
+            Error::InvalidDump(reason) => write!(f, "Cannot read data dump: {}", reason),
+            Error::InvalidRefreshToken => {
+                write!(f, "Refresh token is invalid, expired or revoked")
+            }
+            Error::InvalidKeyring(reason) => write!(f, "Cannot load PASETO keys: {}", reason),
+            Error::InvalidVerificationToken => {
+                write!(f, "Verification token is invalid, expired or already used")
+            }
+            Error::EmailNotVerified => {
+                write!(f, "Verify your email address before posting")
+            }
+            Error::MailError(reason) => write!(f, "Cannot send email: {}", reason),
         }
     }
 }
 
-const DUPLICATE_KEY: u32 = 23505;
-
 impl Reject for Error {}
 impl Reject for APILayerError {}
 
+// The email is the primary key of the accounts in PostgreSQL; SQLite has a
+// UNIQUE column instead and names it in the message only.
+fn is_account_email(err: &dyn sqlx::error::DatabaseError) -> bool {
+    err.constraint() == Some("accounts_pkey") || err.message().contains("accounts.email")
+}
+
 #[instrument]
 pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
     if let Some(crate::Error::DatabaseQueryError(e)) = r.find() {
@@ -86,9 +144,14 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
 
         match e {
             sqlx::Error::Database(err) => {
-                if err.code().unwrap().parse::<u32>().unwrap() == DUPLICATE_KEY {
+                if err.is_unique_violation() {
+                    let message = if is_account_email(err.as_ref()) {
+                        "Account already exists"
+                    } else {
+                        "Resource already exists"
+                    };
                     Ok(warp::reply::with_status(
-                        "Account already exsists".to_string(),
+                        message.to_string(),
                         StatusCode::UNPROCESSABLE_ENTITY,
                     ))
                 } else {
@@ -115,12 +178,63 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "No permission to change underlying resource".to_string(),
             StatusCode::UNAUTHORIZED,
         ))
+    } else if let Some(
+        error @ (crate::Error::QuestionHasAnswers(_) | crate::Error::TagInUse(_)),
+    ) = r.find()
+    {
+        event!(Level::WARN, "{}", error);
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            StatusCode::CONFLICT,
+        ))
+    } else if let Some(crate::Error::NotFound) = r.find() {
+        event!(Level::WARN, "Requested resource was not found");
+        Ok(warp::reply::with_status(
+            "Resource not found".to_string(),
+            StatusCode::NOT_FOUND,
+        ))
+    } else if let Some(crate::Error::PreconditionFailed) = r.find() {
+        event!(Level::WARN, "If-Match does not match the current version");
+        Ok(warp::reply::with_status(
+            "Resource was changed since it was read".to_string(),
+            StatusCode::PRECONDITION_FAILED,
+        ))
+    } else if let Some(crate::Error::SelfVote) = r.find() {
+        event!(Level::WARN, "Vote on own post");
+        Ok(warp::reply::with_status(
+            "Cannot vote on your own post".to_string(),
+            StatusCode::FORBIDDEN,
+        ))
     } else if let Some(crate::Error::WrongPassword) = r.find() {
         event!(Level::ERROR, "Entered wrong password");
         Ok(warp::reply::with_status(
             "Wrong E-Mail/Password combination".to_string(),
             StatusCode::UNAUTHORIZED,
         ))
+    } else if let Some(crate::Error::InvalidRefreshToken) = r.find() {
+        event!(Level::WARN, "Invalid refresh token");
+        Ok(warp::reply::with_status(
+            "Refresh token is invalid, expired or revoked".to_string(),
+            StatusCode::UNAUTHORIZED,
+        ))
+    } else if let Some(crate::Error::InvalidVerificationToken) = r.find() {
+        event!(Level::WARN, "Invalid verification token");
+        Ok(warp::reply::with_status(
+            "Verification token is invalid, expired or already used".to_string(),
+            StatusCode::BAD_REQUEST,
+        ))
+    } else if let Some(crate::Error::EmailNotVerified) = r.find() {
+        event!(Level::WARN, "Post from an account with an unverified email");
+        Ok(warp::reply::with_status(
+            "Verify your email address before posting".to_string(),
+            StatusCode::FORBIDDEN,
+        ))
+    } else if let Some(crate::Error::MailError(e)) = r.find() {
+        event!(Level::ERROR, "Cannot send email: {}", e);
+        Ok(warp::reply::with_status(
+            "Internal Server Error".to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ))
     } else if let Some(crate::Error::MiddlewareReqwestAPIError(e)) = r.find() {
         event!(Level::ERROR, "{}", e);
         Ok(warp::reply::with_status(
@@ -145,6 +259,18 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             error.to_string(),
             StatusCode::FORBIDDEN,
         ))
+    } else if let Some(error) = r.find::<PayloadTooLarge>() {
+        event!(Level::WARN, "{}", error);
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ))
+    } else if let Some(error) = r.find::<LengthRequired>() {
+        event!(Level::WARN, "{}", error);
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            StatusCode::LENGTH_REQUIRED,
+        ))
     } else if let Some(error) = r.find::<BodyDeserializeError>() {
         event!(Level::ERROR, "Cannot deserizalize request body: {}", error);
         Ok(warp::reply::with_status(
@@ -302,6 +428,62 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_return_error_question_has_answers() {
+        let rejection = reject::custom(Error::QuestionHasAnswers(3));
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_return_error_tag_in_use() {
+        let rejection = reject::custom(Error::TagInUse("rustlang".to_string()));
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_return_error_not_found() {
+        let rejection = reject::custom(Error::NotFound);
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_return_error_precondition_failed() {
+        let rejection = reject::custom(Error::PreconditionFailed);
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn test_return_error_self_vote() {
+        let rejection = reject::custom(Error::SelfVote);
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_return_error_invalid_refresh_token() {
+        let rejection = reject::custom(Error::InvalidRefreshToken);
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_return_error_invalid_verification_token() {
+        let rejection = reject::custom(Error::InvalidVerificationToken);
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_return_error_email_not_verified() {
+        let rejection = reject::custom(Error::EmailNotVerified);
+        let response = return_error(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
     #[test]
     fn test_error_display() {
         let parse_error = "abc".parse::<i32>().unwrap_err();
@@ -319,6 +501,9 @@ mod tests {
 
         let error = Error::Unauthorized;
         assert_eq!(error.to_string(), "No permission to change the underlying resource");
+
+        let error = Error::InvalidParameter("cascade".to_string());
+        assert_eq!(error.to_string(), "Invalid parameter: cascade");
     }
 
     #[test]
@@ -0,0 +1,5 @@
+-- Restore the plain foreign key on answers.corresponding_question
+ALTER TABLE answers
+    DROP CONSTRAINT IF EXISTS answers_corresponding_question_fkey,
+    ADD CONSTRAINT answers_corresponding_question_fkey
+        FOREIGN KEY (corresponding_question) REFERENCES questions;
@@ -0,0 +1,5 @@
+-- Deleting a question deletes its answers instead of failing on the foreign key
+ALTER TABLE answers
+    DROP CONSTRAINT IF EXISTS answers_corresponding_question_fkey,
+    ADD CONSTRAINT answers_corresponding_question_fkey
+        FOREIGN KEY (corresponding_question) REFERENCES questions ON DELETE CASCADE;
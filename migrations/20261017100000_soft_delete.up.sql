@@ -0,0 +1,3 @@
+-- Deleted questions and answers are kept until purged
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE answers ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
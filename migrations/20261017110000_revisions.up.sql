@@ -0,0 +1,18 @@
+-- The state of a question or answer before each edit
+CREATE TABLE IF NOT EXISTS question_revisions (
+    id serial PRIMARY KEY,
+    question_id integer NOT NULL REFERENCES questions ON DELETE CASCADE,
+    title VARCHAR (255) NOT NULL,
+    content TEXT NOT NULL,
+    tags TEXT [],
+    account_id integer NOT NULL,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS answer_revisions (
+    id serial PRIMARY KEY,
+    answer_id integer NOT NULL REFERENCES answers ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    account_id integer NOT NULL,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS answers_search_idx;
+DROP INDEX IF EXISTS questions_search_idx;
+ALTER TABLE answers DROP COLUMN IF EXISTS search;
+ALTER TABLE questions DROP COLUMN IF EXISTS search;
@@ -0,0 +1,11 @@
+-- Search vectors kept up to date by Postgres, titles weigh more than contents
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS search tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', title), 'A') ||
+        setweight(to_tsvector('english', content), 'B')
+    ) STORED;
+ALTER TABLE answers ADD COLUMN IF NOT EXISTS search tsvector
+    GENERATED ALWAYS AS (setweight(to_tsvector('english', content), 'B')) STORED;
+
+CREATE INDEX IF NOT EXISTS questions_search_idx ON questions USING GIN (search);
+CREATE INDEX IF NOT EXISTS answers_search_idx ON answers USING GIN (search);
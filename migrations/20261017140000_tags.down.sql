@@ -0,0 +1,4 @@
+-- `questions.tags` keeps the normalized slugs
+DROP TABLE IF EXISTS tag_synonyms;
+DROP TABLE IF EXISTS question_tags;
+DROP TABLE IF EXISTS tags;
@@ -0,0 +1,42 @@
+-- Canonical tags, the questions using them and synonyms mapping onto them.
+-- `questions.tags` keeps holding the canonical slugs of each question.
+CREATE TABLE IF NOT EXISTS tags (
+    id serial PRIMARY KEY,
+    slug VARCHAR (35) NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS question_tags (
+    question_id integer NOT NULL REFERENCES questions ON DELETE CASCADE,
+    tag_id integer NOT NULL REFERENCES tags ON DELETE CASCADE,
+    PRIMARY KEY (question_id, tag_id)
+);
+CREATE INDEX IF NOT EXISTS question_tags_tag_id_idx ON question_tags (tag_id);
+
+CREATE TABLE IF NOT EXISTS tag_synonyms (
+    slug VARCHAR (35) PRIMARY KEY,
+    tag_id integer NOT NULL REFERENCES tags ON DELETE CASCADE
+);
+
+-- Normalize the existing tags the way new ones are: trimmed, lowercase,
+-- inner whitespace as `-`, without duplicates. Tags that cannot be
+-- normalized are dropped.
+UPDATE questions SET tags = ARRAY(
+    SELECT slug FROM (
+        SELECT lower(regexp_replace(trim(tag), '\s+', '-', 'g')) AS slug, ord
+        FROM unnest(questions.tags) WITH ORDINALITY AS t(tag, ord)
+    ) normalized
+    WHERE slug ~ '^[[:alnum:]+#.-]{1,35}$'
+    GROUP BY slug
+    ORDER BY min(ord)
+)
+WHERE tags IS NOT NULL;
+
+INSERT INTO tags (slug)
+SELECT DISTINCT unnest(tags) FROM questions
+ON CONFLICT (slug) DO NOTHING;
+
+INSERT INTO question_tags (question_id, tag_id)
+SELECT q.id, t.id
+FROM questions q CROSS JOIN LATERAL unnest(q.tags) AS s(slug)
+JOIN tags t ON t.slug = s.slug
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,5 @@
+-- Votes and scores are lost
+DROP TABLE IF EXISTS answer_votes;
+DROP TABLE IF EXISTS question_votes;
+ALTER TABLE answers DROP COLUMN IF EXISTS score;
+ALTER TABLE questions DROP COLUMN IF EXISTS score;
@@ -0,0 +1,2 @@
+-- The answer the question owner accepted, if any
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS accepted_answer_id integer REFERENCES answers ON DELETE SET NULL;
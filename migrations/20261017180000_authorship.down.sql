@@ -0,0 +1,4 @@
+-- Display names and edit times are lost
+ALTER TABLE answers DROP COLUMN IF EXISTS updated_on;
+ALTER TABLE questions DROP COLUMN IF EXISTS updated_on;
+ALTER TABLE accounts DROP COLUMN IF EXISTS display_name;
@@ -0,0 +1,4 @@
+-- The name accounts are shown by and when posts were last edited
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS display_name VARCHAR(255);
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS updated_on TIMESTAMP;
+ALTER TABLE answers ADD COLUMN IF NOT EXISTS updated_on TIMESTAMP;
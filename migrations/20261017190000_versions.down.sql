@@ -0,0 +1,3 @@
+-- Versions are lost
+ALTER TABLE answers DROP COLUMN IF EXISTS version;
+ALTER TABLE questions DROP COLUMN IF EXISTS version;
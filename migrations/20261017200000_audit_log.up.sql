@@ -0,0 +1,16 @@
+-- Every change made through the API, with the row before and after it
+CREATE TABLE IF NOT EXISTS audit_log (
+    id serial PRIMARY KEY,
+    actor integer NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL,
+    target_id integer NOT NULL,
+    row_before JSONB,
+    row_after JSONB,
+    request_id TEXT,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_created_on_idx ON audit_log (created_on DESC, id DESC);
+CREATE INDEX IF NOT EXISTS audit_log_target_idx ON audit_log (target, target_id);
+CREATE INDEX IF NOT EXISTS audit_log_actor_idx ON audit_log (actor);
@@ -0,0 +1,3 @@
+-- Every refresh token is lost
+DROP TABLE IF EXISTS refresh_tokens;
+ALTER TABLE accounts DROP COLUMN IF EXISTS token_generation;
@@ -0,0 +1,14 @@
+-- Refresh tokens, stored as their SHA-256 hash, and the token generation
+-- which revokes every access token of an account when it changes
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS token_generation integer NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_on TIMESTAMPTZ NOT NULL,
+    revoked_on TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS refresh_tokens_account_id_idx ON refresh_tokens (account_id);
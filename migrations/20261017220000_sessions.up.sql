@@ -0,0 +1,18 @@
+-- Login sessions, each holding the refresh tokens handed out since its login.
+-- Refresh tokens from before sessions belong to none, so they are dropped
+-- and their holders log in again
+CREATE TABLE IF NOT EXISTS sessions (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL,
+    user_agent TEXT,
+    ip TEXT,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_on TIMESTAMPTZ NOT NULL,
+    revoked_on TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS sessions_account_id_idx ON sessions (account_id);
+
+DELETE FROM refresh_tokens;
+ALTER TABLE refresh_tokens ADD COLUMN IF NOT EXISTS session_id integer NOT NULL;
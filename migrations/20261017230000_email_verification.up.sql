@@ -0,0 +1,17 @@
+-- Whether accounts proved they own their email, and the one-time tokens
+-- sent to prove it, stored as their SHA-256 hash. Accounts from before
+-- verification are taken as verified
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE accounts SET email_verified = TRUE;
+
+CREATE TABLE IF NOT EXISTS verification_tokens (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_on TIMESTAMPTZ NOT NULL,
+    used_on TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS verification_tokens_account_id_idx ON verification_tokens (account_id);
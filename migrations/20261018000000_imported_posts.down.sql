@@ -0,0 +1,2 @@
+-- Imports no longer remember the posts they made
+DROP TABLE IF EXISTS imported_posts;
@@ -0,0 +1,9 @@
+-- Posts imported from a Stack Exchange dump by their id in the dump, so
+-- answers find their question and an import can run again without adding
+-- the posts twice
+CREATE TABLE IF NOT EXISTS imported_posts (
+    source_id BIGINT PRIMARY KEY,
+    question_id integer NOT NULL REFERENCES questions ON DELETE CASCADE,
+    answer_id integer REFERENCES answers ON DELETE CASCADE,
+    accepted_source_id BIGINT
+);
@@ -22,6 +22,22 @@ run_sql_file() {
 
 # Run down migrations in reverse order
 echo "Reverting migrations..."
+run_sql_file "20261018000000_imported_posts.down.sql"
+run_sql_file "20261017230000_email_verification.down.sql"
+run_sql_file "20261017220000_sessions.down.sql"
+run_sql_file "20261017210000_refresh_tokens.down.sql"
+run_sql_file "20261017200000_audit_log.down.sql"
+run_sql_file "20261017190000_versions.down.sql"
+run_sql_file "20261017180000_authorship.down.sql"
+run_sql_file "20261017170000_comments.down.sql"
+run_sql_file "20261017160000_accepted_answers.down.sql"
+run_sql_file "20261017150000_votes.down.sql"
+run_sql_file "20261017140000_tags.down.sql"
+run_sql_file "20261017130000_full_text_search.down.sql"
+run_sql_file "20261017120000_keyset_indexes.down.sql"
+run_sql_file "20261017110000_revisions.down.sql"
+run_sql_file "20261017100000_soft_delete.down.sql"
+run_sql_file "20261017090000_answers_on_delete_cascade.down.sql"
 run_sql_file "20240221183350_accounts_tables.down.sql"
 run_sql_file "20240221183051_answers_table.down.sql"
 run_sql_file "20240221183024_questions_table.down.sql"
@@ -30,5 +30,21 @@ echo "Running migrations..."
 run_sql_file "20240221183024_questions_table.up.sql"
 run_sql_file "20240221183051_answers_table.up.sql"
 run_sql_file "20240221183350_accounts_tables.up.sql"
+run_sql_file "20261017090000_answers_on_delete_cascade.up.sql"
+run_sql_file "20261017100000_soft_delete.up.sql"
+run_sql_file "20261017110000_revisions.up.sql"
+run_sql_file "20261017120000_keyset_indexes.up.sql"
+run_sql_file "20261017130000_full_text_search.up.sql"
+run_sql_file "20261017140000_tags.up.sql"
+run_sql_file "20261017150000_votes.up.sql"
+run_sql_file "20261017160000_accepted_answers.up.sql"
+run_sql_file "20261017170000_comments.up.sql"
+run_sql_file "20261017180000_authorship.up.sql"
+run_sql_file "20261017190000_versions.up.sql"
+run_sql_file "20261017200000_audit_log.up.sql"
+run_sql_file "20261017210000_refresh_tokens.up.sql"
+run_sql_file "20261017220000_sessions.up.sql"
+run_sql_file "20261017230000_email_verification.up.sql"
+run_sql_file "20261018000000_imported_posts.up.sql"
 
 echo "All migrations completed successfully!" 
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- Combined migration for answers table
+CREATE TABLE IF NOT EXISTS answers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content TEXT NOT NULL,
+    created_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    corresponding_question INTEGER REFERENCES questions,
+    account_id INTEGER NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Combined migration for accounts table
+-- SQLite only generates ids for an INTEGER PRIMARY KEY, so the email
+-- uniqueness that PostgreSQL gets from its primary key is a UNIQUE constraint here.
+CREATE TABLE IF NOT EXISTS accounts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email VARCHAR(255) NOT NULL UNIQUE,
+    password VARCHAR(255) NOT NULL
+);
@@ -0,0 +1,12 @@
+-- SQLite cannot alter a foreign key, so the answers table is rebuilt
+CREATE TABLE answers_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content TEXT NOT NULL,
+    created_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    corresponding_question INTEGER REFERENCES questions,
+    account_id INTEGER NOT NULL
+);
+INSERT INTO answers_new (id, content, created_on, corresponding_question, account_id)
+    SELECT id, content, created_on, corresponding_question, account_id FROM answers;
+DROP TABLE answers;
+ALTER TABLE answers_new RENAME TO answers;
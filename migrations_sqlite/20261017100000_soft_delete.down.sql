@@ -0,0 +1,3 @@
+-- Soft-deleted rows become visible again
+ALTER TABLE answers DROP COLUMN deleted_at;
+ALTER TABLE questions DROP COLUMN deleted_at;
@@ -0,0 +1,3 @@
+-- Deleted questions and answers are kept until purged
+ALTER TABLE questions ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE answers ADD COLUMN deleted_at TIMESTAMP;
@@ -0,0 +1,18 @@
+-- The state of a question or answer before each edit
+CREATE TABLE IF NOT EXISTS question_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    question_id INTEGER NOT NULL REFERENCES questions ON DELETE CASCADE,
+    title VARCHAR (255) NOT NULL,
+    content TEXT NOT NULL,
+    tags TEXT,
+    account_id INTEGER NOT NULL,
+    created_on TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS answer_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    answer_id INTEGER NOT NULL REFERENCES answers ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    account_id INTEGER NOT NULL,
+    created_on TIMESTAMP NOT NULL
+);
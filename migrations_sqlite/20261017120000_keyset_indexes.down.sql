@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS answers_question_created_on_id_idx;
+DROP INDEX IF EXISTS questions_created_on_id_idx;
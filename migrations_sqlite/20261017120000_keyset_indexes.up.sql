@@ -0,0 +1,3 @@
+-- Cursor pagination walks questions and answers in (created_on, id) order
+CREATE INDEX IF NOT EXISTS questions_created_on_id_idx ON questions (created_on, id);
+CREATE INDEX IF NOT EXISTS answers_question_created_on_id_idx ON answers (corresponding_question, created_on, id);
@@ -0,0 +1,39 @@
+-- Canonical tags, the questions using them and synonyms mapping onto them.
+-- `questions.tags` keeps holding the canonical slugs of each question.
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    slug VARCHAR (35) NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS question_tags (
+    question_id INTEGER NOT NULL REFERENCES questions ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags ON DELETE CASCADE,
+    PRIMARY KEY (question_id, tag_id)
+);
+CREATE INDEX IF NOT EXISTS question_tags_tag_id_idx ON question_tags (tag_id);
+
+CREATE TABLE IF NOT EXISTS tag_synonyms (
+    slug VARCHAR (35) PRIMARY KEY,
+    tag_id INTEGER NOT NULL REFERENCES tags ON DELETE CASCADE
+);
+
+-- Normalize the existing tags: trimmed, lowercase, spaces as `-`, without
+-- duplicates
+UPDATE questions SET tags = (
+    SELECT json_group_array(slug) FROM (
+        SELECT replace(lower(trim(value)), ' ', '-') AS slug, min(key) AS ord
+        FROM json_each(questions.tags)
+        WHERE trim(value) <> ''
+        GROUP BY slug
+        ORDER BY ord
+    )
+)
+WHERE tags IS NOT NULL;
+
+INSERT OR IGNORE INTO tags (slug)
+SELECT DISTINCT json_each.value FROM questions, json_each(questions.tags);
+
+INSERT OR IGNORE INTO question_tags (question_id, tag_id)
+SELECT questions.id, tags.id
+FROM questions, json_each(questions.tags)
+JOIN tags ON tags.slug = json_each.value;
@@ -0,0 +1,17 @@
+-- One up or down vote per account and post; `score` caches the sum
+ALTER TABLE questions ADD COLUMN score INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE answers ADD COLUMN score INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS question_votes (
+    question_id INTEGER NOT NULL REFERENCES questions ON DELETE CASCADE,
+    account_id INTEGER NOT NULL,
+    value INTEGER NOT NULL CHECK (value IN (-1, 1)),
+    PRIMARY KEY (question_id, account_id)
+);
+
+CREATE TABLE IF NOT EXISTS answer_votes (
+    answer_id INTEGER NOT NULL REFERENCES answers ON DELETE CASCADE,
+    account_id INTEGER NOT NULL,
+    value INTEGER NOT NULL CHECK (value IN (-1, 1)),
+    PRIMARY KEY (answer_id, account_id)
+);
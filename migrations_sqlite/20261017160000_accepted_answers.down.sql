@@ -0,0 +1,2 @@
+-- Accepted answers are lost
+ALTER TABLE questions DROP COLUMN accepted_answer_id;
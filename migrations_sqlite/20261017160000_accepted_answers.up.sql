@@ -0,0 +1,2 @@
+-- The answer the question owner accepted, if any
+ALTER TABLE questions ADD COLUMN accepted_answer_id INTEGER REFERENCES answers ON DELETE SET NULL;
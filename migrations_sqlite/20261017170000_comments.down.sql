@@ -0,0 +1,2 @@
+-- Comments are lost
+DROP TABLE IF EXISTS comments;
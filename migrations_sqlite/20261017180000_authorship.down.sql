@@ -0,0 +1,4 @@
+-- Display names and edit times are lost
+ALTER TABLE answers DROP COLUMN updated_on;
+ALTER TABLE questions DROP COLUMN updated_on;
+ALTER TABLE accounts DROP COLUMN display_name;
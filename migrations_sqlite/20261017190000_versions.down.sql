@@ -0,0 +1,3 @@
+-- Versions are lost
+ALTER TABLE answers DROP COLUMN version;
+ALTER TABLE questions DROP COLUMN version;
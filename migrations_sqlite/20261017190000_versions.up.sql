@@ -0,0 +1,3 @@
+-- Edits bump the version, which clients send back in If-Match
+ALTER TABLE questions ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE answers ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,16 @@
+-- Every change made through the API, with the row before and after it
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL,
+    target_id INTEGER NOT NULL,
+    row_before TEXT,
+    row_after TEXT,
+    request_id TEXT,
+    created_on TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_created_on_idx ON audit_log (created_on DESC, id DESC);
+CREATE INDEX IF NOT EXISTS audit_log_target_idx ON audit_log (target, target_id);
+CREATE INDEX IF NOT EXISTS audit_log_actor_idx ON audit_log (actor);
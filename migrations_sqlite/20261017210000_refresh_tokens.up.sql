@@ -0,0 +1,14 @@
+-- Refresh tokens, stored as their SHA-256 hash, and the token generation
+-- which revokes every access token of an account when it changes
+ALTER TABLE accounts ADD COLUMN token_generation INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_on TIMESTAMP NOT NULL,
+    expires_on TIMESTAMP NOT NULL,
+    revoked_on TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS refresh_tokens_account_id_idx ON refresh_tokens (account_id);
@@ -0,0 +1,18 @@
+-- Login sessions, each holding the refresh tokens handed out since its login.
+-- Refresh tokens from before sessions belong to none, so they are dropped
+-- and their holders log in again
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    user_agent TEXT,
+    ip TEXT,
+    created_on TIMESTAMP NOT NULL,
+    last_used_on TIMESTAMP NOT NULL,
+    expires_on TIMESTAMP NOT NULL,
+    revoked_on TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS sessions_account_id_idx ON sessions (account_id);
+
+DELETE FROM refresh_tokens;
+ALTER TABLE refresh_tokens ADD COLUMN session_id INTEGER NOT NULL DEFAULT 0;
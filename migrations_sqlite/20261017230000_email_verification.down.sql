@@ -0,0 +1,3 @@
+-- Accounts can post without verifying their email
+DROP TABLE IF EXISTS verification_tokens;
+ALTER TABLE accounts DROP COLUMN email_verified;
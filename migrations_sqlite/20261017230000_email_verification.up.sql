@@ -0,0 +1,17 @@
+-- Whether accounts proved they own their email, and the one-time tokens
+-- sent to prove it, stored as their SHA-256 hash. Accounts from before
+-- verification are taken as verified
+ALTER TABLE accounts ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE accounts SET email_verified = TRUE;
+
+CREATE TABLE IF NOT EXISTS verification_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_on TIMESTAMP NOT NULL,
+    expires_on TIMESTAMP NOT NULL,
+    used_on TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS verification_tokens_account_id_idx ON verification_tokens (account_id);
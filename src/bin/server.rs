@@ -1,10 +1,26 @@
-use rust_hour::{config, run, setup_store};
+use rust_hour::types::{account::AccountId, bulk::Format};
+use rust_hour::{config, import, run, setup_store};
 
 #[tokio::main]
 async fn main() -> Result<(), handle_errors::Error> {
     dotenv::dotenv().ok();
     let config = config::Config::new().expect("Config can't be set");
     let store = setup_store(&config).await?;
+
+    if let Some(config::Command::Import { file, format, account }) = &config.command {
+        let format = match format {
+            Some(format) => *format,
+            None => Format::from_path(file)?,
+        };
+        let input = std::fs::read_to_string(file).expect("Import file can't be read");
+        let report = import(store, &input, format, AccountId(*account)).await?;
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        if !report.errors.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     tracing::info!("Q&A service build ID {}", env!("RUST_WEB_DEV_VERSION"));
     run(config, store).await;
     Ok(())
@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use clap::{CommandFactory, ErrorKind, FromArgMatches, Parser};
+use rust_hour::stackexchange::DumpFiles;
+use rust_hour::{config, import_stack_exchange, setup_store};
+
+/// Imports the questions and answers of a Stack Exchange data dump
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// `Posts.xml` of the dump
+    posts: PathBuf,
+    /// `Users.xml` of the dump, for the names of the authors
+    #[clap(long)]
+    users: Option<PathBuf>,
+    /// `Tags.xml` of the dump; only the tags it lists are kept
+    #[clap(long)]
+    tags: Option<PathBuf>,
+    #[clap(flatten)]
+    config: config::Config,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), handle_errors::Error> {
+    dotenv::dotenv().ok();
+    // The options of the server come with its subcommands, which make no
+    // sense here: they are hidden and refused.
+    let mut command = Args::command()
+        .mut_subcommand("import", |subcommand| subcommand.hide(true))
+        .disable_help_subcommand(true);
+    let args = Args::from_arg_matches(&command.get_matches_mut()).unwrap_or_else(|e| e.exit());
+    if args.config.command.is_some() {
+        command
+            .error(ErrorKind::UnknownArgument, "the importer takes no subcommand")
+            .exit();
+    }
+    let config = config::Config::from_args(args.config).expect("Config can't be set");
+    let store = setup_store(&config).await?;
+
+    let files = DumpFiles {
+        posts: args.posts,
+        users: args.users,
+        tags: args.tags,
+    };
+    let report = import_stack_exchange(store, &files).await?;
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    Ok(())
+}
@@ -0,0 +1,281 @@
+//! The keys PASETO tokens are encrypted or signed with, see `Keyring`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Arc;
+
+use clap::ValueEnum;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+
+use handle_errors::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Id of the key made from `PASETO_KEY` when no `PASETO_KEYS` are set.
+pub const DEFAULT_KEY_ID: &str = "default";
+
+/// Length in bytes of a v2.local key, and of the seed of an Ed25519 key.
+const KEY_LENGTH: usize = 32;
+
+/// Kinds of PASETO tokens the server issues
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TokenMode {
+    /// v2.local tokens, encrypted with a secret key
+    #[default]
+    Local,
+    /// v2.public tokens, signed with an Ed25519 key; anyone with the
+    /// published public keys can verify them
+    Public,
+}
+
+/// Footer of a token, naming the key it was encrypted or signed with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Footer {
+    pub kid: String,
+}
+
+/// A public key tokens are verified with, as published at
+/// `/.well-known/paseto-keys`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PublicKey {
+    pub kid: String,
+    pub version: String,
+    pub purpose: String,
+    /// The raw Ed25519 public key, in URL-safe base64 without padding
+    pub public_key: String,
+}
+
+/// The public keys of a keyring, see `Keyring::public_keys`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PublicKeys {
+    pub keys: Vec<PublicKey>,
+}
+
+// A secret key, with the key pair made from it when it signs tokens.
+struct Key {
+    secret: Vec<u8>,
+    pair: Option<Ed25519KeyPair>,
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        self.secret == other.secret
+    }
+}
+
+/// The keys tokens are accepted with, by id, and the active one new tokens
+/// are encrypted or signed with.
+///
+/// Tokens carry the id of their key in their footer, so a key can be
+/// rotated without logging anyone out: add the new key, make it active once
+/// every server knows it, and keep the old one to verify the tokens it
+/// encrypted. Once the last of them has expired, or right away if the key
+/// leaked, retire the old key: its tokens are rejected from then on. Keys
+/// which are not listed at all are rejected too.
+///
+/// The default keyring has no keys. It rejects every token and cannot issue
+/// any.
+#[derive(Clone, Default, PartialEq)]
+pub struct Keyring {
+    mode: TokenMode,
+    keys: Arc<BTreeMap<String, Key>>,
+    active: String,
+    retired: BTreeSet<String>,
+}
+
+impl Keyring {
+    /// A keyring of `keys`, given as id and key, which encrypts or signs with
+    /// the key `active`, or with the first of `keys` without it. The keys of
+    /// the `Public` mode are the 32 byte seeds of Ed25519 keys.
+    pub fn new(
+        mode: TokenMode,
+        keys: Vec<(String, Vec<u8>)>,
+        active: Option<String>,
+    ) -> Result<Keyring, Error> {
+        let active = match active.or_else(|| keys.first().map(|(id, _)| id.clone())) {
+            Some(active) => active,
+            None => return Err(Error::InvalidKeyring("no keys".to_string())),
+        };
+        let mut ring = BTreeMap::new();
+        for (id, secret) in keys {
+            if id.is_empty() {
+                return Err(Error::InvalidKeyring("a key has no id".to_string()));
+            }
+            if secret.len() != KEY_LENGTH {
+                return Err(Error::InvalidKeyring(format!(
+                    "key {} is not {} bytes long",
+                    id, KEY_LENGTH
+                )));
+            }
+            let pair = match mode {
+                TokenMode::Local => None,
+                TokenMode::Public => Some(
+                    Ed25519KeyPair::from_seed_unchecked(&secret)
+                        .map_err(|e| Error::InvalidKeyring(format!("key {}: {}", id, e)))?,
+                ),
+            };
+            if ring.insert(id.clone(), Key { secret, pair }).is_some() {
+                return Err(Error::InvalidKeyring(format!("key {} is given twice", id)));
+            }
+        }
+        if !ring.contains_key(&active) {
+            return Err(Error::InvalidKeyring(format!("there is no active key {}", active)));
+        }
+        Ok(Keyring {
+            mode,
+            keys: Arc::new(ring),
+            active,
+            retired: BTreeSet::new(),
+        })
+    }
+
+    /// The keyring with the keys of `ids` retired: they stay listed, but
+    /// tokens of theirs are rejected. The active key cannot be retired.
+    /// # Example usage
+    /// ```rust
+    /// use rust_hour::keyring::{Keyring, TokenMode};
+    /// let keys = "2024=RANDOM_KEY_ONLY_USED_FOR_TESTS32,2025=ANOTHER_KEY_ONLY_USED_FOR_TEST32";
+    /// let keyring = Keyring::parse(TokenMode::Local, keys, Some("2025".to_string()))
+    ///     .unwrap()
+    ///     .retire(&["2024".to_string()])
+    ///     .unwrap();
+    /// assert!(keyring.key("2024").is_none());
+    /// assert!(keyring.key("2025").is_some());
+    /// ```
+    pub fn retire(mut self, ids: &[String]) -> Result<Keyring, Error> {
+        for id in ids {
+            if !self.keys.contains_key(id) {
+                return Err(Error::InvalidKeyring(format!("there is no key {} to retire", id)));
+            }
+            if *id == self.active {
+                return Err(Error::InvalidKeyring(format!("the active key {} cannot be retired", id)));
+            }
+            self.retired.insert(id.clone());
+        }
+        Ok(self)
+    }
+
+    /// A keyring of a single key, named `DEFAULT_KEY_ID`, given like the
+    /// `PASETO_KEY` environment variable: as is in the `Local` mode, and in
+    /// base64 in the `Public` mode.
+    /// # Example usage
+    /// ```rust
+    /// use rust_hour::keyring::{Keyring, TokenMode};
+    /// let keyring = Keyring::single(TokenMode::Local, "RANDOM_KEY_ONLY_USED_FOR_TESTS32").unwrap();
+    /// assert_eq!(keyring.active().unwrap().0, "default");
+    /// ```
+    pub fn single(mode: TokenMode, key: &str) -> Result<Keyring, Error> {
+        let key = decode_key(mode, DEFAULT_KEY_ID, key)?;
+        Keyring::new(mode, vec![(DEFAULT_KEY_ID.to_string(), key)], None)
+    }
+
+    /// Reads keys given as `id=key`, separated by commas, like the
+    /// `PASETO_KEYS` environment variable. Keys are in base64 in the
+    /// `Public` mode.
+    /// # Example usage
+    /// ```rust
+    /// use rust_hour::keyring::{Keyring, TokenMode};
+    /// let keys = "2024=RANDOM_KEY_ONLY_USED_FOR_TESTS32,2025=ANOTHER_KEY_ONLY_USED_FOR_TEST32";
+    /// let keyring = Keyring::parse(TokenMode::Local, keys, Some("2025".to_string())).unwrap();
+    /// assert_eq!(keyring.active().unwrap().0, "2025");
+    /// assert!(keyring.key("2024").is_some());
+    /// ```
+    pub fn parse(mode: TokenMode, keys: &str, active: Option<String>) -> Result<Keyring, Error> {
+        let keys = keys
+            .split(',')
+            .filter(|key| !key.trim().is_empty())
+            .map(|key| match key.split_once('=') {
+                Some((id, key)) => Ok((id.trim().to_string(), decode_key(mode, id.trim(), key)?)),
+                None => Err(Error::InvalidKeyring(format!(
+                    "{} is not of the form id=key",
+                    key.trim()
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Keyring::new(mode, keys, active)
+    }
+
+    /// The kind of tokens the keyring issues.
+    pub fn mode(&self) -> TokenMode {
+        self.mode
+    }
+
+    /// The id and the key new tokens are encrypted or signed with. Fails
+    /// if there is none, like in the default keyring.
+    pub fn active(&self) -> Result<(&str, &[u8]), Error> {
+        let key = self.active_key()?;
+        Ok((&self.active, &key.secret))
+    }
+
+    /// The key with `id`, if it is listed and not retired.
+    pub fn key(&self, id: &str) -> Option<&[u8]> {
+        self.accepted(id).map(|key| key.secret.as_slice())
+    }
+
+    /// The key pair new tokens are signed with in the `Public` mode, none in
+    /// the `Local` mode. Fails like `active`.
+    pub fn signing_key(&self) -> Result<Option<&Ed25519KeyPair>, Error> {
+        Ok(self.active_key()?.pair.as_ref())
+    }
+
+    /// The public key with `id` in the `Public` mode, if it is listed and
+    /// not retired.
+    pub fn public_key(&self, id: &str) -> Option<&[u8]> {
+        self.accepted(id)
+            .and_then(|key| key.pair.as_ref())
+            .map(|pair| pair.public_key().as_ref())
+    }
+
+    fn active_key(&self) -> Result<&Key, Error> {
+        self.keys
+            .get(&self.active)
+            .ok_or_else(|| Error::InvalidKeyring("there is no active key".to_string()))
+    }
+
+    // The key tokens of `id` are verified with, unless it is retired.
+    fn accepted(&self, id: &str) -> Option<&Key> {
+        self.keys.get(id).filter(|_| !self.retired.contains(id))
+    }
+
+    /// The public keys tokens are verified with, to be published for other
+    /// services. There are none in the `Local` mode.
+    pub fn public_keys(&self) -> PublicKeys {
+        let keys = self
+            .keys
+            .keys()
+            .filter_map(|id| {
+                self.public_key(id).map(|public_key| PublicKey {
+                    kid: id.clone(),
+                    version: "v2".to_string(),
+                    purpose: "public".to_string(),
+                    public_key: base64::encode_config(public_key, base64::URL_SAFE_NO_PAD),
+                })
+            })
+            .collect();
+        PublicKeys { keys }
+    }
+}
+
+// Keys are given as is in the `Local` mode, and as base64 in the `Public`
+// mode, as Ed25519 seeds are not text.
+fn decode_key(mode: TokenMode, id: &str, key: &str) -> Result<Vec<u8>, Error> {
+    match mode {
+        TokenMode::Local => Ok(key.as_bytes().to_vec()),
+        TokenMode::Public => base64::decode(key.trim())
+            .map_err(|_| Error::InvalidKeyring(format!("key {} is not base64", id))),
+    }
+}
+
+// Keys are secret, so only their ids are shown.
+impl fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Keyring")
+            .field("mode", &self.mode)
+            .field("keys", &self.keys.keys().collect::<Vec<_>>())
+            .field("active", &self.active)
+            .field("retired", &self.retired)
+            .finish()
+    }
+}
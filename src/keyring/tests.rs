@@ -0,0 +1,103 @@
+use super::*;
+
+const KEY: &[u8] = b"RANDOM_KEY_ONLY_USED_FOR_TESTS32";
+
+#[test]
+fn test_new_defaults_to_first_key() {
+    let keys = vec![("a".to_string(), KEY.to_vec()), ("b".to_string(), KEY.to_vec())];
+    let keyring = Keyring::new(TokenMode::Local, keys, None).unwrap();
+    assert_eq!(keyring.active().unwrap(), ("a", KEY));
+    assert_eq!(keyring.key("b"), Some(KEY));
+    assert_eq!(keyring.key("c"), None);
+}
+
+#[test]
+fn test_new_rejects_invalid_keys() {
+    for (keys, active) in [
+        (vec![], None),
+        (vec![("a".to_string(), b"short".to_vec())], None),
+        (vec![("".to_string(), KEY.to_vec())], None),
+        (vec![("a".to_string(), KEY.to_vec()), ("a".to_string(), KEY.to_vec())], None),
+        (vec![("a".to_string(), KEY.to_vec())], Some("b".to_string())),
+    ] {
+        assert!(matches!(Keyring::new(TokenMode::Local, keys, active), Err(Error::InvalidKeyring(_))));
+    }
+}
+
+#[test]
+fn test_parse() {
+    let keyring = Keyring::parse(
+        TokenMode::Local,
+        " old=RANDOM_KEY_ONLY_USED_FOR_TESTS32, new=ANOTHER_KEY_ONLY_USED_FOR_TEST32,",
+        Some("new".to_string()),
+    )
+    .unwrap();
+    assert_eq!(keyring.active().unwrap(), ("new", b"ANOTHER_KEY_ONLY_USED_FOR_TEST32".as_slice()));
+    assert_eq!(keyring.key("old"), Some(KEY));
+
+    assert!(matches!(
+        Keyring::parse(TokenMode::Local, "RANDOM_KEY_ONLY_USED_FOR_TESTS32", None),
+        Err(Error::InvalidKeyring(_))
+    ));
+}
+
+#[test]
+fn test_debug_hides_keys() {
+    let keyring = Keyring::single(TokenMode::Local, "RANDOM_KEY_ONLY_USED_FOR_TESTS32").unwrap();
+    assert!(!format!("{:?}", keyring).contains("RANDOM"));
+}
+
+#[test]
+fn test_public_keys() {
+    let seed = base64::encode(KEY);
+    let keyring = Keyring::parse(TokenMode::Public, &format!("a={}", seed), None).unwrap();
+    assert_eq!(keyring.mode(), TokenMode::Public);
+    assert!(keyring.signing_key().unwrap().is_some());
+    let public_key = keyring.public_key("a").unwrap();
+    assert_eq!(public_key.len(), 32);
+
+    let published = keyring.public_keys();
+    assert_eq!(published.keys.len(), 1);
+    assert_eq!(published.keys[0].kid, "a");
+    assert_eq!(
+        published.keys[0].public_key,
+        base64::encode_config(public_key, base64::URL_SAFE_NO_PAD)
+    );
+
+    // Public-mode keys are base64, and local keyrings publish nothing.
+    assert!(matches!(
+        Keyring::parse(TokenMode::Public, "a=RANDOM_KEY_ONLY_USED_FOR_TESTS32!", None),
+        Err(Error::InvalidKeyring(_))
+    ));
+    let local = Keyring::single(TokenMode::Local, "RANDOM_KEY_ONLY_USED_FOR_TESTS32").unwrap();
+    assert!(local.public_keys().keys.is_empty());
+    assert!(local.signing_key().unwrap().is_none());
+}
+
+#[test]
+fn test_default_has_no_active_key() {
+    let keyring = Keyring::default();
+    assert!(matches!(keyring.active(), Err(Error::InvalidKeyring(_))));
+    assert!(matches!(keyring.signing_key(), Err(Error::InvalidKeyring(_))));
+    assert_eq!(keyring.key(""), None);
+}
+
+#[test]
+fn test_retire() {
+    let seeds = format!("old={},new={}", base64::encode(KEY), base64::encode(b"ANOTHER_KEY_ONLY_USED_FOR_TEST32"));
+    let keyring = Keyring::parse(TokenMode::Public, &seeds, Some("new".to_string())).unwrap();
+    assert!(keyring.public_key("old").is_some());
+
+    let retired = keyring.clone().retire(&["old".to_string()]).unwrap();
+    assert_eq!(retired.key("old"), None);
+    assert_eq!(retired.public_key("old"), None);
+    assert!(retired.key("new").is_some());
+    assert_eq!(retired.public_keys().keys.len(), 1);
+
+    for id in ["new", "missing"] {
+        assert!(matches!(
+            keyring.clone().retire(&[id.to_string()]),
+            Err(Error::InvalidKeyring(_))
+        ));
+    }
+}
@@ -1,4 +1,6 @@
 #![warn(clippy::all)]
+// The chain of warp filters in `build_routes` nests deeper than the default.
+#![recursion_limit = "256"]
 
 pub use handle_errors;
 use tokio::sync::oneshot::Sender;
@@ -6,7 +8,10 @@ use tracing_subscriber::fmt::format::FmtSpan;
 use warp::{http::Method, Filter, Reply};
 
 pub mod config;
+pub mod keyring;
+pub mod mailer;
 mod routes;
+pub mod stackexchange;
 mod store;
 pub mod types;
 
@@ -14,37 +19,76 @@ pub struct OneshotHandler {
     pub sender: Sender<i32>,
 }
 
-async fn build_routes<T>(store: T) -> impl Filter<Extract = impl Reply> + Clone 
+async fn build_routes<T>(
+    store: T,
+    admins: Vec<types::account::AccountId>,
+    keyring: keyring::Keyring,
+    mailer: std::sync::Arc<dyn mailer::Mailer>,
+    import_limit: u64,
+) -> impl Filter<Extract = impl Reply> + Clone 
 where 
     T: routes::question::store_trait::StoreTrait 
         + routes::answer::store_trait::StoreTrait 
         + routes::authentication::StoreTrait 
+        + routes::tag::store_trait::StoreTrait
+        + routes::comment::store_trait::StoreTrait
+        + routes::audit::store_trait::StoreTrait
+        + routes::transaction::Transactional
         + Clone 
         + Send 
         + Sync 
         + 'static
 {
-    let store_filter = warp::any().map(move || store.clone());
+    // Every request gets its own handle, which records its changes in the
+    // audit log under the id of the request.
+    let store = store::audit::AuditedStore::new(store);
+    let auth = routes::authentication::auth(store.clone(), keyring.clone());
+    let admin = routes::authentication::admin(store.clone(), keyring.clone(), admins);
+    // Only accounts with a verified email can post questions and answers.
+    let verified = routes::authentication::verified(store.clone(), keyring.clone());
+    let keyring_filter = warp::any().map(move || keyring.clone());
+    let mailer_filter = warp::any().map(move || mailer.clone());
+    let store_filter = routes::audit::request_id().map(move |request_id| store.for_request(request_id));
 
     let cors = warp::cors()
         .allow_any_origin()
-        .allow_header("content-type")
+        .allow_headers(vec!["content-type", "if-match", "if-none-match", "x-request-id"])
+        .expose_headers(vec!["etag"])
         .allow_methods(&[Method::PUT, Method::DELETE, Method::GET, Method::POST]);
 
     let get_questions = warp::get()
         .and(warp::path("questions"))
         .and(warp::path::end())
-        .and(warp::query())
+        // A list of pairs keeps repeated parameters like `tag`.
+        .and(warp::query::<Vec<(String, String)>>())
+        .and(warp::header::optional::<String>("if-none-match"))
         .and(store_filter.clone())
         .and_then(routes::question::get_questions);
 
+    let search = warp::get()
+        .and(warp::path("search"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::question::search);
+
+    let get_question = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::question::get_question);
+
     let update_question = warp::put()
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
         .map(types::question::QuestionId)
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(auth.clone())
         .and(store_filter.clone())
+        .and(warp::header::optional::<String>("if-match"))
         .and(warp::body::json())
         .and_then(routes::question::update_question);
 
@@ -53,14 +97,67 @@ where
         .and(warp::path::param::<i32>())
         .map(types::question::QuestionId)
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(auth.clone())
+        .and(warp::query())
         .and(store_filter.clone())
         .and_then(routes::question::delete_question);
 
+    let restore_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::question::restore_question);
+
+    let vote_question = warp::put()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::question::vote_question);
+
+    let retract_question_vote = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::question::retract_question_vote);
+
+    let accept_answer = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("accept"))
+        .and(warp::path::param::<i32>().map(types::answer::AnswerId))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::question::accept_answer);
+
+    let unaccept_answer = warp::delete()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("accept"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::question::unaccept_answer);
+
     let add_question = warp::post()
         .and(warp::path("questions"))
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(verified.clone())
         .and(store_filter.clone())
         .and(warp::body::json())
         .and_then(routes::question::add_question);
@@ -68,7 +165,7 @@ where
     let add_answer = warp::post()
         .and(warp::path("answers"))
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(verified.clone())
         .and(store_filter.clone())
         .and(warp::body::json())
         .and_then(routes::answer::add_answer);
@@ -77,6 +174,7 @@ where
         .and(warp::path("registration"))
         .and(warp::path::end())
         .and(store_filter.clone())
+        .and(mailer_filter.clone())
         .and(warp::body::json())
         .and_then(routes::authentication::register);
 
@@ -84,14 +182,33 @@ where
         .and(warp::path("login"))
         .and(warp::path::end())
         .and(store_filter.clone())
+        .and(keyring_filter.clone())
+        .and(warp::header::optional::<String>("user-agent"))
+        .and(warp::addr::remote())
         .and(warp::body::json())
         .and_then(routes::authentication::login);
 
+    let refresh_token = warp::post()
+        .and(warp::path("token"))
+        .and(warp::path("refresh"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(keyring_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::authentication::refresh_token);
+
+    let logout = warp::post()
+        .and(warp::path("logout"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::authentication::logout);
+
     let update_password = warp::put()
         .and(warp::path("accounts"))
         .and(warp::path("update_password"))
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(auth.clone())
         .and(store_filter.clone())
         .and(warp::body::json())
         .and_then(routes::authentication::update_password);
@@ -99,18 +216,90 @@ where
     let update_account = warp::put()
         .and(warp::path("accounts"))
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(auth.clone())
         .and(store_filter.clone())
+        .and(mailer_filter.clone())
         .and(warp::body::json())
         .and_then(routes::authentication::update_account);
 
+    let verify_email = warp::post()
+        .and(warp::path("accounts"))
+        .and(warp::path("verify"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::authentication::verify_email);
+
+    let resend_verification = warp::post()
+        .and(warp::path("accounts"))
+        .and(warp::path("verify"))
+        .and(warp::path("resend"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(mailer_filter.clone())
+        .and_then(routes::authentication::resend_verification);
+
     let get_account_information = warp::get()
         .and(warp::path("accounts"))
         .and(warp::path("me"))
-        .and(routes::authentication::auth())
+        .and(warp::path::end())
+        .and(auth.clone())
         .and(store_filter.clone())
         .and_then(routes::authentication::get_account_information);
 
+    let get_sessions = warp::get()
+        .and(warp::path("accounts"))
+        .and(warp::path("me"))
+        .and(warp::path("sessions"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::authentication::get_sessions);
+
+    let revoke_session = warp::delete()
+        .and(warp::path("accounts"))
+        .and(warp::path("me"))
+        .and(warp::path("sessions"))
+        .and(warp::path::param::<i32>().map(types::account::SessionId))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::authentication::revoke_session);
+
+    let revoke_other_sessions = warp::delete()
+        .and(warp::path("accounts"))
+        .and(warp::path("me"))
+        .and(warp::path("sessions"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::authentication::revoke_other_sessions);
+
+    let get_public_keys = warp::get()
+        .and(warp::path(".well-known"))
+        .and(warp::path("paseto-keys"))
+        .and(warp::path::end())
+        .and(keyring_filter.clone())
+        .and_then(routes::authentication::get_public_keys);
+
+    // Boxed, so the future of a request through the whole chain still fits
+    // the stack of a worker thread.
+    let accounts = registration
+        .or(login)
+        .or(refresh_token)
+        .or(logout)
+        .or(update_password)
+        .or(update_account)
+        .or(verify_email)
+        .or(resend_verification)
+        .or(get_account_information)
+        .or(get_sessions)
+        .or(revoke_session)
+        .or(revoke_other_sessions)
+        .or(get_public_keys)
+        .boxed();
+
     let get_answers = warp::get()
         .and(warp::path("questions"))
         .and(warp::path::param::<i32>())
@@ -118,15 +307,24 @@ where
         .and(warp::path("answers"))
         .and(warp::path::end())
         .and(warp::query())
+        .and(warp::header::optional::<String>("if-none-match"))
         .and(store_filter.clone())
         .and_then(routes::question::get_answers);
 
+    let get_answer = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(routes::answer::get_answer);
+
     let update_answer = warp::put()
         .and(warp::path("answers"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(auth.clone())
         .and(store_filter.clone())
+        .and(warp::header::optional::<String>("if-match"))
         .and(warp::body::json())
         .and_then(routes::answer::update_answer);
 
@@ -134,40 +332,315 @@ where
         .and(warp::path("answers"))
         .and(warp::path::param::<i32>())
         .and(warp::path::end())
-        .and(routes::authentication::auth())
+        .and(auth.clone())
         .and(store_filter.clone())
         .and_then(routes::answer::delete_answer);
 
+    let restore_answer = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::answer::restore_answer);
+
+    let vote_answer = warp::put()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::answer::vote_answer);
+
+    let retract_answer_vote = warp::delete()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::answer::retract_answer_vote);
+
+    let get_question_revisions = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("revisions"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(routes::question::get_question_revisions);
+
+    let get_question_diff = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("revisions"))
+        .and(warp::path("diff"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::question::get_question_diff);
+
+    let rollback_question = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("revisions"))
+        .and(warp::path::param::<i32>().map(types::revision::RevisionId))
+        .and(warp::path("rollback"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::question::rollback_question);
+
+    let get_answer_revisions = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path::end())
+        .and(store_filter.clone())
+        .and_then(routes::answer::get_answer_revisions);
+
+    let get_answer_diff = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path("diff"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::answer::get_answer_diff);
+
+    let rollback_answer = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revisions"))
+        .and(warp::path::param::<i32>().map(types::revision::RevisionId))
+        .and(warp::path("rollback"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::answer::rollback_answer);
+
+    let get_tags = warp::get()
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::tag::get_tags);
+
+    let get_tag_questions = warp::get()
+        .and(warp::path("tags"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("questions"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::tag::get_tag_questions);
+
+    let add_tag_synonym = warp::post()
+        .and(warp::path("tags"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("synonyms"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::tag::add_tag_synonym);
+
+    let get_question_comments = warp::get()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::comment::get_question_comments);
+
+    let get_answer_comments = warp::get()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>().map(types::answer::AnswerId))
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::comment::get_answer_comments);
+
+    let add_question_comment = warp::post()
+        .and(warp::path("questions"))
+        .and(warp::path::param::<i32>())
+        .map(types::question::QuestionId)
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::comment::add_question_comment);
+
+    let add_answer_comment = warp::post()
+        .and(warp::path("answers"))
+        .and(warp::path::param::<i32>().map(types::answer::AnswerId))
+        .and(warp::path("comments"))
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::comment::add_answer_comment);
+
+    let update_comment = warp::put()
+        .and(warp::path("comments"))
+        .and(warp::path::param::<i32>())
+        .map(types::comment::CommentId)
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::comment::update_comment);
+
+    let delete_comment = warp::delete()
+        .and(warp::path("comments"))
+        .and(warp::path::param::<i32>())
+        .map(types::comment::CommentId)
+        .and(warp::path::end())
+        .and(auth.clone())
+        .and(store_filter.clone())
+        .and_then(routes::comment::delete_comment);
+
+    let import = warp::post()
+        .and(warp::path("admin"))
+        .and(warp::path("import"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and(warp::body::content_length_limit(import_limit))
+        .and(warp::body::bytes())
+        .and_then(routes::admin::import);
+
+    let export = warp::get()
+        .and(warp::path("admin"))
+        .and(warp::path("export"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::admin::export);
+
+    let get_audit_records = warp::get()
+        .and(warp::path("admin"))
+        .and(warp::path("audit"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(warp::query())
+        .and(store_filter.clone())
+        .and_then(routes::audit::get_audit_records);
+
     get_questions
+        .or(get_question)
         .or(update_question)
         .or(add_question)
         .or(delete_question)
+        .or(restore_question)
+        .or(vote_question)
+        .or(retract_question_vote)
+        .or(accept_answer)
+        .or(unaccept_answer)
         .or(add_answer)
-        .or(registration)
-        .or(login)
-        .or(update_password)
-        .or(update_account)
-        .or(get_account_information)
+        .or(accounts)
         .or(get_answers)
+        .or(search)
+        .or(get_answer)
         .or(update_answer)
         .or(delete_answer)
+        .or(restore_answer)
+        .or(vote_answer)
+        .or(retract_answer_vote)
+        .or(get_question_revisions)
+        .or(get_question_diff)
+        .or(rollback_question)
+        .or(get_answer_revisions)
+        .or(get_answer_diff)
+        .or(rollback_answer)
+        .or(get_tags)
+        .or(get_tag_questions)
+        .or(add_tag_synonym)
+        .or(get_question_comments)
+        .or(get_answer_comments)
+        .or(add_question_comment)
+        .or(add_answer_comment)
+        .or(update_comment)
+        .or(delete_comment)
+        .or(import)
+        .or(export)
+        .or(get_audit_records)
         .with(cors)
         .with(warp::trace::request())
         .recover(handle_errors::return_error)
 }
 
-pub async fn setup_store(config: &config::Config) -> Result<store::Store, handle_errors::Error> {
-    let store = store::Store::new(&format!(
-        "postgres://{}:{}@{}:{}/{}",
-        config.db_user, config.db_password, config.db_host, config.db_port, config.db_name
-    ))
-    .await
-    .map_err(handle_errors::Error::DatabaseQueryError)?;
+/// The mailer `config` asks for.
+pub fn setup_mailer(config: &config::Config) -> std::sync::Arc<dyn mailer::Mailer> {
+    match config.mailer {
+        config::MailerKind::Smtp => std::sync::Arc::new(mailer::SmtpMailer {
+            host: config.smtp_host.clone(),
+            port: config.smtp_port,
+            from: config.mail_from.clone(),
+            credentials: config.smtp_user.clone().zip(config.smtp_password.clone()),
+            timeout: mailer::SMTP_TIMEOUT,
+        }),
+        config::MailerKind::File => std::sync::Arc::new(mailer::FileMailer {
+            dir: config.mail_dir.clone().into(),
+            from: config.mail_from.clone(),
+        }),
+    }
+}
+
+pub async fn setup_store(config: &config::Config) -> Result<store::Backend, handle_errors::Error> {
+    let store = match config.store {
+        config::StoreKind::Database => {
+            let db_url = config.database_url();
+            let scheme = db_url.split_once(':').map(|(scheme, _)| scheme);
+            match scheme {
+                Some("postgres") | Some("postgresql") => {
+                    let store = store::Store::new(&db_url)
+                        .await
+                        .map_err(handle_errors::Error::DatabaseQueryError)?;
+
+                    sqlx::migrate!()
+                        .run(&store.clone().connection)
+                        .await
+                        .map_err(handle_errors::Error::MigrationError)?;
+
+                    store::Backend::Postgres(store)
+                }
+                #[cfg(feature = "sqlite")]
+                Some("sqlite") => {
+                    let store = store::sqlite::SqliteStore::new(&db_url)
+                        .await
+                        .map_err(handle_errors::Error::DatabaseQueryError)?;
+
+                    sqlx::migrate!("./migrations_sqlite")
+                        .run(&store.clone().connection)
+                        .await
+                        .map_err(handle_errors::Error::MigrationError)?;
 
-    sqlx::migrate!()
-        .run(&store.clone().connection)
-        .await
-        .map_err(handle_errors::Error::MigrationError)?;
+                    store::Backend::Sqlite(store)
+                }
+                _ => {
+                    return Err(handle_errors::Error::DatabaseQueryError(
+                        sqlx::Error::Configuration(
+                            format!("unsupported database URL scheme: {:?}", scheme).into(),
+                        ),
+                    ))
+                }
+            }
+        }
+        config::StoreKind::Memory => store::Backend::Memory(store::memory::MemoryStore::new()),
+    };
 
     let log_filter = format!(
         "handle_errors={},rust_hour={},warp={}",
@@ -182,9 +655,78 @@ pub async fn setup_store(config: &config::Config) -> Result<store::Store, handle
     Ok(store)
 }
 
-pub async fn run(config: config::Config, store: store::Store) {
-    let routes = build_routes(store).await;
-    warp::serve(routes).run(([0, 0, 0, 0], config.port)).await;
+/// Purges deleted questions and answers once they are older than the
+/// retention window, checking once an hour.
+async fn purge_deleted(store: store::Backend, retention_days: i64) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+    loop {
+        interval.tick().await;
+        let deleted_before = chrono::Utc::now() - chrono::Duration::days(retention_days);
+        match store.purge_deleted(deleted_before).await {
+            Ok(purged) => tracing::info!("purged {} deleted rows", purged),
+            Err(e) => tracing::error!("purging deleted rows failed: {}", e),
+        }
+    }
+}
+
+/// Imports the questions of `input`, with their answers, into `store` as the
+/// `import` subcommand does; see `POST /admin/import`.
+pub async fn import(
+    store: store::Backend,
+    input: &str,
+    format: types::bulk::Format,
+    account_id: types::account::AccountId,
+) -> Result<types::bulk::ImportReport, handle_errors::Error> {
+    match store {
+        store::Backend::Postgres(store) => {
+            routes::admin::import_input(&store, input, format, account_id).await
+        }
+        store::Backend::Memory(store) => {
+            routes::admin::import_input(&store, input, format, account_id).await
+        }
+        #[cfg(feature = "sqlite")]
+        store::Backend::Sqlite(store) => {
+            routes::admin::import_input(&store, input, format, account_id).await
+        }
+    }
+}
+
+/// Imports a Stack Exchange data dump into `store`, as the
+/// `stackexchange_import` binary does; see `stackexchange::import_dump`.
+pub async fn import_stack_exchange(
+    store: store::Backend,
+    files: &stackexchange::DumpFiles,
+) -> Result<stackexchange::DumpReport, handle_errors::Error> {
+    match store {
+        store::Backend::Postgres(store) => stackexchange::import_dump(&store, files).await,
+        store::Backend::Memory(store) => stackexchange::import_dump(&store, files).await,
+        #[cfg(feature = "sqlite")]
+        store::Backend::Sqlite(store) => stackexchange::import_dump(&store, files).await,
+    }
+}
+
+pub async fn run(config: config::Config, store: store::Backend) {
+    tokio::spawn(purge_deleted(store.clone(), config.retention_days));
+    let cache_ttl = std::time::Duration::from_secs(config.cache_ttl);
+    let admins: Vec<_> = config.admins.iter().copied().map(types::account::AccountId).collect();
+    let mailer = setup_mailer(&config);
+    let import_limit = config.import_limit_mb * 1024 * 1024;
+
+    match store {
+        store::Backend::Postgres(store) => {
+            let routes = build_routes(store::cache::CachedStore::new(store, cache_ttl), admins, config.keyring.clone(), mailer, import_limit).await;
+            warp::serve(routes).run(([0, 0, 0, 0], config.port)).await;
+        }
+        store::Backend::Memory(store) => {
+            let routes = build_routes(store::cache::CachedStore::new(store, cache_ttl), admins, config.keyring.clone(), mailer, import_limit).await;
+            warp::serve(routes).run(([0, 0, 0, 0], config.port)).await;
+        }
+        #[cfg(feature = "sqlite")]
+        store::Backend::Sqlite(store) => {
+            let routes = build_routes(store::cache::CachedStore::new(store, cache_ttl), admins, config.keyring.clone(), mailer, import_limit).await;
+            warp::serve(routes).run(([0, 0, 0, 0], config.port)).await;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -198,11 +740,24 @@ mod tests {
     use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
     use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
     use crate::routes::authentication::StoreTrait as AuthStoreTrait;
+    use crate::routes::tag::store_trait::StoreTrait as TagStoreTrait;
+    use crate::routes::comment::store_trait::StoreTrait as CommentStoreTrait;
+    use crate::routes::audit::store_trait::StoreTrait as AuditStoreTrait;
+    use crate::routes::transaction::Transactional;
+    use crate::types::audit::{AuditFilter, AuditRecord, AuditRecordId, NewAuditRecord};
     use crate::types::question::{Question, QuestionId, NewQuestion};
-    use crate::types::account::{AccountId, Account, AccountUpdateRequest, AccountUpdatePassword, AccountResponse};
+    use crate::types::account::{AccountId, Account, Author, AccountUpdateRequest, AccountUpdatePassword, AccountResponse, ActiveSession, NewSession, SessionId, Tokens};
+    use chrono::{DateTime, Utc};
     use crate::types::answer::{Answer, AnswerId, NewAnswer};
+    use crate::types::comment::{Comment, CommentId, CommentParent, NewComment};
+    use crate::types::filter::{AnswerSort, QuestionFilter};
+    use crate::types::search::SearchResult;
+    use crate::types::tag::Tag;
+    use crate::types::vote::Vote;
+    use crate::types::revision::{AnswerRevision, QuestionRevision, RevisionId};
     use async_trait::async_trait;
-    use crate::types::pagination::Pagination;
+    use crate::types::pagination::{Cursor, Page};
+    use std::sync::Arc;
 
     mock! {
         #[derive(Debug)]
@@ -210,12 +765,23 @@ mod tests {
 
         #[async_trait]
         impl QuestionStoreTrait for Store {
-            async fn get_questions(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+            async fn get_questions(&self, filter: QuestionFilter, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+            async fn get_questions_page(&self, filter: QuestionFilter, cursor: Option<Cursor>, limit: i32) -> Result<Page<Question>, handle_errors::Error>;
             async fn is_question_owner(&self, question_id: QuestionId, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
             async fn add_question(&self, new_question: NewQuestion, account_id: AccountId) -> Result<Question, handle_errors::Error>;
             async fn update_question(&self, question: Question, id: QuestionId, account_id: AccountId) -> Result<Question, handle_errors::Error>;
             async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, handle_errors::Error>;
-            async fn get_answers(&self, question_id: QuestionId, limit: Option<i32>, offset: i32) -> Result<Vec<Answer>, handle_errors::Error>;
+            async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, handle_errors::Error>;
+            async fn get_answers(&self, question_id: QuestionId, sort: AnswerSort, limit: Option<i32>, offset: i32) -> Result<Vec<Answer>, handle_errors::Error>;
+            async fn get_answers_page(&self, question_id: QuestionId, cursor: Option<Cursor>, limit: i32) -> Result<Page<Answer>, handle_errors::Error>;
+            async fn count_answers(&self, question_id: QuestionId) -> Result<i64, handle_errors::Error>;
+            async fn get_question(&self, id: QuestionId) -> Result<Question, handle_errors::Error>;
+            async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, handle_errors::Error>;
+            async fn get_question_revision(&self, id: QuestionId, revision_id: RevisionId) -> Result<QuestionRevision, handle_errors::Error>;
+            async fn vote_question(&self, id: QuestionId, account_id: AccountId, vote: Option<Vote>) -> Result<Question, handle_errors::Error>;
+            async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, handle_errors::Error>;
+            async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, handle_errors::Error>;
+            async fn search(&self, query: String, limit: Option<i32>, offset: i32) -> Result<Vec<SearchResult>, handle_errors::Error>;
         }
 
         #[async_trait]
@@ -224,6 +790,11 @@ mod tests {
             async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
             async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
             async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+            async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
+            async fn get_answer(&self, id: i32) -> Result<Answer, handle_errors::Error>;
+            async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, handle_errors::Error>;
+            async fn vote_answer(&self, id: i32, account_id: AccountId, vote: Option<Vote>) -> Result<Answer, handle_errors::Error>;
+            async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, handle_errors::Error>;
         }
 
         #[async_trait]
@@ -233,6 +804,39 @@ mod tests {
             async fn update_account(&self, account_id: AccountId, account: AccountUpdateRequest) -> Result<AccountResponse, handle_errors::Error>;
             async fn update_password(&self, account_id: AccountId, password: AccountUpdatePassword) -> Result<bool, handle_errors::Error>;
             async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, handle_errors::Error>;
+            async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, handle_errors::Error>;
+            async fn add_session(&self, session: NewSession, token_hash: String, expires_on: DateTime<Utc>) -> Result<SessionId, handle_errors::Error>;
+            async fn rotate_refresh_token(&self, token_hash: String, new_token_hash: String, expires_on: DateTime<Utc>) -> Result<(AccountId, SessionId), handle_errors::Error>;
+            async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, handle_errors::Error>;
+            async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, handle_errors::Error>;
+            async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, handle_errors::Error>;
+            async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, handle_errors::Error>;
+            async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, handle_errors::Error>;
+            async fn add_verification_token(&self, account_id: AccountId, email: String, token_hash: String, expires_on: DateTime<Utc>) -> Result<bool, handle_errors::Error>;
+            async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, handle_errors::Error>;
+            async fn verify_email(&self, token_hash: String) -> Result<AccountId, handle_errors::Error>;
+        }
+
+        #[async_trait]
+        impl TagStoreTrait for Store {
+            async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, handle_errors::Error>;
+            async fn get_tag_questions(&self, slug: String, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+            async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, handle_errors::Error>;
+        }
+
+        #[async_trait]
+        impl CommentStoreTrait for Store {
+            async fn get_comments(&self, parent: CommentParent, limit: Option<i32>, offset: i32) -> Result<Vec<Comment>, handle_errors::Error>;
+            async fn add_comment(&self, parent: CommentParent, new_comment: NewComment, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+            async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
+            async fn update_comment(&self, comment: NewComment, id: CommentId, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+            async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+        }
+
+        #[async_trait]
+        impl Transactional for Store {
+            async fn begin(&self) -> Result<Self, handle_errors::Error>;
+            async fn commit(self) -> Result<(), handle_errors::Error>;
         }
 
         impl Clone for Store {
@@ -247,12 +851,22 @@ mod tests {
     impl QuestionStoreTrait for Store {
         async fn get_questions(
             &self,
+            _filter: QuestionFilter,
             _limit: Option<i32>,
             _offset: i32,
         ) -> Result<Vec<Question>, handle_errors::Error> {
             Ok(vec![])
         }
 
+        async fn get_questions_page(
+            &self,
+            _filter: QuestionFilter,
+            _cursor: Option<Cursor>,
+            _limit: i32,
+        ) -> Result<Page<Question>, handle_errors::Error> {
+            Ok(Page { items: vec![], next_cursor: None })
+        }
+
         async fn is_question_owner(
             &self,
             _question_id: QuestionId,
@@ -271,6 +885,12 @@ mod tests {
                 title: "Test Question".to_string(),
                 content: "Test Content".to_string(),
                 tags: Some(vec!["test".to_string()]),
+                score: 0,
+                accepted_answer_id: None,
+                author: Author::default(),
+                created_on: chrono::Utc::now(),
+                updated_on: None,
+                version: 1,
             })
         }
 
@@ -291,27 +911,131 @@ mod tests {
             Ok(true)
         }
 
+        async fn restore_question(
+            &self,
+            id: QuestionId,
+            _account_id: AccountId,
+        ) -> Result<Question, handle_errors::Error> {
+            Ok(Question {
+                id,
+                title: "Test Question".to_string(),
+                content: "Test Content".to_string(),
+                tags: Some(vec!["test".to_string()]),
+                score: 0,
+                accepted_answer_id: None,
+                author: Author::default(),
+                created_on: chrono::Utc::now(),
+                updated_on: None,
+                version: 1,
+            })
+        }
+
         async fn get_answers(
             &self,
             _question_id: QuestionId,
+            _sort: AnswerSort,
             _limit: Option<i32>,
             _offset: i32,
         ) -> Result<Vec<Answer>, handle_errors::Error> {
             Ok(vec![])
         }
-    }
 
-    #[async_trait::async_trait]
-    impl AnswerStoreTrait for Store {
-        async fn add_answer(
+        async fn get_answers_page(
             &self,
-            new_answer: NewAnswer,
-            _account_id: AccountId,
-        ) -> Result<Answer, handle_errors::Error> {
+            _question_id: QuestionId,
+            _cursor: Option<Cursor>,
+            _limit: i32,
+        ) -> Result<Page<Answer>, handle_errors::Error> {
+            Ok(Page { items: vec![], next_cursor: None })
+        }
+
+        async fn count_answers(
+            &self,
+            _question_id: QuestionId,
+        ) -> Result<i64, handle_errors::Error> {
+            Ok(0)
+        }
+
+        async fn get_question(
+            &self,
+            id: QuestionId,
+        ) -> Result<Question, handle_errors::Error> {
+            Ok(Question {
+                id,
+                title: "Test Question".to_string(),
+                content: "Test Content".to_string(),
+                tags: Some(vec!["test".to_string()]),
+                score: 0,
+                accepted_answer_id: None,
+                author: Author::default(),
+                created_on: chrono::Utc::now(),
+                updated_on: None,
+                version: 1,
+            })
+        }
+
+        async fn get_question_revisions(
+            &self,
+            _id: QuestionId,
+        ) -> Result<Vec<QuestionRevision>, handle_errors::Error> {
+            Ok(vec![])
+        }
+
+        async fn get_question_revision(
+            &self,
+            _id: QuestionId,
+            _revision_id: RevisionId,
+        ) -> Result<QuestionRevision, handle_errors::Error> {
+            Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound))
+        }
+
+        async fn vote_question(
+            &self,
+            id: QuestionId,
+            _account_id: AccountId,
+            _vote: Option<Vote>,
+        ) -> Result<Question, handle_errors::Error> {
+            self.restore_question(id, AccountId(1)).await
+        }
+
+        async fn accept_answer(
+            &self,
+            id: QuestionId,
+            _answer_id: AnswerId,
+        ) -> Result<Question, handle_errors::Error> {
+            self.restore_question(id, AccountId(1)).await
+        }
+
+        async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, handle_errors::Error> {
+            self.restore_question(id, AccountId(1)).await
+        }
+
+        async fn search(
+            &self,
+            _query: String,
+            _limit: Option<i32>,
+            _offset: i32,
+        ) -> Result<Vec<SearchResult>, handle_errors::Error> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AnswerStoreTrait for Store {
+        async fn add_answer(
+            &self,
+            new_answer: NewAnswer,
+            _account_id: AccountId,
+        ) -> Result<Answer, handle_errors::Error> {
             Ok(Answer {
                 id: AnswerId(1),
                 content: new_answer.content,
                 question_id: new_answer.question_id,
+                score: 0,
+                author: Author::default(),
+                created_on: chrono::Utc::now(),
+                updated_on: None,
+                version: 1,
             })
         }
 
@@ -339,6 +1063,63 @@ mod tests {
         ) -> Result<bool, handle_errors::Error> {
             Ok(true)
         }
+
+        async fn restore_answer(
+            &self,
+            id: i32,
+            _account_id: AccountId,
+        ) -> Result<Answer, handle_errors::Error> {
+            Ok(Answer {
+                id: AnswerId(id),
+                content: "Test Answer".to_string(),
+                question_id: QuestionId(1),
+                score: 0,
+                author: Author::default(),
+                created_on: chrono::Utc::now(),
+                updated_on: None,
+                version: 1,
+            })
+        }
+
+        async fn get_answer(
+            &self,
+            id: i32,
+        ) -> Result<Answer, handle_errors::Error> {
+            Ok(Answer {
+                id: AnswerId(id),
+                content: "Test Answer".to_string(),
+                question_id: QuestionId(1),
+                score: 0,
+                author: Author::default(),
+                created_on: chrono::Utc::now(),
+                updated_on: None,
+                version: 1,
+            })
+        }
+
+        async fn get_answer_revisions(
+            &self,
+            _id: i32,
+        ) -> Result<Vec<AnswerRevision>, handle_errors::Error> {
+            Ok(vec![])
+        }
+
+        async fn vote_answer(
+            &self,
+            id: i32,
+            _account_id: AccountId,
+            _vote: Option<Vote>,
+        ) -> Result<Answer, handle_errors::Error> {
+            self.get_answer(id).await
+        }
+
+        async fn get_answer_revision(
+            &self,
+            _id: i32,
+            _revision_id: RevisionId,
+        ) -> Result<AnswerRevision, handle_errors::Error> {
+            Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound))
+        }
     }
 
     #[async_trait::async_trait]
@@ -358,6 +1139,7 @@ mod tests {
                 id: Some(AccountId(1)),
                 email: "test@test.com".to_string(),
                 password: "password".to_string(),
+                display_name: None,
             })
         }
 
@@ -369,6 +1151,7 @@ mod tests {
             Ok(AccountResponse {
                 id: AccountId(1),
                 email: "updated@test.com".to_string(),
+                email_verified: false,
             })
         }
 
@@ -387,17 +1170,742 @@ mod tests {
             Ok(AccountResponse {
                 id: AccountId(1),
                 email: "test@test.com".to_string(),
+                email_verified: true,
+            })
+        }
+
+        async fn get_token_generation(
+            &self,
+            _account_id: AccountId,
+        ) -> Result<i32, handle_errors::Error> {
+            Ok(0)
+        }
+
+        async fn add_session(
+            &self,
+            _session: NewSession,
+            _token_hash: String,
+            _expires_on: DateTime<Utc>,
+        ) -> Result<SessionId, handle_errors::Error> {
+            Ok(SessionId(1))
+        }
+
+        async fn rotate_refresh_token(
+            &self,
+            _token_hash: String,
+            _new_token_hash: String,
+            _expires_on: DateTime<Utc>,
+        ) -> Result<(AccountId, SessionId), handle_errors::Error> {
+            Ok((AccountId(1), SessionId(1)))
+        }
+
+        async fn revoke_refresh_token(
+            &self,
+            _token_hash: String,
+        ) -> Result<bool, handle_errors::Error> {
+            Ok(true)
+        }
+
+        async fn touch_session(
+            &self,
+            _account_id: AccountId,
+            _session_id: SessionId,
+        ) -> Result<bool, handle_errors::Error> {
+            Ok(true)
+        }
+
+        async fn get_sessions(
+            &self,
+            _account_id: AccountId,
+        ) -> Result<Vec<ActiveSession>, handle_errors::Error> {
+            Ok(vec![])
+        }
+
+        async fn revoke_session(
+            &self,
+            _account_id: AccountId,
+            _session_id: SessionId,
+        ) -> Result<bool, handle_errors::Error> {
+            Ok(true)
+        }
+
+        async fn revoke_other_sessions(
+            &self,
+            _account_id: AccountId,
+            _session_id: SessionId,
+        ) -> Result<u64, handle_errors::Error> {
+            Ok(0)
+        }
+
+        async fn add_verification_token(
+            &self,
+            _account_id: AccountId,
+            _email: String,
+            _token_hash: String,
+            _expires_on: DateTime<Utc>,
+        ) -> Result<bool, handle_errors::Error> {
+            Ok(true)
+        }
+
+        async fn get_verification_sent_on(
+            &self,
+            _account_id: AccountId,
+        ) -> Result<Option<DateTime<Utc>>, handle_errors::Error> {
+            Ok(None)
+        }
+
+        async fn verify_email(
+            &self,
+            _token_hash: String,
+        ) -> Result<AccountId, handle_errors::Error> {
+            Ok(AccountId(1))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TagStoreTrait for Store {
+        async fn get_tags(
+            &self,
+            _limit: Option<i32>,
+            _offset: i32,
+        ) -> Result<Vec<Tag>, handle_errors::Error> {
+            Ok(vec![])
+        }
+
+        async fn get_tag_questions(
+            &self,
+            _slug: String,
+            _limit: Option<i32>,
+            _offset: i32,
+        ) -> Result<Vec<Question>, handle_errors::Error> {
+            Ok(vec![])
+        }
+
+        async fn add_tag_synonym(
+            &self,
+            slug: String,
+            synonym: String,
+        ) -> Result<Tag, handle_errors::Error> {
+            Ok(Tag {
+                slug,
+                questions: 0,
+                synonyms: vec![synonym],
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl CommentStoreTrait for Store {
+        async fn get_comments(
+            &self,
+            _parent: CommentParent,
+            _limit: Option<i32>,
+            _offset: i32,
+        ) -> Result<Vec<Comment>, handle_errors::Error> {
+            Ok(vec![])
+        }
+
+        async fn add_comment(
+            &self,
+            parent: CommentParent,
+            new_comment: NewComment,
+            account_id: AccountId,
+        ) -> Result<Comment, handle_errors::Error> {
+            let (question_id, answer_id) = match parent {
+                CommentParent::Question(id) => (Some(id), None),
+                CommentParent::Answer(id) => (None, Some(id)),
+            };
+            Ok(Comment {
+                id: CommentId(1),
+                content: new_comment.content,
+                question_id,
+                answer_id,
+                account_id,
+                created_on: chrono::Utc::now(),
+            })
+        }
+
+        async fn is_comment_owner(
+            &self,
+            _comment_id: CommentId,
+            _account_id: &AccountId,
+        ) -> Result<bool, handle_errors::Error> {
+            Ok(true)
+        }
+
+        async fn update_comment(
+            &self,
+            comment: NewComment,
+            id: CommentId,
+            account_id: AccountId,
+        ) -> Result<Comment, handle_errors::Error> {
+            Ok(Comment {
+                id,
+                content: comment.content,
+                question_id: Some(QuestionId(1)),
+                answer_id: None,
+                account_id,
+                created_on: chrono::Utc::now(),
+            })
+        }
+
+        async fn delete_comment(
+            &self,
+            _id: CommentId,
+            _account_id: AccountId,
+        ) -> Result<bool, handle_errors::Error> {
+            Ok(true)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Transactional for Store {
+        async fn begin(&self) -> Result<Self, handle_errors::Error> {
+            Ok(Store)
+        }
+
+        async fn commit(self) -> Result<(), handle_errors::Error> {
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AuditStoreTrait for Store {
+        async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, handle_errors::Error> {
+            Ok(AuditRecord {
+                id: AuditRecordId(1),
+                actor: record.actor,
+                action: record.action,
+                target: record.action.target(),
+                target_id: record.target_id,
+                before: record.before,
+                after: record.after,
+                request_id: record.request_id,
+                created_on: chrono::Utc::now(),
             })
         }
+
+        async fn get_audit_records(
+            &self,
+            _filter: AuditFilter,
+            _limit: Option<i32>,
+            _offset: i32,
+        ) -> Result<Vec<AuditRecord>, handle_errors::Error> {
+            Ok(vec![])
+        }
+    }
+
+    const IMPORT_LIMIT: u64 = 1024 * 1024;
+
+    fn test_keyring() -> keyring::Keyring {
+        keyring::Keyring::single(keyring::TokenMode::Local, "RANDOM_KEY_ONLY_USED_FOR_TESTS32").unwrap()
+    }
+
+    // The token of the last verification mail `mailer` sent.
+    fn verification_token(mailer: &mailer::MemoryMailer) -> serde_json::Value {
+        let body = mailer.sent().last().unwrap().body.clone();
+        let start = body.find('{').unwrap();
+        let end = body.find('}').unwrap();
+        serde_json::from_str(&body[start..=end]).unwrap()
     }
 
     #[tokio::test]
     async fn test_build_routes() {
         let store = Store;
-        let _routes = build_routes(store).await;
+        let _routes = build_routes(store, vec![], test_keyring(), Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
         // If we got here without panicking, the routes were built successfully
     }
 
+    #[tokio::test]
+    async fn test_memory_store_end_to_end() {
+        let store = store::memory::MemoryStore::new();
+        let store = store::cache::CachedStore::new(store, std::time::Duration::from_secs(60));
+        let mailer = mailer::MemoryMailer::new();
+        let routes = build_routes(store, vec![], test_keyring(), Arc::new(mailer.clone()), IMPORT_LIMIT).await;
+
+        let res = request()
+            .method("POST")
+            .path("/registration")
+            .json(&serde_json::json!({ "email": "test@test.com", "password": "password" }))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = request()
+            .method("POST")
+            .path("/login")
+            .json(&serde_json::json!({ "email": "test@test.com", "password": "password" }))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let tokens: Tokens = serde_json::from_slice(res.body()).unwrap();
+        let token = tokens.access_token;
+
+        let question = serde_json::json!({ "title": "How?", "content": "Please help!", "tags": ["general"] });
+        let res = request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", token.as_str())
+            .json(&question)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = request()
+            .method("POST")
+            .path("/accounts/verify")
+            .json(&verification_token(&mailer))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let account: AccountResponse = serde_json::from_slice(res.body()).unwrap();
+        assert!(account.email_verified);
+
+        let res = request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", token.as_str())
+            .json(&question)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = request().method("GET").path("/questions").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let questions: Vec<Question> = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].title, "How?");
+
+        let etag = res.headers()["etag"].clone();
+        let res = request()
+            .method("GET")
+            .path("/questions")
+            .header("If-None-Match", etag)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert!(res.body().is_empty());
+
+        let path = format!("/questions/{}?include=comments", questions[0].id.0);
+        let res = request().method("GET").path(&path).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let detail: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(detail["title"], "How?");
+        assert_eq!(detail["answer_count"], 0);
+        assert_eq!(detail["comments"], serde_json::json!([]));
+        assert!(detail.get("revisions").is_none());
+        assert_eq!(res.headers()["etag"], "\"1\"");
+
+        let path = format!("/questions/{}", questions[0].id.0);
+        let edit = serde_json::json!({ "id": questions[0].id.0, "title": "How?", "content": "Please help me!" });
+        let res = request()
+            .method("PUT")
+            .path(&path)
+            .header("Authorization", token.as_str())
+            .header("If-Match", "\"2\"")
+            .json(&edit)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::PRECONDITION_FAILED);
+
+        let res = request()
+            .method("PUT")
+            .path(&path)
+            .header("Authorization", token.as_str())
+            .header("If-Match", "\"1\"")
+            .json(&edit)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["etag"], "\"2\"");
+
+        // The edit is not hidden by the cached list.
+        let res = request().method("GET").path("/questions").reply(&routes).await;
+        let questions: Vec<Question> = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(questions[0].content, "Please help me!");
+
+        let res = request().method("GET").path("/questions/42").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_logout_and_revocation() {
+        let routes = build_routes(store::memory::MemoryStore::new(), vec![], test_keyring(), Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+
+        let account = serde_json::json!({ "email": "test@test.com", "password": "password" });
+        request().method("POST").path("/registration").json(&account).reply(&routes).await;
+        let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+        let login: Tokens = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(login.expires_in, 15 * 60);
+
+        let refresh = serde_json::json!({ "refresh_token": login.refresh_token });
+        let res = request().method("POST").path("/token/refresh").json(&refresh).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let tokens: Tokens = serde_json::from_slice(res.body()).unwrap();
+        assert_ne!(tokens.refresh_token, login.refresh_token);
+
+        // Refresh tokens are single use.
+        let res = request().method("POST").path("/token/refresh").json(&refresh).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let refresh = serde_json::json!({ "refresh_token": tokens.refresh_token });
+        let res = request().method("POST").path("/logout").json(&refresh).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = request().method("POST").path("/token/refresh").json(&refresh).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        // Logging out ends the session of the access token too.
+        let res = request()
+            .method("GET")
+            .path("/accounts/me")
+            .header("Authorization", tokens.access_token.as_str())
+            .reply(&routes)
+            .await;
+        assert_ne!(res.status(), StatusCode::OK);
+
+        // Changing the password revokes the access tokens issued before.
+        let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+        let tokens: Tokens = serde_json::from_slice(res.body()).unwrap();
+        let res = request()
+            .method("GET")
+            .path("/accounts/me")
+            .header("Authorization", tokens.access_token.as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = request()
+            .method("PUT")
+            .path("/accounts/update_password")
+            .header("Authorization", tokens.access_token.as_str())
+            .json(&"new password")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = request()
+            .method("GET")
+            .path("/accounts/me")
+            .header("Authorization", tokens.access_token.as_str())
+            .reply(&routes)
+            .await;
+        assert_ne!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_key_rotation() {
+        let store = store::memory::MemoryStore::new();
+        let old = keyring::Keyring::parse(keyring::TokenMode::Local, "old=RANDOM_KEY_ONLY_USED_FOR_TESTS32", None).unwrap();
+        let routes = build_routes(store.clone(), vec![], old, Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+        let account = serde_json::json!({ "email": "test@test.com", "password": "password" });
+        request().method("POST").path("/registration").json(&account).reply(&routes).await;
+        let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+        let token = serde_json::from_slice::<Tokens>(res.body()).unwrap().access_token;
+
+        // Tokens of the old key are still accepted once the new key is active.
+        let rotated = keyring::Keyring::parse(
+            keyring::TokenMode::Local,
+            "old=RANDOM_KEY_ONLY_USED_FOR_TESTS32,new=ANOTHER_KEY_ONLY_USED_FOR_TEST32",
+            Some("new".to_string()),
+        )
+        .unwrap();
+        let routes = build_routes(store.clone(), vec![], rotated.clone(), Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+        let res = request()
+            .method("GET")
+            .path("/accounts/me")
+            .header("Authorization", token.as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+        let new_token = serde_json::from_slice::<Tokens>(res.body()).unwrap().access_token;
+
+        // Retiring the old key rejects its tokens only.
+        let retired = rotated.retire(&["old".to_string()]).unwrap();
+        let routes = build_routes(store, vec![], retired, Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+        for (token, accepted) in [(token, false), (new_token, true)] {
+            let res = request()
+                .method("GET")
+                .path("/accounts/me")
+                .header("Authorization", token.as_str())
+                .reply(&routes)
+                .await;
+            assert_eq!(res.status() == StatusCode::OK, accepted);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_tokens() {
+        let seed = base64::encode(b"RANDOM_KEY_ONLY_USED_FOR_TESTS32");
+        let keyring = keyring::Keyring::parse(keyring::TokenMode::Public, &format!("k1={}", seed), None).unwrap();
+        let routes = build_routes(store::memory::MemoryStore::new(), vec![], keyring, Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+        let account = serde_json::json!({ "email": "test@test.com", "password": "password" });
+        request().method("POST").path("/registration").json(&account).reply(&routes).await;
+        let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+        let token = serde_json::from_slice::<Tokens>(res.body()).unwrap().access_token;
+        assert!(token.starts_with("v2.public."));
+
+        let res = request()
+            .method("GET")
+            .path("/accounts/me")
+            .header("Authorization", token.as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // Another service verifies the token with the published key alone.
+        let res = request().method("GET").path("/.well-known/paseto-keys").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let keys = serde_json::from_slice::<keyring::PublicKeys>(res.body()).unwrap();
+        assert_eq!(keys.keys.len(), 1);
+        assert_eq!(keys.keys[0].kid, "k1");
+        let public_key = base64::decode_config(&keys.keys[0].public_key, base64::URL_SAFE_NO_PAD).unwrap();
+        let claims = paseto::tokens::validate_public_token(
+            &token,
+            Some(r#"{"kid":"k1"}"#),
+            &paseto::tokens::PasetoPublicKey::ED25519PublicKey(&public_key),
+            &paseto::tokens::TimeBackend::Chrono,
+        )
+        .unwrap();
+        assert_eq!(claims["account_id"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_sessions() {
+        let routes = build_routes(store::memory::MemoryStore::new(), vec![], test_keyring(), Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+
+        let account = serde_json::json!({ "email": "test@test.com", "password": "password" });
+        request().method("POST").path("/registration").json(&account).reply(&routes).await;
+        let mut tokens = vec![];
+        for user_agent in ["laptop", "phone", "tablet"] {
+            let res = request()
+                .method("POST")
+                .path("/login")
+                .header("user-agent", user_agent)
+                .remote_addr("127.0.0.1:8080".parse().unwrap())
+                .json(&account)
+                .reply(&routes)
+                .await;
+            tokens.push(serde_json::from_slice::<Tokens>(res.body()).unwrap().access_token);
+        }
+
+        let res = request()
+            .method("GET")
+            .path("/accounts/me/sessions")
+            .header("Authorization", tokens[0].as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let sessions: Vec<ActiveSession> = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(sessions.len(), 3);
+        let current: Vec<_> = sessions.iter().filter(|session| session.current).collect();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].user_agent.as_deref(), Some("laptop"));
+        assert_eq!(current[0].ip.as_deref(), Some("127.0.0.1"));
+
+        // Sign the phone out.
+        let phone = sessions.iter().find(|session| session.user_agent.as_deref() == Some("phone")).unwrap();
+        let res = request()
+            .method("DELETE")
+            .path(&format!("/accounts/me/sessions/{}", phone.id.0))
+            .header("Authorization", tokens[0].as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = request()
+            .method("GET")
+            .path("/accounts/me")
+            .header("Authorization", tokens[1].as_str())
+            .reply(&routes)
+            .await;
+        assert_ne!(res.status(), StatusCode::OK);
+
+        // Signing out everywhere else leaves only the laptop.
+        let res = request()
+            .method("DELETE")
+            .path("/accounts/me/sessions")
+            .header("Authorization", tokens[0].as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(serde_json::from_slice::<u64>(res.body()).unwrap(), 1);
+        let res = request()
+            .method("GET")
+            .path("/accounts/me/sessions")
+            .header("Authorization", tokens[0].as_str())
+            .reply(&routes)
+            .await;
+        let sessions: Vec<ActiveSession> = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert!(sessions[0].current);
+    }
+
+    #[tokio::test]
+    async fn test_add_tag_synonym_admin_only() {
+        let routes = build_routes(store::memory::MemoryStore::new(), vec![AccountId(1)], test_keyring(), Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+
+        let mut tokens = Vec::new();
+        for email in ["admin@test.com", "user@test.com"] {
+            let account = serde_json::json!({ "email": email, "password": "password" });
+            request().method("POST").path("/registration").json(&account).reply(&routes).await;
+            let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+            tokens.push(serde_json::from_slice::<Tokens>(res.body()).unwrap().access_token);
+        }
+
+        let synonym = serde_json::json!({ "synonym": "rustlang" });
+        let res = request()
+            .method("POST")
+            .path("/tags/rust/synonyms")
+            .header("Authorization", tokens[1].as_str())
+            .json(&synonym)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = request()
+            .method("POST")
+            .path("/tags/rust/synonyms")
+            .header("Authorization", tokens[0].as_str())
+            .json(&synonym)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_registration_duplicate_email() {
+        let routes = build_routes(store::memory::MemoryStore::new(), vec![], test_keyring(), Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+
+        let account = serde_json::json!({ "email": "user@test.com", "password": "password" });
+        let res = request().method("POST").path("/registration").json(&account).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = request().method("POST").path("/registration").json(&account).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(res.body(), "Account already exists");
+    }
+
+    #[tokio::test]
+    async fn test_admin_import_export() {
+        let routes = build_routes(store::memory::MemoryStore::new(), vec![AccountId(1)], test_keyring(), Arc::new(mailer::MemoryMailer::new()), IMPORT_LIMIT).await;
+
+        let mut tokens = Vec::new();
+        for email in ["admin@test.com", "user@test.com"] {
+            let account = serde_json::json!({ "email": email, "password": "password" });
+            request().method("POST").path("/registration").json(&account).reply(&routes).await;
+            let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+            tokens.push(serde_json::from_slice::<Tokens>(res.body()).unwrap().access_token);
+        }
+
+        let csv = "id,question_id,title,content,tags\n1,,How?,Please help!,general\n,1,,Like this,\n";
+        let res = request()
+            .method("POST")
+            .path("/admin/import?format=csv")
+            .header("Authorization", tokens[1].as_str())
+            .body(csv)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = request()
+            .method("POST")
+            .path("/admin/import?format=csv")
+            .header("Authorization", tokens[0].as_str())
+            .body(vec![b'a'; IMPORT_LIMIT as usize + 1])
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let res = request()
+            .method("POST")
+            .path("/admin/import?format=csv")
+            .header("Authorization", tokens[0].as_str())
+            .body(csv.replace("How?", ""))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let report: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(report["errors"][0]["row"], 2);
+
+        let res = request()
+            .method("POST")
+            .path("/admin/import?format=csv")
+            .header("Authorization", tokens[0].as_str())
+            .body(csv)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let report: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(report, serde_json::json!({ "questions": 1, "answers": 1, "errors": [] }));
+
+        let res = request()
+            .method("GET")
+            .path("/admin/export")
+            .header("Authorization", tokens[0].as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "application/json");
+        let export: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(export["1"]["title"], "How?");
+        assert_eq!(export["1"]["answers"], serde_json::json!([{ "content": "Like this" }]));
+    }
+
+    #[tokio::test]
+    async fn test_admin_audit_log() {
+        let mailer = mailer::MemoryMailer::new();
+        let routes = build_routes(store::memory::MemoryStore::new(), vec![AccountId(1)], test_keyring(), Arc::new(mailer.clone()), IMPORT_LIMIT).await;
+
+        let account = serde_json::json!({ "email": "admin@test.com", "password": "password" });
+        request().method("POST").path("/registration").json(&account).reply(&routes).await;
+        request().method("POST").path("/accounts/verify").json(&verification_token(&mailer)).reply(&routes).await;
+        let res = request().method("POST").path("/login").json(&account).reply(&routes).await;
+        let token = serde_json::from_slice::<Tokens>(res.body()).unwrap().access_token;
+
+        let question = serde_json::json!({ "title": "How?", "content": "Please help!" });
+        let res = request()
+            .method("POST")
+            .path("/questions")
+            .header("Authorization", token.as_str())
+            .header("X-Request-Id", "req-42")
+            .json(&question)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = request()
+            .method("GET")
+            .path("/admin/audit?target=question")
+            .header("Authorization", token.as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let records: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(records.as_array().unwrap().len(), 1);
+        assert_eq!(records[0]["action"], "add_question");
+        assert_eq!(records[0]["actor"], 1);
+        assert_eq!(records[0]["request_id"], "req-42");
+        assert_eq!(records[0]["before"], serde_json::Value::Null);
+        assert_eq!(records[0]["after"]["title"], "How?");
+
+        // Registering and verifying are recorded too, newest first.
+        let res = request()
+            .method("GET")
+            .path("/admin/audit")
+            .header("Authorization", token.as_str())
+            .reply(&routes)
+            .await;
+        let records: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(records[1]["action"], "verify_email");
+        assert_eq!(records[1]["after"]["emailVerified"], true);
+        assert_eq!(records[2]["action"], "add_account");
+        assert_eq!(records[2]["after"]["email"], "admin@test.com");
+        assert!(records[2]["after"].get("password").is_none());
+
+        let res = request()
+            .method("GET")
+            .path("/admin/audit?action=drop_table")
+            .header("Authorization", token.as_str())
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     #[tokio::test]
     async fn test_setup_store_invalid_config() {
         let result = setup_store(&Config {
@@ -408,8 +1916,62 @@ mod tests {
             db_name: "invalid".to_string(),
             port: 8080,
             log_level: "info".to_string(),
+            database_url: None,
+            store: config::StoreKind::Database,
+            retention_days: 30,
+            cache_ttl: 5,
+            admins: vec![],
+            import_limit_mb: 16,
+            command: None,
+            paseto_key_id: None,
+            paseto_retired_keys: vec![],
+            token_mode: keyring::TokenMode::Local,
+            mailer: config::MailerKind::File,
+            mail_from: "no-reply@localhost".to_string(),
+            mail_dir: "mail".to_string(),
+            smtp_host: "localhost".to_string(),
+            smtp_port: 25,
+            smtp_user: None,
+            smtp_password: None,
+            keyring: keyring::Keyring::default(),
         })
         .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_setup_store_unsupported_scheme() {
+        let result = setup_store(&Config {
+            db_user: "invalid".to_string(),
+            db_password: "invalid".to_string(),
+            db_host: "invalid".to_string(),
+            db_port: 5432,
+            db_name: "invalid".to_string(),
+            port: 8080,
+            log_level: "info".to_string(),
+            database_url: Some("mysql://localhost/rust_hour".to_string()),
+            store: config::StoreKind::Database,
+            retention_days: 30,
+            cache_ttl: 5,
+            admins: vec![],
+            import_limit_mb: 16,
+            command: None,
+            paseto_key_id: None,
+            paseto_retired_keys: vec![],
+            token_mode: keyring::TokenMode::Local,
+            mailer: config::MailerKind::File,
+            mail_from: "no-reply@localhost".to_string(),
+            mail_dir: "mail".to_string(),
+            smtp_host: "localhost".to_string(),
+            smtp_port: 25,
+            smtp_user: None,
+            smtp_password: None,
+            keyring: keyring::Keyring::default(),
+        })
+        .await;
+        assert!(matches!(
+            result,
+            Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::Configuration(_)))
+        ));
+    }
 }
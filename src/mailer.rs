@@ -0,0 +1,200 @@
+//! Sending email, see `Mailer`.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use rand::Rng;
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use handle_errors::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// A plain text email.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Mail {
+    /// The mail as an RFC 5322 message from `from`, with CRLF line endings.
+    /// Fails if a header would span several lines, which would let the
+    /// address or the subject add headers of their own.
+    pub fn to_message(&self, from: &str) -> Result<String, Error> {
+        for header in [from, &self.to, &self.subject] {
+            if header.contains(['\r', '\n']) {
+                return Err(Error::MailError(format!("{:?} is not a valid header", header)));
+            }
+        }
+        let body = self.body.lines().collect::<Vec<_>>().join("\r\n");
+        Ok(format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            from,
+            self.to,
+            self.subject,
+            Utc::now().to_rfc2822(),
+            body
+        ))
+    }
+}
+
+/// Sends the mails of the server, like the links to verify email addresses.
+#[async_trait::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), Error>;
+}
+
+/// Sends mail through an SMTP relay.
+///
+/// The connection is not encrypted, so the relay should run next to the
+/// server, like a local MTA or a sidecar which forwards the mail over TLS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtpMailer {
+    pub host: String,
+    pub port: u16,
+    /// Address the mails are sent from.
+    pub from: String,
+    /// User name and password for `AUTH PLAIN`, if the relay wants them.
+    pub credentials: Option<(String, String)>,
+    /// How long a mail may take from connecting to `QUIT`, so a relay which
+    /// stops answering does not hold up the request sending the mail.
+    pub timeout: Duration,
+}
+
+/// The `timeout` of the `SmtpMailer` of the server.
+pub const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[async_trait::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Error> {
+        let message = mail.to_message(&self.from)?;
+        tokio::time::timeout(self.timeout, self.exchange(&mail, message))
+            .await
+            .map_err(|_| {
+                Error::MailError(format!("{}:{}: timed out after {:?}", self.host, self.port, self.timeout))
+            })?
+    }
+}
+
+impl SmtpMailer {
+    // Hands `message` for `mail` over to the relay.
+    async fn exchange(&self, mail: &Mail, message: String) -> Result<(), Error> {
+        let stream = TcpStream::connect((self.host.as_str(), self.port))
+            .await
+            .map_err(|e| Error::MailError(format!("{}:{}: {}", self.host, self.port, e)))?;
+        let mut stream = BufReader::new(stream);
+
+        reply(&mut stream, 220).await?;
+        command(&mut stream, "EHLO localhost", 250).await?;
+        if let Some((user, password)) = &self.credentials {
+            let auth = base64::encode(format!("\0{}\0{}", user, password));
+            command(&mut stream, &format!("AUTH PLAIN {}", auth), 235).await?;
+        }
+        command(&mut stream, &format!("MAIL FROM:<{}>", self.from), 250).await?;
+        command(&mut stream, &format!("RCPT TO:<{}>", mail.to), 250).await?;
+        command(&mut stream, "DATA", 354).await?;
+        // Lines starting with a dot get another one, so they do not end
+        // the message.
+        let message = message
+            .split("\r\n")
+            .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        command(&mut stream, &format!("{}.", message), 250).await?;
+        command(&mut stream, "QUIT", 221).await
+    }
+}
+
+// Sends a line to the SMTP server and reads its reply, which has to have
+// the status `expected`.
+async fn command<S>(stream: &mut BufReader<S>, line: &str, expected: u16) -> Result<(), Error>
+where
+    S: tokio::io::AsyncRead + AsyncWrite + Unpin,
+{
+    let stream_error = |e: std::io::Error| Error::MailError(e.to_string());
+    stream.get_mut().write_all(line.as_bytes()).await.map_err(stream_error)?;
+    stream.get_mut().write_all(b"\r\n").await.map_err(stream_error)?;
+    stream.get_mut().flush().await.map_err(stream_error)?;
+    reply(stream, expected).await
+}
+
+// Reads a reply of the SMTP server, which spans several lines as long as
+// a dash follows their status.
+async fn reply<S>(stream: &mut BufReader<S>, expected: u16) -> Result<(), Error>
+where
+    S: tokio::io::AsyncRead + Unpin,
+{
+    loop {
+        let mut line = String::new();
+        stream
+            .read_line(&mut line)
+            .await
+            .map_err(|e| Error::MailError(e.to_string()))?;
+        let status = line.get(..3).and_then(|status| status.parse::<u16>().ok());
+        if status != Some(expected) {
+            return Err(Error::MailError(format!("SMTP server replied {:?}", line.trim_end())));
+        }
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+/// Writes every mail into a `.eml` file in a directory instead of sending
+/// it, for development.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMailer {
+    pub dir: PathBuf,
+    /// Address the mails are sent from.
+    pub from: String,
+}
+
+#[async_trait::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Error> {
+        let message = mail.to_message(&self.from)?;
+        let file_error = |e: std::io::Error| Error::MailError(format!("{}: {}", self.dir.display(), e));
+        tokio::fs::create_dir_all(&self.dir).await.map_err(file_error)?;
+        // Files sort by the time their mail was sent.
+        let name = format!(
+            "{}-{:08x}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            rand::thread_rng().gen::<u32>()
+        );
+        tokio::fs::write(self.dir.join(name), message).await.map_err(file_error)
+    }
+}
+
+/// Keeps the mails in memory instead of sending them, for tests.
+///
+/// Cloning a `MemoryMailer` is cheap and every clone shares the same mails.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMailer {
+    sent: Arc<Mutex<Vec<Mail>>>,
+}
+
+impl MemoryMailer {
+    pub fn new() -> Self {
+        MemoryMailer::default()
+    }
+
+    /// The mails sent so far, oldest first.
+    pub fn sent(&self) -> Vec<Mail> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for MemoryMailer {
+    async fn send(&self, mail: Mail) -> Result<(), Error> {
+        self.sent.lock().unwrap().push(mail);
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use super::*;
+
+fn test_mail() -> Mail {
+    Mail {
+        to: "test@test.com".to_string(),
+        subject: "Hello".to_string(),
+        body: "First line\n.second line".to_string(),
+    }
+}
+
+#[test]
+fn test_to_message() {
+    let message = test_mail().to_message("no-reply@test.com").unwrap();
+    assert!(message.starts_with("From: no-reply@test.com\r\nTo: test@test.com\r\nSubject: Hello\r\n"));
+    assert!(message.ends_with("\r\n\r\nFirst line\r\n.second line\r\n"));
+}
+
+#[test]
+fn test_to_message_rejects_header_injection() {
+    let mail = Mail {
+        to: "test@test.com\r\nBcc: other@test.com".to_string(),
+        ..test_mail()
+    };
+    assert!(matches!(mail.to_message("no-reply@test.com"), Err(Error::MailError(_))));
+}
+
+#[tokio::test]
+async fn test_memory_mailer() {
+    let mailer = MemoryMailer::new();
+    mailer.clone().send(test_mail()).await.unwrap();
+    assert_eq!(mailer.sent(), vec![test_mail()]);
+}
+
+#[tokio::test]
+async fn test_file_mailer() {
+    let dir = std::env::temp_dir().join(format!("rust_hour_mail_{}", rand::thread_rng().gen::<u32>()));
+    let mailer = FileMailer {
+        dir: dir.clone(),
+        from: "no-reply@test.com".to_string(),
+    };
+    mailer.send(test_mail()).await.unwrap();
+
+    let files = std::fs::read_dir(&dir).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(files.len(), 1);
+    let message = std::fs::read_to_string(files[0].path()).unwrap();
+    assert!(message.contains("To: test@test.com\r\n"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_smtp_mailer() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // A relay which accepts everything and keeps what it was sent.
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+        stream.get_mut().write_all(b"220 test ESMTP\r\n").await.unwrap();
+        let mut received = Vec::new();
+        let mut in_data = false;
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            let line = line.trim_end().to_string();
+            let reply: &[u8] = if in_data {
+                if line == "." {
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else {
+                    b""
+                }
+            } else if line.starts_with("EHLO") {
+                b"250-test\r\n250 AUTH PLAIN\r\n"
+            } else if line.starts_with("AUTH") {
+                b"235 ok\r\n"
+            } else if line == "DATA" {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line == "QUIT" {
+                b"221 bye\r\n"
+            } else {
+                b"250 ok\r\n"
+            };
+            received.push(line);
+            stream.get_mut().write_all(reply).await.unwrap();
+        }
+        received
+    });
+
+    let mailer = SmtpMailer {
+        host: "127.0.0.1".to_string(),
+        port,
+        from: "no-reply@test.com".to_string(),
+        credentials: Some(("user".to_string(), "password".to_string())),
+        timeout: SMTP_TIMEOUT,
+    };
+    mailer.send(test_mail()).await.unwrap();
+
+    let received = server.await.unwrap();
+    assert_eq!(received[0], "EHLO localhost");
+    assert_eq!(received[1], format!("AUTH PLAIN {}", base64::encode("\0user\0password")));
+    assert_eq!(received[2], "MAIL FROM:<no-reply@test.com>");
+    assert_eq!(received[3], "RCPT TO:<test@test.com>");
+    assert_eq!(received[4], "DATA");
+    // The line starting with a dot is escaped.
+    assert!(received.contains(&"..second line".to_string()));
+    assert_eq!(received[received.len() - 2], ".");
+    assert_eq!(received[received.len() - 1], "QUIT");
+}
+
+#[tokio::test]
+async fn test_smtp_mailer_rejected() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.write_all(b"554 no service\r\n").await.unwrap();
+    });
+
+    let mailer = SmtpMailer {
+        host: "127.0.0.1".to_string(),
+        port,
+        from: "no-reply@test.com".to_string(),
+        credentials: None,
+        timeout: SMTP_TIMEOUT,
+    };
+    assert!(matches!(mailer.send(test_mail()).await, Err(Error::MailError(_))));
+}
+
+#[tokio::test]
+async fn test_smtp_mailer_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    // A relay which takes the connection but never greets.
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        drop(stream);
+    });
+
+    let mailer = SmtpMailer {
+        host: "127.0.0.1".to_string(),
+        port,
+        from: "no-reply@test.com".to_string(),
+        credentials: None,
+        timeout: Duration::from_millis(100),
+    };
+    assert!(matches!(mailer.send(test_mail()).await, Err(Error::MailError(_))));
+    server.abort();
+}
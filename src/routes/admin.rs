@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use futures::TryStreamExt;
+use tracing::{event, instrument, Level};
+use warp::http::{header, HeaderValue, StatusCode};
+use warp::hyper::body::{Body, Bytes};
+
+use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
+use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
+use crate::types::account::{AccountId, Session};
+use crate::types::answer::NewAnswer;
+use crate::types::bulk::{
+    export_footer, export_header, export_record, extract_format, parse_records, AnswerRecord,
+    Format, ImportReport, QuestionRecord,
+};
+use crate::types::filter::{AnswerSort, QuestionFilter};
+use crate::types::pagination::Cursor;
+use crate::types::question::NewQuestion;
+use crate::handle_errors;
+use super::transaction::Transactional;
+
+#[cfg(test)]
+mod tests;
+
+/// Number of questions an export reads from the store at a time.
+const EXPORT_PAGE_SIZE: i32 = 100;
+
+/// Imports the questions of `input`, with their answers, in one transaction
+/// and attributes them to `account_id`. Every record is validated first; if
+/// any is invalid nothing is imported and the report lists the errors.
+pub async fn import_input<S>(
+    store: &S,
+    input: &str,
+    format: Format,
+    account_id: AccountId,
+) -> Result<ImportReport, handle_errors::Error>
+where
+    S: QuestionStoreTrait + AnswerStoreTrait + Transactional,
+{
+    let (records, errors) = parse_records(input, format);
+    let mut report = ImportReport {
+        errors,
+        ..ImportReport::default()
+    };
+    if !report.errors.is_empty() {
+        return Ok(report);
+    }
+    let tx = store.begin().await?;
+    for record in records {
+        let new_question = NewQuestion {
+            title: record.title,
+            content: record.content,
+            tags: record.tags,
+        };
+        let question = tx.add_question(new_question, account_id.clone()).await?;
+        report.questions += 1;
+        for answer in record.answers {
+            let new_answer = NewAnswer {
+                content: answer.content,
+                question_id: question.id,
+            };
+            tx.add_answer(new_answer, account_id.clone()).await?;
+            report.answers += 1;
+        }
+    }
+    tx.commit().await?;
+    Ok(report)
+}
+
+/**
+ * @Notice Import questions
+ *
+ * @Dev Imports questions with their answers from the request body, all or nothing, as posts of the admin; if a record is invalid the reply is 422 with an error for every invalid row.
+ *
+ * @params  `session`: The authenticated admin session object.
+ * @params `params`: Query parameters, the `format` of the body.
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `body`: The questions to import.
+*/
+#[instrument(skip(body))]
+pub async fn import<S>(
+    session: Session,
+    params: HashMap<String, String>,
+    store: S,
+    body: Bytes,
+) -> Result<impl warp::Reply, warp::Rejection>
+where
+    S: QuestionStoreTrait + AnswerStoreTrait + Transactional,
+{
+    event!(target: "rust_hour", Level::INFO, "importing questions");
+    let format = extract_format(&params)?;
+    let input = std::str::from_utf8(&body)
+        .map_err(|_| handle_errors::Error::InvalidParameter("body".to_string()))?;
+    let report = import_input(&store, input, format, session.account_id).await?;
+    let status = if report.errors.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
+// How far an export has got.
+enum ExportState {
+    Header,
+    Questions { cursor: Option<Cursor>, first: bool },
+    Footer,
+    Done,
+}
+
+// Writes the next part of an export, a page of questions at a time.
+async fn export_chunk<S: QuestionStoreTrait>(
+    store: &S,
+    format: Format,
+    state: ExportState,
+) -> Result<Option<(String, ExportState)>, handle_errors::Error> {
+    match state {
+        ExportState::Header => {
+            let next = ExportState::Questions { cursor: None, first: true };
+            Ok(Some((export_header(format), next)))
+        }
+        ExportState::Questions { cursor, mut first } => {
+            let page = store
+                .get_questions_page(QuestionFilter::default(), cursor, EXPORT_PAGE_SIZE)
+                .await?;
+            let mut chunk = String::new();
+            for question in page.items {
+                let answers = store
+                    .get_answers(question.id, AnswerSort::Oldest, None, 0)
+                    .await?;
+                let record = QuestionRecord {
+                    id: Some(question.id.0.to_string()),
+                    title: question.title,
+                    content: question.content,
+                    tags: question.tags,
+                    answers: answers
+                        .into_iter()
+                        .map(|answer| AnswerRecord { content: answer.content })
+                        .collect(),
+                };
+                chunk.push_str(&export_record(format, &record, first));
+                first = false;
+            }
+            let next = match page.next_cursor {
+                Some(cursor) => ExportState::Questions { cursor: Some(cursor), first },
+                None => ExportState::Footer,
+            };
+            Ok(Some((chunk, next)))
+        }
+        ExportState::Footer => Ok(Some((export_footer(format), ExportState::Done))),
+        ExportState::Done => Ok(None),
+    }
+}
+
+/**
+ * @Notice Export questions
+ *
+ * @Dev Streams every live question with its answers, oldest first, in the `format` `/admin/import` reads; questions are read a page at a time, so it is not a snapshot.
+ *
+ * @params  `session`: The authenticated admin session object.
+ * @params `params`: Query parameters, the `format` of the export.
+ * @params  `store`: A `Store` instance used to interact with the database.
+*/
+#[instrument]
+pub async fn export<S>(
+    _session: Session,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection>
+where
+    S: QuestionStoreTrait + Send + Sync + 'static,
+{
+    event!(target: "rust_hour", Level::INFO, "exporting questions");
+    let format = extract_format(&params)?;
+    let chunks = futures::stream::try_unfold(ExportState::Header, move |state| {
+        let store = store.clone();
+        async move { export_chunk(&store, format, state).await }
+    })
+    .map_err(|e| {
+        // The status has been sent already, so the export just stops short.
+        event!(Level::ERROR, "export failed: {}", e);
+        std::io::Error::other(e.to_string())
+    });
+    let mut res = warp::reply::Response::new(Body::wrap_stream(chunks));
+    res.headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+    Ok(res)
+}
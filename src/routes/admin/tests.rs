@@ -0,0 +1,86 @@
+use chrono::prelude::*;
+use warp::Reply;
+
+use super::*;
+use crate::store::memory::MemoryStore;
+use crate::types::account::SessionId;
+
+fn create_test_session() -> Session {
+    Session {
+        account_id: AccountId(1),
+        exp: Utc::now() + chrono::Duration::days(1),
+        nbf: Utc::now(),
+        generation: 0,
+        session_id: SessionId(1),
+    }
+}
+
+async fn count_questions(store: &MemoryStore) -> usize {
+    store
+        .get_questions(QuestionFilter::default(), None, 0)
+        .await
+        .unwrap()
+        .len()
+}
+
+#[tokio::test]
+async fn test_import_is_all_or_nothing() {
+    let store = MemoryStore::new();
+    let input = concat!(
+        "{\"title\": \"How?\", \"content\": \"Please help!\", \"answers\": [{\"content\": \"Like this\"}]}\n",
+        "{\"title\": \"Why?\", \"content\": \"\"}\n",
+    );
+
+    let report = import_input(&store, input, Format::Ndjson, AccountId(1)).await.unwrap();
+    assert_eq!(report.questions, 0);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].row, 2);
+    assert_eq!(count_questions(&store).await, 0);
+
+    let input = input.lines().next().unwrap();
+    let report = import_input(&store, input, Format::Ndjson, AccountId(1)).await.unwrap();
+    assert_eq!((report.questions, report.answers), (1, 1));
+    let questions = store.get_questions(QuestionFilter::default(), None, 0).await.unwrap();
+    assert_eq!(questions[0].author.id, AccountId(1));
+    let answers = store.get_answers(questions[0].id, AnswerSort::Oldest, None, 0).await.unwrap();
+    assert_eq!(answers[0].content, "Like this");
+}
+
+#[tokio::test]
+async fn test_import_rejects_unknown_format() {
+    let mut params = HashMap::new();
+    params.insert("format".to_string(), "xml".to_string());
+
+    let result = import(create_test_session(), params, MemoryStore::new(), Bytes::new()).await;
+    let rejection = result.err().unwrap();
+    let error = rejection.find::<handle_errors::Error>().unwrap();
+    assert!(matches!(error, handle_errors::Error::InvalidParameter(name) if name == "format"));
+}
+
+#[tokio::test]
+async fn test_export_streams_every_page() {
+    let store = MemoryStore::new();
+    let questions = EXPORT_PAGE_SIZE as usize * 2 + 1;
+    let input: String = (0..questions)
+        .map(|n| format!("{{\"title\": \"Question {}\", \"content\": \"Please help!\"}}\n", n))
+        .collect();
+    import_input(&store, &input, Format::Ndjson, AccountId(1)).await.unwrap();
+
+    let mut params = HashMap::new();
+    params.insert("format".to_string(), "ndjson".to_string());
+    let res = export(create_test_session(), params, store.clone())
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(res.headers()["content-type"], "application/x-ndjson");
+    let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let body = std::str::from_utf8(&body).unwrap();
+    assert_eq!(body.lines().count(), questions);
+    assert!(body.lines().last().unwrap().contains(&format!("Question {}", questions - 1)));
+
+    // An export imports back as it was.
+    let copy = MemoryStore::new();
+    let report = import_input(&copy, body, Format::Ndjson, AccountId(1)).await.unwrap();
+    assert_eq!(report.questions, questions);
+    assert_eq!(count_questions(&copy).await, questions);
+}
@@ -1,11 +1,17 @@
+use std::collections::HashMap;
+
 use warp::http::StatusCode;
 
 use crate::types::account::Session;
 use crate::types::answer::{Answer, NewAnswer};
+use crate::types::etag::{etag, extract_if_match};
+use crate::types::revision::{extract_diff_range, AnswerDiff, RevisionId};
+use crate::types::vote::{NewVote, Vote};
 use crate::handle_errors;
 
 pub mod store_trait;
 use store_trait::StoreTrait;
+use super::transaction::Transactional;
 
 #[cfg(test)]
 mod tests;
@@ -40,35 +46,90 @@ pub async fn add_answer<S: StoreTrait>(
     }
 }
 
+// A missing or deleted answer, or a missing revision of it, is a 404 rather
+// than a failed query.
+fn answer_not_found(error: handle_errors::Error) -> handle_errors::Error {
+    match error {
+        handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound) => {
+            handle_errors::Error::NotFound
+        }
+        error => error,
+    }
+}
+
+/**
+ * @Notice Get answer
+ *
+ * @Dev Returns an answer with its version as the `ETag` header.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the answer
+*/
+pub async fn get_answer<S: StoreTrait>(
+    id: i32,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_answer(id).await {
+        Ok(res) => Ok(warp::reply::with_header(
+            warp::reply::json(&res),
+            "ETag",
+            etag(res.version),
+        )),
+        Err(e) => Err(warp::reject::custom(answer_not_found(e))),
+    }
+}
+
 /**
  * @Notice Update answer
  *
  * @Dev Allows a user to update an existing answer, provided they are the owner.
+ *      With an `If-Match` header the update only goes through while the
+ *      answer is still at that version, and fails with 412 otherwise.
  *
  * @params  `store`: A `Store` instance used to interact with the database.
  * @params `id`: The ID of the answer to be updated.
  * @params `session`: The authenticated user session object.
+ * @params `if_match`: The optional `If-Match` header, the `ETag` the answer was read with.
  * @params `answer`: The updated answer details.
 */
-pub async fn update_answer<S: StoreTrait>(
+pub async fn update_answer<S: StoreTrait + Transactional>(
     id: i32,
     session: Session,
     store: S,
+    if_match: Option<String>,
     answer: Answer,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     // Extract the account ID from the session for authorization.
     let account_id = session.account_id;
+    let expected_version = extract_if_match(if_match)?;
+    // Run the ownership check, the version check and the update in one transaction.
+    let tx = store.begin().await?;
     // Check if the currently logged-in user owns the answer they're trying to delete.
-    if store.is_answer_owner(id, &account_id).await? {
+    if tx.is_answer_owner(id, &account_id).await? {
+        // Refuse to overwrite an edit the user has not seen.
+        if let Some(expected_version) = expected_version {
+            if tx.get_answer(id).await?.version != expected_version {
+                return Err(warp::reject::custom(handle_errors::Error::PreconditionFailed));
+            }
+        }
         // Update the answer object with the provided details.
         let answer = Answer {
             id: answer.id,
             content: answer.content,
             question_id: answer.question_id,
+            score: answer.score,
+            author: answer.author,
+            created_on: answer.created_on,
+            updated_on: answer.updated_on,
+            version: answer.version,
         };
         // Delegate the answer update to the `store`.
-        match store.update_answer(answer, id, account_id).await {
-            Ok(res) => Ok(warp::reply::json(&res)),
+        match tx.update_answer(answer, id, account_id).await {
+            Ok(res) => {
+                tx.commit().await?;
+                let etag = etag(res.version);
+                Ok(warp::reply::with_header(warp::reply::json(&res), "ETag", etag))
+            }
             Err(e) => Err(warp::reject::custom(e)),
         }
     } else {
@@ -85,24 +146,211 @@ pub async fn update_answer<S: StoreTrait>(
  * @params `session`: The authenticated user session object.
  * @params `id`: The ID of the answer to be updated.
 */
-pub async fn delete_answer<S: StoreTrait>(
+pub async fn delete_answer<S: StoreTrait + Transactional>(
     id: i32,
     session: Session,
     store: S,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     // Extract the account ID from the session for authorization.
     let account_id = session.account_id;
+    // Run the ownership check and the delete in one transaction.
+    let tx = store.begin().await?;
     // Check if the currently logged-in user owns the answer they're trying to delete.
-    if store.is_answer_owner(id, &account_id).await? {
+    if tx.is_answer_owner(id, &account_id).await? {
         // User is authorized to delete the answer.
-        match store.delete_answer(id, account_id).await {
-            Ok(_) => Ok(warp::reply::with_status(
-                format!("Answer {} deleted", id),
-                StatusCode::OK,
-            )),
+        match tx.delete_answer(id, account_id).await {
+            Ok(_) => {
+                tx.commit().await?;
+                Ok(warp::reply::with_status(
+                    format!("Answer {} deleted", id),
+                    StatusCode::OK,
+                ))
+            }
             Err(e) => Err(warp::reject::custom(e)),
         }
     } else {
         Err(warp::reject::custom(handle_errors::Error::Unauthorized))
     }
 }
+
+/**
+ * @Notice Restore answer
+ *
+ * @Dev Allows the owner to bring back a deleted answer until it is purged.
+ *      Answers of a deleted question come back by restoring the question.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the answer to be restored.
+*/
+pub async fn restore_answer<S: StoreTrait + Transactional>(
+    id: i32,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    match tx.restore_answer(id, account_id).await {
+        Ok(res) => {
+            tx.commit().await?;
+            Ok(warp::reply::json(&res))
+        }
+        Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)) => {
+            Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+// Sets or, with `None`, retracts the vote of the session's account.
+async fn set_answer_vote<S: StoreTrait + Transactional>(
+    id: i32,
+    session: Session,
+    store: S,
+    vote: Option<Vote>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    if tx.is_answer_owner(id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::SelfVote));
+    }
+    match tx.vote_answer(id, account_id, vote).await {
+        Ok(res) => {
+            tx.commit().await?;
+            Ok(warp::reply::json(&res))
+        }
+        Err(e) => Err(warp::reject::custom(answer_not_found(e))),
+    }
+}
+
+/**
+ * @Notice Vote on answer
+ *
+ * @Dev Casts an up or down vote, or changes the one cast before; an account has one vote per answer.
+ *      Voting on your own answer is refused with 403 Forbidden.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the answer
+ * @params `new_vote`: The vote, `up` or `down`.
+*/
+pub async fn vote_answer<S: StoreTrait + Transactional>(
+    id: i32,
+    session: Session,
+    store: S,
+    new_vote: NewVote,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_answer_vote(id, session, store, Some(new_vote.vote)).await
+}
+
+/**
+ * @Notice Retract answer vote
+ *
+ * @Dev Removes the vote the account cast on an answer, if any.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the answer
+*/
+pub async fn retract_answer_vote<S: StoreTrait + Transactional>(
+    id: i32,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_answer_vote(id, session, store, None).await
+}
+
+/**
+ * @Notice Get answer revisions
+ *
+ * @Dev Lists the earlier versions of an answer, oldest first. Each revision
+ *      holds the content before an edit, with the editor and time.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the answer
+*/
+pub async fn get_answer_revisions<S: StoreTrait>(
+    id: i32,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // The revisions of a deleted answer are hidden along with it.
+    store.get_answer(id).await.map_err(answer_not_found)?;
+    match store.get_answer_revisions(id).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Diff answer revisions
+ *
+ * @Dev Returns a line-level diff of the content between the revisions `from`
+ *      and `to`, or between `from` and the current version when `to` is left out.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the answer
+ * @params `params`: Query parameters `from` and optional `to`.
+*/
+pub async fn get_answer_diff<S: StoreTrait>(
+    id: i32,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (from, to) = extract_diff_range(&params)?;
+    let current = store.get_answer(id).await.map_err(answer_not_found)?;
+    let old = store
+        .get_answer_revision(id, from)
+        .await
+        .map_err(answer_not_found)?
+        .content;
+    let new = match to {
+        Some(to) => store
+            .get_answer_revision(id, to)
+            .await
+            .map_err(answer_not_found)?
+            .content,
+        None => current.content,
+    };
+    Ok(warp::reply::json(&AnswerDiff::new(from, to, &old, &new)))
+}
+
+/**
+ * @Notice Roll back answer
+ *
+ * @Dev Allows the owner to restore the content of an earlier revision. The
+ *      rollback is an edit itself, so the version it replaces becomes a new
+ *      revision.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the answer
+ * @params `revision_id`: The ID of the revision to go back to.
+ * @params `session`: The authenticated user session object.
+*/
+pub async fn rollback_answer<S: StoreTrait + Transactional>(
+    id: i32,
+    revision_id: RevisionId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    if !tx.is_answer_owner(id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+    let revision = tx
+        .get_answer_revision(id, revision_id)
+        .await
+        .map_err(answer_not_found)?;
+    let answer = Answer {
+        content: revision.content,
+        ..tx.get_answer(id).await?
+    };
+    match tx.update_answer(answer, id, account_id).await {
+        Ok(res) => {
+            tx.commit().await?;
+            Ok(warp::reply::json(&res))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use crate::types::account::AccountId;
 use crate::types::answer::{Answer, NewAnswer};
+use crate::types::revision::{AnswerRevision, RevisionId};
+use crate::types::vote::Vote;
 use crate::handle_errors;
 
 #[async_trait]
@@ -9,4 +11,9 @@ pub trait StoreTrait: Clone {
     async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
     async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
     async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+    async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
+    async fn get_answer(&self, id: i32) -> Result<Answer, handle_errors::Error>;
+    async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, handle_errors::Error>;
+    async fn vote_answer(&self, id: i32, account_id: AccountId, vote: Option<Vote>) -> Result<Answer, handle_errors::Error>;
+    async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, handle_errors::Error>;
 } 
\ No newline at end of file
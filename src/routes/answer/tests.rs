@@ -5,8 +5,11 @@ use chrono::prelude::*;
 use crate::types::account::{AccountId, Session};
 use crate::types::answer::{Answer, AnswerId, NewAnswer};
 use crate::types::question::QuestionId;
+use crate::types::revision::{AnswerRevision, RevisionId};
+use crate::types::vote::Vote;
 use crate::handle_errors;
 use super::store_trait::StoreTrait;
+use super::Transactional;
 
 mock! {
     Store {}
@@ -17,6 +20,17 @@ mock! {
         async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
         async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
         async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+        async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
+        async fn get_answer(&self, id: i32) -> Result<Answer, handle_errors::Error>;
+        async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, handle_errors::Error>;
+        async fn vote_answer(&self, id: i32, account_id: AccountId, vote: Option<Vote>) -> Result<Answer, handle_errors::Error>;
+        async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, handle_errors::Error>;
+    }
+
+    #[async_trait::async_trait]
+    impl Transactional for Store {
+        async fn begin(&self) -> Result<Self, handle_errors::Error>;
+        async fn commit(self) -> Result<(), handle_errors::Error>;
     }
 
     impl Clone for Store {
@@ -29,14 +43,15 @@ mod tests {
     use super::*;
     use crate::types::answer::{Answer, AnswerId, NewAnswer};
     use crate::types::question::QuestionId;
-    use crate::types::account::{AccountId, Session};
+    use crate::types::account::{AccountId, Author, Session, SessionId};
     use chrono::prelude::*;
     use mockall::predicate::*;
     use mockall::*;
     use std::sync::{Arc, Mutex};
     use warp::http::StatusCode;
     use async_trait::async_trait;
-    use crate::routes::answer::{add_answer, update_answer, delete_answer};
+    use crate::routes::answer::{add_answer, update_answer, delete_answer, restore_answer, rollback_answer, vote_answer};
+    use crate::types::vote::NewVote;
 
     mock! {
         Store {}
@@ -47,6 +62,17 @@ mod tests {
             async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
             async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
             async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+            async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, handle_errors::Error>;
+            async fn get_answer(&self, id: i32) -> Result<Answer, handle_errors::Error>;
+            async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, handle_errors::Error>;
+            async fn vote_answer(&self, id: i32, account_id: AccountId, vote: Option<Vote>) -> Result<Answer, handle_errors::Error>;
+            async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, handle_errors::Error>;
+        }
+
+        #[async_trait]
+        impl Transactional for Store {
+            async fn begin(&self) -> Result<Self, handle_errors::Error>;
+            async fn commit(self) -> Result<(), handle_errors::Error>;
         }
 
         impl Clone for Store {
@@ -65,11 +91,24 @@ mod tests {
         mock_store
     }
 
+    // Sets up the expectations of the transaction handle `begin` returns.
+    fn expect_transaction(store: &mut MockStore, setup: impl Fn(&mut MockStore) + Send + 'static) {
+        store.expect_begin()
+            .times(1)
+            .returning(move || {
+                let mut tx = MockStore::new();
+                setup(&mut tx);
+                Ok(tx)
+            });
+    }
+
     fn create_test_session() -> Session {
         Session {
             account_id: AccountId(1),
             exp: Utc::now() + chrono::Duration::days(1),
             nbf: Utc::now(),
+            generation: 0,
+            session_id: SessionId(1),
         }
     }
 
@@ -93,6 +132,11 @@ mod tests {
                 id: AnswerId(1),
                 content: a.content,
                 question_id: a.question_id,
+                score: 0,
+                author: Author::default(),
+                created_on: Utc::now(),
+                updated_on: None,
+                version: 1,
             }));
         
         let result = add_answer(session, store, new_answer).await;
@@ -129,14 +173,21 @@ mod tests {
             id: AnswerId(1),
             content: "Updated answer".to_string(),
             question_id: QuestionId(1),
+            score: 0,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
         };
         
-        store.expect_is_answer_owner()
-            .with(eq(1), eq(&AccountId(1)))
-            .times(1)
-            .returning(|_, _| Ok(false));
+        expect_transaction(&mut store, |tx| {
+            tx.expect_is_answer_owner()
+                .with(eq(1), eq(&AccountId(1)))
+                .times(1)
+                .returning(|_, _| Ok(false));
+        });
         
-        let result = update_answer(1, session, store, answer).await;
+        let result = update_answer(1, session, store, None, answer).await;
         assert!(result.is_err());
     }
 
@@ -146,15 +197,17 @@ mod tests {
         let mut store = mock_store.lock().unwrap().clone();
         let session = create_test_session();
         
-        store.expect_is_answer_owner()
-            .with(eq(1), eq(&AccountId(1)))
-            .times(1)
-            .returning(|_, _| Ok(true));
-            
-        store.expect_delete_answer()
-            .with(eq(1), eq(AccountId(1)))
-            .times(1)
-            .returning(|_, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+        expect_transaction(&mut store, |tx| {
+            tx.expect_is_answer_owner()
+                .with(eq(1), eq(&AccountId(1)))
+                .times(1)
+                .returning(|_, _| Ok(true));
+
+            tx.expect_delete_answer()
+                .with(eq(1), eq(AccountId(1)))
+                .times(1)
+                .returning(|_, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+        });
         
         let result = delete_answer(1, session, store).await;
         assert!(result.is_err());
@@ -166,17 +219,167 @@ mod tests {
         let mut store = mock_store.lock().unwrap().clone();
         let session = create_test_session();
         
-        store.expect_is_answer_owner()
-            .with(eq(1), eq(&AccountId(1)))
-            .times(1)
-            .returning(|_, _| Ok(true));
-            
-        store.expect_delete_answer()
-            .with(eq(1), eq(AccountId(1)))
-            .times(1)
-            .returning(|_, _| Ok(true));
+        expect_transaction(&mut store, |tx| {
+            tx.expect_is_answer_owner()
+                .with(eq(1), eq(&AccountId(1)))
+                .times(1)
+                .returning(|_, _| Ok(true));
+
+            tx.expect_delete_answer()
+                .with(eq(1), eq(AccountId(1)))
+                .times(1)
+                .returning(|_, _| Ok(true));
+
+            tx.expect_commit()
+                .times(1)
+                .returning(|| Ok(()));
+        });
         
         let result = delete_answer(1, session, store).await;
         assert!(result.is_ok());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_restore_answer_success() {
+        let mock_store = setup_mock_store();
+        let mut store = mock_store.lock().unwrap().clone();
+        let session = create_test_session();
+
+        expect_transaction(&mut store, |tx| {
+            tx.expect_restore_answer()
+                .with(eq(1), eq(AccountId(1)))
+                .times(1)
+                .returning(|id, _| Ok(Answer {
+                    id: AnswerId(id),
+                    content: "Restored answer".to_string(),
+                    question_id: QuestionId(1),
+                    score: 0,
+                    author: Author::default(),
+                    created_on: Utc::now(),
+                    updated_on: None,
+                    version: 1,
+                }));
+
+            tx.expect_commit()
+                .times(1)
+                .returning(|| Ok(()));
+        });
+
+        let result = restore_answer(1, session, store).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_vote_answer_success() {
+        let mock_store = setup_mock_store();
+        let mut store = mock_store.lock().unwrap().clone();
+        let session = create_test_session();
+
+        expect_transaction(&mut store, |tx| {
+            tx.expect_is_answer_owner()
+                .with(eq(1), eq(&AccountId(1)))
+                .times(1)
+                .returning(|_, _| Ok(false));
+
+            tx.expect_vote_answer()
+                .with(eq(1), eq(AccountId(1)), eq(Some(Vote::Down)))
+                .times(1)
+                .returning(|id, _, _| Ok(Answer {
+                    id: AnswerId(id),
+                    content: "Answer".to_string(),
+                    question_id: QuestionId(1),
+                    score: -1,
+                    author: Author::default(),
+                    created_on: Utc::now(),
+                    updated_on: None,
+                    version: 1,
+                }));
+
+            tx.expect_commit()
+                .times(1)
+                .returning(|| Ok(()));
+        });
+
+        let result = vote_answer(1, session, store, NewVote { vote: Vote::Down }).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_vote_own_answer() {
+        let mock_store = setup_mock_store();
+        let mut store = mock_store.lock().unwrap().clone();
+        let session = create_test_session();
+
+        expect_transaction(&mut store, |tx| {
+            tx.expect_is_answer_owner()
+                .with(eq(1), eq(&AccountId(1)))
+                .times(1)
+                .returning(|_, _| Ok(true));
+        });
+
+        let result = vote_answer(1, session, store, NewVote { vote: Vote::Up }).await;
+        match result {
+            Err(rejection) => {
+                let error = rejection.find::<handle_errors::Error>().unwrap();
+                assert!(matches!(*error, handle_errors::Error::SelfVote));
+            }
+            _ => panic!("Expected self vote error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vote_missing_answer() {
+        let mock_store = setup_mock_store();
+        let mut store = mock_store.lock().unwrap().clone();
+        let session = create_test_session();
+
+        expect_transaction(&mut store, |tx| {
+            tx.expect_is_answer_owner()
+                .times(1)
+                .returning(|_, _| Ok(false));
+
+            tx.expect_vote_answer()
+                .times(1)
+                .returning(|_, _, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+        });
+
+        let result = vote_answer(1, session, store, NewVote { vote: Vote::Up }).await;
+        match result {
+            Err(rejection) => {
+                let error = rejection.find::<handle_errors::Error>().unwrap();
+                assert!(matches!(*error, handle_errors::Error::NotFound));
+            }
+            _ => panic!("Expected not found error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rollback_answer_unknown_revision() {
+        let mock_store = setup_mock_store();
+        let mut store = mock_store.lock().unwrap().clone();
+        let session = create_test_session();
+
+        expect_transaction(&mut store, |tx| {
+            tx.expect_is_answer_owner()
+                .with(eq(1), eq(&AccountId(1)))
+                .times(1)
+                .returning(|_, _| Ok(true));
+
+            tx.expect_get_answer_revision()
+                .with(eq(1), eq(RevisionId(42)))
+                .times(1)
+                .returning(|_, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+
+            tx.expect_update_answer().times(0);
+        });
+
+        let result = rollback_answer(1, RevisionId(42), session, store).await;
+        match result {
+            Err(rejection) => {
+                let error = rejection.find::<handle_errors::Error>().unwrap();
+                assert!(matches!(*error, handle_errors::Error::NotFound));
+            }
+            _ => panic!("Expected not found error"),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use tracing::{event, instrument, Level};
+use warp::Filter;
+
+use crate::types::account::Session;
+use crate::types::audit::extract_audit_filter;
+use crate::types::pagination::{extract_pagination, Pagination};
+
+pub mod store_trait;
+use store_trait::StoreTrait;
+
+#[cfg(test)]
+mod tests;
+
+/// Longest `X-Request-Id` taken from a client.
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// Extracts the id audit records of the request are written with: the
+/// `X-Request-Id` header if it is a short printable string, a random id
+/// otherwise.
+pub fn request_id() -> impl Filter<Extract = (String,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("x-request-id").map(|request_id: Option<String>| {
+        request_id
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LENGTH
+                    && id.chars().all(|c| c.is_ascii_graphic())
+            })
+            .unwrap_or_else(|| format!("{:016x}", rand::random::<u64>()))
+    })
+}
+
+/**
+ * @Notice Get audit log
+ *
+ * @Dev Lists the records of the audit log, newest first, filtered by `actor`, `action`, `target`, `target_id`, `request_id`, `created_after` and `created_before`.
+ *
+ * @params  `session`: The authenticated admin session object.
+ * @params `params`: Query parameters for the filter and pagination.
+ * @params  `store`: A `Store` instance used to interact with the database.
+*/
+#[instrument]
+pub async fn get_audit_records<S: StoreTrait>(
+    _session: Session,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "rust_hour", Level::INFO, "querying audit log");
+    let (filter, params) = extract_audit_filter(params)?;
+    let mut pagination = Pagination::default();
+
+    if !params.is_empty() {
+        event!(Level::INFO, pagination = true);
+        pagination = extract_pagination(params)?;
+    }
+
+    match store
+        .get_audit_records(filter, pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+use std::fmt::Debug;
+use crate::types::audit::{AuditFilter, AuditRecord, NewAuditRecord};
+use crate::handle_errors;
+
+#[async_trait]
+pub trait StoreTrait: Clone + Debug {
+    async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, handle_errors::Error>;
+    async fn get_audit_records(&self, filter: AuditFilter, limit: Option<i32>, offset: i32) -> Result<Vec<AuditRecord>, handle_errors::Error>;
+}
@@ -0,0 +1,106 @@
+use mockall::predicate::*;
+use mockall::*;
+use chrono::prelude::*;
+
+use crate::types::account::{AccountId, Session, SessionId};
+use crate::types::audit::{AuditAction, AuditFilter, AuditRecord, AuditRecordId, NewAuditRecord};
+use crate::handle_errors;
+use super::*;
+
+mock! {
+    #[derive(Debug)]
+    Store {}
+
+    #[async_trait::async_trait]
+    impl StoreTrait for Store {
+        async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, handle_errors::Error>;
+        async fn get_audit_records(&self, filter: AuditFilter, limit: Option<i32>, offset: i32) -> Result<Vec<AuditRecord>, handle_errors::Error>;
+    }
+
+    impl Clone for Store {
+        fn clone(&self) -> Self;
+    }
+}
+
+fn create_test_session() -> Session {
+    Session {
+        account_id: AccountId(1),
+        exp: Utc::now() + chrono::Duration::days(1),
+        nbf: Utc::now(),
+        generation: 0,
+        session_id: SessionId(1),
+    }
+}
+
+#[tokio::test]
+async fn test_get_audit_records_with_filter_and_pagination() {
+    let mut store = MockStore::new();
+    let filter = AuditFilter {
+        actor: Some(AccountId(2)),
+        action: Some(AuditAction::DeleteAnswer),
+        ..AuditFilter::default()
+    };
+
+    store
+        .expect_get_audit_records()
+        .with(eq(filter), eq(Some(10)), eq(5))
+        .times(1)
+        .returning(|_, _, _| {
+            Ok(vec![AuditRecord {
+                id: AuditRecordId(1),
+                actor: AccountId(2),
+                action: AuditAction::DeleteAnswer,
+                target: AuditAction::DeleteAnswer.target(),
+                target_id: 3,
+                before: Some(serde_json::json!({ "content": "Answer" })),
+                after: None,
+                request_id: Some("abc".to_string()),
+                created_on: Utc::now(),
+            }])
+        });
+
+    let params: HashMap<String, String> = [
+        ("actor", "2"),
+        ("action", "delete_answer"),
+        ("limit", "10"),
+        ("offset", "5"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+    let result = get_audit_records(create_test_session(), params, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_audit_records_rejects_unknown_action() {
+    let store = MockStore::new();
+    let params = HashMap::from([("action".to_string(), "drop_table".to_string())]);
+    let result = get_audit_records(create_test_session(), params, store).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_request_id() {
+    let filter = request_id();
+
+    let id = warp::test::request()
+        .header("x-request-id", "req-42")
+        .filter(&filter)
+        .await
+        .unwrap();
+    assert_eq!(id, "req-42");
+
+    // Ids a client cannot be trusted with are replaced.
+    for header in ["", "has spaces", &"x".repeat(65)] {
+        let id = warp::test::request()
+            .header("x-request-id", header)
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(id.len(), 16);
+    }
+    let first = warp::test::request().filter(&filter).await.unwrap();
+    let second = warp::test::request().filter(&filter).await.unwrap();
+    assert_ne!(first, second);
+}
@@ -1,14 +1,38 @@
 use argon2::Config;
 use chrono::prelude::*;
 use rand::Rng;
-use std::{env, future};
+use sha2::{Digest, Sha256};
+use std::future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{event, Level};
 use warp::Filter;
 
-use crate::store::Store;
+use crate::keyring::{Footer, Keyring};
+use crate::mailer::{Mail, Mailer};
+
 use crate::types::account::{
     Account, AccountId, AccountUpdatePassword, AccountUpdateRequest, Session, AccountResponse,
+    ActiveSession, NewSession, RefreshRequest, SessionId, Tokens, VerificationRequest,
 };
 
+/// Minutes an access token is valid. Clients get a new one with their
+/// refresh token.
+const ACCESS_TOKEN_MINUTES: i64 = 15;
+/// Days a refresh token is valid if it is not used.
+const REFRESH_TOKEN_DAYS: i64 = 30;
+/// Hours a verification token mailed to an address is valid.
+const VERIFICATION_TOKEN_HOURS: i64 = 24;
+/// Seconds the last use of a session may lag behind. Requests only write it
+/// once it is older, not every time they check the session.
+pub const SESSION_TOUCH_SECONDS: i64 = 60;
+/// Seconds an account waits after a verification mail before it can ask for
+/// another one.
+const VERIFICATION_RESEND_SECONDS: i64 = 60;
+/// Stored instead of a password hash for accounts no one can log into, like
+/// the authors of an imported dump. No password matches it.
+pub const NO_PASSWORD: &str = "!";
+
 #[cfg(test)]
 mod tests;
 
@@ -19,29 +43,40 @@ pub trait StoreTrait {
     async fn update_account(&self, account_id: AccountId, account: AccountUpdateRequest) -> Result<AccountResponse, handle_errors::Error>;
     async fn update_password(&self, account_id: AccountId, password: AccountUpdatePassword) -> Result<bool, handle_errors::Error>;
     async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, handle_errors::Error>;
-}
-
-#[async_trait::async_trait]
-impl StoreTrait for Store {
-    async fn add_account(&self, account: Account) -> Result<bool, handle_errors::Error> {
-        self.add_account(account).await
-    }
-
-    async fn get_account(&self, email: String) -> Result<Account, handle_errors::Error> {
-        self.get_account(email).await
-    }
-
-    async fn update_account(&self, account_id: AccountId, account: AccountUpdateRequest) -> Result<AccountResponse, handle_errors::Error> {
-        self.update_account(account_id, account).await
-    }
-
-    async fn update_password(&self, account_id: AccountId, password: AccountUpdatePassword) -> Result<bool, handle_errors::Error> {
-        self.update_password(account_id, password).await
-    }
-
-    async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, handle_errors::Error> {
-        self.get_account_information(account_id).await
-    }
+    /// The current token generation of the account. Changing the password
+    /// starts a new one and revokes every refresh token of the account.
+    async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, handle_errors::Error>;
+    /// Starts a login session with its first refresh token, stored as its hash.
+    async fn add_session(&self, session: NewSession, token_hash: String, expires_on: DateTime<Utc>) -> Result<SessionId, handle_errors::Error>;
+    /// Revokes the refresh token with `token_hash` and stores `new_token_hash`
+    /// in its place, in the same session, which now expires on `expires_on`.
+    /// Fails with `InvalidRefreshToken` if the token is unknown, expired or
+    /// already revoked.
+    async fn rotate_refresh_token(&self, token_hash: String, new_token_hash: String, expires_on: DateTime<Utc>) -> Result<(AccountId, SessionId), handle_errors::Error>;
+    /// Revokes the session of the refresh token with `token_hash`, returning
+    /// whether it was still active.
+    async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, handle_errors::Error>;
+    /// Marks the session as used now, unless that was done less than
+    /// `SESSION_TOUCH_SECONDS` ago, returning whether it is still active.
+    async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, handle_errors::Error>;
+    /// The active sessions of the account, most recently used first.
+    async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, handle_errors::Error>;
+    /// Revokes a session of the account, returning whether it was active.
+    async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, handle_errors::Error>;
+    /// Revokes every active session of the account but `session_id`,
+    /// returning how many there were.
+    async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, handle_errors::Error>;
+    /// Stores a one-time token, as its hash, which proves the account owns
+    /// `email` until `expires_on`.
+    async fn add_verification_token(&self, account_id: AccountId, email: String, token_hash: String, expires_on: DateTime<Utc>) -> Result<bool, handle_errors::Error>;
+    /// When the newest verification token of the account which is neither
+    /// used nor expired was made, if there is one.
+    async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, handle_errors::Error>;
+    /// Uses up the verification token with `token_hash` and marks the email
+    /// of its account as verified. Fails with `InvalidVerificationToken` if
+    /// the token is unknown, expired or already used, or if the account
+    /// changed its email since.
+    async fn verify_email(&self, token_hash: String) -> Result<AccountId, handle_errors::Error>;
 }
 
 /**
@@ -50,9 +85,14 @@ impl StoreTrait for Store {
  * @Dev Registers a new account in the database
  *
  * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `mailer`: Sends the token which verifies the email of the account
  * @params `account`: An `Account` struct containing the account information to be registered.
 */
-pub async fn register<S: StoreTrait>(store: S, account: Account) -> Result<impl warp::Reply, warp::Rejection> {
+pub async fn register<S: StoreTrait>(
+    store: S,
+    mailer: Arc<dyn Mailer>,
+    account: Account,
+) -> Result<impl warp::Reply, warp::Rejection> {
     // Hashes the provided password using a secure algorithm.
     let hashed_password = hash_password(account.password.as_bytes())
         .map_err(|e| warp::reject::custom(handle_errors::Error::ArgonLibraryError(e)))?;
@@ -61,33 +101,72 @@ pub async fn register<S: StoreTrait>(store: S, account: Account) -> Result<impl
         id: account.id,
         email: account.email,
         password: hashed_password,
+        // A blank display name falls back to the email.
+        display_name: account
+            .display_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty()),
     };
+    let email = account.email.clone();
     // Attempts to add the account to the database using the `store` instance.
-    match store.add_account(account).await {
-        Ok(_) => Ok(warp::reply::json(&"Account added".to_string())),
-        Err(e) => Err(warp::reject::custom(e)),
+    store.add_account(account).await.map_err(warp::reject::custom)?;
+    let account_id = store
+        .get_account(email.clone())
+        .await
+        .map_err(warp::reject::custom)?
+        .id
+        .ok_or_else(|| warp::reject::custom(handle_errors::Error::NotFound))?;
+    // The account exists either way, and a new mail can be asked for.
+    if let Err(e) = send_verification(&store, mailer.as_ref(), account_id, email).await {
+        event!(Level::ERROR, "cannot send verification mail: {}", e);
     }
+    Ok(warp::reply::json(&"Account added".to_string()))
 }
 
 /**
  * @Notice Log in
  *
- * @Dev Attempts to log in a user by validating their credentials.
+ * @Dev Attempts to log in a user by validating their credentials, and starts a session.
  *
  * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `keyring`: The keys the access token is encrypted with
+ * @params `user_agent`: The `User-Agent` header of the request, kept with the session
+ * @params `remote`: The address the request came from, kept with the session
  * @params `login`: An `Account` struct containing the user's email and password
 */
-pub async fn login<S: StoreTrait>(store: S, login: Account) -> Result<impl warp::Reply, warp::Rejection> {
+pub async fn login<S: StoreTrait>(
+    store: S,
+    keyring: Keyring,
+    user_agent: Option<String>,
+    remote: Option<SocketAddr>,
+    login: Account,
+) -> Result<impl warp::Reply, warp::Rejection> {
     // Attempts to retrieve the account associated with the provided email.
     match store.get_account(login.email).await {
         Ok(account) => match verify_password(&account.password, login.password.as_bytes()) {
             Ok(verified) => {
                 // Verifies the provided password against the stored password hash.
                 if verified {
-                    // Generates a token if password verification is successful.
-                    Ok(warp::reply::json(&issue_token(
-                        account.id.expect("id not found"),
-                    )))
+                    // Generates the tokens if password verification is successful.
+                    let account_id = account.id.expect("id not found");
+                    let refresh_token = new_token();
+                    let session = NewSession {
+                        account_id: account_id.clone(),
+                        user_agent,
+                        ip: remote.map(|remote| remote.ip().to_string()),
+                    };
+                    let session_id = store
+                        .add_session(
+                            session,
+                            hash_token(&refresh_token),
+                            Utc::now() + chrono::Duration::days(REFRESH_TOKEN_DAYS),
+                        )
+                        .await
+                        .map_err(warp::reject::custom)?;
+                    let tokens = issue_tokens(&store, &keyring, account_id, session_id, refresh_token)
+                        .await
+                        .map_err(warp::reject::custom)?;
+                    Ok(warp::reply::json(&tokens))
                 } else {
                     // Returns an error if the password is incorrect.
                     Err(warp::reject::custom(handle_errors::Error::WrongPassword))
@@ -103,31 +182,91 @@ pub async fn login<S: StoreTrait>(store: S, login: Account) -> Result<impl warp:
     }
 }
 
+/**
+ * @Notice Refresh tokens
+ *
+ * @Dev Trades a refresh token for a new access token and a new refresh token.
+ * The refresh token can only be used once.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `keyring`: The keys the access token is encrypted with
+ * @params `request`: A `RefreshRequest` struct containing the refresh token
+*/
+pub async fn refresh_token<S: StoreTrait>(
+    store: S,
+    keyring: Keyring,
+    request: RefreshRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let refresh_token = new_token();
+    let (account_id, session_id) = store
+        .rotate_refresh_token(
+            hash_token(&request.refresh_token),
+            hash_token(&refresh_token),
+            Utc::now() + chrono::Duration::days(REFRESH_TOKEN_DAYS),
+        )
+        .await
+        .map_err(warp::reject::custom)?;
+    match issue_tokens(&store, &keyring, account_id, session_id, refresh_token).await {
+        Ok(tokens) => Ok(warp::reply::json(&tokens)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Log out
+ *
+ * @Dev Ends the session of a refresh token, which revokes the refresh token and
+ * the access tokens of the session.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `request`: A `RefreshRequest` struct containing the refresh token
+*/
+pub async fn logout<S: StoreTrait>(
+    store: S,
+    request: RefreshRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.revoke_refresh_token(hash_token(&request.refresh_token)).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
 /**
  * @Notice Update account
  *
  * @Dev Attempts to update a user's email with new email by validating their credentials.
+ * A new email is unverified until the token mailed to it is sent to `/accounts/verify`.
  *
  * @params  `session`: A `Session` struct containing the user's id
  * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `mailer`: Sends the token which verifies the new email
  * @params `account`: An `AccountUpdateRequest` struct containing the user's email to update
 */
 pub async fn update_account<S: StoreTrait>(
     session: Session,
     store: S,
+    mailer: Arc<dyn Mailer>,
     account: AccountUpdateRequest,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    match store.update_account(account_id, account).await {
-        Ok(res) => Ok(warp::reply::json(&res)),
-        Err(e) => Err(warp::reject::custom(e)),
+    let res = store
+        .update_account(account_id.clone(), account)
+        .await
+        .map_err(warp::reject::custom)?;
+    // A new email has to be verified; the change stands either way.
+    if !res.email_verified {
+        if let Err(e) = send_verification(&store, mailer.as_ref(), account_id, res.email.clone()).await {
+            event!(Level::ERROR, "cannot send verification mail: {}", e);
+        }
     }
+    Ok(warp::reply::json(&res))
 }
 
 /**
  * @Notice Update password
  *
  * @Dev Attempts to update a user's password with new password by validating their credentials.
+ * Every token of the account issued before is revoked.
  *
  * @params  `session`: A `Session` struct containing the user's id
  * @params  `store`: A `Store` instance used to interact with the database.
@@ -167,6 +306,146 @@ pub async fn get_account_information<S: StoreTrait>(
     }
 }
 
+/**
+ * @Notice Get sessions
+ *
+ * @Dev Lists the active sessions of the user, marking the one of the request as `current`.
+ *
+ * @params `session`: A `Session` struct containing the user's id
+ * @params  `store`: A `Store` instance used to interact with the database.
+*/
+pub async fn get_sessions<S: StoreTrait>(
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.get_sessions(session.account_id).await {
+        Ok(sessions) => {
+            let sessions: Vec<ActiveSession> = sessions
+                .into_iter()
+                .map(|active| ActiveSession {
+                    current: active.id == session.session_id,
+                    ..active
+                })
+                .collect();
+            Ok(warp::reply::json(&sessions))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Revoke session
+ *
+ * @Dev Signs one of the user's sessions out, which revokes its tokens.
+ *
+ * @params `session_id`: The id of the session to revoke
+ * @params `session`: A `Session` struct containing the user's id
+ * @params  `store`: A `Store` instance used to interact with the database.
+*/
+pub async fn revoke_session<S: StoreTrait>(
+    session_id: SessionId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.revoke_session(session.account_id, session_id).await {
+        Ok(true) => Ok(warp::reply::json(&true)),
+        Ok(false) => Err(warp::reject::custom(handle_errors::Error::NotFound)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Revoke other sessions
+ *
+ * @Dev Signs the user out everywhere but in the session of the request, returning
+ * how many sessions were revoked.
+ *
+ * @params `session`: A `Session` struct containing the user's id
+ * @params  `store`: A `Store` instance used to interact with the database.
+*/
+pub async fn revoke_other_sessions<S: StoreTrait>(
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match store.revoke_other_sessions(session.account_id, session.session_id).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Verify email
+ *
+ * @Dev Marks the email of an account as verified with the one-time token mailed to it,
+ * and returns the account.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `request`: A `VerificationRequest` struct containing the mailed token
+*/
+pub async fn verify_email<S: StoreTrait>(
+    store: S,
+    request: VerificationRequest,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = store
+        .verify_email(hash_token(&request.token))
+        .await
+        .map_err(warp::reject::custom)?;
+    match store.get_account_information(account_id).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Resend verification
+ *
+ * @Dev Mails a new verification token to the user's email, returning whether one was sent.
+ * Verified accounts get none, and neither do accounts sent one less than
+ * `VERIFICATION_RESEND_SECONDS` ago.
+ *
+ * @params `session`: A `Session` struct containing the user's id
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `mailer`: Sends the token
+*/
+pub async fn resend_verification<S: StoreTrait>(
+    session: Session,
+    store: S,
+    mailer: Arc<dyn Mailer>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account = store
+        .get_account_information(session.account_id)
+        .await
+        .map_err(warp::reject::custom)?;
+    if account.email_verified {
+        return Ok(warp::reply::json(&false));
+    }
+    let sent_on = store
+        .get_verification_sent_on(account.id.clone())
+        .await
+        .map_err(warp::reject::custom)?;
+    let resend_after = Utc::now() - chrono::Duration::seconds(VERIFICATION_RESEND_SECONDS);
+    if sent_on.is_some_and(|sent_on| sent_on > resend_after) {
+        return Ok(warp::reply::json(&false));
+    }
+    match send_verification(&store, mailer.as_ref(), account.id, account.email).await {
+        Ok(()) => Ok(warp::reply::json(&true)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Published keys
+ *
+ * @Dev Lists the public keys access tokens are signed with, so other services
+ * can verify them offline. The list is empty unless tokens are issued in the
+ * `public` mode.
+ *
+ * @params `keyring`: The keys access tokens are signed with
+*/
+pub async fn get_public_keys(keyring: Keyring) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&keyring.public_keys()))
+}
+
 // Hashes a password securely using Argon2id and returns the result as a string.
 fn hash_password(password: &[u8]) -> Result<String, argon2::Error> {
     if password.is_empty() {
@@ -177,57 +456,220 @@ fn hash_password(password: &[u8]) -> Result<String, argon2::Error> {
     argon2::hash_encoded(password, &salt, &config)
 }
 
-// Verifies a password against its hash using Argon2id.
+// Verifies a password against its hash using Argon2id. Accounts without a
+// password, including those imported before `NO_PASSWORD`, match none.
 fn verify_password(hash: &str, password: &[u8]) -> Result<bool, argon2::Error> {
+    if hash == NO_PASSWORD || hash.is_empty() {
+        return Ok(false);
+    }
     argon2::verify_encoded(hash, password)
 }
 
-// Verifies a provided token and extracts the associated session data
-pub fn verify_token(token: String) -> Result<Session, handle_errors::Error> {
-    // Retrieve the PASETO key from the environment variable.
-    let key = env::var("PASETO_KEY").map_err(handle_errors::Error::EnvironmentError)?;
-    // Attempt to validate the token using PASETO's local token validation.
-    let token = paseto::tokens::validate_local_token(
-        &token,
-        None,
-        key.as_bytes(),
-        &paseto::tokens::TimeBackend::Chrono,
-    )
+// Verifies a provided token and extracts the associated session data. Tokens
+// of an older generation than the account's, issued before its password was
+// changed, and tokens of a revoked session are rejected.
+pub async fn verify_token<S: StoreTrait>(
+    store: &S,
+    keyring: &Keyring,
+    token: String,
+) -> Result<Session, handle_errors::Error> {
+    // The footer names the key the token was encrypted or signed with.
+    let footer = token
+        .split('.')
+        .nth(3)
+        .and_then(|footer| base64::decode_config(footer, base64::URL_SAFE_NO_PAD).ok())
+        .and_then(|footer| String::from_utf8(footer).ok())
+        .ok_or(handle_errors::Error::CannotDecryptToken)?;
+    let kid = serde_json::from_str::<Footer>(&footer)
+        .map_err(|_| handle_errors::Error::CannotDecryptToken)?
+        .kid;
+    // Signed tokens are checked with the public key, and encrypted ones
+    // decrypted with the secret key.
+    let token = match keyring.public_key(&kid) {
+        Some(public_key) => paseto::tokens::validate_public_token(
+            &token,
+            Some(&footer),
+            &paseto::tokens::PasetoPublicKey::ED25519PublicKey(public_key),
+            &paseto::tokens::TimeBackend::Chrono,
+        ),
+        None => paseto::tokens::validate_local_token(
+            &token,
+            Some(&footer),
+            keyring.key(&kid).ok_or(handle_errors::Error::CannotDecryptToken)?,
+            &paseto::tokens::TimeBackend::Chrono,
+        ),
+    }
     .map_err(|_| handle_errors::Error::CannotDecryptToken)?;
     // Deserialize the token's payload into a `Session` struct.
-    serde_json::from_value::<Session>(token).map_err(|_| handle_errors::Error::CannotDecryptToken)
+    let session = serde_json::from_value::<Session>(token)
+        .map_err(|_| handle_errors::Error::CannotDecryptToken)?;
+
+    let generation = store.get_token_generation(session.account_id.clone()).await?;
+    if generation != session.generation
+        || !store
+            .touch_session(session.account_id.clone(), session.session_id.clone())
+            .await?
+    {
+        return Err(handle_errors::Error::CannotDecryptToken);
+    }
+    Ok(session)
 }
 
-// Generates a PASETO token containing session information.
-fn issue_token(account_id: AccountId) -> String {
-    let key = env::var("PASETO_KEY").expect("PASETO_KEY must be set");
+// Generates a PASETO token containing session information, encrypted or
+// signed with the active key of `keyring` and naming it in its footer.
+fn issue_token(
+    keyring: &Keyring,
+    account_id: AccountId,
+    generation: i32,
+    session_id: SessionId,
+) -> Result<String, handle_errors::Error> {
+    let (kid, key) = keyring.active()?;
+    let footer = serde_json::to_string(&Footer { kid: kid.to_string() })
+        .expect("Failed to serialize paseto footer");
     let current_date_time = Utc::now();
-    let exp = current_date_time + chrono::Duration::days(1);
+    let exp = current_date_time + chrono::Duration::minutes(ACCESS_TOKEN_MINUTES);
 
     let session = Session {
         account_id,
         exp,
         nbf: current_date_time,
+        generation,
+        session_id,
     };
 
-    paseto::tokens::PasetoBuilder::new()
-        .set_encryption_key(&Vec::from(key.as_bytes()))
+    let mut builder = paseto::tokens::PasetoBuilder::new();
+    let builder = match keyring.signing_key()? {
+        Some(key_pair) => builder.set_ed25519_key(key_pair),
+        None => builder.set_encryption_key(key),
+    };
+    Ok(builder
+        .set_footer(&footer)
         .set_expiration(&exp)
         .set_not_before(&session.nbf)
         .set_claim("account_id", serde_json::json!(session.account_id))
+        .set_claim("generation", serde_json::json!(session.generation))
+        .set_claim("session_id", serde_json::json!(session.session_id))
         .build()
-        .expect("Failed to construct paseto token w/ builder!")
+        .expect("Failed to construct paseto token w/ builder!"))
+}
+
+// Generates an access token of the current generation to go with `refresh_token`.
+async fn issue_tokens<S: StoreTrait>(
+    store: &S,
+    keyring: &Keyring,
+    account_id: AccountId,
+    session_id: SessionId,
+    refresh_token: String,
+) -> Result<Tokens, handle_errors::Error> {
+    let generation = store.get_token_generation(account_id.clone()).await?;
+    Ok(Tokens {
+        access_token: issue_token(keyring, account_id, generation, session_id)?,
+        expires_in: ACCESS_TOKEN_MINUTES * 60,
+        refresh_token,
+    })
+}
+
+// Mails a new one-time token to `email`, which verifies it for the account.
+async fn send_verification<S: StoreTrait>(
+    store: &S,
+    mailer: &dyn Mailer,
+    account_id: AccountId,
+    email: String,
+) -> Result<(), handle_errors::Error> {
+    let token = new_token();
+    let expires_on = Utc::now() + chrono::Duration::hours(VERIFICATION_TOKEN_HOURS);
+    store
+        .add_verification_token(account_id, email.clone(), hash_token(&token), expires_on)
+        .await?;
+    mailer
+        .send(Mail {
+            subject: "Verify your email address".to_string(),
+            body: format!(
+                "To verify {} within {} hours, send {{\"token\": \"{}\"}} to POST /accounts/verify.\n\n\
+                If you did not ask for this, ignore this mail.",
+                email, VERIFICATION_TOKEN_HOURS, token
+            ),
+            to: email,
+        })
+        .await
+}
+
+// Generates a random refresh or verification token. Only its hash is stored.
+fn new_token() -> String {
+    hex(&rand::thread_rng().gen::<[u8; 32]>())
+}
+
+// Hashes a refresh or verification token for storage. The tokens are random,
+// so a fast hash without salt is enough and lets them be looked up by their
+// hash.
+fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
 }
 
-pub fn auth() -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Extracts the session of a valid access token from the "Authorization"
+/// header, see `verify_token`.
+pub fn auth<S>(
+    store: S,
+    keyring: Keyring,
+) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone
+where
+    S: StoreTrait + Clone + Send + Sync + 'static,
+{
     // Extract the "Authorization" header from the request.
-    warp::header::<String>("Authorization").and_then(|token: String| {
-        // Attempt to verify the provided token using the `verify_token` function.
-        let token = match verify_token(token) {
-            Ok(t) => t,
-            Err(_) => return future::ready(Err(warp::reject::reject())),
-        };
-
-        future::ready(Ok(token))
+    warp::header::<String>("Authorization").and_then(move |token: String| {
+        let store = store.clone();
+        let keyring = keyring.clone();
+        async move {
+            // Attempt to verify the provided token using the `verify_token` function.
+            match verify_token(&store, &keyring, token).await {
+                Ok(session) => Ok(session),
+                Err(handle_errors::Error::CannotDecryptToken) => Err(warp::reject::reject()),
+                Err(e) => Err(warp::reject::custom(e)),
+            }
+        }
+    })
+}
+
+/// Like `auth`, but only lets the accounts in `admins` through and rejects
+/// everyone else as unauthorized.
+pub fn admin<S>(
+    store: S,
+    keyring: Keyring,
+    admins: Vec<AccountId>,
+) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone
+where
+    S: StoreTrait + Clone + Send + Sync + 'static,
+{
+    auth(store, keyring).and_then(move |session: Session| {
+        future::ready(if admins.contains(&session.account_id) {
+            Ok(session)
+        } else {
+            Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+        })
+    })
+}
+
+/// Like `auth`, but only lets accounts with a verified email through and
+/// rejects the others as forbidden.
+pub fn verified<S>(
+    store: S,
+    keyring: Keyring,
+) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone
+where
+    S: StoreTrait + Clone + Send + Sync + 'static,
+{
+    auth(store.clone(), keyring).and_then(move |session: Session| {
+        let store = store.clone();
+        async move {
+            match store.get_account_information(session.account_id.clone()).await {
+                Ok(account) if account.email_verified => Ok(session),
+                Ok(_) => Err(warp::reject::custom(handle_errors::Error::EmailNotVerified)),
+                Err(e) => Err(warp::reject::custom(e)),
+            }
+        }
     })
 }
@@ -3,9 +3,12 @@ use mockall::predicate::*;
 use mockall::*;
 use chrono::prelude::*;
 use std::sync::{Arc, Mutex};
+use warp::Reply;
 
-use crate::types::account::{Account, AccountId, Session, AccountUpdateRequest, AccountUpdatePassword, AccountResponse};
+use crate::types::account::{Account, AccountId, Session, AccountUpdateRequest, AccountUpdatePassword, AccountResponse, ActiveSession, NewSession, RefreshRequest, SessionId, VerificationRequest};
 use crate::handle_errors;
+use crate::keyring::{Keyring, TokenMode};
+use crate::mailer::MemoryMailer;
 use super::StoreTrait;
 
 mock! {
@@ -19,6 +22,17 @@ mock! {
         async fn update_account(&self, account_id: AccountId, account: AccountUpdateRequest) -> Result<AccountResponse, handle_errors::Error>;
         async fn update_password(&self, account_id: AccountId, password: AccountUpdatePassword) -> Result<bool, handle_errors::Error>;
         async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, handle_errors::Error>;
+        async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, handle_errors::Error>;
+        async fn add_session(&self, session: NewSession, token_hash: String, expires_on: DateTime<Utc>) -> Result<SessionId, handle_errors::Error>;
+        async fn rotate_refresh_token(&self, token_hash: String, new_token_hash: String, expires_on: DateTime<Utc>) -> Result<(AccountId, SessionId), handle_errors::Error>;
+        async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, handle_errors::Error>;
+        async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, handle_errors::Error>;
+        async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, handle_errors::Error>;
+        async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, handle_errors::Error>;
+        async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, handle_errors::Error>;
+        async fn add_verification_token(&self, account_id: AccountId, email: String, token_hash: String, expires_on: DateTime<Utc>) -> Result<bool, handle_errors::Error>;
+        async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, handle_errors::Error>;
+        async fn verify_email(&self, token_hash: String) -> Result<AccountId, handle_errors::Error>;
     }
 
     impl Clone for Store {
@@ -37,14 +51,65 @@ fn setup_mock_store() -> Arc<Mutex<MockStore>> {
     mock_store
 }
 
+fn test_keyring() -> Keyring {
+    Keyring::single(TokenMode::Local, "RANDOM_KEY_ONLY_USED_FOR_TESTS32").unwrap()
+}
+
 fn create_test_session() -> Session {
     Session {
         account_id: AccountId(1),
         exp: Utc::now() + chrono::Duration::days(1),
         nbf: Utc::now(),
+        generation: 0,
+        session_id: SessionId(1),
     }
 }
 
+// A store which, like all its clones taken by `auth`, reports `generation`
+// as the token generation of every account, and every session as `active`.
+fn store_with_session(generation: i32, active: bool) -> MockStore {
+    let mut store = MockStore::new();
+    store.expect_clone().returning(move || store_with_session(generation, active));
+    store.expect_get_token_generation().returning(move |_| Ok(generation));
+    store.expect_touch_session().returning(move |_, _| Ok(active));
+    store
+}
+
+// Like `store_with_session(0, true)`, with an account whose email is
+// `email_verified` or not.
+fn store_with_account(email_verified: bool) -> MockStore {
+    let mut store = MockStore::new();
+    store.expect_clone().returning(move || store_with_account(email_verified));
+    store.expect_get_token_generation().returning(|_| Ok(0));
+    store.expect_touch_session().returning(|_, _| Ok(true));
+    store.expect_get_account_information().returning(move |id| {
+        Ok(AccountResponse {
+            id,
+            email: "test@test.com".to_string(),
+            email_verified,
+        })
+    });
+    store
+}
+
+// Expects `register` to look up the new account and store the hash of the
+// token it mails.
+fn expect_verification(store: &mut MockStore) {
+    store.expect_get_account()
+        .with(eq("test@test.com".to_string()))
+        .times(1)
+        .returning(|email| Ok(Account {
+            id: Some(AccountId(1)),
+            email,
+            password: "hash".to_string(),
+            display_name: None,
+        }));
+    store.expect_add_verification_token()
+        .with(eq(AccountId(1)), eq("test@test.com".to_string()), predicate::always(), predicate::always())
+        .times(1)
+        .returning(|_, _, _, _| Ok(true));
+}
+
 #[tokio::test]
 async fn test_register_success() {
     let mock_store = setup_mock_store();
@@ -54,6 +119,7 @@ async fn test_register_success() {
         id: None,
         email: "test@test.com".to_string(),
         password: "password123".to_string(),
+        display_name: None,
     };
     
     store.expect_add_account()
@@ -62,14 +128,40 @@ async fn test_register_success() {
         }))
         .times(1)
         .returning(|_| Ok(true));
+    expect_verification(&mut store);
     
-    let result = super::register(store, account).await;
+    let mailer = MemoryMailer::new();
+    let result = super::register(store, Arc::new(mailer.clone()), account).await;
+    assert!(result.is_ok());
+    let sent = mailer.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "test@test.com");
+}
+
+#[tokio::test]
+async fn test_register_trims_display_name() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    let account = Account {
+        id: None,
+        email: "test@test.com".to_string(),
+        password: "password123".to_string(),
+        display_name: Some("  Jane ".to_string()),
+    };
+
+    store.expect_add_account()
+        .with(predicate::function(|a: &Account| a.display_name.as_deref() == Some("Jane")))
+        .times(1)
+        .returning(|_| Ok(true));
+    expect_verification(&mut store);
+
+    let result = super::register(store, Arc::new(MemoryMailer::new()), account).await;
     assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_login_success() {
-    std::env::set_var("PASETO_KEY", "RANDOM_KEY_ONLY_USED_FOR_TESTS32");
     
     let mock_store = setup_mock_store();
     let mut store = mock_store.lock().unwrap().clone();
@@ -78,6 +170,7 @@ async fn test_login_success() {
         id: None,
         email: "test@test.com".to_string(),
         password: "password123".to_string(),
+        display_name: None,
     };
     
     // Generate a valid Argon2 hash for "password123"
@@ -90,9 +183,27 @@ async fn test_login_success() {
             id: Some(AccountId(1)),
             email: "test@test.com".to_string(),
             password: hashed_password.clone(),
+            display_name: None,
         }));
+    store.expect_add_session()
+        .with(
+            eq(NewSession {
+                account_id: AccountId(1),
+                user_agent: Some("curl".to_string()),
+                ip: Some("127.0.0.1".to_string()),
+            }),
+            predicate::function(|hash: &String| hash.len() == 64),
+            predicate::always(),
+        )
+        .times(1)
+        .returning(|_, _, _| Ok(SessionId(1)));
+    store.expect_get_token_generation()
+        .with(eq(AccountId(1)))
+        .times(1)
+        .returning(|_| Ok(0));
     
-    let result = super::login(store, login).await;
+    let remote = Some("127.0.0.1:8080".parse().unwrap());
+    let result = super::login(store, test_keyring(), Some("curl".to_string()), remote, login).await;
     assert!(result.is_ok());
 }
 
@@ -105,6 +216,7 @@ async fn test_login_wrong_password() {
         id: None,
         email: "test@test.com".to_string(),
         password: "wrongpassword".to_string(),
+        display_name: None,
     };
     
     // Generate a valid Argon2 hash for "password123"
@@ -117,12 +229,38 @@ async fn test_login_wrong_password() {
             id: Some(AccountId(1)),
             email: "test@test.com".to_string(),
             password: hashed_password.clone(),
+            display_name: None,
         }));
     
-    let result = super::login(store, login).await;
+    let result = super::login(store, test_keyring(), None, None, login).await;
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_login_account_without_password() {
+    // Imported accounts, and those imported before `NO_PASSWORD`.
+    for password in [super::NO_PASSWORD, ""] {
+        let mut store = MockStore::new();
+        store.expect_get_account()
+            .times(1)
+            .returning(move |email| Ok(Account {
+                id: Some(AccountId(1)),
+                email,
+                password: password.to_string(),
+                display_name: None,
+            }));
+
+        let login = Account {
+            id: None,
+            email: "user8@stackexchange.invalid".to_string(),
+            password: "password123".to_string(),
+            display_name: None,
+        };
+        let rejection = super::login(store, test_keyring(), None, None, login).await.err().unwrap();
+        assert!(matches!(rejection.find(), Some(handle_errors::Error::WrongPassword)));
+    }
+}
+
 #[tokio::test]
 async fn test_update_account_success() {
     let mock_store = setup_mock_store();
@@ -139,10 +277,18 @@ async fn test_update_account_success() {
         .returning(|_, _| Ok(AccountResponse {
             id: AccountId(1),
             email: "updated@test.com".to_string(),
+            email_verified: false,
         }));
+    // The changed email has to be verified again.
+    store.expect_add_verification_token()
+        .with(eq(AccountId(1)), eq("updated@test.com".to_string()), predicate::always(), predicate::always())
+        .times(1)
+        .returning(|_, _, _, _| Ok(true));
     
-    let result = super::update_account(session, store, update_request).await;
+    let mailer = MemoryMailer::new();
+    let result = super::update_account(session, store, Arc::new(mailer.clone()), update_request).await;
     assert!(result.is_ok());
+    assert_eq!(mailer.sent()[0].to, "updated@test.com");
 }
 
 #[tokio::test]
@@ -176,6 +322,7 @@ async fn test_get_account_information_success() {
         .returning(|_| Ok(AccountResponse {
             id: AccountId(1),
             email: "test@test.com".to_string(),
+            email_verified: true,
         }));
     
     let result = super::get_account_information(session, store).await;
@@ -191,6 +338,7 @@ async fn test_register_duplicate_email() {
         id: None,
         email: "test@test.com".to_string(),
         password: "password123".to_string(),
+        display_name: None,
     };
     
     store.expect_add_account()
@@ -198,7 +346,7 @@ async fn test_register_duplicate_email() {
         .times(1)
         .returning(|_| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
     
-    let result = super::register(store, account).await;
+    let result = super::register(store, Arc::new(MemoryMailer::new()), account).await;
     assert!(result.is_err());
 }
 
@@ -211,6 +359,7 @@ async fn test_login_account_not_found() {
         id: None,
         email: "nonexistent@test.com".to_string(),
         password: "password123".to_string(),
+        display_name: None,
     };
     
     store.expect_get_account()
@@ -218,29 +367,27 @@ async fn test_login_account_not_found() {
         .times(1)
         .returning(|_| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
     
-    let result = super::login(store, login).await;
+    let result = super::login(store, test_keyring(), None, None, login).await;
     assert!(result.is_err());
 }
 
 #[tokio::test]
 async fn test_verify_token_success() {
-    std::env::set_var("PASETO_KEY", "RANDOM_KEY_ONLY_USED_FOR_TESTS32");
     let session = create_test_session();
-    let token = super::issue_token(session.account_id);
-    let result = super::verify_token(token);
+    let token = super::issue_token(&test_keyring(), session.account_id, session.generation, session.session_id).unwrap();
+    let result = super::verify_token(&store_with_session(0, true), &test_keyring(), token).await;
     assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_verify_token_invalid() {
-    std::env::set_var("PASETO_KEY", "RANDOM_KEY_ONLY_USED_FOR_TESTS32");
-    let result = super::verify_token("invalid_token".to_string());
+    let result = super::verify_token(&store_with_session(0, true), &test_keyring(), "invalid_token".to_string()).await;
     assert!(result.is_err());
 }
 
 #[tokio::test]
 async fn test_auth_header_missing() {
-    let auth_filter = super::auth();
+    let auth_filter = super::auth(store_with_session(0, true), test_keyring());
     let result = warp::test::request()
         .path("/")
         .filter(&auth_filter);
@@ -249,7 +396,7 @@ async fn test_auth_header_missing() {
 
 #[tokio::test]
 async fn test_auth_header_invalid() {
-    let auth_filter = super::auth();
+    let auth_filter = super::auth(store_with_session(0, true), test_keyring());
     let result = warp::test::request()
         .header("Authorization", "invalid_token")
         .path("/")
@@ -259,10 +406,9 @@ async fn test_auth_header_invalid() {
 
 #[tokio::test]
 async fn test_auth_header_valid() {
-    std::env::set_var("PASETO_KEY", "RANDOM_KEY_ONLY_USED_FOR_TESTS32");
     let session = create_test_session();
-    let token = super::issue_token(session.account_id);
-    let auth_filter = super::auth();
+    let token = super::issue_token(&test_keyring(), session.account_id, session.generation, session.session_id).unwrap();
+    let auth_filter = super::auth(store_with_session(0, true), test_keyring());
     
     let result = warp::test::request()
         .header("Authorization", token)
@@ -271,6 +417,83 @@ async fn test_auth_header_valid() {
     assert!(result.await.is_ok());
 }
 
+#[tokio::test]
+async fn test_auth_token_of_older_generation() {
+    let session = create_test_session();
+    let token = super::issue_token(&test_keyring(), session.account_id, session.generation, session.session_id).unwrap();
+    // The password was changed after the token was issued.
+    let auth_filter = super::auth(store_with_session(1, true), test_keyring());
+
+    let result = warp::test::request()
+        .header("Authorization", token)
+        .path("/")
+        .filter(&auth_filter);
+    assert!(result.await.is_err());
+}
+
+#[tokio::test]
+async fn test_auth_token_of_revoked_session() {
+    let session = create_test_session();
+    let token = super::issue_token(&test_keyring(), session.account_id, session.generation, session.session_id).unwrap();
+    let auth_filter = super::auth(store_with_session(0, false), test_keyring());
+
+    let result = warp::test::request()
+        .header("Authorization", token)
+        .path("/")
+        .filter(&auth_filter);
+    assert!(result.await.is_err());
+}
+
+#[tokio::test]
+async fn test_refresh_token_success() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    let old_hash = super::hash_token("old");
+    store.expect_rotate_refresh_token()
+        .with(eq(old_hash), predicate::function(|hash: &String| hash.len() == 64), predicate::always())
+        .times(1)
+        .returning(|_, _, _| Ok((AccountId(1), SessionId(1))));
+    store.expect_get_token_generation()
+        .with(eq(AccountId(1)))
+        .times(1)
+        .returning(|_| Ok(2));
+
+    let request = RefreshRequest { refresh_token: "old".to_string() };
+    let result = super::refresh_token(store, test_keyring(), request).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_refresh_token_revoked() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_rotate_refresh_token()
+        .times(1)
+        .returning(|_, _, _| Err(handle_errors::Error::InvalidRefreshToken));
+    store.expect_get_token_generation().times(0);
+
+    let request = RefreshRequest { refresh_token: "old".to_string() };
+    let result = super::refresh_token(store, test_keyring(), request).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_logout() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_revoke_refresh_token()
+        .with(eq(super::hash_token("token")))
+        .times(1)
+        .returning(|_| Ok(true));
+
+    let request = RefreshRequest { refresh_token: "token".to_string() };
+    let result = super::logout(store, request).await;
+    assert!(result.is_ok());
+}
+
 #[tokio::test]
 async fn test_store_trait_add_account_error() {
     let mock_store = setup_mock_store();
@@ -280,6 +503,7 @@ async fn test_store_trait_add_account_error() {
         id: None,
         email: "test@test.com".to_string(),
         password: "password123".to_string(),
+        display_name: None,
     };
     
     store.expect_add_account()
@@ -361,14 +585,15 @@ async fn test_register_argon_error() {
     let account = Account {
         id: None,
         email: "test@test.com".to_string(),
-        password: "".to_string(), // Empty password to trigger Argon2 error
+        password: "".to_string(), // Empty password to trigger Argon2 error,
+        display_name: None,
     };
     
     store.expect_add_account()
         .with(predicate::always())
         .times(0); // We expect no calls to add_account because hashing will fail
     
-    let result = super::register(store, account).await;
+    let result = super::register(store, Arc::new(MemoryMailer::new()), account).await;
     assert!(result.is_err());
 }
 
@@ -387,7 +612,7 @@ async fn test_update_account_database_error() {
         .times(1)
         .returning(|_, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
     
-    let result = super::update_account(session, store, update_request).await;
+    let result = super::update_account(session, store, Arc::new(MemoryMailer::new()), update_request).await;
     assert!(result.is_err());
 }
 
@@ -426,8 +651,207 @@ async fn test_get_account_information_database_error() {
 }
 
 #[tokio::test]
-async fn test_verify_token_invalid_key() {
-    std::env::remove_var("PASETO_KEY");
-    let result = super::verify_token("some_token".to_string());
-    assert!(matches!(result, Err(handle_errors::Error::EnvironmentError(_))));
-} 
\ No newline at end of file
+async fn test_verify_token_retired_key() {
+    let session = create_test_session();
+    let old = Keyring::parse(TokenMode::Local, "old=RANDOM_KEY_ONLY_USED_FOR_TESTS32", None).unwrap();
+    let token = super::issue_token(&old, session.account_id, session.generation, session.session_id).unwrap();
+
+    // Neither a keyring without the key, nor one where it is retired, nor
+    // another key of the same id decrypts the token.
+    for keyring in [
+        Keyring::parse(TokenMode::Local, "new=RANDOM_KEY_ONLY_USED_FOR_TESTS32", None).unwrap(),
+        Keyring::parse(
+            TokenMode::Local,
+            "old=RANDOM_KEY_ONLY_USED_FOR_TESTS32,new=ANOTHER_KEY_ONLY_USED_FOR_TEST32",
+            Some("new".to_string()),
+        )
+        .unwrap()
+        .retire(&["old".to_string()])
+        .unwrap(),
+        Keyring::parse(TokenMode::Local, "old=ANOTHER_KEY_ONLY_USED_FOR_TEST32", None).unwrap(),
+    ] {
+        let result = super::verify_token(&store_with_session(0, true), &keyring, token.clone()).await;
+        assert!(matches!(result, Err(handle_errors::Error::CannotDecryptToken)));
+    }
+}
+
+#[tokio::test]
+async fn test_verify_public_token() {
+    let session = create_test_session();
+    let seed = base64::encode(b"RANDOM_KEY_ONLY_USED_FOR_TESTS32");
+    let keyring = Keyring::single(TokenMode::Public, &seed).unwrap();
+    let token = super::issue_token(&keyring, session.account_id, session.generation, session.session_id).unwrap();
+    assert!(token.starts_with("v2.public."));
+    let result = super::verify_token(&store_with_session(0, true), &keyring, token.clone()).await;
+    assert!(result.is_ok());
+
+    // A local keyring with the same key does not accept signed tokens.
+    let local = Keyring::single(TokenMode::Local, "RANDOM_KEY_ONLY_USED_FOR_TESTS32").unwrap();
+    let result = super::verify_token(&store_with_session(0, true), &local, token).await;
+    assert!(matches!(result, Err(handle_errors::Error::CannotDecryptToken)));
+}
+
+#[tokio::test]
+async fn test_verify_token_after_rotation() {
+    let session = create_test_session();
+    let old = Keyring::parse(TokenMode::Local, "old=RANDOM_KEY_ONLY_USED_FOR_TESTS32", None).unwrap();
+    let token = super::issue_token(&old, session.account_id, session.generation, session.session_id).unwrap();
+
+    let rotated = Keyring::parse(
+        TokenMode::Local,
+        "old=RANDOM_KEY_ONLY_USED_FOR_TESTS32,new=ANOTHER_KEY_ONLY_USED_FOR_TEST32",
+        Some("new".to_string()),
+    )
+    .unwrap();
+    let result = super::verify_token(&store_with_session(0, true), &rotated, token).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_sessions_marks_current() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    store.expect_get_sessions()
+        .with(eq(AccountId(1)))
+        .times(1)
+        .returning(|_| {
+            Ok([1, 2]
+                .into_iter()
+                .map(|id| ActiveSession {
+                    id: SessionId(id),
+                    created_on: Utc::now(),
+                    last_used_on: Utc::now(),
+                    user_agent: None,
+                    ip: None,
+                    current: false,
+                })
+                .collect())
+        });
+
+    let response = super::get_sessions(session, store).await.unwrap().into_response();
+    let body = warp::hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let sessions: Vec<ActiveSession> = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        sessions.iter().map(|session| session.current).collect::<Vec<_>>(),
+        vec![true, false]
+    );
+}
+
+#[tokio::test]
+async fn test_revoke_session_not_found() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_revoke_session()
+        .with(eq(AccountId(1)), eq(SessionId(7)))
+        .times(1)
+        .returning(|_, _| Ok(false));
+
+    let result = super::revoke_session(SessionId(7), create_test_session(), store).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_revoke_other_sessions() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_revoke_other_sessions()
+        .with(eq(AccountId(1)), eq(SessionId(1)))
+        .times(1)
+        .returning(|_, _| Ok(2));
+
+    let result = super::revoke_other_sessions(create_test_session(), store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_verify_email_success() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    // Only the hash of the token reaches the store.
+    store.expect_verify_email()
+        .with(eq(super::hash_token("token")))
+        .times(1)
+        .returning(|_| Ok(AccountId(1)));
+    store.expect_get_account_information()
+        .with(eq(AccountId(1)))
+        .times(1)
+        .returning(|id| Ok(AccountResponse {
+            id,
+            email: "test@test.com".to_string(),
+            email_verified: true,
+        }));
+
+    let request = VerificationRequest { token: "token".to_string() };
+    let response = super::verify_email(store, request).await.unwrap().into_response();
+    let body = warp::hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let account: AccountResponse = serde_json::from_slice(&body).unwrap();
+    assert!(account.email_verified);
+}
+
+#[tokio::test]
+async fn test_verify_email_invalid_token() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_verify_email()
+        .times(1)
+        .returning(|_| Err(handle_errors::Error::InvalidVerificationToken));
+
+    let request = VerificationRequest { token: "token".to_string() };
+    let result = super::verify_email(store, request).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_resend_verification() {
+    let mailer = MemoryMailer::new();
+    let mut store = store_with_account(false);
+    store.expect_get_verification_sent_on()
+        .times(1)
+        .returning(|_| Ok(Some(Utc::now() - chrono::Duration::minutes(2))));
+    store.expect_add_verification_token()
+        .times(1)
+        .returning(|_, _, _, _| Ok(true));
+    let result = super::resend_verification(create_test_session(), store, Arc::new(mailer.clone())).await;
+    assert!(result.is_ok());
+    assert_eq!(mailer.sent().len(), 1);
+
+    // No new mail while the last one is less than a minute old.
+    let mailer = MemoryMailer::new();
+    let mut store = store_with_account(false);
+    store.expect_get_verification_sent_on()
+        .times(1)
+        .returning(|_| Ok(Some(Utc::now() - chrono::Duration::seconds(10))));
+    store.expect_add_verification_token().never();
+    let result = super::resend_verification(create_test_session(), store, Arc::new(mailer.clone())).await;
+    let body = warp::hyper::body::to_bytes(result.unwrap().into_response().into_body()).await.unwrap();
+    assert_eq!(body, "false");
+    assert!(mailer.sent().is_empty());
+
+    // Verified accounts get no mail.
+    let mailer = MemoryMailer::new();
+    let result = super::resend_verification(create_test_session(), store_with_account(true), Arc::new(mailer.clone())).await;
+    assert!(result.is_ok());
+    assert!(mailer.sent().is_empty());
+}
+
+#[tokio::test]
+async fn test_verified_filter() {
+    let session = create_test_session();
+    let token = super::issue_token(&test_keyring(), session.account_id, session.generation, session.session_id).unwrap();
+
+    for (email_verified, accepted) in [(true, true), (false, false)] {
+        let verified_filter = super::verified(store_with_account(email_verified), test_keyring());
+        let result = warp::test::request()
+            .header("Authorization", token.as_str())
+            .path("/")
+            .filter(&verified_filter)
+            .await;
+        assert_eq!(result.is_ok(), accepted);
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use tracing::{event, instrument, Level};
+use warp::http::StatusCode;
+
+use crate::types::account::Session;
+use crate::types::answer::AnswerId;
+use crate::types::comment::{validate_comment, CommentId, CommentParent, NewComment};
+use crate::types::pagination::{extract_pagination, Pagination};
+use crate::types::question::QuestionId;
+use crate::handle_errors;
+
+pub mod store_trait;
+use store_trait::StoreTrait;
+use super::transaction::Transactional;
+
+#[cfg(test)]
+mod tests;
+
+async fn get_comments<S: StoreTrait>(
+    parent: CommentParent,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut pagination = Pagination::default();
+
+    if !params.is_empty() {
+        event!(Level::INFO, pagination = true);
+        pagination = extract_pagination(params)?;
+    }
+
+    match store
+        .get_comments(parent, pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+async fn add_comment<S: StoreTrait>(
+    parent: CommentParent,
+    session: Session,
+    store: S,
+    new_comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let comment = NewComment {
+        content: validate_comment(&new_comment.content)?,
+    };
+
+    match store.add_comment(parent, comment, session.account_id).await {
+        Ok(comment) => Ok(warp::reply::json(&comment)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Get comments of question
+ *
+ * @Dev Lists the comments on a question, oldest first.
+ *
+ * @params `id`: The ID of the question
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `params`: Query parameters for pagination.
+*/
+#[instrument]
+pub async fn get_question_comments<S: StoreTrait>(
+    id: QuestionId,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "rust_hour", Level::INFO, "querying comments of question");
+    get_comments(CommentParent::Question(id), params, store).await
+}
+
+/**
+ * @Notice Get comments of answer
+ *
+ * @Dev Lists the comments on an answer, oldest first.
+ *
+ * @params `id`: The ID of the answer
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `params`: Query parameters for pagination.
+*/
+#[instrument]
+pub async fn get_answer_comments<S: StoreTrait>(
+    id: AnswerId,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "rust_hour", Level::INFO, "querying comments of answer");
+    get_comments(CommentParent::Answer(id), params, store).await
+}
+
+/**
+ * @Notice Comment on question
+ *
+ * @Dev Adds a comment to a live question. The content is trimmed and limited to `MAX_COMMENT_LENGTH` characters.
+ *
+ * @params `id`: The ID of the question
+ * @params `session`: The authenticated user session object.
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `new_comment`: The content of the comment.
+*/
+pub async fn add_question_comment<S: StoreTrait>(
+    id: QuestionId,
+    session: Session,
+    store: S,
+    new_comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    add_comment(CommentParent::Question(id), session, store, new_comment).await
+}
+
+/**
+ * @Notice Comment on answer
+ *
+ * @Dev Adds a comment to a live answer. The content is trimmed and limited to `MAX_COMMENT_LENGTH` characters.
+ *
+ * @params `id`: The ID of the answer
+ * @params `session`: The authenticated user session object.
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `new_comment`: The content of the comment.
+*/
+pub async fn add_answer_comment<S: StoreTrait>(
+    id: AnswerId,
+    session: Session,
+    store: S,
+    new_comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    add_comment(CommentParent::Answer(id), session, store, new_comment).await
+}
+
+/**
+ * @Notice Update comment
+ *
+ * @Dev Allows a user to edit an existing comment, provided they are the owner.
+ *
+ * @params `id`: The ID of the comment to be updated.
+ * @params `session`: The authenticated user session object.
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `comment`: The new content of the comment.
+*/
+pub async fn update_comment<S: StoreTrait + Transactional>(
+    id: CommentId,
+    session: Session,
+    store: S,
+    comment: NewComment,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let comment = NewComment {
+        content: validate_comment(&comment.content)?,
+    };
+    let tx = store.begin().await?;
+    if tx.is_comment_owner(id, &account_id).await? {
+        match tx.update_comment(comment, id, account_id).await {
+            Ok(res) => {
+                tx.commit().await?;
+                Ok(warp::reply::json(&res))
+            }
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
+
+/**
+ * @Notice Delete comment
+ *
+ * @Dev Allows a user to delete an existing comment, provided they are the owner.
+ *      Comments are deleted for good, they cannot be restored.
+ *
+ * @params `id`: The ID of the comment to be deleted.
+ * @params `session`: The authenticated user session object.
+ * @params  `store`: A `Store` instance used to interact with the database.
+*/
+pub async fn delete_comment<S: StoreTrait + Transactional>(
+    id: CommentId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    if tx.is_comment_owner(id, &account_id).await? {
+        match tx.delete_comment(id, account_id).await {
+            Ok(_) => {
+                tx.commit().await?;
+                Ok(warp::reply::with_status(
+                    format!("Comment {} deleted", id.0),
+                    StatusCode::OK,
+                ))
+            }
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+use std::fmt::Debug;
+use crate::types::account::AccountId;
+use crate::types::comment::{Comment, CommentId, CommentParent, NewComment};
+use crate::handle_errors;
+
+#[async_trait]
+pub trait StoreTrait: Clone + Debug {
+    async fn get_comments(&self, parent: CommentParent, limit: Option<i32>, offset: i32) -> Result<Vec<Comment>, handle_errors::Error>;
+    async fn add_comment(&self, parent: CommentParent, new_comment: NewComment, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+    async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
+    async fn update_comment(&self, comment: NewComment, id: CommentId, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+    async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+}
@@ -0,0 +1,195 @@
+use mockall::predicate::*;
+use mockall::*;
+use chrono::prelude::*;
+use std::collections::HashMap;
+
+use crate::types::account::{AccountId, Session, SessionId};
+use crate::types::answer::AnswerId;
+use crate::types::comment::{Comment, CommentId, CommentParent, NewComment, MAX_COMMENT_LENGTH};
+use crate::types::question::QuestionId;
+use crate::handle_errors;
+use super::store_trait::StoreTrait;
+use super::Transactional;
+
+mock! {
+    #[derive(Debug)]
+    Store {}
+
+    #[async_trait::async_trait]
+    impl StoreTrait for Store {
+        async fn get_comments(&self, parent: CommentParent, limit: Option<i32>, offset: i32) -> Result<Vec<Comment>, handle_errors::Error>;
+        async fn add_comment(&self, parent: CommentParent, new_comment: NewComment, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+        async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
+        async fn update_comment(&self, comment: NewComment, id: CommentId, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+        async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+    }
+
+    #[async_trait::async_trait]
+    impl Transactional for Store {
+        async fn begin(&self) -> Result<Self, handle_errors::Error>;
+        async fn commit(self) -> Result<(), handle_errors::Error>;
+    }
+
+    impl Clone for Store {
+        fn clone(&self) -> Self;
+    }
+}
+
+// Sets up the expectations of the transaction handle `begin` returns.
+fn expect_transaction(store: &mut MockStore, setup: impl Fn(&mut MockStore) + Send + 'static) {
+    store.expect_begin()
+        .times(1)
+        .returning(move || {
+            let mut tx = MockStore::new();
+            setup(&mut tx);
+            Ok(tx)
+        });
+}
+
+fn create_test_session() -> Session {
+    Session {
+        account_id: AccountId(1),
+        exp: Utc::now() + chrono::Duration::days(1),
+        nbf: Utc::now(),
+        generation: 0,
+        session_id: SessionId(1),
+    }
+}
+
+fn create_test_comment(content: String) -> Comment {
+    Comment {
+        id: CommentId(1),
+        content,
+        question_id: Some(QuestionId(1)),
+        answer_id: None,
+        account_id: AccountId(1),
+        created_on: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_get_answer_comments_with_pagination() {
+    let mut store = MockStore::new();
+
+    store.expect_get_comments()
+        .with(eq(CommentParent::Answer(AnswerId(2))), eq(Some(10)), eq(0))
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+
+    let mut params = HashMap::new();
+    params.insert("limit".to_string(), "10".to_string());
+    params.insert("offset".to_string(), "0".to_string());
+
+    let result = super::get_answer_comments(AnswerId(2), params, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_add_question_comment_trims() {
+    let mut store = MockStore::new();
+
+    store.expect_add_comment()
+        .with(
+            eq(CommentParent::Question(QuestionId(1))),
+            eq(NewComment { content: "Which version?".to_string() }),
+            eq(AccountId(1)),
+        )
+        .times(1)
+        .returning(|_, new_comment, _| Ok(create_test_comment(new_comment.content)));
+
+    let new_comment = NewComment {
+        content: "  Which version?\n".to_string(),
+    };
+    let result = super::add_question_comment(QuestionId(1), create_test_session(), store, new_comment).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_add_comment_too_long() {
+    let store = MockStore::new();
+
+    let new_comment = NewComment {
+        content: "a".repeat(MAX_COMMENT_LENGTH + 1),
+    };
+    let result = super::add_answer_comment(AnswerId(2), create_test_session(), store, new_comment).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::InvalidComment(_)));
+        }
+        _ => panic!("Expected invalid comment error"),
+    }
+}
+
+#[tokio::test]
+async fn test_update_comment_success() {
+    let mut store = MockStore::new();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_comment_owner()
+            .with(eq(CommentId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_update_comment()
+            .with(eq(NewComment { content: "Edited".to_string() }), eq(CommentId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|comment, _, _| Ok(create_test_comment(comment.content)));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let comment = NewComment {
+        content: "Edited".to_string(),
+    };
+    let result = super::update_comment(CommentId(1), create_test_session(), store, comment).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_update_comment_unauthorized() {
+    let mut store = MockStore::new();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_comment_owner()
+            .times(1)
+            .returning(|_, _| Ok(false));
+    });
+
+    let comment = NewComment {
+        content: "Edited".to_string(),
+    };
+    let result = super::update_comment(CommentId(1), create_test_session(), store, comment).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::Unauthorized));
+        }
+        _ => panic!("Expected unauthorized error"),
+    }
+}
+
+#[tokio::test]
+async fn test_delete_comment_success() {
+    let mut store = MockStore::new();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_comment_owner()
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_delete_comment()
+            .with(eq(CommentId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let result = super::delete_comment(CommentId(1), create_test_session(), store).await;
+    assert!(result.is_ok());
+}
@@ -1,3 +1,8 @@
+pub mod admin;
 pub mod answer;
+pub mod audit;
 pub mod authentication;
+pub mod comment;
 pub mod question;
+pub mod tag;
+pub mod transaction;
@@ -1,15 +1,28 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
 use tracing::{event, instrument, Level};
 use warp::http::StatusCode;
+use warp::Reply;
 
 use crate::types::account::Session;
-use crate::types::pagination::{extract_pagination, Pagination};
-use crate::types::question::{NewQuestion, Question, QuestionId};
+use crate::types::answer::AnswerId;
+use crate::types::comment::CommentParent;
+use crate::types::etag::{body_etag, etag, extract_if_match, if_none_match};
+use crate::types::filter::{extract_answer_sort, extract_question_filter, AnswerSort};
+use crate::types::pagination::{extract_keyset_pagination, extract_pagination, Pagination, DEFAULT_PAGE_SIZE};
+use crate::types::question::{
+    extract_cascade, extract_include, NewQuestion, Question, QuestionDetail, QuestionId,
+};
+use crate::types::revision::{extract_diff_range, QuestionDiff, RevisionId};
+use crate::types::search::extract_query;
+use crate::types::vote::{NewVote, Vote};
 use crate::handle_errors;
 
 pub mod store_trait;
 use store_trait::StoreTrait;
+use super::comment::store_trait::StoreTrait as CommentStoreTrait;
+use super::transaction::Transactional;
 
 #[cfg(test)]
 mod tests;
@@ -17,17 +30,35 @@ mod tests;
 /**
  * @Notice Get questions
  *
- * @Dev Retrieves questions, with optional pagination.
+ * @Dev Retrieves questions, filtered and sorted, with optional offset or cursor pagination.
  *
  * @params  `store`: A `Store` instance used to interact with the database.
- * @params `params`: Query parameters for pagination.
+ * @params `params`: Query parameters for filtering, sorting and pagination, `limit` with `offset` or an optional `cursor`.
+ * @params `if_none_match`: The optional `If-None-Match` header; 304 Not Modified when it names the `ETag` of the list.
 */
 #[instrument]
 pub async fn get_questions<S: StoreTrait>(
-    params: HashMap<String, String>,
+    params: Vec<(String, String)>,
+    if_none_match: Option<String>,
     store: S,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     event!(target: "rust_hour", Level::INFO, "querying questions");
+    let (filter, params) = extract_question_filter(params)?;
+
+    if let Some(keyset) = extract_keyset_pagination(&params)? {
+        event!(Level::INFO, cursor = true);
+        // Cursors point into the creation order.
+        if !filter.sort.is_keyset() {
+            return Err(warp::reject::custom(handle_errors::Error::InvalidParameter(
+                "sort".to_string(),
+            )));
+        }
+        let page = store
+            .get_questions_page(filter, keyset.cursor, keyset.limit)
+            .await?;
+        return Ok(json_with_etag(&page, if_none_match));
+    }
+
     let mut pagination = Pagination::default();
 
     if !params.is_empty() {
@@ -36,40 +67,79 @@ pub async fn get_questions<S: StoreTrait>(
     }
 
     match store
-        .get_questions(pagination.limit, pagination.offset)
+        .get_questions(filter, pagination.limit, pagination.offset)
         .await
     {
-        Ok(res) => Ok(warp::reply::json(&res)),
+        Ok(res) => Ok(json_with_etag(&res, if_none_match)),
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
 
+// Replies with `value` as JSON and its `ETag`, or with an empty 304 Not
+// Modified when the `If-None-Match` header already names that tag.
+fn json_with_etag<T: Serialize>(value: &T, header: Option<String>) -> warp::reply::Response {
+    let body = match serde_json::to_vec(value) {
+        Ok(body) => body,
+        // Let warp report the error.
+        Err(_) => return warp::reply::json(value).into_response(),
+    };
+    let etag = body_etag(&body);
+    if if_none_match(header.as_deref(), &etag) {
+        return warp::reply::with_header(StatusCode::NOT_MODIFIED, "ETag", etag).into_response();
+    }
+    let json = warp::reply::with_header(body, "Content-Type", "application/json");
+    warp::reply::with_header(json, "ETag", etag).into_response()
+}
+
 /**
  * @Notice Update question
  *
  * @Dev Allows a user to update an existing question, provided they are the owner.
+ *      With an `If-Match` header the update only goes through while the
+ *      question is still at that version, and fails with 412 otherwise.
  *
  * @params  `store`: A `Store` instance used to interact with the database.
  * @params `id`: The ID of the question to be updated.
  * @params `session`: The authenticated user session object.
+ * @params `if_match`: The optional `If-Match` header, the `ETag` the question was read with.
  * @params `question`: The updated question details.
 */
-pub async fn update_question<S: StoreTrait>(
+pub async fn update_question<S: StoreTrait + Transactional>(
     id: QuestionId,
     session: Session,
     store: S,
+    if_match: Option<String>,
     question: Question,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    if store.is_question_owner(id, &account_id).await? {
+    let expected_version = extract_if_match(if_match)?;
+    // Check ownership, version and update in one transaction so the question
+    // cannot change hands or be edited in between.
+    let tx = store.begin().await?;
+    if tx.is_question_owner(id, &account_id).await? {
+        if let Some(expected_version) = expected_version {
+            if tx.get_question(id).await?.version != expected_version {
+                return Err(warp::reject::custom(handle_errors::Error::PreconditionFailed));
+            }
+        }
         let question = Question {
             id: question.id,
             title: question.title,
             content: question.content,
             tags: question.tags,
+            score: question.score,
+            accepted_answer_id: question.accepted_answer_id,
+            author: question.author,
+            created_on: question.created_on,
+            updated_on: question.updated_on,
+            version: question.version,
         };
-        match store.update_question(question, id, account_id).await {
-            Ok(res) => Ok(warp::reply::json(&res)),
+        match tx.update_question(question, id, account_id).await {
+            Ok(res) => {
+                tx.commit().await?;
+                let etag = etag(res.version);
+                Ok(warp::reply::with_header(warp::reply::json(&res), "ETag", etag))
+            }
             Err(e) => Err(warp::reject::custom(e)),
         }
     } else {
@@ -81,23 +151,191 @@ pub async fn update_question<S: StoreTrait>(
  * @Notice Delete question
  *
  * @Dev Allows a user to delete an existing question, provided they are the owner.
+ *      A question that still has answers is only deleted, together with its
+ *      answers, when `cascade=true` is passed; otherwise the request fails with
+ *      409 Conflict and the number of answers.
  *
  * @params  `store`: A `Store` instance used to interact with the database.
  * @params `session`: The authenticated user session object.
  * @params `id`: The ID of the question to be deleted.
+ * @params `params`: Query parameters, the optional `cascade` flag.
 */
-pub async fn delete_question<S: StoreTrait>(
+pub async fn delete_question<S: StoreTrait + Transactional>(
     id: QuestionId,
     session: Session,
+    params: HashMap<String, String>,
     store: S,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let account_id = session.account_id;
-    if store.is_question_owner(id, &account_id).await? {
-        match store.delete_question(id, account_id).await {
-            Ok(_) => Ok(warp::reply::with_status(
+    let cascade = extract_cascade(&params)?;
+    // Counting the answers and deleting happen in one transaction; the
+    // ownership check locks the question so no answer can be added in between.
+    let tx = store.begin().await?;
+    if !tx.is_question_owner(id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+    if !cascade {
+        let answers = tx.count_answers(id).await?;
+        if answers > 0 {
+            return Err(warp::reject::custom(
+                handle_errors::Error::QuestionHasAnswers(answers),
+            ));
+        }
+    }
+    match tx.delete_question(id, account_id).await {
+        Ok(_) => {
+            tx.commit().await?;
+            Ok(warp::reply::with_status(
                 format!("Question {} deleted", id.0),
                 StatusCode::OK,
-            )),
+            ))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Restore question
+ *
+ * @Dev Allows the owner to bring back a deleted question, together with the
+ *      answers that were deleted with it, until it is purged. The store only
+ *      finds deleted questions of the account itself.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the question to be restored.
+*/
+pub async fn restore_question<S: StoreTrait + Transactional>(
+    id: QuestionId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    match tx.restore_question(id, account_id).await {
+        Ok(res) => {
+            tx.commit().await?;
+            Ok(warp::reply::json(&res))
+        }
+        Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)) => {
+            Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+// Sets or, with `None`, retracts the vote of the session's account.
+async fn set_question_vote<S: StoreTrait + Transactional>(
+    id: QuestionId,
+    session: Session,
+    store: S,
+    vote: Option<Vote>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    if tx.is_question_owner(id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::SelfVote));
+    }
+    match tx.vote_question(id, account_id, vote).await {
+        Ok(res) => {
+            tx.commit().await?;
+            Ok(warp::reply::json(&res))
+        }
+        Err(e) => Err(warp::reject::custom(question_not_found(e))),
+    }
+}
+
+/**
+ * @Notice Vote on question
+ *
+ * @Dev Casts an up or down vote, or changes the one cast before; an account has one vote per question.
+ *      Voting on your own question is refused with 403 Forbidden.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the question
+ * @params `new_vote`: The vote, `up` or `down`.
+*/
+pub async fn vote_question<S: StoreTrait + Transactional>(
+    id: QuestionId,
+    session: Session,
+    store: S,
+    new_vote: NewVote,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_question_vote(id, session, store, Some(new_vote.vote)).await
+}
+
+/**
+ * @Notice Retract question vote
+ *
+ * @Dev Removes the vote the account cast on a question, if any.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the question
+*/
+pub async fn retract_question_vote<S: StoreTrait + Transactional>(
+    id: QuestionId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    set_question_vote(id, session, store, None).await
+}
+
+/**
+ * @Notice Accept answer
+ *
+ * @Dev Marks an answer as the accepted one, replacing the one accepted before. Only the owner of the question can accept.
+ *      The answer has to be a live answer to the question.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the question
+ * @params `answer_id`: The ID of the answer to accept
+*/
+pub async fn accept_answer<S: StoreTrait + Transactional>(
+    id: QuestionId,
+    answer_id: AnswerId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    if tx.is_question_owner(id, &account_id).await? {
+        match tx.accept_answer(id, answer_id).await {
+            Ok(res) => {
+                tx.commit().await?;
+                Ok(warp::reply::json(&res))
+            }
+            Err(e) => Err(warp::reject::custom(e)),
+        }
+    } else {
+        Err(warp::reject::custom(handle_errors::Error::Unauthorized))
+    }
+}
+
+/**
+ * @Notice Unaccept answer
+ *
+ * @Dev Removes the mark from the accepted answer, if any. Only the owner of the question can unaccept.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `session`: The authenticated user session object.
+ * @params `id`: The ID of the question
+*/
+pub async fn unaccept_answer<S: StoreTrait + Transactional>(
+    id: QuestionId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    if tx.is_question_owner(id, &account_id).await? {
+        match tx.unaccept_answer(id).await {
+            Ok(res) => {
+                tx.commit().await?;
+                Ok(warp::reply::json(&res))
+            }
             Err(e) => Err(warp::reject::custom(e)),
         }
     } else {
@@ -137,19 +375,35 @@ pub async fn add_question<S: StoreTrait>(
 /**
  * @Notice Get answers of question
  *
- * @Dev Retrieves answers for a specific question, with optional pagination.
+ * @Dev Retrieves answers for a specific question, oldest or best scored first after the accepted answer, with optional offset or cursor pagination.
  *
  * @params  `store`: A `Store` instance used to interact with the database.
  * @params `id`: The ID of the question
- * @params `params`: Query parameters for pagination.
+ * @params `params`: Query parameters, an optional `sort` and the pagination, `limit` with `offset` or an optional `cursor`.
+ * @params `if_none_match`: The optional `If-None-Match` header; 304 Not Modified when it names the `ETag` of the list.
 */
 #[instrument]
 pub async fn get_answers<S: StoreTrait>(
     id: QuestionId,
-    params: HashMap<String, String>,
+    mut params: HashMap<String, String>,
+    if_none_match: Option<String>,
     store: S,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     event!(target: "rust_hour", Level::INFO, "querying questions");
+    let sort = extract_answer_sort(&mut params)?;
+
+    if let Some(keyset) = extract_keyset_pagination(&params)? {
+        event!(Level::INFO, cursor = true);
+        // Cursors point into the creation order.
+        if sort != AnswerSort::Oldest {
+            return Err(warp::reject::custom(handle_errors::Error::InvalidParameter(
+                "sort".to_string(),
+            )));
+        }
+        let page = store.get_answers_page(id, keyset.cursor, keyset.limit).await?;
+        return Ok(json_with_etag(&page, if_none_match));
+    }
+
     let mut pagination = Pagination::default();
 
     if !params.is_empty() {
@@ -158,10 +412,192 @@ pub async fn get_answers<S: StoreTrait>(
     }
 
     match store
-        .get_answers(id, pagination.limit, pagination.offset)
+        .get_answers(id, sort, pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(json_with_etag(&res, if_none_match)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Search questions and answers
+ *
+ * @Dev Full-text search over question titles and contents and answer contents, best match first.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `params`: Query parameters, the search query `q` with optional `limit` and `offset`.
+*/
+#[instrument]
+pub async fn search<S: StoreTrait>(
+    mut params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "rust_hour", Level::INFO, "searching questions");
+    let query = extract_query(&params)?;
+    params.remove("q");
+    let mut pagination = Pagination::default();
+
+    if !params.is_empty() {
+        event!(Level::INFO, pagination = true);
+        pagination = extract_pagination(params)?;
+    }
+
+    match store
+        .search(query, pagination.limit, pagination.offset)
         .await
     {
         Ok(res) => Ok(warp::reply::json(&res)),
         Err(e) => Err(warp::reject::custom(e)),
     }
 }
+
+// A missing or deleted question, or a missing revision of it, is a 404
+// rather than a failed query.
+fn question_not_found(error: handle_errors::Error) -> handle_errors::Error {
+    match error {
+        handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound) => {
+            handle_errors::Error::NotFound
+        }
+        error => error,
+    }
+}
+
+/**
+ * @Notice Get question
+ *
+ * @Dev Returns a question with its author, creation time, answer count and first page of answers, or 404 if there is none.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the question
+ * @params `params`: Query parameters, an optional `include` of `comments` and `revisions`.
+*/
+#[instrument]
+pub async fn get_question<S: StoreTrait + CommentStoreTrait>(
+    id: QuestionId,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "rust_hour", Level::INFO, "querying question");
+    let include = extract_include(&params)?;
+
+    let question = store.get_question(id).await.map_err(question_not_found)?;
+    let answer_count = store.count_answers(id).await?;
+    let answers = store.get_answers_page(id, None, DEFAULT_PAGE_SIZE).await?;
+    let comments = if include.comments {
+        Some(store.get_comments(CommentParent::Question(id), None, 0).await?)
+    } else {
+        None
+    };
+    let revisions = if include.revisions {
+        Some(store.get_question_revisions(id).await?)
+    } else {
+        None
+    };
+
+    let etag = etag(question.version);
+    Ok(warp::reply::with_header(
+        warp::reply::json(&QuestionDetail {
+            question,
+            answer_count,
+            answers,
+            comments,
+            revisions,
+        }),
+        "ETag",
+        etag,
+    ))
+}
+
+/**
+ * @Notice Get question revisions
+ *
+ * @Dev Lists the earlier versions of a question, oldest first. Each revision
+ *      holds the question as it was before an edit, with the editor and time.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the question
+*/
+pub async fn get_question_revisions<S: StoreTrait>(
+    id: QuestionId,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // The revisions of a deleted question are hidden along with it.
+    store.get_question(id).await.map_err(question_not_found)?;
+    match store.get_question_revisions(id).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Diff question revisions
+ *
+ * @Dev Returns a line-level diff of the title, content and tags between the
+ *      revisions `from` and `to`, or between `from` and the current version
+ *      when `to` is left out.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the question
+ * @params `params`: Query parameters `from` and optional `to`.
+*/
+pub async fn get_question_diff<S: StoreTrait>(
+    id: QuestionId,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (from, to) = extract_diff_range(&params)?;
+    let current = store.get_question(id).await.map_err(question_not_found)?;
+    let old = store
+        .get_question_revision(id, from)
+        .await
+        .map_err(question_not_found)?
+        .to_question();
+    let new = match to {
+        Some(to) => store
+            .get_question_revision(id, to)
+            .await
+            .map_err(question_not_found)?
+            .to_question(),
+        None => current,
+    };
+    Ok(warp::reply::json(&QuestionDiff::new(from, to, &old, &new)))
+}
+
+/**
+ * @Notice Roll back question
+ *
+ * @Dev Allows the owner to restore the title, content and tags of an earlier
+ *      revision. The rollback is an edit itself, so the version it replaces
+ *      becomes a new revision.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `id`: The ID of the question
+ * @params `revision_id`: The ID of the revision to go back to.
+ * @params `session`: The authenticated user session object.
+*/
+pub async fn rollback_question<S: StoreTrait + Transactional>(
+    id: QuestionId,
+    revision_id: RevisionId,
+    session: Session,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let tx = store.begin().await?;
+    if !tx.is_question_owner(id, &account_id).await? {
+        return Err(warp::reject::custom(handle_errors::Error::Unauthorized));
+    }
+    let question = tx
+        .get_question_revision(id, revision_id)
+        .await
+        .map_err(question_not_found)?
+        .to_question();
+    match tx.update_question(question, id, account_id).await {
+        Ok(res) => {
+            tx.commit().await?;
+            Ok(warp::reply::json(&res))
+        }
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
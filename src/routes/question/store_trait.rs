@@ -2,15 +2,31 @@ use async_trait::async_trait;
 use std::fmt::Debug;
 use crate::types::account::AccountId;
 use crate::types::question::{Question, NewQuestion, QuestionId};
-use crate::types::answer::Answer;
+use crate::types::answer::{Answer, AnswerId};
+use crate::types::filter::{AnswerSort, QuestionFilter};
+use crate::types::pagination::{Cursor, Page};
+use crate::types::revision::{QuestionRevision, RevisionId};
+use crate::types::search::SearchResult;
+use crate::types::vote::Vote;
 use crate::handle_errors;
 
 #[async_trait]
 pub trait StoreTrait: Clone + Debug {
-    async fn get_questions(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+    async fn get_questions(&self, filter: QuestionFilter, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+    async fn get_questions_page(&self, filter: QuestionFilter, cursor: Option<Cursor>, limit: i32) -> Result<Page<Question>, handle_errors::Error>;
     async fn is_question_owner(&self, question_id: QuestionId, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
     async fn add_question(&self, new_question: NewQuestion, account_id: AccountId) -> Result<Question, handle_errors::Error>;
     async fn update_question(&self, question: Question, id: QuestionId, account_id: AccountId) -> Result<Question, handle_errors::Error>;
     async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, handle_errors::Error>;
-    async fn get_answers(&self, question_id: QuestionId, limit: Option<i32>, offset: i32) -> Result<Vec<Answer>, handle_errors::Error>;
+    async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, handle_errors::Error>;
+    async fn get_answers(&self, question_id: QuestionId, sort: AnswerSort, limit: Option<i32>, offset: i32) -> Result<Vec<Answer>, handle_errors::Error>;
+    async fn get_answers_page(&self, question_id: QuestionId, cursor: Option<Cursor>, limit: i32) -> Result<Page<Answer>, handle_errors::Error>;
+    async fn count_answers(&self, question_id: QuestionId) -> Result<i64, handle_errors::Error>;
+    async fn get_question(&self, id: QuestionId) -> Result<Question, handle_errors::Error>;
+    async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, handle_errors::Error>;
+    async fn get_question_revision(&self, id: QuestionId, revision_id: RevisionId) -> Result<QuestionRevision, handle_errors::Error>;
+    async fn vote_question(&self, id: QuestionId, account_id: AccountId, vote: Option<Vote>) -> Result<Question, handle_errors::Error>;
+    async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, handle_errors::Error>;
+    async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, handle_errors::Error>;
+    async fn search(&self, query: String, limit: Option<i32>, offset: i32) -> Result<Vec<SearchResult>, handle_errors::Error>;
 } 
\ No newline at end of file
@@ -5,12 +5,20 @@ use chrono::prelude::*;
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use warp::http::StatusCode;
+use warp::Reply;
 
-use crate::types::account::{AccountId, Session};
+use crate::types::account::{AccountId, Author, Session, SessionId};
 use crate::types::answer::{Answer, AnswerId};
+use crate::types::comment::{Comment, CommentId, CommentParent, NewComment};
+use crate::types::filter::{AnswerSort, QuestionFilter, QuestionSort};
+use crate::types::pagination::{Cursor, Page};
 use crate::types::question::{NewQuestion, Question, QuestionId};
+use crate::types::search::SearchResult;
+use crate::types::revision::{QuestionRevision, RevisionId};
+use crate::types::vote::{NewVote, Vote};
 use crate::handle_errors;
 use super::store_trait::StoreTrait;
+use super::{CommentStoreTrait, Transactional};
 
 mock! {
     #[derive(Debug)]
@@ -18,12 +26,38 @@ mock! {
 
     #[async_trait::async_trait]
     impl StoreTrait for Store {
-        async fn get_questions(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+        async fn get_questions(&self, filter: QuestionFilter, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+        async fn get_questions_page(&self, filter: QuestionFilter, cursor: Option<Cursor>, limit: i32) -> Result<Page<Question>, handle_errors::Error>;
         async fn is_question_owner(&self, question_id: QuestionId, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
         async fn add_question(&self, new_question: NewQuestion, account_id: AccountId) -> Result<Question, handle_errors::Error>;
         async fn update_question(&self, question: Question, id: QuestionId, account_id: AccountId) -> Result<Question, handle_errors::Error>;
         async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, handle_errors::Error>;
-        async fn get_answers(&self, question_id: QuestionId, limit: Option<i32>, offset: i32) -> Result<Vec<Answer>, handle_errors::Error>;
+        async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, handle_errors::Error>;
+        async fn get_answers(&self, question_id: QuestionId, sort: AnswerSort, limit: Option<i32>, offset: i32) -> Result<Vec<Answer>, handle_errors::Error>;
+        async fn get_answers_page(&self, question_id: QuestionId, cursor: Option<Cursor>, limit: i32) -> Result<Page<Answer>, handle_errors::Error>;
+        async fn count_answers(&self, question_id: QuestionId) -> Result<i64, handle_errors::Error>;
+        async fn get_question(&self, id: QuestionId) -> Result<Question, handle_errors::Error>;
+        async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, handle_errors::Error>;
+        async fn get_question_revision(&self, id: QuestionId, revision_id: RevisionId) -> Result<QuestionRevision, handle_errors::Error>;
+        async fn vote_question(&self, id: QuestionId, account_id: AccountId, vote: Option<Vote>) -> Result<Question, handle_errors::Error>;
+        async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, handle_errors::Error>;
+        async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, handle_errors::Error>;
+        async fn search(&self, query: String, limit: Option<i32>, offset: i32) -> Result<Vec<SearchResult>, handle_errors::Error>;
+    }
+
+    #[async_trait::async_trait]
+    impl CommentStoreTrait for Store {
+        async fn get_comments(&self, parent: CommentParent, limit: Option<i32>, offset: i32) -> Result<Vec<Comment>, handle_errors::Error>;
+        async fn add_comment(&self, parent: CommentParent, new_comment: NewComment, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+        async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, handle_errors::Error>;
+        async fn update_comment(&self, comment: NewComment, id: CommentId, account_id: AccountId) -> Result<Comment, handle_errors::Error>;
+        async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, handle_errors::Error>;
+    }
+
+    #[async_trait::async_trait]
+    impl Transactional for Store {
+        async fn begin(&self) -> Result<Self, handle_errors::Error>;
+        async fn commit(self) -> Result<(), handle_errors::Error>;
     }
 
     impl Clone for Store {
@@ -42,11 +76,24 @@ fn setup_mock_store() -> Arc<Mutex<MockStore>> {
     mock_store
 }
 
+// Sets up the expectations of the transaction handle `begin` returns.
+fn expect_transaction(store: &mut MockStore, setup: impl Fn(&mut MockStore) + Send + 'static) {
+    store.expect_begin()
+        .times(1)
+        .returning(move || {
+            let mut tx = MockStore::new();
+            setup(&mut tx);
+            Ok(tx)
+        });
+}
+
 fn create_test_session() -> Session {
     Session {
         account_id: AccountId(1),
         exp: Utc::now() + chrono::Duration::days(1),
         nbf: Utc::now(),
+        generation: 0,
+        session_id: SessionId(1),
     }
 }
 
@@ -56,20 +103,48 @@ async fn test_get_questions_success() {
     let mut store = mock_store.lock().unwrap().clone();
     
     store.expect_get_questions()
-        .with(eq(None), eq(0))
+        .with(eq(QuestionFilter::default()), eq(None), eq(0))
         .times(1)
-        .returning(|_, _| Ok(vec![Question {
+        .returning(|_, _, _| Ok(vec![Question {
             id: QuestionId(1),
             title: "Test Question".to_string(),
             content: "Test Content".to_string(),
             tags: Some(vec!["test".to_string()]),
+            score: 0,
+            accepted_answer_id: None,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
         }]));
     
-    let params = HashMap::new();
-    let result = super::get_questions(params, store).await;
+    let result = super::get_questions(Vec::new(), None, store).await;
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_get_questions_not_modified() {
+    let mock_store = setup_mock_store();
+    let store = || {
+        let mut store = mock_store.lock().unwrap().clone();
+        store.expect_get_questions()
+            .times(1)
+            .returning(|_, _, _| Ok(vec![]));
+        store
+    };
+
+    let res = super::get_questions(Vec::new(), None, store()).await.unwrap().into_response();
+    assert_eq!(res.status(), StatusCode::OK);
+    let etag = res.headers()["etag"].to_str().unwrap().to_string();
+
+    let res = super::get_questions(Vec::new(), Some(etag.clone()), store()).await.unwrap().into_response();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers()["etag"], etag.as_str());
+
+    let res = super::get_questions(Vec::new(), Some("\"stale\"".to_string()), store()).await.unwrap().into_response();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
 #[tokio::test]
 async fn test_update_question_success() {
     let mock_store = setup_mock_store();
@@ -81,39 +156,163 @@ async fn test_update_question_success() {
         title: "Updated Title".to_string(),
         content: "Updated Content".to_string(),
         tags: Some(vec!["updated".to_string()]),
+        score: 0,
+        accepted_answer_id: None,
+        author: Author::default(),
+        created_on: Utc::now(),
+        updated_on: None,
+        version: 1,
     };
     
-    store.expect_is_question_owner()
-        .with(eq(QuestionId(1)), eq(&AccountId(1)))
-        .times(1)
-        .returning(|_, _| Ok(true));
-        
-    store.expect_update_question()
-        .with(eq(question.clone()), eq(QuestionId(1)), eq(AccountId(1)))
-        .times(1)
-        .returning(|q, _, _| Ok(q));
+    let expected = question.clone();
+    expect_transaction(&mut store, move |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_update_question()
+            .with(eq(expected.clone()), eq(QuestionId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|q, _, _| Ok(q));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
     
-    let result = super::update_question(QuestionId(1), session, store, question).await;
+    let result = super::update_question(QuestionId(1), session, store, None, question).await;
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_update_question_version_mismatch() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    let question = Question {
+        id: QuestionId(1),
+        title: "Updated Title".to_string(),
+        content: "Updated Content".to_string(),
+        tags: None,
+        score: 0,
+        accepted_answer_id: None,
+        author: Author::default(),
+        created_on: Utc::now(),
+        updated_on: None,
+        version: 1,
+    };
+
+    let current = question.clone();
+    expect_transaction(&mut store, move |tx| {
+        tx.expect_is_question_owner()
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        let current = current.clone();
+        tx.expect_get_question()
+            .with(eq(QuestionId(1)))
+            .times(1)
+            .returning(move |_| Ok(Question { version: 2, ..current.clone() }));
+
+        tx.expect_update_question().times(0);
+    });
+
+    let if_match = Some("\"1\"".to_string());
+    let result = super::update_question(QuestionId(1), session, store, if_match, question).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::PreconditionFailed));
+        }
+        _ => panic!("Expected precondition failed error"),
+    }
+}
+
 #[tokio::test]
 async fn test_delete_question_success() {
     let mock_store = setup_mock_store();
     let mut store = mock_store.lock().unwrap().clone();
     let session = create_test_session();
     
-    store.expect_is_question_owner()
-        .with(eq(QuestionId(1)), eq(&AccountId(1)))
-        .times(1)
-        .returning(|_, _| Ok(true));
-        
-    store.expect_delete_question()
-        .with(eq(QuestionId(1)), eq(AccountId(1)))
-        .times(1)
-        .returning(|_, _| Ok(true));
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_count_answers()
+            .with(eq(QuestionId(1)))
+            .times(1)
+            .returning(|_| Ok(0));
+
+        tx.expect_delete_question()
+            .with(eq(QuestionId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
     
-    let result = super::delete_question(QuestionId(1), session, store).await;
+    let result = super::delete_question(QuestionId(1), session, HashMap::new(), store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_delete_question_with_answers_conflict() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_count_answers()
+            .with(eq(QuestionId(1)))
+            .times(1)
+            .returning(|_| Ok(2));
+    });
+
+    let result = super::delete_question(QuestionId(1), session, HashMap::new(), store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::QuestionHasAnswers(2)));
+        }
+        _ => panic!("Expected conflict error"),
+    }
+}
+
+#[tokio::test]
+async fn test_delete_question_cascade() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_delete_question()
+            .with(eq(QuestionId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let params = HashMap::from([("cascade".to_string(), "true".to_string())]);
+    let result = super::delete_question(QuestionId(1), session, params, store).await;
     assert!(result.is_ok());
 }
 
@@ -137,6 +336,12 @@ async fn test_add_question_success() {
             title: "New Question".to_string(),
             content: "New Content".to_string(),
             tags: Some(vec!["new".to_string()]),
+            score: 0,
+            accepted_answer_id: None,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
         }));
     
     let result = super::add_question(session, store, new_question).await;
@@ -149,16 +354,21 @@ async fn test_get_answers_success() {
     let mut store = mock_store.lock().unwrap().clone();
     
     store.expect_get_answers()
-        .with(eq(QuestionId(1)), eq(None), eq(0))
+        .with(eq(QuestionId(1)), eq(AnswerSort::Oldest), eq(None), eq(0))
         .times(1)
-        .returning(|_, _, _| Ok(vec![Answer {
+        .returning(|_, _, _, _| Ok(vec![Answer {
             id: AnswerId(1),
             content: "Test Answer".to_string(),
             question_id: QuestionId(1),
+            score: 0,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
         }]));
     
     let params = HashMap::new();
-    let result = super::get_answers(QuestionId(1), params, store).await;
+    let result = super::get_answers(QuestionId(1), params, None, store).await;
     assert!(result.is_ok());
 }
 
@@ -173,14 +383,22 @@ async fn test_update_question_unauthorized() {
         title: "Updated Title".to_string(),
         content: "Updated Content".to_string(),
         tags: Some(vec!["updated".to_string()]),
+        score: 0,
+        accepted_answer_id: None,
+        author: Author::default(),
+        created_on: Utc::now(),
+        updated_on: None,
+        version: 1,
     };
     
-    store.expect_is_question_owner()
-        .with(eq(QuestionId(1)), eq(&AccountId(1)))
-        .times(1)
-        .returning(|_, _| Ok(false));
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(false));
+    });
     
-    let result = super::update_question(QuestionId(1), session, store, question).await;
+    let result = super::update_question(QuestionId(1), session, store, None, question).await;
     assert!(result.is_err());
     match result {
         Err(rejection) => {
@@ -197,12 +415,14 @@ async fn test_delete_question_unauthorized() {
     let mut store = mock_store.lock().unwrap().clone();
     let session = create_test_session();
     
-    store.expect_is_question_owner()
-        .with(eq(QuestionId(1)), eq(&AccountId(1)))
-        .times(1)
-        .returning(|_, _| Ok(false));
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(false));
+    });
     
-    let result = super::delete_question(QuestionId(1), session, store).await;
+    let result = super::delete_question(QuestionId(1), session, HashMap::new(), store).await;
     assert!(result.is_err());
     match result {
         Err(rejection) => {
@@ -219,19 +439,679 @@ async fn test_get_questions_with_pagination() {
     let mut store = mock_store.lock().unwrap().clone();
     
     store.expect_get_questions()
-        .with(eq(Some(5)), eq(10))
+        .with(eq(QuestionFilter::default()), eq(Some(5)), eq(10))
         .times(1)
-        .returning(|_, _| Ok(vec![Question {
+        .returning(|_, _, _| Ok(vec![Question {
             id: QuestionId(1),
             title: "Test Question".to_string(),
             content: "Test Content".to_string(),
             tags: Some(vec!["test".to_string()]),
+            score: 0,
+            accepted_answer_id: None,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
         }]));
     
+    let params = vec![
+        ("limit".to_string(), "5".to_string()),
+        ("offset".to_string(), "10".to_string()),
+    ];
+    
+    let result = super::get_questions(params, None, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_questions_with_cursor() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let cursor = Cursor {
+        created_on: Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap(),
+        id: 3,
+    };
+
+    store.expect_get_questions_page()
+        .with(eq(QuestionFilter::default()), eq(Some(cursor)), eq(2))
+        .times(1)
+        .returning(|_, _, _| Ok(Page {
+            items: vec![],
+            next_cursor: None,
+        }));
+
+    let params = vec![
+        ("limit".to_string(), "2".to_string()),
+        ("cursor".to_string(), cursor.encode()),
+    ];
+
+    let result = super::get_questions(params, None, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_questions_with_filter() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let filter = QuestionFilter {
+        tags: vec!["rust".to_string(), "async".to_string()],
+        author: Some(AccountId(2)),
+        unanswered: true,
+        sort: QuestionSort::MostAnswers,
+        ..QuestionFilter::default()
+    };
+
+    store.expect_get_questions()
+        .with(eq(filter), eq(Some(5)), eq(0))
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+
+    let params = vec![
+        ("tag".to_string(), "Rust".to_string()),
+        ("tag".to_string(), "async".to_string()),
+        ("author".to_string(), "2".to_string()),
+        ("unanswered".to_string(), "true".to_string()),
+        ("sort".to_string(), "most_answers".to_string()),
+        ("limit".to_string(), "5".to_string()),
+        ("offset".to_string(), "0".to_string()),
+    ];
+
+    let result = super::get_questions(params, None, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_questions_cursor_requires_creation_order() {
+    let mock_store = setup_mock_store();
+    let store = mock_store.lock().unwrap().clone();
+
+    let params = vec![
+        ("sort".to_string(), "recent_activity".to_string()),
+        ("limit".to_string(), "2".to_string()),
+    ];
+
+    match super::get_questions(params, None, store).await {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(error, handle_errors::Error::InvalidParameter(name) if name == "sort"));
+        }
+        _ => panic!("Expected invalid sort error"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_answers_first_page() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_get_answers_page()
+        .with(eq(QuestionId(1)), eq(None), eq(5))
+        .times(1)
+        .returning(|_, _, _| Ok(Page {
+            items: vec![],
+            next_cursor: None,
+        }));
+
     let mut params = HashMap::new();
     params.insert("limit".to_string(), "5".to_string());
-    params.insert("offset".to_string(), "10".to_string());
-    
-    let result = super::get_questions(params, store).await;
+
+    let result = super::get_answers(QuestionId(1), params, None, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_question_with_comments() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_get_question()
+        .with(eq(QuestionId(1)))
+        .times(1)
+        .returning(|id| Ok(Question {
+            id,
+            title: "Title".to_string(),
+            content: "Content".to_string(),
+            tags: None,
+            score: 0,
+            accepted_answer_id: None,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
+        }));
+    store.expect_count_answers()
+        .times(1)
+        .returning(|_| Ok(0));
+    store.expect_get_answers_page()
+        .with(eq(QuestionId(1)), eq(None), eq(crate::types::pagination::DEFAULT_PAGE_SIZE))
+        .times(1)
+        .returning(|_, _, _| Ok(Page {
+            items: vec![],
+            next_cursor: None,
+        }));
+    store.expect_get_comments()
+        .with(eq(CommentParent::Question(QuestionId(1))), eq(None), eq(0))
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+    store.expect_get_question_revisions().times(0);
+
+    let mut params = HashMap::new();
+    params.insert("include".to_string(), "comments".to_string());
+
+    let result = super::get_question(QuestionId(1), params, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_question_not_found() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_get_question()
+        .times(1)
+        .returning(|_| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+
+    let result = super::get_question(QuestionId(42), HashMap::new(), store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::NotFound));
+        }
+        _ => panic!("Expected not found error"),
+    }
+}
+
+#[tokio::test]
+async fn test_search_with_pagination() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_search()
+        .with(eq("borrow checker".to_string()), eq(Some(5)), eq(0))
+        .times(1)
+        .returning(|_, _, _| Ok(vec![SearchResult {
+            question_id: QuestionId(1),
+            answer_id: None,
+            title: "Test Question".to_string(),
+            snippet: "the <b>borrow</b> <b>checker</b>".to_string(),
+            rank: 0.5,
+        }]));
+
+    let mut params = HashMap::new();
+    params.insert("q".to_string(), "borrow checker".to_string());
+    params.insert("limit".to_string(), "5".to_string());
+    params.insert("offset".to_string(), "0".to_string());
+
+    let result = super::search(params, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_search_missing_query() {
+    let mock_store = setup_mock_store();
+    let store = mock_store.lock().unwrap().clone();
+
+    let result = super::search(HashMap::new(), store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::MissingParameters));
+        }
+        _ => panic!("Expected missing parameters error"),
+    }
+}
+
+#[tokio::test]
+async fn test_restore_question_success() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_restore_question()
+            .with(eq(QuestionId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|id, _| Ok(Question {
+                id,
+                title: "Restored".to_string(),
+                content: "Restored Content".to_string(),
+                tags: None,
+                score: 0,
+                accepted_answer_id: None,
+                author: Author::default(),
+                created_on: Utc::now(),
+                updated_on: None,
+                version: 1,
+            }));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let result = super::restore_question(QuestionId(1), session, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_restore_question_unauthorized() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_restore_question()
+            .with(eq(QuestionId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|_, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+    });
+
+    let result = super::restore_question(QuestionId(1), session, store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::Unauthorized));
+        }
+        _ => panic!("Expected unauthorized error"),
+    }
+}
+
+#[tokio::test]
+async fn test_vote_question_success() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(false));
+
+        tx.expect_vote_question()
+            .with(eq(QuestionId(1)), eq(AccountId(1)), eq(Some(Vote::Up)))
+            .times(1)
+            .returning(|id, _, _| Ok(Question {
+                id,
+                title: "Title".to_string(),
+                content: "Content".to_string(),
+                tags: None,
+                score: 1,
+                accepted_answer_id: None,
+                author: Author::default(),
+                created_on: Utc::now(),
+                updated_on: None,
+                version: 1,
+            }));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let result = super::vote_question(QuestionId(1), session, store, NewVote { vote: Vote::Up }).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_retract_question_vote() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .times(1)
+            .returning(|_, _| Ok(false));
+
+        tx.expect_vote_question()
+            .with(eq(QuestionId(1)), eq(AccountId(1)), eq(None))
+            .times(1)
+            .returning(|id, _, _| Ok(Question {
+                id,
+                title: "Title".to_string(),
+                content: "Content".to_string(),
+                tags: None,
+                score: 0,
+                accepted_answer_id: None,
+                author: Author::default(),
+                created_on: Utc::now(),
+                updated_on: None,
+                version: 1,
+            }));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let result = super::retract_question_vote(QuestionId(1), session, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_vote_own_question() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .times(1)
+            .returning(|_, _| Ok(true));
+    });
+
+    let result = super::vote_question(QuestionId(1), session, store, NewVote { vote: Vote::Down }).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::SelfVote));
+        }
+        _ => panic!("Expected self vote error"),
+    }
+}
+
+#[tokio::test]
+async fn test_vote_missing_question() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .times(1)
+            .returning(|_, _| Ok(false));
+
+        tx.expect_vote_question()
+            .times(1)
+            .returning(|_, _, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+    });
+
+    let result = super::vote_question(QuestionId(1), session, store, NewVote { vote: Vote::Up }).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::NotFound));
+        }
+        _ => panic!("Expected not found error"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_answers_by_score() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_get_answers()
+        .with(eq(QuestionId(1)), eq(AnswerSort::Score), eq(Some(10)), eq(0))
+        .times(1)
+        .returning(|_, _, _, _| Ok(vec![]));
+
+    let mut params = HashMap::new();
+    params.insert("sort".to_string(), "score".to_string());
+    params.insert("limit".to_string(), "10".to_string());
+    params.insert("offset".to_string(), "0".to_string());
+
+    let result = super::get_answers(QuestionId(1), params, None, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_accept_answer_success() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_accept_answer()
+            .with(eq(QuestionId(1)), eq(AnswerId(2)))
+            .times(1)
+            .returning(|id, answer_id| Ok(Question {
+                id,
+                title: "Title".to_string(),
+                content: "Content".to_string(),
+                tags: None,
+                score: 0,
+                accepted_answer_id: Some(answer_id),
+                author: Author::default(),
+                created_on: Utc::now(),
+                updated_on: None,
+                version: 1,
+            }));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let result = super::accept_answer(QuestionId(1), AnswerId(2), session, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_accept_answer_unauthorized() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .times(1)
+            .returning(|_, _| Ok(false));
+    });
+
+    let result = super::accept_answer(QuestionId(1), AnswerId(2), session, store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::Unauthorized));
+        }
+        _ => panic!("Expected unauthorized error"),
+    }
+}
+
+#[tokio::test]
+async fn test_unaccept_answer_success() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_unaccept_answer()
+            .with(eq(QuestionId(1)))
+            .times(1)
+            .returning(|id| Ok(Question {
+                id,
+                title: "Title".to_string(),
+                content: "Content".to_string(),
+                tags: None,
+                score: 0,
+                accepted_answer_id: None,
+                author: Author::default(),
+                created_on: Utc::now(),
+                updated_on: None,
+                version: 1,
+            }));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let result = super::unaccept_answer(QuestionId(1), session, store).await;
+    assert!(result.is_ok());
+}
+
+fn create_test_revision() -> QuestionRevision {
+    QuestionRevision {
+        id: RevisionId(1),
+        question_id: QuestionId(1),
+        title: "Old Title".to_string(),
+        content: "Old Content".to_string(),
+        tags: None,
+        editor: AccountId(1),
+        created_on: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_rollback_question_success() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_get_question_revision()
+            .with(eq(QuestionId(1)), eq(RevisionId(1)))
+            .times(1)
+            .returning(|_, _| Ok(create_test_revision()));
+
+        tx.expect_update_question()
+            .with(eq(create_test_revision().to_question()), eq(QuestionId(1)), eq(AccountId(1)))
+            .times(1)
+            .returning(|q, _, _| Ok(q));
+
+        tx.expect_commit()
+            .times(1)
+            .returning(|| Ok(()));
+    });
+
+    let result = super::rollback_question(QuestionId(1), RevisionId(1), session, store).await;
     assert!(result.is_ok());
-} 
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_rollback_question_unauthorized() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(false));
+    });
+
+    let result = super::rollback_question(QuestionId(1), RevisionId(1), session, store).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_rollback_question_unknown_revision() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+    let session = create_test_session();
+
+    expect_transaction(&mut store, |tx| {
+        tx.expect_is_question_owner()
+            .with(eq(QuestionId(1)), eq(&AccountId(1)))
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        tx.expect_get_question_revision()
+            .with(eq(QuestionId(1)), eq(RevisionId(42)))
+            .times(1)
+            .returning(|_, _| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+
+        tx.expect_update_question().times(0);
+    });
+
+    let result = super::rollback_question(QuestionId(1), RevisionId(42), session, store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::NotFound));
+        }
+        _ => panic!("Expected not found error"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_question_diff_to_current() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_get_question()
+        .with(eq(QuestionId(1)))
+        .times(1)
+        .returning(|id| Ok(Question {
+            id,
+            title: "New Title".to_string(),
+            content: "Old Content".to_string(),
+            tags: None,
+            score: 0,
+            accepted_answer_id: None,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
+        }));
+
+    store.expect_get_question_revision()
+        .with(eq(QuestionId(1)), eq(RevisionId(1)))
+        .times(1)
+        .returning(|_, _| Ok(create_test_revision()));
+
+    let params = HashMap::from([("from".to_string(), "1".to_string())]);
+    let result = super::get_question_diff(QuestionId(1), params, store).await;
+    assert!(result.is_ok());
+}
+
+
+#[tokio::test]
+async fn test_get_question_revisions_not_found() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_get_question()
+        .with(eq(QuestionId(42)))
+        .times(1)
+        .returning(|_| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+    store.expect_get_question_revisions().times(0);
+
+    let result = super::get_question_revisions(QuestionId(42), store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::NotFound));
+        }
+        _ => panic!("Expected not found error"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_question_diff_not_found() {
+    let mock_store = setup_mock_store();
+    let mut store = mock_store.lock().unwrap().clone();
+
+    store.expect_get_question()
+        .with(eq(QuestionId(42)))
+        .times(1)
+        .returning(|_| Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)));
+    store.expect_get_question_revision().times(0);
+
+    let params = HashMap::from([("from".to_string(), "1".to_string())]);
+    let result = super::get_question_diff(QuestionId(42), params, store).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::NotFound));
+        }
+        _ => panic!("Expected not found error"),
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use percent_encoding::percent_decode_str;
+use tracing::{event, instrument, Level};
+
+use crate::types::account::Session;
+use crate::types::pagination::{extract_pagination, Pagination};
+use crate::types::tag::{normalize_tag, NewSynonym};
+use crate::handle_errors;
+
+pub mod store_trait;
+use store_trait::StoreTrait;
+
+#[cfg(test)]
+mod tests;
+
+// Path segments arrive percent-encoded, e.g. `c%23` for `c#`.
+fn slug_from_path(slug: &str) -> Result<String, handle_errors::Error> {
+    let decoded = percent_decode_str(slug)
+        .decode_utf8()
+        .map_err(|_| handle_errors::Error::InvalidTag(slug.to_string()))?;
+    normalize_tag(&decoded)
+}
+
+/**
+ * @Notice Get tags
+ *
+ * @Dev Lists the canonical tags with the number of questions using them, most used first.
+ *
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `params`: Query parameters for pagination.
+*/
+#[instrument]
+pub async fn get_tags<S: StoreTrait>(
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "rust_hour", Level::INFO, "querying tags");
+    let mut pagination = Pagination::default();
+
+    if !params.is_empty() {
+        event!(Level::INFO, pagination = true);
+        pagination = extract_pagination(params)?;
+    }
+
+    match store.get_tags(pagination.limit, pagination.offset).await {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Get questions of tag
+ *
+ * @Dev Retrieves the questions with a tag, or with the tag a synonym maps onto.
+ *
+ * @params  `slug`: The tag, percent-encoded.
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `params`: Query parameters for pagination.
+*/
+#[instrument]
+pub async fn get_tag_questions<S: StoreTrait>(
+    slug: String,
+    params: HashMap<String, String>,
+    store: S,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    event!(target: "rust_hour", Level::INFO, "querying questions of tag");
+    let slug = slug_from_path(&slug)?;
+    let mut pagination = Pagination::default();
+
+    if !params.is_empty() {
+        event!(Level::INFO, pagination = true);
+        pagination = extract_pagination(params)?;
+    }
+
+    match store
+        .get_tag_questions(slug, pagination.limit, pagination.offset)
+        .await
+    {
+        Ok(res) => Ok(warp::reply::json(&res)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+/**
+ * @Notice Add tag synonym
+ *
+ * @Dev Maps a synonym onto a tag, so questions saved with the synonym get the tag instead.
+ *      A synonym that is still used by questions is refused with 409 Conflict.
+ *      Synonyms rewrite the tags of every question, so only admins may add them.
+ *
+ * @params  `slug`: The canonical tag, percent-encoded.
+ * @params `session`: The session of the admin.
+ * @params  `store`: A `Store` instance used to interact with the database.
+ * @params `new_synonym`: The synonym to add.
+*/
+pub async fn add_tag_synonym<S: StoreTrait>(
+    slug: String,
+    _session: Session,
+    store: S,
+    new_synonym: NewSynonym,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let slug = slug_from_path(&slug)?;
+    let synonym = normalize_tag(&new_synonym.synonym)?;
+
+    match store.add_tag_synonym(slug, synonym).await {
+        Ok(tag) => Ok(warp::reply::json(&tag)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+use std::fmt::Debug;
+use crate::types::question::Question;
+use crate::types::tag::Tag;
+use crate::handle_errors;
+
+#[async_trait]
+pub trait StoreTrait: Clone + Debug {
+    async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, handle_errors::Error>;
+    async fn get_tag_questions(&self, slug: String, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+    async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, handle_errors::Error>;
+}
@@ -0,0 +1,107 @@
+use mockall::predicate::*;
+use mockall::*;
+use chrono::prelude::*;
+use std::collections::HashMap;
+
+use crate::types::account::{AccountId, Session, SessionId};
+use crate::types::question::Question;
+use crate::types::tag::{NewSynonym, Tag};
+use crate::handle_errors;
+use super::store_trait::StoreTrait;
+
+mock! {
+    #[derive(Debug)]
+    Store {}
+
+    #[async_trait::async_trait]
+    impl StoreTrait for Store {
+        async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, handle_errors::Error>;
+        async fn get_tag_questions(&self, slug: String, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, handle_errors::Error>;
+        async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, handle_errors::Error>;
+    }
+
+    impl Clone for Store {
+        fn clone(&self) -> Self;
+    }
+}
+
+fn create_test_session() -> Session {
+    Session {
+        account_id: AccountId(1),
+        exp: Utc::now() + chrono::Duration::days(1),
+        nbf: Utc::now(),
+        generation: 0,
+        session_id: SessionId(1),
+    }
+}
+
+#[tokio::test]
+async fn test_get_tags_with_pagination() {
+    let mut store = MockStore::new();
+
+    store.expect_get_tags()
+        .with(eq(Some(10)), eq(0))
+        .times(1)
+        .returning(|_, _| Ok(vec![Tag {
+            slug: "rust".to_string(),
+            questions: 3,
+            synonyms: vec!["rustlang".to_string()],
+        }]));
+
+    let mut params = HashMap::new();
+    params.insert("limit".to_string(), "10".to_string());
+    params.insert("offset".to_string(), "0".to_string());
+
+    let result = super::get_tags(params, store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_get_tag_questions_decodes_slug() {
+    let mut store = MockStore::new();
+
+    store.expect_get_tag_questions()
+        .with(eq("c#".to_string()), eq(None), eq(0))
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+
+    let result = super::get_tag_questions("C%23".to_string(), HashMap::new(), store).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_add_tag_synonym_normalizes() {
+    let mut store = MockStore::new();
+
+    store.expect_add_tag_synonym()
+        .with(eq("rust".to_string()), eq("rust-lang".to_string()))
+        .times(1)
+        .returning(|slug, synonym| Ok(Tag {
+            slug,
+            questions: 0,
+            synonyms: vec![synonym],
+        }));
+
+    let new_synonym = NewSynonym {
+        synonym: " Rust Lang ".to_string(),
+    };
+    let result = super::add_tag_synonym("rust".to_string(), create_test_session(), store, new_synonym).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_add_tag_synonym_invalid() {
+    let store = MockStore::new();
+
+    let new_synonym = NewSynonym {
+        synonym: "not/a/tag".to_string(),
+    };
+    let result = super::add_tag_synonym("rust".to_string(), create_test_session(), store, new_synonym).await;
+    match result {
+        Err(rejection) => {
+            let error = rejection.find::<handle_errors::Error>().unwrap();
+            assert!(matches!(*error, handle_errors::Error::InvalidTag(_)));
+        }
+        _ => panic!("Expected invalid tag error"),
+    }
+}
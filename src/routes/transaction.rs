@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use crate::handle_errors;
+
+/// A store that can group several calls into one atomic unit of work.
+///
+/// `begin` returns a handle of the same store type whose calls all run inside
+/// one transaction. `commit` makes them permanent; dropping the handle without
+/// committing rolls everything back. Calling `begin` on a handle that is
+/// already inside a transaction joins it, and only the outermost `commit`
+/// takes effect.
+#[async_trait]
+pub trait Transactional: Sized + Send + Sync {
+    async fn begin(&self) -> Result<Self, handle_errors::Error>;
+    async fn commit(self) -> Result<(), handle_errors::Error>;
+}
@@ -0,0 +1,376 @@
+//! Imports a Stack Exchange data dump, see `import_dump`.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use handle_errors::Error;
+
+use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
+use crate::routes::authentication::{StoreTrait as AuthStoreTrait, NO_PASSWORD};
+use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
+use crate::routes::transaction::Transactional;
+use crate::types::account::{Account, AccountId};
+use crate::types::answer::{AnswerId, NewAnswer};
+use crate::types::question::{NewQuestion, QuestionId};
+use crate::types::tag::{dedup_tags, normalize_tag, MAX_TAGS};
+
+pub mod store_trait;
+use store_trait::StoreTrait;
+
+#[cfg(test)]
+mod tests;
+
+/// Number of posts imported per transaction.
+const BATCH_SIZE: u64 = 1000;
+
+/// Number of rows parsed ahead of the import.
+const READ_AHEAD: usize = 1024;
+
+/// Domain of the emails of the placeholder accounts. `.invalid` is reserved,
+/// so no one can own such an address.
+const PLACEHOLDER_DOMAIN: &str = "stackexchange.invalid";
+
+/// The files of a dump to import.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DumpFiles {
+    /// `Posts.xml`, with the questions and answers.
+    pub posts: PathBuf,
+    /// `Users.xml`, for the display names of the authors.
+    pub users: Option<PathBuf>,
+    /// `Tags.xml`; when given, only the tags it lists are kept.
+    pub tags: Option<PathBuf>,
+}
+
+/// What an import did.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct DumpReport {
+    pub questions: u64,
+    pub answers: u64,
+    /// Placeholder accounts created for authors.
+    pub accounts: u64,
+    /// Questions without a title or content, and answers to questions that
+    /// were not imported.
+    pub skipped: u64,
+    /// Posts an earlier import added already, left as they are.
+    pub existing: u64,
+}
+
+/// A post of a dump as it was imported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedPost {
+    pub question_id: QuestionId,
+    /// The answer, for an answer; `None` for a question.
+    pub answer_id: Option<AnswerId>,
+    /// The dump id of the answer a question accepted.
+    pub accepted_source_id: Option<i64>,
+}
+
+/// The attributes of one `<row>` of a dump file.
+type Row = HashMap<String, String>;
+
+/// Reads the `<row>` elements of a dump file one at a time, so files of any
+/// size can be read.
+struct Rows<R> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    path: PathBuf,
+}
+
+impl Rows<BufReader<File>> {
+    fn open(path: &Path) -> Result<Self, Error> {
+        let file = File::open(path)
+            .map_err(|e| Error::InvalidDump(format!("{}: {}", path.display(), e)))?;
+        Ok(Rows {
+            reader: Reader::from_reader(BufReader::new(file)),
+            buf: Vec::new(),
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl<R: BufRead> Rows<R> {
+    fn invalid(&self, e: impl std::fmt::Display) -> Error {
+        Error::InvalidDump(format!(
+            "{} at byte {}: {}",
+            self.path.display(),
+            self.reader.buffer_position(),
+            e
+        ))
+    }
+}
+
+impl<R: BufRead> Iterator for Rows<R> {
+    type Item = Result<Row, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            let element = match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Eof) => return None,
+                Ok(Event::Empty(element)) | Ok(Event::Start(element)) if element.name().as_ref() == b"row" => {
+                    element.into_owned()
+                }
+                Ok(_) => continue,
+                Err(e) => return Some(Err(self.invalid(e))),
+            };
+            let mut row = Row::new();
+            for attribute in element.attributes() {
+                let attribute = match attribute {
+                    Ok(attribute) => attribute,
+                    Err(e) => return Some(Err(self.invalid(e))),
+                };
+                let value = match attribute.unescape_value() {
+                    Ok(value) => value.into_owned(),
+                    Err(e) => return Some(Err(self.invalid(e))),
+                };
+                row.insert(String::from_utf8_lossy(attribute.key.as_ref()).into_owned(), value);
+            }
+            return Some(Ok(row));
+        }
+    }
+}
+
+/// Parses the rows of the dump file at `path` on a blocking thread, so a
+/// large file does not hold up the runtime, and hands them over through a
+/// bounded channel. Reading stops at the first error, which is the last item.
+fn read_rows(path: &Path) -> mpsc::Receiver<Result<Row, Error>> {
+    let (sender, receiver) = mpsc::channel(READ_AHEAD);
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let rows = match Rows::open(&path) {
+            Ok(rows) => rows,
+            Err(e) => {
+                let _ = sender.blocking_send(Err(e));
+                return;
+            }
+        };
+        for row in rows {
+            let failed = row.is_err();
+            // The import is gone once it stops receiving.
+            if sender.blocking_send(row).is_err() || failed {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+fn id(row: &Row, name: &str) -> Option<i64> {
+    row.get(name).and_then(|id| id.parse().ok())
+}
+
+// Dump times are UTC without a zone, like `2008-07-31T21:42:52.667`.
+fn created_on(row: &Row) -> Option<DateTime<Utc>> {
+    let created_on = row.get("CreationDate")?;
+    NaiveDateTime::parse_from_str(created_on, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// The tags of a question, written `<rust><async>` in older dumps and
+/// `|rust|async|` in newer ones. Tags that cannot be normalized or, with
+/// `known`, are not listed there are dropped.
+fn tags(row: &Row, known: &Option<HashSet<String>>) -> Option<Vec<String>> {
+    let tags = row
+        .get("Tags")?
+        .split(['<', '>', '|'])
+        .filter_map(|tag| normalize_tag(tag).ok())
+        .filter(|tag| known.as_ref().is_none_or(|known| known.contains(tag)))
+        .collect();
+    let mut tags = dedup_tags(tags);
+    tags.truncate(MAX_TAGS);
+    Some(tags).filter(|tags| !tags.is_empty())
+}
+
+/// The email of the placeholder account of the dump user `user_id`, or of the
+/// account shared by deleted users.
+fn placeholder_email(user_id: Option<i64>) -> String {
+    match user_id {
+        Some(user_id) => format!("user{}@{}", user_id, PLACEHOLDER_DOMAIN),
+        None => format!("deleted@{}", PLACEHOLDER_DOMAIN),
+    }
+}
+
+/// The account standing in for the author of `row`, created on first use and
+/// named after the post until `Users.xml` is read. Posts of deleted users
+/// share one account.
+async fn author<S>(store: &S, row: &Row, report: &mut DumpReport) -> Result<AccountId, Error>
+where
+    S: AuthStoreTrait,
+{
+    let user_id = id(row, "OwnerUserId");
+    let email = placeholder_email(user_id);
+    // An earlier post or import may have made the account already.
+    let account = match store.get_account(email.clone()).await {
+        Ok(account) => account,
+        Err(_) => {
+            let display_name = match user_id {
+                Some(user_id) => row
+                    .get("OwnerDisplayName")
+                    .cloned()
+                    .unwrap_or_else(|| format!("user{}", user_id)),
+                None => "deleted user".to_string(),
+            };
+            store
+                .add_account(Account {
+                    id: None,
+                    email: email.clone(),
+                    password: NO_PASSWORD.to_string(),
+                    display_name: Some(display_name),
+                })
+                .await?;
+            report.accounts += 1;
+            store.get_account(email).await?
+        }
+    };
+    account.id.ok_or(Error::NotFound)
+}
+
+/// Imports the questions and answers of a Stack Exchange dump into `store`.
+///
+/// The files are streamed and parsed off the runtime, and the store keeps
+/// which dump post each question and answer came from, so only the tag names
+/// of `Tags.xml` are held in memory. Posts keep their tags and creation time,
+/// and accepted answers stay accepted. Each author gets a placeholder account
+/// named after them in `Users.xml` that cannot be logged into.
+///
+/// Posts are committed in batches of `BATCH_SIZE`; an import that fails half
+/// way keeps the batches before the error. Running an import again reuses the
+/// placeholder accounts and skips the posts it already added, so it can pick
+/// up after a failure. Dump ids are only unique within one site, so a store
+/// takes the dump of one site.
+pub async fn import_dump<S>(store: &S, files: &DumpFiles) -> Result<DumpReport, Error>
+where
+    S: QuestionStoreTrait + AnswerStoreTrait + AuthStoreTrait + StoreTrait + Transactional,
+{
+    let known_tags = match &files.tags {
+        Some(path) => {
+            let mut known = HashSet::new();
+            let mut rows = read_rows(path);
+            while let Some(row) = rows.recv().await {
+                if let Some(tag) = row?.get("TagName").and_then(|tag| normalize_tag(tag).ok()) {
+                    known.insert(tag);
+                }
+            }
+            Some(known)
+        }
+        None => None,
+    };
+
+    let mut report = DumpReport::default();
+    let mut tx = store.begin().await?;
+    let mut batch = 0;
+    let mut rows = read_rows(&files.posts);
+    while let Some(row) = rows.recv().await {
+        let row = row?;
+        let post_type = match row.get("PostTypeId").map(String::as_str) {
+            Some(post_type @ ("1" | "2")) => post_type,
+            // Tag wikis, moderator nominations and the like.
+            _ => continue,
+        };
+        let post_id = match id(&row, "Id") {
+            Some(post_id) => post_id,
+            None => {
+                report.skipped += 1;
+                continue;
+            }
+        };
+        if tx.get_imported_post(post_id).await?.is_some() {
+            report.existing += 1;
+            continue;
+        }
+        let content = row.get("Body").map(|body| body.trim()).unwrap_or_default();
+        let post = if post_type == "1" {
+            let title = row.get("Title").map(|title| title.trim()).unwrap_or_default();
+            if title.is_empty() || content.is_empty() {
+                report.skipped += 1;
+                continue;
+            }
+            let account_id = author(&tx, &row, &mut report).await?;
+            let new_question = NewQuestion {
+                title: title.to_string(),
+                content: content.to_string(),
+                tags: tags(&row, &known_tags),
+            };
+            let question = tx.add_question(new_question, account_id).await?;
+            if let Some(created_on) = created_on(&row) {
+                tx.set_question_created_on(question.id, created_on).await?;
+            }
+            report.questions += 1;
+            ImportedPost {
+                question_id: question.id,
+                answer_id: None,
+                accepted_source_id: id(&row, "AcceptedAnswerId"),
+            }
+        } else {
+            let question = match id(&row, "ParentId") {
+                Some(parent_id) => tx.get_imported_post(parent_id).await?,
+                None => None,
+            };
+            let question = match question.filter(|question| question.answer_id.is_none()) {
+                Some(question) if !content.is_empty() => question,
+                _ => {
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+            let account_id = author(&tx, &row, &mut report).await?;
+            let new_answer = NewAnswer {
+                content: content.to_string(),
+                question_id: question.question_id,
+            };
+            let answer = tx.add_answer(new_answer, account_id).await?;
+            if let Some(created_on) = created_on(&row) {
+                tx.set_answer_created_on(answer.id.clone(), created_on).await?;
+            }
+            if question.accepted_source_id == Some(post_id) {
+                tx.accept_answer(question.question_id, answer.id.clone()).await?;
+            }
+            report.answers += 1;
+            ImportedPost {
+                question_id: question.question_id,
+                answer_id: Some(answer.id),
+                accepted_source_id: None,
+            }
+        };
+        tx.add_imported_post(post_id, post).await?;
+        batch += 1;
+        if batch == BATCH_SIZE {
+            tx.commit().await?;
+            tx = store.begin().await?;
+            batch = 0;
+        }
+    }
+    tx.commit().await?;
+
+    // Authors are named once the posts are in, so no names wait in memory.
+    if let Some(path) = &files.users {
+        let mut tx = store.begin().await?;
+        let mut batch = 0;
+        let mut rows = read_rows(path);
+        while let Some(row) = rows.recv().await {
+            let mut row = row?;
+            if let (Some(user_id), Some(name)) = (id(&row, "Id"), row.remove("DisplayName")) {
+                if !tx.set_display_name(placeholder_email(Some(user_id)), name).await? {
+                    continue;
+                }
+                batch += 1;
+                if batch == BATCH_SIZE {
+                    tx.commit().await?;
+                    tx = store.begin().await?;
+                    batch = 0;
+                }
+            }
+        }
+        tx.commit().await?;
+    }
+    Ok(report)
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use crate::types::answer::AnswerId;
+use crate::types::question::QuestionId;
+use crate::handle_errors;
+
+use super::ImportedPost;
+
+/// What an import needs besides the route store traits: posts keep the time
+/// they were written where they come from, and the store remembers which
+/// posts of a dump it holds.
+#[async_trait]
+pub trait StoreTrait {
+    async fn set_question_created_on(&self, id: QuestionId, created_on: DateTime<Utc>) -> Result<(), handle_errors::Error>;
+    async fn set_answer_created_on(&self, id: AnswerId, created_on: DateTime<Utc>) -> Result<(), handle_errors::Error>;
+    /// The post the dump post `source_id` was imported as, if it is still there.
+    async fn get_imported_post(&self, source_id: i64) -> Result<Option<ImportedPost>, handle_errors::Error>;
+    /// Records that the dump post `source_id` was imported as `post`.
+    async fn add_imported_post(&self, source_id: i64, post: ImportedPost) -> Result<(), handle_errors::Error>;
+    /// Sets the display name of the account with `email`, returning whether
+    /// there is one.
+    async fn set_display_name(&self, email: String, display_name: String) -> Result<bool, handle_errors::Error>;
+}
@@ -0,0 +1,146 @@
+use crate::store::memory::MemoryStore;
+use crate::types::answer::AnswerId;
+use crate::types::filter::{AnswerSort, QuestionFilter};
+
+use super::*;
+
+const POSTS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<posts>
+  <row Id="1" PostTypeId="1" AcceptedAnswerId="3" CreationDate="2008-07-31T21:42:52.667" Title="Convert a &quot;decimal&quot;" Body="&lt;p&gt;How?&lt;/p&gt;" Tags="&lt;C#&gt;&lt;Floating-Point&gt;&lt;not!valid&gt;" OwnerUserId="8" />
+  <row Id="2" PostTypeId="2" ParentId="1" CreationDate="2008-07-31T22:17:57.883" Body="&lt;p&gt;Cast it.&lt;/p&gt;" OwnerUserId="9" />
+  <row Id="3" PostTypeId="2" ParentId="1" CreationDate="2008-08-01T14:45:37.133" Body="&lt;p&gt;Use Convert.&lt;/p&gt;" OwnerUserId="11" OwnerDisplayName="anon" />
+  <row Id="4" PostTypeId="2" ParentId="99" CreationDate="2008-08-01T15:00:00.000" Body="Lost" OwnerUserId="8" />
+  <row Id="5" PostTypeId="4" CreationDate="2008-08-01T15:00:00.000" Body="Tag wiki" />
+  <row Id="6" PostTypeId="1" CreationDate="2008-08-02T10:00:00.000" Title="Newer tags" Body="Pipes" Tags="|c#|linq|" OwnerUserId="9" />
+</posts>
+"#;
+
+const USERS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<users>
+  <row Id="8" DisplayName="Jeff Atwood" />
+  <row Id="9" DisplayName="Joel Spolsky" />
+  <row Id="10" DisplayName="Lurker" />
+</users>
+"#;
+
+const TAGS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<tags>
+  <row Id="1" TagName="c#" Count="2" />
+  <row Id="2" TagName="floating-point" Count="1" />
+</tags>
+"#;
+
+// Writes the dump files to a directory of their own, so tests can run at
+// the same time.
+fn write_dump(name: &str, users: bool, tags: bool) -> DumpFiles {
+    let dir = std::env::temp_dir().join(format!("rust_hour_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let write = |file: &str, content: &str| {
+        let path = dir.join(file);
+        std::fs::write(&path, content).unwrap();
+        path
+    };
+    DumpFiles {
+        posts: write("Posts.xml", POSTS),
+        users: users.then(|| write("Users.xml", USERS)),
+        tags: tags.then(|| write("Tags.xml", TAGS)),
+    }
+}
+
+fn time(time: &str) -> DateTime<Utc> {
+    NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%.f").unwrap().and_utc()
+}
+
+#[tokio::test]
+async fn test_import_dump() {
+    let store = MemoryStore::new();
+    let files = write_dump("import_dump", true, true);
+
+    let report = import_dump(&store, &files).await.unwrap();
+    assert_eq!(
+        report,
+        DumpReport {
+            questions: 2,
+            answers: 2,
+            accounts: 3,
+            skipped: 1,
+            existing: 0,
+        }
+    );
+
+    let questions = store.get_questions(QuestionFilter::default(), None, 0).await.unwrap();
+    assert_eq!(questions.len(), 2);
+    let question = questions.iter().find(|question| question.title == "Convert a \"decimal\"").unwrap();
+    assert_eq!(question.content, "<p>How?</p>");
+    assert_eq!(question.tags, Some(vec!["c#".to_string(), "floating-point".to_string()]));
+    assert_eq!(question.created_on, time("2008-07-31T21:42:52.667"));
+    assert_eq!(question.author.display_name, "Jeff Atwood");
+    let newer = questions.iter().find(|question| question.title == "Newer tags").unwrap();
+    // `linq` is not in Tags.xml.
+    assert_eq!(newer.tags, Some(vec!["c#".to_string()]));
+    assert_eq!(newer.author.display_name, "Joel Spolsky");
+
+    let answers = store
+        .get_answers(question.id, AnswerSort::Oldest, None, 0)
+        .await
+        .unwrap();
+    assert_eq!(answers.len(), 2);
+    // The accepted answer is pinned first.
+    assert_eq!(answers[0].content, "<p>Use Convert.</p>");
+    assert_eq!(answers[0].created_on, time("2008-08-01T14:45:37.133"));
+    assert_eq!(answers[0].author.display_name, "anon");
+    assert_eq!(answers[1].created_on, time("2008-07-31T22:17:57.883"));
+    let accepted: Option<AnswerId> = store.get_question(question.id).await.unwrap().accepted_answer_id;
+    assert_eq!(accepted, Some(answers[0].id.clone()));
+
+    let placeholder = store
+        .get_account("user8@stackexchange.invalid".to_string())
+        .await
+        .unwrap();
+    assert_eq!(placeholder.password, NO_PASSWORD);
+    assert!(store.get_account("user10@stackexchange.invalid".to_string()).await.is_err());
+
+    // A second import reuses the placeholder accounts and skips the posts of
+    // the first.
+    let report = import_dump(&store, &files).await.unwrap();
+    assert_eq!(
+        report,
+        DumpReport {
+            questions: 0,
+            answers: 0,
+            accounts: 0,
+            skipped: 1,
+            existing: 4,
+        }
+    );
+    assert_eq!(store.get_questions(QuestionFilter::default(), None, 0).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_import_dump_without_users_and_tags() {
+    let store = MemoryStore::new();
+    let files = write_dump("without_users_and_tags", false, false);
+
+    let report = import_dump(&store, &files).await.unwrap();
+    assert_eq!((report.questions, report.answers), (2, 2));
+
+    let questions = store.get_questions(QuestionFilter::default(), None, 0).await.unwrap();
+    let newer = questions.iter().find(|question| question.title == "Newer tags").unwrap();
+    assert_eq!(newer.tags, Some(vec!["c#".to_string(), "linq".to_string()]));
+    assert_eq!(newer.author.display_name, "user9");
+}
+
+#[tokio::test]
+async fn test_import_dump_rejects_malformed_xml() {
+    let store = MemoryStore::new();
+    let mut files = write_dump("malformed", false, false);
+    files.posts = files.posts.with_file_name("Broken.xml");
+    std::fs::write(&files.posts, "<posts><row Id=\"1 PostTypeId=\"1\" /></posts>").unwrap();
+
+    let result = import_dump(&store, &files).await;
+    assert!(matches!(result, Err(Error::InvalidDump(_))));
+
+    files.posts = files.posts.with_file_name("Missing.xml");
+    let result = import_dump(&store, &files).await;
+    assert!(matches!(result, Err(Error::InvalidDump(_))));
+}
@@ -1,21 +1,76 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx::{
-    postgres::{PgPool, PgPoolOptions, PgRow},
-    Row,
+    postgres::{PgPool, PgPoolOptions, PgRow, Postgres},
+    types::Json,
+    QueryBuilder, Row,
 };
 
 use handle_errors::Error;
 
 use crate::types::{
-    account::{Account, AccountId, AccountResponse, AccountUpdatePassword, AccountUpdateRequest},
+    account::{
+        Account, AccountId, AccountResponse, AccountUpdatePassword, AccountUpdateRequest, ActiveSession, Author,
+        NewSession, SessionId,
+    },
     answer::{Answer, AnswerId, NewAnswer},
+    audit::{AuditAction, AuditFilter, AuditRecord, AuditRecordId, NewAuditRecord},
+    comment::{Comment, CommentId, CommentParent, NewComment},
+    filter::{AnswerSort, QuestionFilter, QuestionSort, TagMatch},
+    pagination::{Cursor, Page},
     question::{NewQuestion, Question, QuestionId},
+    revision::{AnswerRevision, QuestionRevision, RevisionId},
+    search::SearchResult,
+    tag::{dedup_tags, normalize_tags, Tag},
+    vote::Vote,
 };
 use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
+use crate::routes::audit::store_trait::StoreTrait as AuditStoreTrait;
+use crate::routes::authentication::{StoreTrait as AuthStoreTrait, SESSION_TOUCH_SECONDS};
+use crate::routes::comment::store_trait::StoreTrait as CommentStoreTrait;
 use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
+use crate::routes::tag::store_trait::StoreTrait as TagStoreTrait;
+use crate::routes::transaction::Transactional;
+use crate::stackexchange::store_trait::StoreTrait as ImportStoreTrait;
+use crate::stackexchange::ImportedPost;
+use connection::{Conn, TransactionHandle};
 
+pub mod audit;
+pub mod cache;
+mod connection;
+pub mod memory;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[cfg(test)]
+mod fixtures;
 #[cfg(test)]
 mod tests;
 
+/// The storage backend selected by `setup_store`.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    /// A PostgreSQL database, see `Store`.
+    Postgres(Store),
+    /// An in-memory store, see `memory::MemoryStore`.
+    Memory(memory::MemoryStore),
+    /// A SQLite database, see `sqlite::SqliteStore`.
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlite::SqliteStore),
+}
+
+impl Backend {
+    /// Permanently removes the questions and answers deleted before
+    /// `deleted_before`. Returns the number of rows removed.
+    pub async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, Error> {
+        match self {
+            Backend::Postgres(store) => store.purge_deleted(deleted_before).await,
+            Backend::Memory(store) => store.purge_deleted(deleted_before).await,
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite(store) => store.purge_deleted(deleted_before).await,
+        }
+    }
+}
+
 /// Represents a persistent storage unit for your application.
 ///
 /// This struct provides a connection pool to a PostgreSQL database (`PgPool`).
@@ -24,11 +79,164 @@ mod tests;
 /// # Fields
 ///
 /// * `connection`: A connection pool to a PostgreSQL database.
+/// * `transaction`: The open transaction of a handle returned by
+///   `Transactional::begin`. Every query of such a handle runs inside it.
 ///
 /// # Examples
 #[derive(Debug, Clone)]
 pub struct Store {
     pub connection: PgPool,
+    transaction: Option<TransactionHandle<Postgres>>,
+}
+
+// The columns of a question or an answer besides the name of its author.
+const QUESTION_COLUMNS: &str =
+    "id, title, content, tags, score, accepted_answer_id, account_id, created_on, updated_on, version";
+const ANSWER_COLUMNS: &str =
+    "id, content, corresponding_question, score, account_id, created_on, updated_on, version";
+
+// The name the author of a post goes by: the display name of the account, or
+// the part of its email before the `@`. `account_id` is the one of the post,
+// as `accounts` has no column of that name.
+const AUTHOR_NAME: &str = "(SELECT COALESCE(display_name, split_part(email, '@', 1))
+    FROM accounts WHERE accounts.id = account_id) AS author_name";
+
+fn to_author(row: &PgRow) -> Author {
+    Author {
+        id: AccountId(row.get("account_id")),
+        display_name: row.get::<Option<String>, _>("author_name").unwrap_or_default(),
+    }
+}
+
+fn to_question(row: PgRow) -> Question {
+    Question {
+        id: QuestionId(row.get("id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        tags: row.get("tags"),
+        score: row.get("score"),
+        accepted_answer_id: row
+            .get::<Option<i32>, _>("accepted_answer_id")
+            .map(AnswerId),
+        author: to_author(&row),
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+        updated_on: row
+            .get::<Option<NaiveDateTime>, _>("updated_on")
+            .map(|time| time.and_utc()),
+        version: row.get("version"),
+    }
+}
+
+fn to_answer(row: PgRow) -> Answer {
+    Answer {
+        id: AnswerId(row.get("id")),
+        content: row.get("content"),
+        question_id: QuestionId(row.get("corresponding_question")),
+        score: row.get("score"),
+        author: to_author(&row),
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+        updated_on: row
+            .get::<Option<NaiveDateTime>, _>("updated_on")
+            .map(|time| time.and_utc()),
+        version: row.get("version"),
+    }
+}
+
+// `created_on` is a `TIMESTAMP` holding UTC.
+fn to_cursor(row: &PgRow) -> Cursor {
+    Cursor {
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+        id: row.get("id"),
+    }
+}
+
+fn to_search_result(row: PgRow) -> SearchResult {
+    SearchResult {
+        question_id: QuestionId(row.get("question_id")),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        title: row.get("title"),
+        snippet: row.get("snippet"),
+        rank: row.get("rank"),
+    }
+}
+
+fn to_tag(row: PgRow) -> Tag {
+    Tag {
+        slug: row.get("slug"),
+        questions: row.get("questions"),
+        synonyms: row.get("synonyms"),
+    }
+}
+
+fn to_question_revision(row: PgRow) -> QuestionRevision {
+    QuestionRevision {
+        id: RevisionId(row.get("id")),
+        question_id: QuestionId(row.get("question_id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        tags: row.get("tags"),
+        editor: AccountId(row.get("account_id")),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn to_answer_revision(row: PgRow) -> AnswerRevision {
+    AnswerRevision {
+        id: RevisionId(row.get("id")),
+        answer_id: AnswerId(row.get("answer_id")),
+        content: row.get("content"),
+        editor: AccountId(row.get("account_id")),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn to_comment(row: PgRow) -> Comment {
+    Comment {
+        id: CommentId(row.get("id")),
+        content: row.get("content"),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        account_id: AccountId(row.get("account_id")),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn to_session(row: PgRow) -> ActiveSession {
+    ActiveSession {
+        id: SessionId(row.get("id")),
+        created_on: row.get("created_on"),
+        last_used_on: row.get("last_used_on"),
+        user_agent: row.get("user_agent"),
+        ip: row.get("ip"),
+        current: false,
+    }
+}
+
+fn to_account_response(row: PgRow) -> AccountResponse {
+    AccountResponse {
+        email: row.get("email"),
+        id: AccountId(row.get("id")),
+        email_verified: row.get("email_verified"),
+    }
+}
+
+// The action is written by `add_audit_record`, so it always parses.
+fn to_audit_record(row: PgRow) -> Result<AuditRecord, sqlx::Error> {
+    let action = row
+        .get::<String, _>("action")
+        .parse::<AuditAction>()
+        .map_err(|e| sqlx::Error::Decode(e.to_string().into()))?;
+    Ok(AuditRecord {
+        id: AuditRecordId(row.get("id")),
+        actor: AccountId(row.get("actor")),
+        action,
+        target: action.target(),
+        target_id: row.get("target_id"),
+        before: row.get::<Option<Json<serde_json::Value>>, _>("row_before").map(|json| json.0),
+        after: row.get::<Option<Json<serde_json::Value>>, _>("row_after").map(|json| json.0),
+        request_id: row.get("request_id"),
+        created_on: row.get("created_on"),
+    })
 }
 
 impl Store {
@@ -40,180 +248,1203 @@ impl Store {
             .connect(db_url)
             .await?;
 
-        Ok(Store {
-            connection: db_pool,
-        })
+        Ok(Store {
+            connection: db_pool,
+            transaction: None,
+        })
+    }
+
+    /// Helper function to handle database errors consistently
+    fn handle_error<T>(result: Result<T, sqlx::Error>) -> Result<T, Error> {
+        result.map_err(|e| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", e);
+            Error::DatabaseQueryError(e)
+        })
+    }
+
+    /// Helper function to borrow the connection a query runs on
+    async fn conn(&self) -> Result<Conn<'_, Postgres>, Error> {
+        Self::handle_error(connection::acquire(&self.connection, self.transaction.as_ref()).await)
+    }
+
+    /// Helper function to check ownership of a resource.
+    ///
+    /// Inside a transaction the row stays locked until commit, so the
+    /// ownership cannot change before the follow-up update or delete.
+    /// Deleted rows have no owner; restoring them checks the owner itself.
+    async fn check_ownership(
+        &self,
+        table: &str,
+        id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        let query = format!(
+            "SELECT account_id = $2 FROM {} WHERE id = $1{} FOR UPDATE",
+            table,
+            live_filter(table)
+        );
+
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(id)
+                .bind(account_id.0)
+                .fetch_optional(&mut *conn)
+                .await
+                .map(|row: Option<PgRow>| row.is_some_and(|row| row.get(0)))
+        )
+    }
+
+    /// Normalizes the tags of a question and replaces synonyms with their
+    /// canonical tag.
+    async fn canonical_tags(&self, tags: Option<Vec<String>>) -> Result<Option<Vec<String>>, Error> {
+        match normalize_tags(tags)? {
+            Some(tags) => Ok(Some(self.resolve_synonyms(tags).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Replaces synonyms among normalized tags with their canonical tag.
+    async fn resolve_synonyms(&self, tags: Vec<String>) -> Result<Vec<String>, Error> {
+        if tags.is_empty() {
+            return Ok(tags);
+        }
+        let mut conn = self.conn().await?;
+        let tags = Self::handle_error(
+            sqlx::query(
+                "SELECT coalesce(t.slug, input.slug) AS slug
+                FROM unnest($1::text[]) WITH ORDINALITY AS input(slug, ord)
+                LEFT JOIN tag_synonyms s ON s.slug = input.slug
+                LEFT JOIN tags t ON t.id = s.tag_id
+                ORDER BY ord"
+            )
+            .bind(tags)
+            .map(|row: PgRow| row.get("slug"))
+            .fetch_all(&mut *conn)
+            .await
+        )?;
+        Ok(dedup_tags(tags))
+    }
+
+    /// Links a question to exactly its tags, creating the missing ones.
+    async fn set_question_tags(&self, id: QuestionId, tags: &Option<Vec<String>>) -> Result<(), Error> {
+        let tags = tags.clone().unwrap_or_default();
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("INSERT INTO tags (slug) SELECT unnest($1::text[]) ON CONFLICT (slug) DO NOTHING")
+                .bind(&tags)
+                .execute(&mut *conn)
+                .await
+        )?;
+        Self::handle_error(
+            sqlx::query("DELETE FROM question_tags WHERE question_id = $1")
+                .bind(id.0)
+                .execute(&mut *conn)
+                .await
+        )?;
+        Self::handle_error(
+            sqlx::query(
+                "INSERT INTO question_tags (question_id, tag_id)
+                SELECT $1, id FROM tags WHERE slug = ANY($2)"
+            )
+            .bind(id.0)
+            .bind(&tags)
+            .execute(&mut *conn)
+            .await
+        )?;
+        Ok(())
+    }
+
+    /// Permanently removes the questions and answers deleted before
+    /// `deleted_before`. Returns the number of rows removed.
+    pub async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, Error> {
+        let tx = self.begin().await?;
+        let purged = {
+            let mut conn = tx.conn().await?;
+            let answers = Self::handle_error(
+                sqlx::query("DELETE FROM answers WHERE deleted_at < $1")
+                    .bind(deleted_before)
+                    .execute(&mut *conn)
+                    .await
+            )?;
+            let questions = Self::handle_error(
+                sqlx::query("DELETE FROM questions WHERE deleted_at < $1")
+                    .bind(deleted_before)
+                    .execute(&mut *conn)
+                    .await
+            )?;
+            answers.rows_affected() + questions.rows_affected()
+        };
+        tx.commit().await?;
+        Ok(purged)
+    }
+
+    /// Retrieves a list of questions from the database with optional pagination.
+    #[deprecated(note = "use `routes::question::store_trait::StoreTrait::get_questions`")]
+    pub async fn get_questions(self, limit: Option<i32>, offset: i32) -> Result<Vec<Question>, Error> {
+        QuestionStoreTrait::get_questions(&self, QuestionFilter::default(), limit, offset).await
+    }
+
+    /// Adds a new question to the database and returns the created question.
+    #[deprecated(note = "use `routes::question::store_trait::StoreTrait::add_question`")]
+    pub async fn add_question(self, new_question: NewQuestion, account_id: AccountId) -> Result<Question, Error> {
+        QuestionStoreTrait::add_question(&self, new_question, account_id).await
+    }
+
+    /// Updates an existing question in the database and returns the updated question.
+    #[deprecated(note = "use `routes::question::store_trait::StoreTrait::update_question`")]
+    pub async fn update_question(self, question: Question, id: i32, account_id: AccountId) -> Result<Question, Error> {
+        QuestionStoreTrait::update_question(&self, question, QuestionId(id), account_id).await
+    }
+
+    /// Delete an existing question in the database
+    #[deprecated(note = "use `routes::question::store_trait::StoreTrait::delete_question`")]
+    pub async fn delete_question(self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+        QuestionStoreTrait::delete_question(&self, QuestionId(id), account_id).await
+    }
+
+    /// Retrieves a list of answers for a question from the database with optional pagination.
+    #[deprecated(note = "use `routes::question::store_trait::StoreTrait::get_answers`")]
+    pub async fn get_answers(self, question_id: i32, limit: Option<i32>, offset: i32) -> Result<Vec<Answer>, Error> {
+        QuestionStoreTrait::get_answers(&self, QuestionId(question_id), AnswerSort::Oldest, limit, offset).await
+    }
+
+    /// Adds a new account to the database
+    #[deprecated(note = "use `routes::authentication::StoreTrait::add_account`")]
+    pub async fn add_account(self, account: Account) -> Result<bool, Error> {
+        AuthStoreTrait::add_account(&self, account).await
+    }
+
+    /// Retrieves an account by email
+    #[deprecated(note = "use `routes::authentication::StoreTrait::get_account`")]
+    pub async fn get_account(self, email: String) -> Result<Account, Error> {
+        AuthStoreTrait::get_account(&self, email).await
+    }
+
+    /// Updates email of an existing account
+    #[deprecated(note = "use `routes::authentication::StoreTrait::update_account`")]
+    pub async fn update_account(self, account_id: AccountId, account: AccountUpdateRequest) -> Result<AccountResponse, Error> {
+        AuthStoreTrait::update_account(&self, account_id, account).await
+    }
+
+    /// Updates password of an existing account
+    #[deprecated(note = "use `routes::authentication::StoreTrait::update_password`")]
+    pub async fn update_password(self, account_id: AccountId, password: AccountUpdatePassword) -> Result<bool, Error> {
+        AuthStoreTrait::update_password(&self, account_id, password).await
+    }
+
+    /// Retrieves account information
+    #[deprecated(note = "use `routes::authentication::StoreTrait::get_account_information`")]
+    pub async fn get_account_information(self, account_id: AccountId) -> Result<AccountResponse, Error> {
+        AuthStoreTrait::get_account_information(&self, account_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Transactional for Store {
+    async fn begin(&self) -> Result<Self, Error> {
+        let transaction = match &self.transaction {
+            Some(transaction) => transaction.join(),
+            None => Self::handle_error(TransactionHandle::begin(&self.connection).await)?,
+        };
+
+        Ok(Store {
+            connection: self.connection.clone(),
+            transaction: Some(transaction),
+        })
+    }
+
+    async fn commit(self) -> Result<(), Error> {
+        match self.transaction {
+            Some(transaction) => Self::handle_error(transaction.commit().await),
+            None => Ok(()),
+        }
+    }
+}
+
+// Live answers of the question `q`.
+const LIVE_ANSWERS: &str = "FROM answers a WHERE a.corresponding_question = q.id AND a.deleted_at IS NULL";
+
+/// Appends the conditions of `filter` to a query over `questions q`. `tags`
+/// are the tags of the filter with synonyms resolved.
+fn push_question_filter(query: &mut QueryBuilder<'_, Postgres>, filter: &QuestionFilter, tags: Vec<String>) {
+    const HAS_TAG: &str = " AND EXISTS (
+        SELECT 1 FROM question_tags qt JOIN tags t ON t.id = qt.tag_id
+        WHERE qt.question_id = q.id AND t.slug";
+
+    query.push(" WHERE q.deleted_at IS NULL");
+    if !tags.is_empty() {
+        match filter.tag_match {
+            TagMatch::Any => {
+                query.push(HAS_TAG).push(" = ANY(").push_bind(tags).push("))");
+            }
+            TagMatch::All => {
+                for tag in tags {
+                    query.push(HAS_TAG).push(" = ").push_bind(tag).push(")");
+                }
+            }
+        }
+    }
+    if let Some(author) = &filter.author {
+        query.push(" AND q.account_id = ").push_bind(author.0);
+    }
+    if let Some(after) = filter.created_after {
+        query.push(" AND q.created_on >= ").push_bind(after.naive_utc());
+    }
+    if let Some(before) = filter.created_before {
+        query.push(" AND q.created_on < ").push_bind(before.naive_utc());
+    }
+    if filter.unanswered {
+        query.push(format!(" AND NOT EXISTS (SELECT 1 {})", LIVE_ANSWERS));
+    }
+}
+
+fn push_question_order(query: &mut QueryBuilder<'_, Postgres>, sort: QuestionSort) {
+    match sort {
+        QuestionSort::Oldest => query.push(" ORDER BY q.created_on, q.id"),
+        QuestionSort::Newest => query.push(" ORDER BY q.created_on DESC, q.id DESC"),
+        QuestionSort::MostAnswers => query.push(format!(
+            " ORDER BY (SELECT COUNT(*) {}) DESC, q.created_on DESC, q.id DESC",
+            LIVE_ANSWERS
+        )),
+        // GREATEST ignores the NULL of a question without answers.
+        QuestionSort::RecentActivity => query.push(format!(
+            " ORDER BY GREATEST(q.created_on, (SELECT MAX(a.created_on) {})) DESC, q.id DESC",
+            LIVE_ANSWERS
+        )),
+    };
+}
+
+fn answer_order(sort: AnswerSort) -> &'static str {
+    match sort {
+        AnswerSort::Oldest => "created_on, id",
+        AnswerSort::Score => "score DESC, created_on, id",
+    }
+}
+
+// Comments are deleted for good; questions and answers keep deleted rows
+// until they are purged.
+fn live_filter(table: &str) -> &'static str {
+    match table {
+        "comments" => "",
+        _ => " AND deleted_at IS NULL",
+    }
+}
+
+#[async_trait::async_trait]
+impl QuestionStoreTrait for Store {
+    async fn get_questions(
+        &self,
+        filter: QuestionFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        let tags = self.resolve_synonyms(filter.tags.clone()).await?;
+        let mut query = QueryBuilder::new(format!("SELECT q.*, {} FROM questions q", AUTHOR_NAME));
+        push_question_filter(&mut query, &filter, tags);
+        push_question_order(&mut query, filter.sort);
+        query.push(" LIMIT ").push_bind(limit);
+        query.push(" OFFSET ").push_bind(offset);
+
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            query
+                .build()
+                .map(to_question)
+                .fetch_all(&mut *conn)
+                .await
+        )
+    }
+
+    async fn get_questions_page(
+        &self,
+        filter: QuestionFilter,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Question>, Error> {
+        let tags = self.resolve_synonyms(filter.tags.clone()).await?;
+        let newest_first = filter.sort == QuestionSort::Newest;
+        let mut query = QueryBuilder::new(format!("SELECT q.*, {} FROM questions q", AUTHOR_NAME));
+        push_question_filter(&mut query, &filter, tags);
+        if let Some(cursor) = cursor {
+            query
+                .push(if newest_first { " AND (q.created_on, q.id) < (" } else { " AND (q.created_on, q.id) > (" })
+                .push_bind(cursor.created_on.naive_utc())
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        push_question_order(&mut query, if newest_first { QuestionSort::Newest } else { QuestionSort::Oldest });
+        query.push(" LIMIT ").push_bind(limit.max(0) + 1);
+
+        let mut conn = self.conn().await?;
+        let rows = Self::handle_error(
+            query
+                .build()
+                .map(|row: PgRow| {
+                    let cursor = to_cursor(&row);
+                    (to_question(row), cursor)
+                })
+                .fetch_all(&mut *conn)
+                .await
+        )?;
+        Ok(Page::from_rows(rows, limit))
+    }
+
+    async fn is_question_owner(
+        &self,
+        question_id: QuestionId,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        self.check_ownership("questions", question_id.0, account_id).await
+    }
+
+    async fn add_question(
+        &self,
+        new_question: NewQuestion,
+        account_id: AccountId,
+    ) -> Result<Question, Error> {
+        let tx = self.begin().await?;
+        let tags = tx.canonical_tags(new_question.tags).await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "INSERT INTO questions (title, content, tags, account_id) 
+                    VALUES ($1, $2, $3, $4) 
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(new_question.title)
+                .bind(new_question.content)
+                .bind(tags)
+                .bind(account_id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await
+            )?
+        };
+        tx.set_question_tags(question.id, &question.tags).await?;
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn update_question(
+        &self,
+        question: Question,
+        id: QuestionId,
+        account_id: AccountId,
+    ) -> Result<Question, Error> {
+        // The revision is rolled back with the transaction if the update
+        // finds nothing to change.
+        let tx = self.begin().await?;
+        let tags = tx.canonical_tags(question.tags).await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO question_revisions (question_id, title, content, tags, account_id)
+                    SELECT id, title, content, tags, $2 FROM questions
+                    WHERE id = $1 AND account_id = $2 AND deleted_at IS NULL"
+                )
+                .bind(id.0)
+                .bind(account_id.0)
+                .execute(&mut *conn)
+                .await
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE questions 
+                    SET title = $1, content = $2, tags = $3, updated_on = NOW(),
+                        version = version + 1
+                    WHERE id = $4 AND account_id = $5 AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(question.title)
+                .bind(question.content)
+                .bind(tags)
+                .bind(id.0)
+                .bind(account_id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await
+            )?
+        };
+        tx.set_question_tags(question.id, &question.tags).await?;
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, Error> {
+        // The question's answers are deleted with the same timestamp, which
+        // is how `restore_question` finds them again.
+        let deleted_at = Utc::now();
+        let tx = self.begin().await?;
+        {
+            let mut conn = tx.conn().await?;
+            let deleted = Self::handle_error(
+                sqlx::query(
+                    "UPDATE questions SET deleted_at = $3
+                    WHERE id = $1 AND account_id = $2 AND deleted_at IS NULL"
+                )
+                .bind(id.0)
+                .bind(account_id.0)
+                .bind(deleted_at)
+                .execute(&mut *conn)
+                .await
+            )?;
+            if deleted.rows_affected() > 0 {
+                Self::handle_error(
+                    sqlx::query(
+                        "UPDATE answers SET deleted_at = $2
+                        WHERE corresponding_question = $1 AND deleted_at IS NULL"
+                    )
+                    .bind(id.0)
+                    .bind(deleted_at)
+                    .execute(&mut *conn)
+                    .await
+                )?;
+            }
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, Error> {
+        let tx = self.begin().await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            let deleted_at: DateTime<Utc> = Self::handle_error(
+                sqlx::query(
+                    "SELECT deleted_at FROM questions
+                    WHERE id = $1 AND account_id = $2 AND deleted_at IS NOT NULL"
+                )
+                .bind(id.0)
+                .bind(account_id.0)
+                .fetch_one(&mut *conn)
+                .await
+                .map(|row: PgRow| row.get(0))
+            )?;
+            Self::handle_error(
+                sqlx::query(
+                    "UPDATE answers SET deleted_at = NULL
+                    WHERE corresponding_question = $1 AND deleted_at = $2"
+                )
+                .bind(id.0)
+                .bind(deleted_at)
+                .execute(&mut *conn)
+                .await
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE questions SET deleted_at = NULL
+                    WHERE id = $1
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await
+            )?
+        };
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn get_answers(
+        &self,
+        question_id: QuestionId,
+        sort: AnswerSort,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Answer>, Error> {
+        // The accepted answer is pinned first whatever the order.
+        let query = format!(
+            "SELECT *, {} FROM answers
+            WHERE corresponding_question = $1 AND deleted_at IS NULL
+            ORDER BY (id = (SELECT accepted_answer_id FROM questions WHERE id = $1)) IS TRUE DESC, {}
+            LIMIT $2 OFFSET $3",
+            AUTHOR_NAME,
+            answer_order(sort)
+        );
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+            .bind(question_id.0)
+            .bind(limit)
+            .bind(offset)
+            .map(to_answer)
+            .fetch_all(&mut *conn)
+            .await
+        )
+    }
+
+    async fn get_answers_page(
+        &self,
+        question_id: QuestionId,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Answer>, Error> {
+        let mut conn = self.conn().await?;
+        // The accepted answer is left out of the walk and put on top of the
+        // first page instead.
+        let accepted = match cursor {
+            Some(_) => None,
+            None => Self::handle_error(
+                sqlx::query(&format!(
+                    "SELECT *, {} FROM answers
+                    WHERE id = (SELECT accepted_answer_id FROM questions WHERE id = $1)
+                    AND deleted_at IS NULL",
+                    AUTHOR_NAME
+                ))
+                .bind(question_id.0)
+                .map(to_answer)
+                .fetch_optional(&mut *conn)
+                .await
+            )?,
+        };
+        let rows = Self::handle_error(
+            sqlx::query(&format!(
+                "SELECT *, {} FROM answers
+                WHERE corresponding_question = $1 AND deleted_at IS NULL
+                AND id IS DISTINCT FROM (SELECT accepted_answer_id FROM questions WHERE id = $1)
+                AND ($2::timestamp IS NULL OR (created_on, id) > ($2, $3))
+                ORDER BY created_on, id
+                LIMIT $4",
+                AUTHOR_NAME
+            ))
+            .bind(question_id.0)
+            .bind(cursor.map(|cursor| cursor.created_on.naive_utc()))
+            .bind(cursor.map(|cursor| cursor.id))
+            .bind(limit.max(0) + i32::from(accepted.is_none()))
+            .map(|row: PgRow| {
+                let cursor = to_cursor(&row);
+                (to_answer(row), cursor)
+            })
+            .fetch_all(&mut *conn)
+            .await
+        )?;
+        Ok(match accepted {
+            Some(answer) => Page::pinned(answer, rows, limit),
+            None => Page::from_rows(rows, limit),
+        })
+    }
+
+    async fn get_question(&self, id: QuestionId) -> Result<Question, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!("SELECT *, {} FROM questions WHERE id = $1 AND deleted_at IS NULL", AUTHOR_NAME))
+                .bind(id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await
+        )
+    }
+
+    async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM question_revisions WHERE question_id = $1 ORDER BY id")
+                .bind(id.0)
+                .map(to_question_revision)
+                .fetch_all(&mut *conn)
+                .await
+        )
+    }
+
+    async fn get_question_revision(
+        &self,
+        id: QuestionId,
+        revision_id: RevisionId,
+    ) -> Result<QuestionRevision, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM question_revisions WHERE question_id = $1 AND id = $2")
+                .bind(id.0)
+                .bind(revision_id.0)
+                .map(to_question_revision)
+                .fetch_one(&mut *conn)
+                .await
+        )
+    }
+
+    async fn vote_question(
+        &self,
+        id: QuestionId,
+        account_id: AccountId,
+        vote: Option<Vote>,
+    ) -> Result<Question, Error> {
+        let tx = self.begin().await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            // Only live posts take votes; for any other the update below
+            // finds no row.
+            let query = match vote {
+                Some(vote) => sqlx::query(
+                    "INSERT INTO question_votes (question_id, account_id, value)
+                    SELECT $1, $2, $3 FROM questions WHERE id = $1 AND deleted_at IS NULL
+                    ON CONFLICT (question_id, account_id) DO UPDATE SET value = excluded.value"
+                )
+                .bind(id.0)
+                .bind(account_id.0)
+                .bind(vote.value() as i16),
+                None => sqlx::query("DELETE FROM question_votes WHERE question_id = $1 AND account_id = $2")
+                    .bind(id.0)
+                    .bind(account_id.0),
+            };
+            Self::handle_error(query.execute(&mut *conn).await)?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE questions
+                    SET score = (SELECT COALESCE(SUM(value), 0) FROM question_votes WHERE question_id = $1)::integer
+                    WHERE id = $1 AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await
+            )?
+        };
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, Error> {
+        let mut conn = self.conn().await?;
+        // Only a live answer to the question itself can be accepted.
+        Self::handle_error(
+            sqlx::query(&format!(
+                "UPDATE questions SET accepted_answer_id = $2
+                WHERE id = $1 AND deleted_at IS NULL
+                AND EXISTS (
+                    SELECT 1 FROM answers
+                    WHERE id = $2 AND corresponding_question = $1 AND deleted_at IS NULL
+                )
+                RETURNING {}, {}",
+                QUESTION_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(id.0)
+            .bind(answer_id.0)
+            .map(to_question)
+            .fetch_one(&mut *conn)
+            .await
+        )
+    }
+
+    async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!(
+                "UPDATE questions SET accepted_answer_id = NULL
+                WHERE id = $1 AND deleted_at IS NULL
+                RETURNING {}, {}",
+                QUESTION_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(id.0)
+            .map(to_question)
+            .fetch_one(&mut *conn)
+            .await
+        )
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<SearchResult>, Error> {
+        let mut conn = self.conn().await?;
+        // Snippets are only built for the rows of the requested page.
+        Self::handle_error(
+            sqlx::query(
+                "WITH terms AS (SELECT websearch_to_tsquery('english', $1) AS query),
+                matches AS (
+                    SELECT q.id AS question_id, NULL::integer AS answer_id, q.title,
+                        q.title || ' ' || q.content AS body, ts_rank(q.search, query) AS rank
+                    FROM questions q, terms
+                    WHERE q.deleted_at IS NULL AND q.search @@ query
+                    UNION ALL
+                    SELECT q.id, a.id, q.title, a.content, ts_rank(a.search, query)
+                    FROM answers a JOIN questions q ON q.id = a.corresponding_question, terms
+                    WHERE a.deleted_at IS NULL AND q.deleted_at IS NULL AND a.search @@ query
+                    ORDER BY rank DESC, question_id, answer_id NULLS FIRST
+                    LIMIT $2 OFFSET $3
+                )
+                SELECT question_id, answer_id, title, rank,
+                    ts_headline('english', body, query) AS snippet
+                FROM matches, terms
+                ORDER BY rank DESC, question_id, answer_id NULLS FIRST"
+            )
+            .bind(query)
+            .bind(limit)
+            .bind(offset)
+            .map(to_search_result)
+            .fetch_all(&mut *conn)
+            .await
+        )
+    }
+
+    async fn count_answers(&self, question_id: QuestionId) -> Result<i64, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "SELECT COUNT(*) FROM answers
+                WHERE corresponding_question = $1 AND deleted_at IS NULL"
+            )
+                .bind(question_id.0)
+                .fetch_one(&mut *conn)
+                .await
+                .map(|row: PgRow| row.get(0))
+        )
+    }
+}
+
+// Every tag with the number of live questions using it and its synonyms.
+const TAGS_QUERY: &str = "SELECT t.slug, COUNT(q.id) AS questions,
+        ARRAY(SELECT s.slug FROM tag_synonyms s WHERE s.tag_id = t.id ORDER BY s.slug)::text[] AS synonyms
+    FROM tags t
+    LEFT JOIN question_tags qt ON qt.tag_id = t.id
+    LEFT JOIN questions q ON q.id = qt.question_id AND q.deleted_at IS NULL";
+
+// The canonical slug of `$1`, which is `$1` itself unless it is a synonym.
+const CANONICAL_TAG: &str = "coalesce(
+        (SELECT t.slug FROM tag_synonyms s JOIN tags t ON t.id = s.tag_id WHERE s.slug = $1),
+        $1
+    )";
+
+#[async_trait::async_trait]
+impl TagStoreTrait for Store {
+    async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, Error> {
+        let query = format!(
+            "{} GROUP BY t.id ORDER BY questions DESC, t.slug LIMIT $1 OFFSET $2",
+            TAGS_QUERY
+        );
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(limit)
+                .bind(offset)
+                .map(to_tag)
+                .fetch_all(&mut *conn)
+                .await
+        )
+    }
+
+    async fn get_tag_questions(
+        &self,
+        slug: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        let query = format!(
+            "SELECT q.*, {} FROM questions q
+            JOIN question_tags qt ON qt.question_id = q.id
+            JOIN tags t ON t.id = qt.tag_id
+            WHERE t.slug = {} AND q.deleted_at IS NULL
+            ORDER BY q.id
+            LIMIT $2 OFFSET $3",
+            AUTHOR_NAME, CANONICAL_TAG
+        );
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(slug)
+                .bind(limit)
+                .bind(offset)
+                .map(to_question)
+                .fetch_all(&mut *conn)
+                .await
+        )
+    }
+
+    async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, Error> {
+        let tx = self.begin().await?;
+        let tag = {
+            let mut conn = tx.conn().await?;
+            let slug: String = Self::handle_error(
+                sqlx::query(&format!("SELECT {}", CANONICAL_TAG))
+                    .bind(slug)
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map(|row: PgRow| row.get(0))
+            )?;
+            if synonym == slug {
+                return Err(Error::InvalidTag(synonym));
+            }
+            // Locking the tag keeps questions from taking it up until commit.
+            // Deleted questions still hold their tags until they are purged.
+            Self::handle_error(
+                sqlx::query("SELECT id FROM tags WHERE slug = $1 FOR UPDATE")
+                    .bind(&synonym)
+                    .fetch_optional(&mut *conn)
+                    .await
+            )?;
+            let in_use: bool = Self::handle_error(
+                sqlx::query(
+                    "SELECT EXISTS (
+                        SELECT 1 FROM question_tags qt JOIN tags t ON t.id = qt.tag_id
+                        WHERE t.slug = $1
+                    )"
+                )
+                .bind(&synonym)
+                .fetch_one(&mut *conn)
+                .await
+                .map(|row: PgRow| row.get(0))
+            )?;
+            if in_use {
+                return Err(Error::TagInUse(synonym));
+            }
+            Self::handle_error(
+                sqlx::query("INSERT INTO tags (slug) VALUES ($1) ON CONFLICT (slug) DO NOTHING")
+                    .bind(&slug)
+                    .execute(&mut *conn)
+                    .await
+            )?;
+            // An unused tag turns into the synonym, and its own synonyms
+            // move over to the canonical tag.
+            Self::handle_error(
+                sqlx::query(
+                    "UPDATE tag_synonyms SET tag_id = (SELECT id FROM tags WHERE slug = $2)
+                    WHERE tag_id = (SELECT id FROM tags WHERE slug = $1)"
+                )
+                .bind(&synonym)
+                .bind(&slug)
+                .execute(&mut *conn)
+                .await
+            )?;
+            Self::handle_error(
+                sqlx::query("DELETE FROM tags WHERE slug = $1")
+                    .bind(&synonym)
+                    .execute(&mut *conn)
+                    .await
+            )?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO tag_synonyms (slug, tag_id)
+                    SELECT $1, id FROM tags WHERE slug = $2
+                    ON CONFLICT (slug) DO UPDATE SET tag_id = EXCLUDED.tag_id"
+                )
+                .bind(&synonym)
+                .bind(&slug)
+                .execute(&mut *conn)
+                .await
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!("{} WHERE t.slug = $1 GROUP BY t.id", TAGS_QUERY))
+                    .bind(&slug)
+                    .map(to_tag)
+                    .fetch_one(&mut *conn)
+                    .await
+            )?
+        };
+        tx.commit().await?;
+        Ok(tag)
+    }
+}
+
+#[async_trait::async_trait]
+impl AnswerStoreTrait for Store {
+    async fn add_answer(&self, new_answer: NewAnswer, account_id: AccountId) -> Result<Answer, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!(
+                "INSERT INTO answers (content, corresponding_question, account_id) 
+                SELECT $1, $2, $3
+                WHERE EXISTS (SELECT 1 FROM questions WHERE id = $2 AND deleted_at IS NULL)
+                RETURNING {}, {}",
+                ANSWER_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(new_answer.content)
+            .bind(new_answer.question_id.0)
+            .bind(account_id.0)
+            .map(to_answer)
+            .fetch_one(&mut *conn)
+            .await
+        )
+    }
+
+    async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, Error> {
+        self.check_ownership("answers", answer_id, account_id).await
+    }
+
+    async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        let tx = self.begin().await?;
+        let answer = {
+            let mut conn = tx.conn().await?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO answer_revisions (answer_id, content, account_id)
+                    SELECT id, content, $2 FROM answers
+                    WHERE id = $1 AND account_id = $2 AND deleted_at IS NULL"
+                )
+                .bind(id)
+                .bind(account_id.0)
+                .execute(&mut *conn)
+                .await
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE answers 
+                    SET content = $1, corresponding_question = $2, updated_on = NOW(),
+                        version = version + 1
+                    WHERE id = $3 AND account_id = $4 AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    ANSWER_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(answer.content)
+                .bind(answer.question_id.0)
+                .bind(id)
+                .bind(account_id.0)
+                .map(to_answer)
+                .fetch_one(&mut *conn)
+                .await
+            )?
+        };
+        tx.commit().await?;
+        Ok(answer)
+    }
+
+    async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE answers SET deleted_at = $3
+                WHERE id = $1 AND account_id = $2 AND deleted_at IS NULL"
+            )
+            .bind(id)
+            .bind(account_id.0)
+            .bind(Utc::now())
+            .execute(&mut *conn)
+            .await
+            .map(|_| true)
+        )
+    }
+
+    async fn get_answer(&self, id: i32) -> Result<Answer, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!("SELECT *, {} FROM answers WHERE id = $1 AND deleted_at IS NULL", AUTHOR_NAME))
+                .bind(id)
+                .map(to_answer)
+                .fetch_one(&mut *conn)
+                .await
+        )
     }
 
-    /// Helper function to handle database errors consistently
-    fn handle_error<T>(result: Result<T, sqlx::Error>) -> Result<T, Error> {
-        result.map_err(|e| {
-            tracing::event!(tracing::Level::ERROR, "{:?}", e);
-            Error::DatabaseQueryError(e)
-        })
+    async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM answer_revisions WHERE answer_id = $1 ORDER BY id")
+                .bind(id)
+                .map(to_answer_revision)
+                .fetch_all(&mut *conn)
+                .await
+        )
     }
 
-    /// Helper function to check ownership of a resource
-    async fn check_ownership(
+    async fn vote_answer(
         &self,
-        table: &str,
         id: i32,
-        account_id: &AccountId,
-    ) -> Result<bool, Error> {
-        let query = format!(
-            "SELECT EXISTS(SELECT 1 FROM {} WHERE id = $1 AND account_id = $2)",
-            table
-        );
-        
-        Self::handle_error(
-            sqlx::query(&query)
+        account_id: AccountId,
+        vote: Option<Vote>,
+    ) -> Result<Answer, Error> {
+        let tx = self.begin().await?;
+        let answer = {
+            let mut conn = tx.conn().await?;
+            // Only live posts take votes; for any other the update below
+            // finds no row.
+            let query = match vote {
+                Some(vote) => sqlx::query(
+                    "INSERT INTO answer_votes (answer_id, account_id, value)
+                    SELECT $1, $2, $3 FROM answers WHERE id = $1 AND deleted_at IS NULL
+                    ON CONFLICT (answer_id, account_id) DO UPDATE SET value = excluded.value"
+                )
                 .bind(id)
                 .bind(account_id.0)
-                .fetch_one(&self.connection)
+                .bind(vote.value() as i16),
+                None => sqlx::query("DELETE FROM answer_votes WHERE answer_id = $1 AND account_id = $2")
+                    .bind(id)
+                    .bind(account_id.0),
+            };
+            Self::handle_error(query.execute(&mut *conn).await)?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE answers
+                    SET score = (SELECT COALESCE(SUM(value), 0) FROM answer_votes WHERE answer_id = $1)::integer
+                    WHERE id = $1 AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    ANSWER_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(id)
+                .map(to_answer)
+                .fetch_one(&mut *conn)
+                .await
+            )?
+        };
+        tx.commit().await?;
+        Ok(answer)
+    }
+
+    async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM answer_revisions WHERE answer_id = $1 AND id = $2")
+                .bind(id)
+                .bind(revision_id.0)
+                .map(to_answer_revision)
+                .fetch_one(&mut *conn)
                 .await
-                .map(|row: PgRow| row.get(0))
         )
     }
 
-    /// Retrieves a list of questions from the database with optional pagination.
-    pub async fn get_questions(
-        self,
+    async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        let mut conn = self.conn().await?;
+        // An answer of a deleted question comes back with the question.
+        Self::handle_error(
+            sqlx::query(&format!(
+                "UPDATE answers SET deleted_at = NULL
+                WHERE id = $1 AND account_id = $2 AND deleted_at IS NOT NULL
+                AND EXISTS (
+                    SELECT 1 FROM questions
+                    WHERE questions.id = answers.corresponding_question
+                    AND questions.deleted_at IS NULL
+                )
+                RETURNING {}, {}",
+                ANSWER_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(id)
+            .bind(account_id.0)
+            .map(to_answer)
+            .fetch_one(&mut *conn)
+            .await
+        )
+    }
+}
+
+// The table of the post a comment is attached to, the column of
+// `comments` pointing to it and its id.
+fn comment_parent(parent: &CommentParent) -> (&'static str, &'static str, i32) {
+    match parent {
+        CommentParent::Question(id) => ("questions", "question_id", id.0),
+        CommentParent::Answer(id) => ("answers", "answer_id", id.0),
+    }
+}
+
+#[async_trait::async_trait]
+impl CommentStoreTrait for Store {
+    async fn get_comments(
+        &self,
+        parent: CommentParent,
         limit: Option<i32>,
         offset: i32,
-    ) -> Result<Vec<Question>, Error> {
+    ) -> Result<Vec<Comment>, Error> {
+        let (table, column, id) = comment_parent(&parent);
+        // Comments go away with their post while it is deleted.
+        let query = format!(
+            "SELECT c.* FROM comments c JOIN {} p ON p.id = c.{}
+            WHERE p.id = $1 AND p.deleted_at IS NULL
+            ORDER BY c.created_on, c.id
+            LIMIT $2 OFFSET $3",
+            table, column
+        );
+        let mut conn = self.conn().await?;
         Self::handle_error(
-            sqlx::query("SELECT * from questions LIMIT $1 OFFSET $2")
+            sqlx::query(&query)
+                .bind(id)
                 .bind(limit)
                 .bind(offset)
-                .map(|row: PgRow| Question {
-                    id: QuestionId(row.get("id")),
-                    title: row.get("title"),
-                    content: row.get("content"),
-                    tags: row.get("tags"),
-                })
-                .fetch_all(&self.connection)
+                .map(to_comment)
+                .fetch_all(&mut *conn)
                 .await
         )
     }
 
-    /// Determines whether the given account is the owner of the specified question.
-    pub async fn is_question_owner(
+    async fn add_comment(
         &self,
-        question_id: i32,
-        account_id: &AccountId,
-    ) -> Result<bool, Error> {
-        self.check_ownership("questions", question_id, account_id).await
-    }
-
-    /// Adds a new question to the database and returns the created question.
-    pub async fn add_question(
-        self,
-        new_question: NewQuestion,
+        parent: CommentParent,
+        new_comment: NewComment,
         account_id: AccountId,
-    ) -> Result<Question, Error> {
+    ) -> Result<Comment, Error> {
+        let (table, column, id) = comment_parent(&parent);
+        // Only live posts take comments; for any other no row is inserted.
+        let query = format!(
+            "INSERT INTO comments (content, {}, account_id)
+            SELECT $1, id, $3 FROM {} WHERE id = $2 AND deleted_at IS NULL
+            RETURNING *",
+            column, table
+        );
+        let mut conn = self.conn().await?;
         Self::handle_error(
-            sqlx::query(
-                "INSERT INTO questions (title, content, tags, account_id) 
-                VALUES ($1, $2, $3, $4) 
-                RETURNING id, title, content, tags"
-            )
-            .bind(new_question.title)
-            .bind(new_question.content)
-            .bind(new_question.tags)
-            .bind(account_id.0)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
-            .fetch_one(&self.connection)
-            .await
+            sqlx::query(&query)
+                .bind(new_comment.content)
+                .bind(id)
+                .bind(account_id.0)
+                .map(to_comment)
+                .fetch_one(&mut *conn)
+                .await
         )
     }
 
-    /// Updates an existing question in the database and returns the updated question.
-    pub async fn update_question(
-        self,
-        question: Question,
-        id: i32,
+    async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, Error> {
+        self.check_ownership("comments", comment_id.0, account_id).await
+    }
+
+    async fn update_comment(
+        &self,
+        comment: NewComment,
+        id: CommentId,
         account_id: AccountId,
-    ) -> Result<Question, Error> {
+    ) -> Result<Comment, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
             sqlx::query(
-                "UPDATE questions 
-                SET title = $1, content = $2, tags = $3
-                WHERE id = $4 AND account_id = $5
-                RETURNING id, title, content, tags"
+                "UPDATE comments SET content = $1
+                WHERE id = $2 AND account_id = $3
+                RETURNING *"
             )
-            .bind(question.title)
-            .bind(question.content)
-            .bind(question.tags)
-            .bind(id)
+            .bind(comment.content)
+            .bind(id.0)
             .bind(account_id.0)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
-            .fetch_one(&self.connection)
+            .map(to_comment)
+            .fetch_one(&mut *conn)
             .await
         )
     }
 
-    /// Delete an existing question in the database
-    pub async fn delete_question(self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+    async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
-            sqlx::query("DELETE FROM questions WHERE id = $1 AND account_id = $2")
-                .bind(id)
+            sqlx::query("DELETE FROM comments WHERE id = $1 AND account_id = $2")
+                .bind(id.0)
                 .bind(account_id.0)
-                .execute(&self.connection)
+                .execute(&mut *conn)
                 .await
                 .map(|_| true)
         )
     }
+}
 
-    /// Retrieves a list of answers for a question from the database with optional pagination.
-    pub async fn get_answers(
-        self,
-        question_id: i32,
-        limit: Option<i32>,
-        offset: i32,
-    ) -> Result<Vec<Answer>, Error> {
-        Self::handle_error(
-            sqlx::query(
-                "SELECT * FROM answers 
-                WHERE corresponding_question = $1 
-                LIMIT $2 OFFSET $3"
-            )
-            .bind(question_id)
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| Answer {
-                id: AnswerId(row.get("id")),
-                content: row.get("content"),
-                question_id: QuestionId(row.get("corresponding_question")),
-            })
-            .fetch_all(&self.connection)
-            .await
-        )
-    }
-
-    /// Adds a new account to the database
-    pub async fn add_account(self, account: Account) -> Result<bool, Error> {
+#[async_trait::async_trait]
+impl AuthStoreTrait for Store {
+    async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
-            sqlx::query("INSERT INTO accounts (email, password) VALUES ($1, $2)")
+            sqlx::query("INSERT INTO accounts (email, password, display_name) VALUES ($1, $2, $3)")
                 .bind(account.email)
                 .bind(account.password)
-                .execute(&self.connection)
+                .bind(account.display_name)
+                .execute(&mut *conn)
                 .await
                 .map(|_| true)
         )
     }
 
-    /// Retrieves an account by email
-    pub async fn get_account(self, email: String) -> Result<Account, Error> {
+    async fn get_account(&self, email: String) -> Result<Account, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
             sqlx::query("SELECT * from accounts where email = $1")
                 .bind(email)
@@ -221,251 +1452,444 @@ impl Store {
                     id: Some(AccountId(row.get("id"))),
                     email: row.get("email"),
                     password: row.get("password"),
+                    display_name: row.get("display_name"),
                 })
-                .fetch_one(&self.connection)
+                .fetch_one(&mut *conn)
                 .await
         )
     }
 
-    /// Updates email of an existing account
-    pub async fn update_account(
-        self,
+    async fn update_account(
+        &self,
         account_id: AccountId,
         account: AccountUpdateRequest,
     ) -> Result<AccountResponse, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
+            // A new email has to be verified again.
             sqlx::query(
                 "UPDATE accounts 
-                SET email = $1
+                SET email = $1, email_verified = email_verified AND email = $1
                 WHERE id = $2
-                RETURNING email, id"
+                RETURNING email, id, email_verified"
             )
             .bind(account.email)
             .bind(account_id.0)
-            .map(|row: PgRow| AccountResponse {
-                email: row.get("email"),
-                id: AccountId(row.get("id")),
-            })
-            .fetch_one(&self.connection)
+            .map(to_account_response)
+            .fetch_one(&mut *conn)
             .await
         )
     }
 
-    /// Updates password of an existing account
-    pub async fn update_password(
-        self,
+    async fn update_password(
+        &self,
         account_id: AccountId,
         password: AccountUpdatePassword,
     ) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        // A new password starts a new token generation and revokes the
+        // refresh tokens, which logs the account out everywhere.
         Self::handle_error(
-            sqlx::query("UPDATE accounts SET password = $1 WHERE id = $2")
-                .bind(password.0)
-                .bind(account_id.0)
-                .execute(&self.connection)
-                .await
-                .map(|_| true)
+            sqlx::query(
+                "WITH revoked AS (
+                    UPDATE sessions SET revoked_on = NOW()
+                    WHERE account_id = $2 AND revoked_on IS NULL
+                )
+                UPDATE accounts
+                SET password = $1, token_generation = token_generation + 1
+                WHERE id = $2"
+            )
+            .bind(password.0)
+            .bind(account_id.0)
+            .execute(&mut *conn)
+            .await
+            .map(|_| true)
         )
     }
 
-    /// Retrieves account information
-    pub async fn get_account_information(
-        self,
+    async fn get_account_information(
+        &self,
         account_id: AccountId,
     ) -> Result<AccountResponse, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
-            sqlx::query("SELECT email, id FROM accounts WHERE id = $1")
+            sqlx::query("SELECT email, id, email_verified FROM accounts WHERE id = $1")
                 .bind(account_id.0)
-                .map(|row: PgRow| AccountResponse {
-                    email: row.get("email"),
-                    id: AccountId(row.get("id")),
-                })
-                .fetch_one(&self.connection)
+                .map(to_account_response)
+                .fetch_one(&mut *conn)
                 .await
         )
     }
-}
 
-#[async_trait::async_trait]
-impl QuestionStoreTrait for Store {
-    async fn get_questions(
+    async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT token_generation FROM accounts WHERE id = $1")
+                .bind(account_id.0)
+                .map(|row: PgRow| row.get("token_generation"))
+                .fetch_one(&mut *conn)
+                .await
+        )
+    }
+
+    async fn add_session(
         &self,
-        limit: Option<i32>,
-        offset: i32,
-    ) -> Result<Vec<Question>, Error> {
+        session: NewSession,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<SessionId, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
             sqlx::query(
-                "SELECT * FROM questions 
-                LIMIT $1 OFFSET $2"
+                "WITH session AS (
+                    INSERT INTO sessions (account_id, user_agent, ip, expires_on)
+                    VALUES ($1, $2, $3, $5)
+                    RETURNING id, account_id
+                )
+                INSERT INTO refresh_tokens (account_id, session_id, token_hash, expires_on)
+                SELECT account_id, id, $4, $5 FROM session
+                RETURNING session_id"
             )
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
-            .fetch_all(&self.connection)
+            .bind(session.account_id.0)
+            .bind(session.user_agent)
+            .bind(session.ip)
+            .bind(token_hash)
+            .bind(expires_on)
+            .map(|row: PgRow| SessionId(row.get("session_id")))
+            .fetch_one(&mut *conn)
             .await
         )
     }
 
-    async fn is_question_owner(
+    async fn rotate_refresh_token(
         &self,
-        question_id: QuestionId,
-        account_id: &AccountId,
-    ) -> Result<bool, Error> {
-        self.check_ownership("questions", question_id.0, account_id).await
+        token_hash: String,
+        new_token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(AccountId, SessionId), Error> {
+        let mut conn = self.conn().await?;
+        // Revoking the old token and adding the new one in one statement
+        // lets only one of two concurrent refreshes with a token succeed.
+        let ids = Self::handle_error(
+            sqlx::query(
+                "WITH revoked AS (
+                    UPDATE refresh_tokens SET revoked_on = NOW()
+                    WHERE token_hash = $1 AND revoked_on IS NULL AND session_id IN (
+                        SELECT id FROM sessions WHERE revoked_on IS NULL AND expires_on > NOW()
+                    )
+                    RETURNING account_id, session_id
+                ), session AS (
+                    UPDATE sessions SET last_used_on = NOW(), expires_on = $3
+                    WHERE id IN (SELECT session_id FROM revoked)
+                )
+                INSERT INTO refresh_tokens (account_id, session_id, token_hash, expires_on)
+                SELECT account_id, session_id, $2, $3 FROM revoked
+                RETURNING account_id, session_id"
+            )
+            .bind(token_hash)
+            .bind(new_token_hash)
+            .bind(expires_on)
+            .map(|row: PgRow| (AccountId(row.get("account_id")), SessionId(row.get("session_id"))))
+            .fetch_optional(&mut *conn)
+            .await
+        )?;
+        ids.ok_or(Error::InvalidRefreshToken)
     }
 
-    async fn add_question(
-        &self,
-        new_question: NewQuestion,
-        account_id: AccountId,
-    ) -> Result<Question, Error> {
+    async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE sessions SET revoked_on = NOW()
+                WHERE revoked_on IS NULL AND expires_on > NOW() AND id IN (
+                    SELECT session_id FROM refresh_tokens
+                    WHERE token_hash = $1 AND revoked_on IS NULL
+                )"
+            )
+            .bind(token_hash)
+            .execute(&mut *conn)
+            .await
+            .map(|result| result.rows_affected() > 0)
+        )
+    }
+
+    async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        // Checked on every request, so the row is only written when its last
+        // use is out of date.
         Self::handle_error(
             sqlx::query(
-                "INSERT INTO questions (title, content, tags, account_id) 
-                VALUES ($1, $2, $3, $4) 
-                RETURNING id, title, content, tags"
+                "WITH active AS (
+                    SELECT id, last_used_on FROM sessions
+                    WHERE id = $1 AND account_id = $2 AND revoked_on IS NULL AND expires_on > NOW()
+                ), touched AS (
+                    UPDATE sessions SET last_used_on = NOW()
+                    WHERE id IN (
+                        SELECT id FROM active WHERE last_used_on < NOW() - make_interval(secs => $3)
+                    )
+                )
+                SELECT EXISTS (SELECT 1 FROM active)"
             )
-            .bind(new_question.title)
-            .bind(new_question.content)
-            .bind(new_question.tags)
+            .bind(session_id.0)
             .bind(account_id.0)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
-            .fetch_one(&self.connection)
+            .bind(SESSION_TOUCH_SECONDS as f64)
+            .fetch_one(&mut *conn)
             .await
+            .map(|row: PgRow| row.get(0))
         )
     }
 
-    async fn update_question(
-        &self,
-        question: Question,
-        id: QuestionId,
-        account_id: AccountId,
-    ) -> Result<Question, Error> {
+    async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
             sqlx::query(
-                "UPDATE questions 
-                SET title = $1, content = $2, tags = $3
-                WHERE id = $4 AND account_id = $5
-                RETURNING id, title, content, tags"
+                "SELECT id, user_agent, ip, created_on, last_used_on FROM sessions
+                WHERE account_id = $1 AND revoked_on IS NULL AND expires_on > NOW()
+                ORDER BY last_used_on DESC, id DESC"
             )
-            .bind(question.title)
-            .bind(question.content)
-            .bind(question.tags)
-            .bind(id.0)
             .bind(account_id.0)
-            .map(|row: PgRow| Question {
-                id: QuestionId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                tags: row.get("tags"),
-            })
-            .fetch_one(&self.connection)
+            .map(to_session)
+            .fetch_all(&mut *conn)
             .await
         )
     }
 
-    async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, Error> {
+    async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
-            sqlx::query("DELETE FROM questions WHERE id = $1 AND account_id = $2")
-                .bind(id.0)
-                .bind(account_id.0)
-                .execute(&self.connection)
-                .await
-                .map(|_| true)
+            sqlx::query(
+                "UPDATE sessions SET revoked_on = NOW()
+                WHERE id = $1 AND account_id = $2 AND revoked_on IS NULL AND expires_on > NOW()"
+            )
+            .bind(session_id.0)
+            .bind(account_id.0)
+            .execute(&mut *conn)
+            .await
+            .map(|result| result.rows_affected() > 0)
         )
     }
 
-    async fn get_answers(
+    async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE sessions SET revoked_on = NOW()
+                WHERE account_id = $1 AND id != $2 AND revoked_on IS NULL AND expires_on > NOW()"
+            )
+            .bind(account_id.0)
+            .bind(session_id.0)
+            .execute(&mut *conn)
+            .await
+            .map(|result| result.rows_affected())
+        )
+    }
+
+    async fn add_verification_token(
         &self,
-        question_id: QuestionId,
-        limit: Option<i32>,
-        offset: i32,
-    ) -> Result<Vec<Answer>, Error> {
+        account_id: AccountId,
+        email: String,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
             sqlx::query(
-                "SELECT * FROM answers 
-                WHERE corresponding_question = $1 
-                LIMIT $2 OFFSET $3"
+                "INSERT INTO verification_tokens (account_id, email, token_hash, expires_on)
+                VALUES ($1, $2, $3, $4)"
             )
-            .bind(question_id.0)
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| Answer {
-                id: AnswerId(row.get("id")),
-                content: row.get("content"),
-                question_id: QuestionId(row.get("corresponding_question")),
-            })
-            .fetch_all(&self.connection)
+            .bind(account_id.0)
+            .bind(email)
+            .bind(token_hash)
+            .bind(expires_on)
+            .execute(&mut *conn)
             .await
+            .map(|_| true)
         )
     }
-}
 
-#[async_trait::async_trait]
-impl AnswerStoreTrait for Store {
-    async fn add_answer(&self, new_answer: NewAnswer, account_id: AccountId) -> Result<Answer, Error> {
+    async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
             sqlx::query(
-                "INSERT INTO answers (content, corresponding_question, account_id) 
-                VALUES ($1, $2, $3) 
-                RETURNING id, content, corresponding_question"
+                "SELECT created_on FROM verification_tokens
+                WHERE account_id = $1 AND used_on IS NULL AND expires_on > NOW()
+                ORDER BY created_on DESC
+                LIMIT 1"
             )
-            .bind(new_answer.content)
-            .bind(new_answer.question_id.0)
             .bind(account_id.0)
-            .map(|row: PgRow| Answer {
-                id: AnswerId(row.get("id")),
-                content: row.get("content"),
-                question_id: QuestionId(row.get("corresponding_question")),
-            })
-            .fetch_one(&self.connection)
+            .fetch_optional(&mut *conn)
             .await
+            .map(|row: Option<PgRow>| row.map(|row| row.get("created_on")))
         )
     }
 
-    async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, Error> {
-        self.check_ownership("answers", answer_id, account_id).await
+    async fn verify_email(&self, token_hash: String) -> Result<AccountId, Error> {
+        let mut conn = self.conn().await?;
+        // A token does not verify an email the account changed since.
+        let account_id = Self::handle_error(
+            sqlx::query(
+                "WITH verified AS (
+                    UPDATE accounts SET email_verified = TRUE
+                    FROM verification_tokens t
+                    WHERE t.token_hash = $1 AND t.used_on IS NULL AND t.expires_on > NOW()
+                        AND t.account_id = accounts.id AND t.email = accounts.email
+                    RETURNING accounts.id
+                ), used AS (
+                    UPDATE verification_tokens SET used_on = NOW()
+                    WHERE token_hash = $1 AND account_id IN (SELECT id FROM verified)
+                )
+                SELECT id FROM verified"
+            )
+            .bind(token_hash)
+            .map(|row: PgRow| AccountId(row.get("id")))
+            .fetch_optional(&mut *conn)
+            .await
+        )?;
+        account_id.ok_or(Error::InvalidVerificationToken)
     }
+}
 
-    async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+#[async_trait::async_trait]
+impl ImportStoreTrait for Store {
+    async fn set_question_created_on(&self, id: QuestionId, created_on: DateTime<Utc>) -> Result<(), Error> {
+        let mut conn = self.conn().await?;
+        let updated = Self::handle_error(
+            sqlx::query("UPDATE questions SET created_on = $1 WHERE id = $2")
+                .bind(created_on.naive_utc())
+                .bind(id.0)
+                .execute(&mut *conn)
+                .await
+        )?;
+        match updated.rows_affected() {
+            0 => Err(Error::DatabaseQueryError(sqlx::Error::RowNotFound)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn set_answer_created_on(&self, id: AnswerId, created_on: DateTime<Utc>) -> Result<(), Error> {
+        let mut conn = self.conn().await?;
+        let updated = Self::handle_error(
+            sqlx::query("UPDATE answers SET created_on = $1 WHERE id = $2")
+                .bind(created_on.naive_utc())
+                .bind(id.0)
+                .execute(&mut *conn)
+                .await
+        )?;
+        match updated.rows_affected() {
+            0 => Err(Error::DatabaseQueryError(sqlx::Error::RowNotFound)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn get_imported_post(&self, source_id: i64) -> Result<Option<ImportedPost>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM imported_posts WHERE source_id = $1")
+                .bind(source_id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map(|row: Option<PgRow>| {
+                    row.map(|row| ImportedPost {
+                        question_id: QuestionId(row.get("question_id")),
+                        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+                        accepted_source_id: row.get("accepted_source_id"),
+                    })
+                })
+        )
+    }
+
+    async fn add_imported_post(&self, source_id: i64, post: ImportedPost) -> Result<(), Error> {
+        let mut conn = self.conn().await?;
         Self::handle_error(
             sqlx::query(
-                "UPDATE answers 
-                SET content = $1, corresponding_question = $2
-                WHERE id = $3 AND account_id = $4
-                RETURNING id, content, corresponding_question"
+                "INSERT INTO imported_posts (source_id, question_id, answer_id, accepted_source_id)
+                VALUES ($1, $2, $3, $4)"
             )
-            .bind(answer.content)
-            .bind(answer.question_id.0)
-            .bind(id)
-            .bind(account_id.0)
-            .map(|row: PgRow| Answer {
-                id: AnswerId(row.get("id")),
-                content: row.get("content"),
-                question_id: QuestionId(row.get("corresponding_question")),
-            })
-            .fetch_one(&self.connection)
+            .bind(source_id)
+            .bind(post.question_id.0)
+            .bind(post.answer_id.map(|id| id.0))
+            .bind(post.accepted_source_id)
+            .execute(&mut *conn)
+            .await
+        )?;
+        Ok(())
+    }
+
+    async fn set_display_name(&self, email: String, display_name: String) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        let updated = Self::handle_error(
+            sqlx::query("UPDATE accounts SET display_name = $1 WHERE email = $2")
+                .bind(display_name)
+                .bind(email)
+                .execute(&mut *conn)
+                .await
+        )?;
+        Ok(updated.rows_affected() > 0)
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditStoreTrait for Store {
+    async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "INSERT INTO audit_log (actor, action, target, target_id, row_before, row_after, request_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *"
+            )
+            .bind(record.actor.0)
+            .bind(record.action.as_str())
+            .bind(record.action.target().as_str())
+            .bind(record.target_id)
+            .bind(record.before.map(Json))
+            .bind(record.after.map(Json))
+            .bind(record.request_id)
+            .try_map(to_audit_record)
+            .fetch_one(&mut *conn)
             .await
         )
     }
 
-    async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+    async fn get_audit_records(
+        &self,
+        filter: AuditFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<AuditRecord>, Error> {
+        let mut query = QueryBuilder::new("SELECT * FROM audit_log WHERE TRUE");
+        if let Some(actor) = filter.actor {
+            query.push(" AND actor = ").push_bind(actor.0);
+        }
+        if let Some(action) = filter.action {
+            query.push(" AND action = ").push_bind(action.as_str());
+        }
+        if let Some(target) = filter.target {
+            query.push(" AND target = ").push_bind(target.as_str());
+        }
+        if let Some(target_id) = filter.target_id {
+            query.push(" AND target_id = ").push_bind(target_id);
+        }
+        if let Some(request_id) = filter.request_id {
+            query.push(" AND request_id = ").push_bind(request_id);
+        }
+        if let Some(after) = filter.created_after {
+            query.push(" AND created_on >= ").push_bind(after);
+        }
+        if let Some(before) = filter.created_before {
+            query.push(" AND created_on < ").push_bind(before);
+        }
+        query.push(" ORDER BY created_on DESC, id DESC");
+        query.push(" LIMIT ").push_bind(limit);
+        query.push(" OFFSET ").push_bind(offset);
+
+        let mut conn = self.conn().await?;
         Self::handle_error(
-            sqlx::query("DELETE FROM answers WHERE id = $1 AND account_id = $2")
-                .bind(id)
-                .bind(account_id.0)
-                .execute(&self.connection)
+            query
+                .build()
+                .try_map(to_audit_record)
+                .fetch_all(&mut *conn)
                 .await
-                .map(|_| true)
         )
     }
 }
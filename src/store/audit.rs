@@ -0,0 +1,610 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use handle_errors::Error;
+
+use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
+use crate::routes::audit::store_trait::StoreTrait as AuditStoreTrait;
+use crate::routes::authentication::StoreTrait as AuthStoreTrait;
+use crate::routes::comment::store_trait::StoreTrait as CommentStoreTrait;
+use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
+use crate::routes::tag::store_trait::StoreTrait as TagStoreTrait;
+use crate::routes::transaction::Transactional;
+use crate::types::{
+    account::{
+        Account, AccountId, AccountResponse, AccountUpdatePassword, AccountUpdateRequest, ActiveSession, NewSession,
+        SessionId,
+    },
+    answer::{Answer, AnswerId, NewAnswer},
+    audit::{AuditAction, AuditFilter, AuditRecord, NewAuditRecord},
+    comment::{Comment, CommentId, CommentParent, NewComment},
+    filter::{AnswerSort, QuestionFilter},
+    pagination::{Cursor, Page},
+    question::{NewQuestion, Question, QuestionId},
+    revision::{AnswerRevision, QuestionRevision, RevisionId},
+    search::SearchResult,
+    tag::Tag,
+    vote::Vote,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Writes an audit record for every change made through it to another store.
+///
+/// Adding, updating, deleting and restoring questions and answers, adding
+/// and updating accounts and changing passwords are recorded with the row
+/// before and after the change. Each change runs in a transaction with its
+/// record, so a change is never made without one. Every other call goes
+/// straight to `inner`.
+///
+/// `for_request` gives a handle whose records carry the id of a request;
+/// `build_routes` makes one for every request.
+#[derive(Debug, Clone)]
+pub struct AuditedStore<S> {
+    inner: S,
+    request_id: Option<String>,
+}
+
+impl<S: Clone> AuditedStore<S> {
+    pub fn new(inner: S) -> Self {
+        AuditedStore {
+            inner,
+            request_id: None,
+        }
+    }
+
+    /// A handle on the same store which records changes as made by the
+    /// request `request_id`.
+    pub fn for_request(&self, request_id: String) -> Self {
+        AuditedStore {
+            inner: self.inner.clone(),
+            request_id: Some(request_id),
+        }
+    }
+}
+
+impl<S: AuditStoreTrait + Transactional> AuditedStore<S> {
+    /// Helper function to make a change and record it in one transaction.
+    /// `change` returns the result of the change with the record to write,
+    /// if anything changed.
+    async fn audited<T, F>(&self, change: F) -> Result<T, Error>
+    where
+        F: for<'a> FnOnce(&'a S) -> futures::future::BoxFuture<'a, Result<(T, Option<Change>), Error>>,
+    {
+        let tx = self.inner.begin().await?;
+        let (result, change) = change(&tx).await?;
+        if let Some(change) = change {
+            tx.add_audit_record(NewAuditRecord {
+                actor: change.actor,
+                action: change.action,
+                target_id: change.target_id,
+                before: change.before,
+                after: change.after,
+                request_id: self.request_id.clone(),
+            })
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(result)
+    }
+}
+
+/// A change to record, see `AuditedStore::audited`.
+struct Change {
+    actor: AccountId,
+    action: AuditAction,
+    target_id: i32,
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+fn json<T: Serialize>(row: &T) -> Option<Value> {
+    serde_json::to_value(row).ok()
+}
+
+#[async_trait::async_trait]
+impl<S: Transactional> Transactional for AuditedStore<S> {
+    async fn begin(&self) -> Result<Self, Error> {
+        Ok(AuditedStore {
+            inner: self.inner.begin().await?,
+            request_id: self.request_id.clone(),
+        })
+    }
+
+    async fn commit(self) -> Result<(), Error> {
+        self.inner.commit().await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> QuestionStoreTrait for AuditedStore<S>
+where
+    S: QuestionStoreTrait + AuditStoreTrait + Transactional,
+{
+    async fn get_questions(
+        &self,
+        filter: QuestionFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        self.inner.get_questions(filter, limit, offset).await
+    }
+
+    async fn get_questions_page(
+        &self,
+        filter: QuestionFilter,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Question>, Error> {
+        self.inner.get_questions_page(filter, cursor, limit).await
+    }
+
+    async fn is_question_owner(&self, question_id: QuestionId, account_id: &AccountId) -> Result<bool, Error> {
+        self.inner.is_question_owner(question_id, account_id).await
+    }
+
+    async fn add_question(&self, new_question: NewQuestion, account_id: AccountId) -> Result<Question, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let question = tx.add_question(new_question, account_id.clone()).await?;
+                let change = Change {
+                    actor: account_id,
+                    action: AuditAction::AddQuestion,
+                    target_id: question.id.0,
+                    before: None,
+                    after: json(&question),
+                };
+                Ok((question, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn update_question(&self, question: Question, id: QuestionId, account_id: AccountId) -> Result<Question, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let before = tx.get_question(id).await.ok();
+                let question = tx.update_question(question, id, account_id.clone()).await?;
+                let change = Change {
+                    actor: account_id,
+                    action: AuditAction::UpdateQuestion,
+                    target_id: id.0,
+                    before: before.as_ref().and_then(json),
+                    after: json(&question),
+                };
+                Ok((question, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let before = tx.get_question(id).await.ok();
+                let deleted = tx.delete_question(id, account_id.clone()).await?;
+                // Nothing is deleted if the question was gone already.
+                let change = before.map(|before| Change {
+                    actor: account_id,
+                    action: AuditAction::DeleteQuestion,
+                    target_id: id.0,
+                    before: json(&before),
+                    after: None,
+                });
+                Ok((deleted, change))
+            })
+        })
+        .await
+    }
+
+    async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let question = tx.restore_question(id, account_id.clone()).await?;
+                let change = Change {
+                    actor: account_id,
+                    action: AuditAction::RestoreQuestion,
+                    target_id: id.0,
+                    before: None,
+                    after: json(&question),
+                };
+                Ok((question, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn get_answers(
+        &self,
+        question_id: QuestionId,
+        sort: AnswerSort,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Answer>, Error> {
+        self.inner.get_answers(question_id, sort, limit, offset).await
+    }
+
+    async fn get_answers_page(
+        &self,
+        question_id: QuestionId,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Answer>, Error> {
+        self.inner.get_answers_page(question_id, cursor, limit).await
+    }
+
+    async fn count_answers(&self, question_id: QuestionId) -> Result<i64, Error> {
+        self.inner.count_answers(question_id).await
+    }
+
+    async fn get_question(&self, id: QuestionId) -> Result<Question, Error> {
+        self.inner.get_question(id).await
+    }
+
+    async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, Error> {
+        self.inner.get_question_revisions(id).await
+    }
+
+    async fn get_question_revision(
+        &self,
+        id: QuestionId,
+        revision_id: RevisionId,
+    ) -> Result<QuestionRevision, Error> {
+        self.inner.get_question_revision(id, revision_id).await
+    }
+
+    async fn vote_question(
+        &self,
+        id: QuestionId,
+        account_id: AccountId,
+        vote: Option<Vote>,
+    ) -> Result<Question, Error> {
+        self.inner.vote_question(id, account_id, vote).await
+    }
+
+    async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, Error> {
+        self.inner.accept_answer(id, answer_id).await
+    }
+
+    async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, Error> {
+        self.inner.unaccept_answer(id).await
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<SearchResult>, Error> {
+        self.inner.search(query, limit, offset).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> AnswerStoreTrait for AuditedStore<S>
+where
+    S: AnswerStoreTrait + AuditStoreTrait + Transactional,
+{
+    async fn add_answer(&self, new_answer: NewAnswer, account_id: AccountId) -> Result<Answer, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let answer = tx.add_answer(new_answer, account_id.clone()).await?;
+                let change = Change {
+                    actor: account_id,
+                    action: AuditAction::AddAnswer,
+                    target_id: answer.id.0,
+                    before: None,
+                    after: json(&answer),
+                };
+                Ok((answer, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, Error> {
+        self.inner.is_answer_owner(answer_id, account_id).await
+    }
+
+    async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let before = tx.get_answer(id).await.ok();
+                let answer = tx.update_answer(answer, id, account_id.clone()).await?;
+                let change = Change {
+                    actor: account_id,
+                    action: AuditAction::UpdateAnswer,
+                    target_id: id,
+                    before: before.as_ref().and_then(json),
+                    after: json(&answer),
+                };
+                Ok((answer, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let before = tx.get_answer(id).await.ok();
+                let deleted = tx.delete_answer(id, account_id.clone()).await?;
+                // Nothing is deleted if the answer was gone already.
+                let change = before.map(|before| Change {
+                    actor: account_id,
+                    action: AuditAction::DeleteAnswer,
+                    target_id: id,
+                    before: json(&before),
+                    after: None,
+                });
+                Ok((deleted, change))
+            })
+        })
+        .await
+    }
+
+    async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let answer = tx.restore_answer(id, account_id.clone()).await?;
+                let change = Change {
+                    actor: account_id,
+                    action: AuditAction::RestoreAnswer,
+                    target_id: id,
+                    before: None,
+                    after: json(&answer),
+                };
+                Ok((answer, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn get_answer(&self, id: i32) -> Result<Answer, Error> {
+        self.inner.get_answer(id).await
+    }
+
+    async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, Error> {
+        self.inner.get_answer_revisions(id).await
+    }
+
+    async fn vote_answer(&self, id: i32, account_id: AccountId, vote: Option<Vote>) -> Result<Answer, Error> {
+        self.inner.vote_answer(id, account_id, vote).await
+    }
+
+    async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, Error> {
+        self.inner.get_answer_revision(id, revision_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: TagStoreTrait + Send + Sync> TagStoreTrait for AuditedStore<S> {
+    async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, Error> {
+        self.inner.get_tags(limit, offset).await
+    }
+
+    async fn get_tag_questions(
+        &self,
+        slug: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        self.inner.get_tag_questions(slug, limit, offset).await
+    }
+
+    async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, Error> {
+        self.inner.add_tag_synonym(slug, synonym).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: CommentStoreTrait + Send + Sync> CommentStoreTrait for AuditedStore<S> {
+    async fn get_comments(
+        &self,
+        parent: CommentParent,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Comment>, Error> {
+        self.inner.get_comments(parent, limit, offset).await
+    }
+
+    async fn add_comment(
+        &self,
+        parent: CommentParent,
+        new_comment: NewComment,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        self.inner.add_comment(parent, new_comment, account_id).await
+    }
+
+    async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, Error> {
+        self.inner.is_comment_owner(comment_id, account_id).await
+    }
+
+    async fn update_comment(
+        &self,
+        comment: NewComment,
+        id: CommentId,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        self.inner.update_comment(comment, id, account_id).await
+    }
+
+    async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, Error> {
+        self.inner.delete_comment(id, account_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> AuthStoreTrait for AuditedStore<S>
+where
+    S: AuthStoreTrait + AuditStoreTrait + Transactional,
+{
+    // Accounts are recorded as their id and email, never with the password.
+    async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let email = account.email.clone();
+                let added = tx.add_account(account).await?;
+                let account = tx.get_account(email).await?;
+                let id = account.id.ok_or(Error::NotFound)?;
+                let change = Change {
+                    actor: id.clone(),
+                    action: AuditAction::AddAccount,
+                    target_id: id.0,
+                    before: None,
+                    after: json(&AccountResponse {
+                        email: account.email,
+                        id,
+                        email_verified: false,
+                    }),
+                };
+                Ok((added, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn get_account(&self, email: String) -> Result<Account, Error> {
+        self.inner.get_account(email).await
+    }
+
+    async fn update_account(
+        &self,
+        account_id: AccountId,
+        account: AccountUpdateRequest,
+    ) -> Result<AccountResponse, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let before = tx.get_account_information(account_id.clone()).await.ok();
+                let after = tx.update_account(account_id.clone(), account).await?;
+                let change = Change {
+                    target_id: account_id.0,
+                    actor: account_id,
+                    action: AuditAction::UpdateAccount,
+                    before: before.as_ref().and_then(json),
+                    after: json(&after),
+                };
+                Ok((after, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn update_password(
+        &self,
+        account_id: AccountId,
+        password: AccountUpdatePassword,
+    ) -> Result<bool, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let updated = tx.update_password(account_id.clone(), password).await?;
+                let change = Change {
+                    target_id: account_id.0,
+                    actor: account_id,
+                    action: AuditAction::UpdatePassword,
+                    before: None,
+                    after: None,
+                };
+                Ok((updated, Some(change)))
+            })
+        })
+        .await
+    }
+
+    async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, Error> {
+        self.inner.get_account_information(account_id).await
+    }
+
+    async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, Error> {
+        self.inner.get_token_generation(account_id).await
+    }
+
+    async fn add_session(
+        &self,
+        session: NewSession,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<SessionId, Error> {
+        self.inner.add_session(session, token_hash, expires_on).await
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        token_hash: String,
+        new_token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(AccountId, SessionId), Error> {
+        self.inner.rotate_refresh_token(token_hash, new_token_hash, expires_on).await
+    }
+
+    async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, Error> {
+        self.inner.revoke_refresh_token(token_hash).await
+    }
+
+    async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        self.inner.touch_session(account_id, session_id).await
+    }
+
+    async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, Error> {
+        self.inner.get_sessions(account_id).await
+    }
+
+    async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        self.inner.revoke_session(account_id, session_id).await
+    }
+
+    async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, Error> {
+        self.inner.revoke_other_sessions(account_id, session_id).await
+    }
+
+    async fn add_verification_token(
+        &self,
+        account_id: AccountId,
+        email: String,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        self.inner.add_verification_token(account_id, email, token_hash, expires_on).await
+    }
+
+    async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, Error> {
+        self.inner.get_verification_sent_on(account_id).await
+    }
+
+    async fn verify_email(&self, token_hash: String) -> Result<AccountId, Error> {
+        self.audited(|tx| {
+            Box::pin(async move {
+                let account_id = tx.verify_email(token_hash).await?;
+                let after = tx.get_account_information(account_id.clone()).await?;
+                let change = Change {
+                    target_id: account_id.0,
+                    actor: account_id.clone(),
+                    action: AuditAction::VerifyEmail,
+                    before: None,
+                    after: json(&after),
+                };
+                Ok((account_id, Some(change)))
+            })
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: AuditStoreTrait + Send + Sync> AuditStoreTrait for AuditedStore<S> {
+    async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, Error> {
+        self.inner.add_audit_record(record).await
+    }
+
+    async fn get_audit_records(
+        &self,
+        filter: AuditFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<AuditRecord>, Error> {
+        self.inner.get_audit_records(filter, limit, offset).await
+    }
+}
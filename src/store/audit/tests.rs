@@ -0,0 +1,99 @@
+use super::*;
+use crate::store::fixtures::new_question;
+use crate::store::memory::MemoryStore;
+
+async fn records<S: AuditStoreTrait>(store: &S) -> Vec<AuditRecord> {
+    store
+        .get_audit_records(AuditFilter::default(), None, 0)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_question_changes_are_recorded() {
+    let store = AuditedStore::new(MemoryStore::new()).for_request("req-1".to_string());
+    let question = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let mut edited = question.clone();
+    edited.title = "Edited".to_string();
+    store.update_question(edited, question.id, AccountId(1)).await.unwrap();
+    store.delete_question(question.id, AccountId(1)).await.unwrap();
+    // The question is gone, so there is nothing to record.
+    store.delete_question(question.id, AccountId(1)).await.unwrap();
+
+    let records = records(&store).await;
+    let actions: Vec<_> = records.iter().map(|record| record.action).collect();
+    assert_eq!(
+        actions,
+        [AuditAction::DeleteQuestion, AuditAction::UpdateQuestion, AuditAction::AddQuestion]
+    );
+    assert!(records.iter().all(|record| record.actor == AccountId(1)
+        && record.target_id == question.id.0
+        && record.request_id.as_deref() == Some("req-1")));
+    assert_eq!(records[2].before, None);
+    assert_eq!(records[2].after.as_ref().unwrap()["title"], "First");
+    assert_eq!(records[1].before.as_ref().unwrap()["title"], "First");
+    assert_eq!(records[1].after.as_ref().unwrap()["title"], "Edited");
+    assert_eq!(records[0].before.as_ref().unwrap()["title"], "Edited");
+    assert_eq!(records[0].after, None);
+}
+
+#[tokio::test]
+async fn test_failed_changes_are_not_recorded() {
+    let inner = MemoryStore::new();
+    let store = AuditedStore::new(inner.clone());
+    let question = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let new_answer = NewAnswer {
+        content: "Answer".to_string(),
+        question_id: QuestionId(99),
+    };
+    assert!(store.add_answer(new_answer, AccountId(2)).await.is_err());
+
+    // Changes made inside a transaction that is rolled back leave no record.
+    let tx = store.begin().await.unwrap();
+    tx.delete_question(question.id, AccountId(1)).await.unwrap();
+    drop(tx);
+
+    let records = records(&inner).await;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].action, AuditAction::AddQuestion);
+    assert_eq!(records[0].request_id, None);
+}
+
+#[tokio::test]
+async fn test_account_changes_leave_out_passwords() {
+    let store = AuditedStore::new(MemoryStore::new());
+    let account = Account {
+        id: None,
+        email: "test@email.com".to_string(),
+        password: "hash".to_string(),
+        display_name: None,
+    };
+    store.add_account(account).await.unwrap();
+    let update = AccountUpdateRequest {
+        email: "new@email.com".to_string(),
+    };
+    store.update_account(AccountId(1), update).await.unwrap();
+    store
+        .update_password(AccountId(1), AccountUpdatePassword("new hash".to_string()))
+        .await
+        .unwrap();
+
+    let records = records(&store).await;
+    let actions: Vec<_> = records.iter().map(|record| record.action).collect();
+    assert_eq!(
+        actions,
+        [AuditAction::UpdatePassword, AuditAction::UpdateAccount, AuditAction::AddAccount]
+    );
+    assert_eq!(records[0].before, None);
+    assert_eq!(records[0].after, None);
+    assert_eq!(records[1].before.as_ref().unwrap()["email"], "test@email.com");
+    assert_eq!(records[1].after.as_ref().unwrap()["email"], "new@email.com");
+    assert_eq!(records[2].after, Some(serde_json::json!({ "email": "test@email.com", "id": 1, "emailVerified": false })));
+
+    let filter = AuditFilter {
+        action: Some(AuditAction::UpdateAccount),
+        ..AuditFilter::default()
+    };
+    let filtered = store.get_audit_records(filter, None, 0).await.unwrap();
+    assert_eq!(filtered, records[1..2]);
+}
@@ -0,0 +1,553 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use handle_errors::Error;
+
+use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
+use crate::routes::audit::store_trait::StoreTrait as AuditStoreTrait;
+use crate::routes::authentication::StoreTrait as AuthStoreTrait;
+use crate::routes::comment::store_trait::StoreTrait as CommentStoreTrait;
+use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
+use crate::routes::tag::store_trait::StoreTrait as TagStoreTrait;
+use crate::routes::transaction::Transactional;
+use crate::types::{
+    account::{
+        Account, AccountId, AccountResponse, AccountUpdatePassword, AccountUpdateRequest, ActiveSession, NewSession,
+        SessionId,
+    },
+    answer::{Answer, AnswerId, NewAnswer},
+    audit::{AuditFilter, AuditRecord, NewAuditRecord},
+    comment::{Comment, CommentId, CommentParent, NewComment},
+    filter::{AnswerSort, QuestionFilter},
+    pagination::{Cursor, Page},
+    question::{NewQuestion, Question, QuestionId},
+    revision::{AnswerRevision, QuestionRevision, RevisionId},
+    search::SearchResult,
+    tag::Tag,
+    vote::Vote,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How many lists a cache holds at most. When it is full, expired lists are
+/// dropped, and everything if none has expired.
+const MAX_ENTRIES: usize = 1024;
+
+/// A read-through cache in front of another store.
+///
+/// `CachedStore` keeps the lists of questions and of a question's answers it
+/// read from `inner` for `ttl`, so polling clients do not hit the database on
+/// every request. Every other call goes straight to `inner`.
+///
+/// Any call that can change a list, from adding a question to voting on an
+/// answer or changing the email an author goes by, empties the whole cache.
+/// Inside a transaction lists are read from `inner` and the cache is only
+/// emptied once the transaction commits, so it never holds uncommitted rows.
+/// A list read while a change was being made is not cached.
+///
+/// Clones share the cache. It lives in process memory, so several server
+/// processes each cache on their own and see each other's changes only once
+/// their lists expire.
+#[derive(Debug, Clone)]
+pub struct CachedStore<S> {
+    inner: S,
+    cache: Arc<Cache>,
+    /// Whether the open transaction of a handle returned by `begin` changed
+    /// anything. Shared by the handles that joined it.
+    transaction: Option<Arc<AtomicBool>>,
+}
+
+#[derive(Debug)]
+struct Cache {
+    ttl: Duration,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Goes up with every change, so a list read before it is not stored.
+    generation: u64,
+    lists: HashMap<Key, (Instant, List)>,
+}
+
+/// The call a cached list was read with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Questions(QuestionFilter, Option<i32>, i32),
+    QuestionsPage(QuestionFilter, Option<Cursor>, i32),
+    Answers(QuestionId, AnswerSort, Option<i32>, i32),
+    AnswersPage(QuestionId, Option<Cursor>, i32),
+}
+
+#[derive(Debug, Clone)]
+enum List {
+    Questions(Vec<Question>),
+    QuestionsPage(Page<Question>),
+    Answers(Vec<Answer>),
+    AnswersPage(Page<Answer>),
+}
+
+impl Cache {
+    /// A fresh list stored under `key`, and the generation to store a newly
+    /// read one with.
+    fn get(&self, key: &Key) -> (Option<List>, u64) {
+        let entries = self.entries.lock().unwrap();
+        let list = entries
+            .lists
+            .get(key)
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, list)| list.clone());
+        (list, entries.generation)
+    }
+
+    /// Stores `list` under `key` unless something changed since `generation`.
+    fn put(&self, key: Key, list: List, generation: u64) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation != generation {
+            return;
+        }
+        if entries.lists.len() >= MAX_ENTRIES {
+            let ttl = self.ttl;
+            entries.lists.retain(|_, (stored, _)| stored.elapsed() < ttl);
+            if entries.lists.len() >= MAX_ENTRIES {
+                entries.lists.clear();
+            }
+        }
+        entries.lists.insert(key, (Instant::now(), list));
+    }
+
+    fn invalidate(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.lists.clear();
+    }
+}
+
+impl<S> CachedStore<S> {
+    /// Wraps `inner`, keeping lists for `ttl`. A zero `ttl` caches nothing.
+    pub fn new(inner: S, ttl: Duration) -> Self {
+        CachedStore {
+            inner,
+            cache: Arc::new(Cache {
+                ttl,
+                entries: Mutex::new(Entries::default()),
+            }),
+            transaction: None,
+        }
+    }
+
+    /// Helper function to read a list through the cache. `wrap` and `unwrap`
+    /// convert between the list and its `List` variant.
+    async fn read_through<T, F>(
+        &self,
+        key: Key,
+        wrap: fn(T) -> List,
+        unwrap: fn(List) -> Option<T>,
+        read: F,
+    ) -> Result<T, Error>
+    where
+        T: Clone,
+        F: std::future::Future<Output = Result<T, Error>>,
+    {
+        if self.transaction.is_some() || self.cache.ttl.is_zero() {
+            return read.await;
+        }
+        let (list, generation) = self.cache.get(&key);
+        if let Some(list) = list.and_then(unwrap) {
+            return Ok(list);
+        }
+        let list = read.await?;
+        self.cache.put(key, wrap(list.clone()), generation);
+        Ok(list)
+    }
+
+    /// Helper function to empty the cache after a call that can change lists.
+    /// Inside a transaction that waits for the commit.
+    fn invalidating<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        match &self.transaction {
+            Some(changed) => changed.store(true, Ordering::SeqCst),
+            None => self.cache.invalidate(),
+        }
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: Transactional> Transactional for CachedStore<S> {
+    async fn begin(&self) -> Result<Self, Error> {
+        Ok(CachedStore {
+            inner: self.inner.begin().await?,
+            cache: self.cache.clone(),
+            transaction: Some(self.transaction.clone().unwrap_or_default()),
+        })
+    }
+
+    async fn commit(self) -> Result<(), Error> {
+        self.inner.commit().await?;
+        // A joined handle commits as a no-op, so this may run before the
+        // outermost commit; that one empties the cache again.
+        if self
+            .transaction
+            .is_some_and(|changed| changed.load(Ordering::SeqCst))
+        {
+            self.cache.invalidate();
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: QuestionStoreTrait + Send + Sync> QuestionStoreTrait for CachedStore<S> {
+    async fn get_questions(
+        &self,
+        filter: QuestionFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        let key = Key::Questions(filter.clone(), limit, offset);
+        self.read_through(
+            key,
+            List::Questions,
+            |list| match list {
+                List::Questions(questions) => Some(questions),
+                _ => None,
+            },
+            self.inner.get_questions(filter, limit, offset),
+        )
+        .await
+    }
+
+    async fn get_questions_page(
+        &self,
+        filter: QuestionFilter,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Question>, Error> {
+        let key = Key::QuestionsPage(filter.clone(), cursor, limit);
+        self.read_through(
+            key,
+            List::QuestionsPage,
+            |list| match list {
+                List::QuestionsPage(page) => Some(page),
+                _ => None,
+            },
+            self.inner.get_questions_page(filter, cursor, limit),
+        )
+        .await
+    }
+
+    async fn is_question_owner(&self, question_id: QuestionId, account_id: &AccountId) -> Result<bool, Error> {
+        self.inner.is_question_owner(question_id, account_id).await
+    }
+
+    async fn add_question(&self, new_question: NewQuestion, account_id: AccountId) -> Result<Question, Error> {
+        self.invalidating(self.inner.add_question(new_question, account_id).await)
+    }
+
+    async fn update_question(&self, question: Question, id: QuestionId, account_id: AccountId) -> Result<Question, Error> {
+        self.invalidating(self.inner.update_question(question, id, account_id).await)
+    }
+
+    async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, Error> {
+        self.invalidating(self.inner.delete_question(id, account_id).await)
+    }
+
+    async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, Error> {
+        self.invalidating(self.inner.restore_question(id, account_id).await)
+    }
+
+    async fn get_answers(
+        &self,
+        question_id: QuestionId,
+        sort: AnswerSort,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Answer>, Error> {
+        let key = Key::Answers(question_id, sort, limit, offset);
+        self.read_through(
+            key,
+            List::Answers,
+            |list| match list {
+                List::Answers(answers) => Some(answers),
+                _ => None,
+            },
+            self.inner.get_answers(question_id, sort, limit, offset),
+        )
+        .await
+    }
+
+    async fn get_answers_page(
+        &self,
+        question_id: QuestionId,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Answer>, Error> {
+        let key = Key::AnswersPage(question_id, cursor, limit);
+        self.read_through(
+            key,
+            List::AnswersPage,
+            |list| match list {
+                List::AnswersPage(page) => Some(page),
+                _ => None,
+            },
+            self.inner.get_answers_page(question_id, cursor, limit),
+        )
+        .await
+    }
+
+    async fn count_answers(&self, question_id: QuestionId) -> Result<i64, Error> {
+        self.inner.count_answers(question_id).await
+    }
+
+    async fn get_question(&self, id: QuestionId) -> Result<Question, Error> {
+        self.inner.get_question(id).await
+    }
+
+    async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, Error> {
+        self.inner.get_question_revisions(id).await
+    }
+
+    async fn get_question_revision(
+        &self,
+        id: QuestionId,
+        revision_id: RevisionId,
+    ) -> Result<QuestionRevision, Error> {
+        self.inner.get_question_revision(id, revision_id).await
+    }
+
+    async fn vote_question(
+        &self,
+        id: QuestionId,
+        account_id: AccountId,
+        vote: Option<Vote>,
+    ) -> Result<Question, Error> {
+        self.invalidating(self.inner.vote_question(id, account_id, vote).await)
+    }
+
+    async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, Error> {
+        self.invalidating(self.inner.accept_answer(id, answer_id).await)
+    }
+
+    async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, Error> {
+        self.invalidating(self.inner.unaccept_answer(id).await)
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<SearchResult>, Error> {
+        self.inner.search(query, limit, offset).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: AnswerStoreTrait + Send + Sync> AnswerStoreTrait for CachedStore<S> {
+    async fn add_answer(&self, new_answer: NewAnswer, account_id: AccountId) -> Result<Answer, Error> {
+        self.invalidating(self.inner.add_answer(new_answer, account_id).await)
+    }
+
+    async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, Error> {
+        self.inner.is_answer_owner(answer_id, account_id).await
+    }
+
+    async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        self.invalidating(self.inner.update_answer(answer, id, account_id).await)
+    }
+
+    async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+        self.invalidating(self.inner.delete_answer(id, account_id).await)
+    }
+
+    async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        self.invalidating(self.inner.restore_answer(id, account_id).await)
+    }
+
+    async fn get_answer(&self, id: i32) -> Result<Answer, Error> {
+        self.inner.get_answer(id).await
+    }
+
+    async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, Error> {
+        self.inner.get_answer_revisions(id).await
+    }
+
+    async fn vote_answer(&self, id: i32, account_id: AccountId, vote: Option<Vote>) -> Result<Answer, Error> {
+        self.invalidating(self.inner.vote_answer(id, account_id, vote).await)
+    }
+
+    async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, Error> {
+        self.inner.get_answer_revision(id, revision_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: TagStoreTrait + Send + Sync> TagStoreTrait for CachedStore<S> {
+    async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, Error> {
+        self.inner.get_tags(limit, offset).await
+    }
+
+    async fn get_tag_questions(
+        &self,
+        slug: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        self.inner.get_tag_questions(slug, limit, offset).await
+    }
+
+    // Questions take the canonical tag in place of the synonym.
+    async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, Error> {
+        self.invalidating(self.inner.add_tag_synonym(slug, synonym).await)
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: CommentStoreTrait + Send + Sync> CommentStoreTrait for CachedStore<S> {
+    async fn get_comments(
+        &self,
+        parent: CommentParent,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Comment>, Error> {
+        self.inner.get_comments(parent, limit, offset).await
+    }
+
+    async fn add_comment(
+        &self,
+        parent: CommentParent,
+        new_comment: NewComment,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        self.inner.add_comment(parent, new_comment, account_id).await
+    }
+
+    async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, Error> {
+        self.inner.is_comment_owner(comment_id, account_id).await
+    }
+
+    async fn update_comment(
+        &self,
+        comment: NewComment,
+        id: CommentId,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        self.inner.update_comment(comment, id, account_id).await
+    }
+
+    async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, Error> {
+        self.inner.delete_comment(id, account_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: AuthStoreTrait + Send + Sync> AuthStoreTrait for CachedStore<S> {
+    async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        self.inner.add_account(account).await
+    }
+
+    async fn get_account(&self, email: String) -> Result<Account, Error> {
+        self.inner.get_account(email).await
+    }
+
+    // Lists show the display name of their authors.
+    async fn update_account(
+        &self,
+        account_id: AccountId,
+        account: AccountUpdateRequest,
+    ) -> Result<AccountResponse, Error> {
+        self.invalidating(self.inner.update_account(account_id, account).await)
+    }
+
+    async fn update_password(
+        &self,
+        account_id: AccountId,
+        password: AccountUpdatePassword,
+    ) -> Result<bool, Error> {
+        self.inner.update_password(account_id, password).await
+    }
+
+    async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, Error> {
+        self.inner.get_account_information(account_id).await
+    }
+
+    async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, Error> {
+        self.inner.get_token_generation(account_id).await
+    }
+
+    async fn add_session(
+        &self,
+        session: NewSession,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<SessionId, Error> {
+        self.inner.add_session(session, token_hash, expires_on).await
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        token_hash: String,
+        new_token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(AccountId, SessionId), Error> {
+        self.inner.rotate_refresh_token(token_hash, new_token_hash, expires_on).await
+    }
+
+    async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, Error> {
+        self.inner.revoke_refresh_token(token_hash).await
+    }
+
+    async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        self.inner.touch_session(account_id, session_id).await
+    }
+
+    async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, Error> {
+        self.inner.get_sessions(account_id).await
+    }
+
+    async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        self.inner.revoke_session(account_id, session_id).await
+    }
+
+    async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, Error> {
+        self.inner.revoke_other_sessions(account_id, session_id).await
+    }
+
+    async fn add_verification_token(
+        &self,
+        account_id: AccountId,
+        email: String,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        self.inner.add_verification_token(account_id, email, token_hash, expires_on).await
+    }
+
+    async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, Error> {
+        self.inner.get_verification_sent_on(account_id).await
+    }
+
+    async fn verify_email(&self, token_hash: String) -> Result<AccountId, Error> {
+        self.inner.verify_email(token_hash).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: AuditStoreTrait + Send + Sync> AuditStoreTrait for CachedStore<S> {
+    async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, Error> {
+        self.inner.add_audit_record(record).await
+    }
+
+    async fn get_audit_records(
+        &self,
+        filter: AuditFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<AuditRecord>, Error> {
+        self.inner.get_audit_records(filter, limit, offset).await
+    }
+}
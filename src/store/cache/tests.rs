@@ -0,0 +1,91 @@
+use super::*;
+use crate::store::fixtures::new_question;
+use crate::store::memory::MemoryStore;
+
+async fn titles<S: QuestionStoreTrait>(store: &S) -> Vec<String> {
+    store
+        .get_questions(QuestionFilter::default(), None, 0)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|question| question.title)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_lists_are_cached_until_a_change() {
+    let inner = MemoryStore::new();
+    let store = CachedStore::new(inner.clone(), Duration::from_secs(60));
+    store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    assert_eq!(titles(&store).await, ["First"]);
+
+    // Changes the cache does not see are only picked up once the list expires.
+    inner.add_question(new_question("Second"), AccountId(1)).await.unwrap();
+    assert_eq!(titles(&store).await, ["First"]);
+    assert_eq!(titles(&store.clone()).await, ["First"]);
+
+    store.add_question(new_question("Third"), AccountId(1)).await.unwrap();
+    assert_eq!(titles(&store).await, ["First", "Second", "Third"]);
+}
+
+#[tokio::test]
+async fn test_lists_expire() {
+    let inner = MemoryStore::new();
+    let store = CachedStore::new(inner.clone(), Duration::from_millis(20));
+    assert!(titles(&store).await.is_empty());
+
+    inner.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    assert!(titles(&store).await.is_empty());
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(titles(&store).await, ["First"]);
+
+    let uncached = CachedStore::new(inner.clone(), Duration::ZERO);
+    assert_eq!(titles(&uncached).await, ["First"]);
+    inner.add_question(new_question("Second"), AccountId(1)).await.unwrap();
+    assert_eq!(titles(&uncached).await, ["First", "Second"]);
+}
+
+#[tokio::test]
+async fn test_answer_changes_invalidate() {
+    let inner = MemoryStore::new();
+    let store = CachedStore::new(inner.clone(), Duration::from_secs(60));
+    let question = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let new_answer = NewAnswer {
+        content: "Answer".to_string(),
+        question_id: question.id,
+    };
+    let answer = store.add_answer(new_answer, AccountId(2)).await.unwrap();
+
+    let answers = store.get_answers(question.id, AnswerSort::Score, None, 0).await.unwrap();
+    assert_eq!(answers[0].score, 0);
+    let page = store.get_answers_page(question.id, None, 10).await.unwrap();
+    assert_eq!(page.items, answers);
+
+    store.vote_answer(answer.id.0, AccountId(1), Some(Vote::Up)).await.unwrap();
+    let answers = store.get_answers(question.id, AnswerSort::Score, None, 0).await.unwrap();
+    assert_eq!(answers[0].score, 1);
+    let page = store.get_answers_page(question.id, None, 10).await.unwrap();
+    assert_eq!(page.items, answers);
+}
+
+#[tokio::test]
+async fn test_transactions_invalidate_on_commit() {
+    let inner = MemoryStore::new();
+    let store = CachedStore::new(inner.clone(), Duration::from_secs(60));
+    assert!(titles(&store).await.is_empty());
+
+    // Rolled back changes leave the cache alone.
+    let tx = store.begin().await.unwrap();
+    tx.add_question(new_question("Dropped"), AccountId(1)).await.unwrap();
+    assert_eq!(titles(&tx).await, ["Dropped"]);
+    drop(tx);
+    inner.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    assert!(titles(&store).await.is_empty());
+
+    let tx = store.begin().await.unwrap();
+    let joined = tx.begin().await.unwrap();
+    joined.add_question(new_question("Second"), AccountId(1)).await.unwrap();
+    joined.commit().await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(titles(&store).await, ["First", "Second"]);
+}
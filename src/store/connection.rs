@@ -0,0 +1,108 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use sqlx::{pool::PoolConnection, Database, Pool, Transaction};
+use tokio::sync::{Mutex, MutexGuard};
+
+/// The transaction a store handle returned by `Transactional::begin` runs in.
+///
+/// Clones of the handle share the transaction. Only the handle that started
+/// it may commit; handles that joined it commit as a no-op.
+pub(crate) struct TransactionHandle<DB: Database> {
+    transaction: Arc<Mutex<Option<Transaction<'static, DB>>>>,
+    owner: bool,
+}
+
+impl<DB: Database> Clone for TransactionHandle<DB> {
+    fn clone(&self) -> Self {
+        TransactionHandle {
+            transaction: self.transaction.clone(),
+            owner: self.owner,
+        }
+    }
+}
+
+impl<DB: Database> std::fmt::Debug for TransactionHandle<DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("TransactionHandle")
+            .field("owner", &self.owner)
+            .finish()
+    }
+}
+
+fn finished() -> sqlx::Error {
+    sqlx::Error::Protocol("transaction has already been committed".to_string())
+}
+
+impl<DB: Database> TransactionHandle<DB> {
+    /// Starts a new transaction on a connection taken from `pool`.
+    pub(crate) async fn begin(pool: &Pool<DB>) -> Result<Self, sqlx::Error> {
+        Ok(TransactionHandle {
+            transaction: Arc::new(Mutex::new(Some(pool.begin().await?))),
+            owner: true,
+        })
+    }
+
+    /// Returns a handle on the same transaction that cannot commit it.
+    pub(crate) fn join(&self) -> Self {
+        TransactionHandle {
+            transaction: self.transaction.clone(),
+            owner: false,
+        }
+    }
+
+    pub(crate) async fn commit(self) -> Result<(), sqlx::Error> {
+        if !self.owner {
+            return Ok(());
+        }
+        match self.transaction.lock().await.take() {
+            Some(transaction) => transaction.commit().await,
+            None => Err(finished()),
+        }
+    }
+}
+
+/// A connection borrowed for the duration of one store call: either a fresh
+/// one from the pool or the connection of the open transaction.
+pub(crate) enum Conn<'a, DB: Database> {
+    Pool(PoolConnection<DB>),
+    Transaction(MutexGuard<'a, Option<Transaction<'static, DB>>>),
+}
+
+impl<DB: Database> Deref for Conn<'_, DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Conn::Pool(connection) => connection,
+            // `acquire` never hands out a guard over a finished transaction.
+            Conn::Transaction(guard) => guard.as_ref().expect("open transaction"),
+        }
+    }
+}
+
+impl<DB: Database> DerefMut for Conn<'_, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Conn::Pool(connection) => connection,
+            Conn::Transaction(guard) => guard.as_mut().expect("open transaction"),
+        }
+    }
+}
+
+/// Borrows the connection a store call should run on.
+pub(crate) async fn acquire<'a, DB: Database>(
+    pool: &Pool<DB>,
+    transaction: Option<&'a TransactionHandle<DB>>,
+) -> Result<Conn<'a, DB>, sqlx::Error> {
+    match transaction {
+        Some(handle) => {
+            let guard = handle.transaction.lock().await;
+            if guard.is_none() {
+                return Err(finished());
+            }
+            Ok(Conn::Transaction(guard))
+        }
+        None => Ok(Conn::Pool(pool.acquire().await?)),
+    }
+}
@@ -0,0 +1,20 @@
+//! Values the tests of the stores build their data from.
+
+use crate::types::account::{AccountId, NewSession};
+use crate::types::question::NewQuestion;
+
+pub fn new_question(title: &str) -> NewQuestion {
+    NewQuestion {
+        title: title.to_string(),
+        content: "Test Content".to_string(),
+        tags: Some(vec!["test".to_string()]),
+    }
+}
+
+pub fn new_session(account_id: &AccountId) -> NewSession {
+    NewSession {
+        account_id: account_id.clone(),
+        user_agent: Some("test".to_string()),
+        ip: Some("127.0.0.1".to_string()),
+    }
+}
@@ -0,0 +1,1617 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::error::{DatabaseError, ErrorKind};
+use tokio::sync::{Mutex, OwnedRwLockWriteGuard, RwLock};
+
+use handle_errors::Error;
+
+use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
+use crate::routes::audit::store_trait::StoreTrait as AuditStoreTrait;
+use crate::routes::authentication::{StoreTrait as AuthStoreTrait, SESSION_TOUCH_SECONDS};
+use crate::routes::comment::store_trait::StoreTrait as CommentStoreTrait;
+use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
+use crate::routes::tag::store_trait::StoreTrait as TagStoreTrait;
+use crate::routes::transaction::Transactional;
+use crate::stackexchange::store_trait::StoreTrait as ImportStoreTrait;
+use crate::stackexchange::ImportedPost;
+use crate::types::{
+    account::{
+        Account, AccountId, AccountResponse, AccountUpdatePassword, AccountUpdateRequest, ActiveSession, Author,
+        NewSession, SessionId,
+    },
+    answer::{Answer, AnswerId, NewAnswer},
+    audit::{AuditFilter, AuditRecord, AuditRecordId, NewAuditRecord},
+    comment::{Comment, CommentId, CommentParent, NewComment},
+    filter::{AnswerSort, QuestionFilter, QuestionSort, TagMatch},
+    pagination::{Cursor, Page},
+    question::{NewQuestion, Question, QuestionId},
+    revision::{AnswerRevision, QuestionRevision, RevisionId},
+    search::{naive_match, query_terms, sort_results, SearchResult},
+    tag::{dedup_tags, normalize_tags, Tag},
+    vote::Vote,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// An in-memory storage backend.
+///
+/// `MemoryStore` implements the same store traits as the PostgreSQL `Store`,
+/// with the same ownership and pagination semantics, but keeps every row in
+/// process memory. Nothing is persisted, which makes it a good fit for local
+/// demos, front-end development and fast end-to-end tests.
+///
+/// Cloning a `MemoryStore` is cheap and every clone shares the same data.
+///
+/// A transaction holds the write lock from `begin` to `commit` and works on
+/// a copy of the data, so transactions are serialized and a dropped one
+/// leaves no trace.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    data: Arc<RwLock<Data>>,
+    transaction: Option<MemoryTransaction>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryTransaction {
+    state: Arc<Mutex<Option<TransactionState>>>,
+    owner: bool,
+}
+
+#[derive(Debug)]
+struct TransactionState {
+    guard: OwnedRwLockWriteGuard<Data>,
+    working: Data,
+}
+
+/// The tables held by a `MemoryStore`.
+#[derive(Debug, Clone, Default)]
+struct Data {
+    questions: BTreeMap<i32, QuestionRow>,
+    answers: BTreeMap<i32, AnswerRow>,
+    accounts: BTreeMap<i32, Account>,
+    question_revisions: BTreeMap<i32, QuestionRevision>,
+    answer_revisions: BTreeMap<i32, AnswerRevision>,
+    /// Canonical tag slugs. Which questions use a tag is read from the
+    /// questions' own tags.
+    tags: BTreeSet<String>,
+    /// Maps a synonym onto its canonical tag.
+    tag_synonyms: BTreeMap<String, String>,
+    /// Votes keyed by question id and account id.
+    question_votes: BTreeMap<(i32, i32), Vote>,
+    /// Votes keyed by answer id and account id.
+    answer_votes: BTreeMap<(i32, i32), Vote>,
+    comments: BTreeMap<i32, Comment>,
+    audit_log: BTreeMap<i32, AuditRecord>,
+    /// Token generations by account id; accounts without one are at 0.
+    token_generations: BTreeMap<i32, i32>,
+    /// Refresh tokens keyed by their hash.
+    refresh_tokens: BTreeMap<String, RefreshTokenRow>,
+    sessions: BTreeMap<i32, SessionRow>,
+    /// Ids of the accounts which verified their email.
+    verified_emails: BTreeSet<i32>,
+    /// Verification tokens keyed by their hash.
+    verification_tokens: BTreeMap<String, VerificationTokenRow>,
+    /// Posts imported from a dump, keyed by their id in the dump.
+    imported_posts: BTreeMap<i64, ImportedPost>,
+    question_seq: i32,
+    answer_seq: i32,
+    account_seq: i32,
+    question_revision_seq: i32,
+    answer_revision_seq: i32,
+    comment_seq: i32,
+    audit_seq: i32,
+    session_seq: i32,
+}
+
+/// A refresh token, which is valid while it is not revoked and its session
+/// is active.
+#[derive(Debug, Clone)]
+struct RefreshTokenRow {
+    session_id: i32,
+    revoked: bool,
+}
+
+/// A one-time token proving `account_id` owns `email`.
+#[derive(Debug, Clone)]
+struct VerificationTokenRow {
+    account_id: AccountId,
+    email: String,
+    created_on: DateTime<Utc>,
+    expires_on: DateTime<Utc>,
+    used: bool,
+}
+
+#[derive(Debug, Clone)]
+struct SessionRow {
+    session: ActiveSession,
+    account_id: AccountId,
+    expires_on: DateTime<Utc>,
+    revoked: bool,
+}
+
+impl SessionRow {
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        !self.revoked && self.expires_on > now
+    }
+}
+
+#[derive(Debug, Clone)]
+struct QuestionRow {
+    question: Question,
+    account_id: AccountId,
+    created_on: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+struct AnswerRow {
+    answer: Answer,
+    account_id: AccountId,
+    created_on: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+impl QuestionRow {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            created_on: self.created_on,
+            id: self.question.id.0,
+        }
+    }
+}
+
+impl AnswerRow {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            created_on: self.created_on,
+            id: self.answer.id.0,
+        }
+    }
+}
+
+/// Mirrors the unique constraint violation PostgreSQL reports for a
+/// duplicate key, like an account email, so callers see the same error in both backends.
+#[derive(Debug)]
+struct UniqueViolation(&'static str);
+
+impl std::fmt::Display for UniqueViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "duplicate key value violates unique constraint \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for UniqueViolation {}
+
+impl DatabaseError for UniqueViolation {
+    fn message(&self) -> &str {
+        "duplicate key value violates unique constraint"
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        Some(self.0)
+    }
+
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::UniqueViolation
+    }
+}
+
+fn not_found() -> Error {
+    Error::DatabaseQueryError(sqlx::Error::RowNotFound)
+}
+
+fn finished() -> Error {
+    Error::DatabaseQueryError(sqlx::Error::Protocol(
+        "transaction has already been committed".to_string(),
+    ))
+}
+
+fn next_id(seq: &mut i32) -> i32 {
+    *seq += 1;
+    *seq
+}
+
+/// Sets or, with `None`, removes the vote of `account_id` on post `id` and
+/// returns the new score of the post.
+fn cast_vote(
+    votes: &mut BTreeMap<(i32, i32), Vote>,
+    id: i32,
+    account_id: &AccountId,
+    vote: Option<Vote>,
+) -> i32 {
+    match vote {
+        Some(vote) => votes.insert((id, account_id.0), vote),
+        None => votes.remove(&(id, account_id.0)),
+    };
+    votes
+        .range((id, i32::MIN)..=(id, i32::MAX))
+        .map(|(_, vote)| vote.value())
+        .sum()
+}
+
+/// Applies `LIMIT`/`OFFSET` semantics to an ordered iterator.
+fn paginate<T>(rows: impl Iterator<Item = T>, limit: Option<i32>, offset: i32) -> Vec<T> {
+    let rows = rows.skip(offset.max(0) as usize);
+    match limit {
+        Some(limit) => rows.take(limit.max(0) as usize).collect(),
+        None => rows.collect(),
+    }
+}
+
+/// Applies keyset pagination over `(created_on, id)` to unordered rows,
+/// oldest first unless `newest_first`.
+fn keyset_page<T>(
+    rows: impl Iterator<Item = (T, Cursor)>,
+    cursor: Option<Cursor>,
+    limit: i32,
+    newest_first: bool,
+) -> Page<T> {
+    Page::from_rows(keyset_rows(rows, cursor, limit.max(0) + 1, newest_first), limit)
+}
+
+/// At most `count` of `rows` after `cursor`, in the order of a page.
+fn keyset_rows<T>(
+    rows: impl Iterator<Item = (T, Cursor)>,
+    cursor: Option<Cursor>,
+    count: i32,
+    newest_first: bool,
+) -> Vec<(T, Cursor)> {
+    let key = |cursor: &Cursor| (cursor.created_on, cursor.id);
+    let after = |row: &Cursor, cursor: &Cursor| {
+        if newest_first {
+            key(row) < key(cursor)
+        } else {
+            key(row) > key(cursor)
+        }
+    };
+    let mut rows: Vec<_> = rows
+        .filter(|(_, row)| cursor.is_none_or(|cursor| after(row, &cursor)))
+        .collect();
+    rows.sort_by_key(|(_, row)| key(row));
+    if newest_first {
+        rows.reverse();
+    }
+    rows.truncate(count.max(0) as usize);
+    rows
+}
+
+impl Data {
+    fn question_is_live(&self, id: QuestionId) -> bool {
+        self.questions
+            .get(&id.0)
+            .is_some_and(|row| row.deleted_at.is_none())
+    }
+
+    /// Normalizes the tags of a question and replaces synonyms with their
+    /// canonical tag.
+    fn canonical_tags(&self, tags: Option<Vec<String>>) -> Result<Option<Vec<String>>, Error> {
+        Ok(normalize_tags(tags)?.map(|tags| {
+            dedup_tags(
+                tags.into_iter()
+                    .map(|slug| self.tag_synonyms.get(&slug).cloned().unwrap_or(slug))
+                    .collect(),
+            )
+        }))
+    }
+
+    fn comment_parent_is_live(&self, parent: &CommentParent) -> bool {
+        match parent {
+            CommentParent::Question(id) => self.question_is_live(*id),
+            CommentParent::Answer(id) => self
+                .answers
+                .get(&id.0)
+                .is_some_and(|row| row.deleted_at.is_none()),
+        }
+    }
+
+    /// The name `account_id` goes by, like `AUTHOR_NAME` in the SQL stores.
+    fn display_name(&self, account_id: &AccountId) -> String {
+        self.accounts
+            .get(&account_id.0)
+            .map(|account| match &account.display_name {
+                Some(name) => name.clone(),
+                None => account.email.split('@').next().unwrap_or_default().to_string(),
+            })
+            .unwrap_or_default()
+    }
+
+    // Rows keep the author's id, the name is looked up as it may change.
+    fn question_with_author(&self, mut question: Question) -> Question {
+        question.author.display_name = self.display_name(&question.author.id);
+        question
+    }
+
+    fn answer_with_author(&self, mut answer: Answer) -> Answer {
+        answer.author.display_name = self.display_name(&answer.author.id);
+        answer
+    }
+
+    fn accepted_answer(&self, id: QuestionId) -> Option<i32> {
+        self.questions
+            .get(&id.0)
+            .and_then(|row| row.question.accepted_answer_id.as_ref())
+            .map(|answer_id| answer_id.0)
+    }
+
+    fn live_answers<'a>(&'a self, id: QuestionId) -> impl Iterator<Item = &'a AnswerRow> + 'a {
+        self.answers
+            .values()
+            .filter(move |row| row.answer.question_id == id && row.deleted_at.is_none())
+    }
+
+    /// The live questions matching `filter`, in its order.
+    fn filtered_questions(&self, filter: &QuestionFilter) -> Vec<&QuestionRow> {
+        let tags: Vec<&String> = filter
+            .tags
+            .iter()
+            .map(|slug| self.tag_synonyms.get(slug).unwrap_or(slug))
+            .collect();
+        let has_tag = |row: &QuestionRow, slug: &String| {
+            row.question.tags.iter().flatten().any(|tag| tag == slug)
+        };
+        let mut rows: Vec<&QuestionRow> = self
+            .questions
+            .values()
+            .filter(|row| {
+                row.deleted_at.is_none()
+                    && (tags.is_empty()
+                        || match filter.tag_match {
+                            TagMatch::Any => tags.iter().any(|slug| has_tag(row, slug)),
+                            TagMatch::All => tags.iter().all(|slug| has_tag(row, slug)),
+                        })
+                    && filter.author.as_ref().is_none_or(|author| &row.account_id == author)
+                    && filter.created_after.is_none_or(|after| row.created_on >= after)
+                    && filter.created_before.is_none_or(|before| row.created_on < before)
+                    && !(filter.unanswered && self.live_answers(row.question.id).next().is_some())
+            })
+            .collect();
+
+        let key = |row: &QuestionRow| (row.created_on, row.question.id.0);
+        match filter.sort {
+            QuestionSort::Oldest => rows.sort_by_key(|row| key(row)),
+            QuestionSort::Newest => rows.sort_by_key(|row| Reverse(key(row))),
+            QuestionSort::MostAnswers => rows.sort_by_key(|row| {
+                Reverse((self.live_answers(row.question.id).count(), key(row)))
+            }),
+            QuestionSort::RecentActivity => rows.sort_by_key(|row| {
+                let last_answer = self
+                    .live_answers(row.question.id)
+                    .map(|answer| answer.created_on)
+                    .max();
+                Reverse((last_answer.unwrap_or(row.created_on).max(row.created_on), row.question.id.0))
+            }),
+        }
+        rows
+    }
+
+    fn register_tags(&mut self, tags: &Option<Vec<String>>) {
+        self.tags.extend(tags.iter().flatten().cloned());
+    }
+
+    fn live_questions_with_tag<'a>(&'a self, slug: &'a str) -> impl Iterator<Item = &'a QuestionRow> + 'a {
+        self.questions.values().filter(move |row| {
+            row.deleted_at.is_none()
+                && row.question.tags.iter().flatten().any(|tag| tag == slug)
+        })
+    }
+
+    fn tag(&self, slug: &str) -> Tag {
+        Tag {
+            slug: slug.to_string(),
+            questions: self.live_questions_with_tag(slug).count() as i64,
+            synonyms: self
+                .tag_synonyms
+                .iter()
+                .filter(|(_, tag)| *tag == slug)
+                .map(|(synonym, _)| synonym.clone())
+                .collect(),
+        }
+    }
+
+    fn email_taken(&self, email: &str, except: Option<i32>) -> bool {
+        self.accounts
+            .iter()
+            .any(|(id, account)| account.email == email && Some(*id) != except)
+    }
+}
+
+impl MemoryStore {
+    /// Creates an empty `MemoryStore`.
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    /// Runs `f` against the data, or the working copy of the open transaction.
+    async fn read<R>(&self, f: impl FnOnce(&Data) -> Result<R, Error>) -> Result<R, Error> {
+        match &self.transaction {
+            Some(transaction) => {
+                let state = transaction.state.lock().await;
+                f(&state.as_ref().ok_or_else(finished)?.working)
+            }
+            None => f(&*self.data.read().await),
+        }
+    }
+
+    /// Runs `f` against the data, or the working copy of the open transaction.
+    async fn write<R>(&self, f: impl FnOnce(&mut Data) -> Result<R, Error>) -> Result<R, Error> {
+        match &self.transaction {
+            Some(transaction) => {
+                let mut state = transaction.state.lock().await;
+                f(&mut state.as_mut().ok_or_else(finished)?.working)
+            }
+            None => f(&mut *self.data.write().await),
+        }
+    }
+
+    /// Permanently removes the questions and answers deleted before
+    /// `deleted_before`. Returns the number of rows removed.
+    pub async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, Error> {
+        self.write(|data| {
+            let expired = |deleted_at: Option<DateTime<Utc>>| {
+                deleted_at.is_some_and(|deleted_at| deleted_at < deleted_before)
+            };
+            let before = data.questions.len() + data.answers.len();
+            data.questions.retain(|_, row| !expired(row.deleted_at));
+            // Mirrors the ON DELETE CASCADE of `answers.corresponding_question`.
+            let questions = &data.questions;
+            data.answers.retain(|_, row| {
+                !expired(row.deleted_at) && questions.contains_key(&row.answer.question_id.0)
+            });
+            let purged = before - data.questions.len() - data.answers.len();
+            // The revision tables cascade as well.
+            let (questions, answers) = (&data.questions, &data.answers);
+            data.question_revisions
+                .retain(|_, revision| questions.contains_key(&revision.question_id.0));
+            data.answer_revisions
+                .retain(|_, revision| answers.contains_key(&revision.answer_id.0));
+            data.question_votes.retain(|(id, _), _| questions.contains_key(id));
+            data.answer_votes.retain(|(id, _), _| answers.contains_key(id));
+            data.comments.retain(|_, comment| {
+                comment.question_id.is_none_or(|id| questions.contains_key(&id.0))
+                    && comment.answer_id.as_ref().is_none_or(|id| answers.contains_key(&id.0))
+            });
+            // Mirrors the ON DELETE SET NULL of `questions.accepted_answer_id`.
+            for row in data.questions.values_mut() {
+                if row
+                    .question
+                    .accepted_answer_id
+                    .as_ref()
+                    .is_some_and(|answer_id| !data.answers.contains_key(&answer_id.0))
+                {
+                    row.question.accepted_answer_id = None;
+                }
+            }
+            let (questions, answers) = (&data.questions, &data.answers);
+            data.imported_posts.retain(|_, post| {
+                questions.contains_key(&post.question_id.0)
+                    && post.answer_id.as_ref().is_none_or(|id| answers.contains_key(&id.0))
+            });
+            Ok(purged as u64)
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl Transactional for MemoryStore {
+    async fn begin(&self) -> Result<Self, Error> {
+        let transaction = match &self.transaction {
+            Some(transaction) => MemoryTransaction {
+                state: transaction.state.clone(),
+                owner: false,
+            },
+            None => {
+                let guard = self.data.clone().write_owned().await;
+                let working = guard.clone();
+                MemoryTransaction {
+                    state: Arc::new(Mutex::new(Some(TransactionState { guard, working }))),
+                    owner: true,
+                }
+            }
+        };
+
+        Ok(MemoryStore {
+            data: self.data.clone(),
+            transaction: Some(transaction),
+        })
+    }
+
+    async fn commit(self) -> Result<(), Error> {
+        match self.transaction {
+            Some(transaction) if transaction.owner => {
+                let TransactionState { mut guard, working } =
+                    transaction.state.lock().await.take().ok_or_else(finished)?;
+                *guard = working;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl QuestionStoreTrait for MemoryStore {
+    async fn get_questions(
+        &self,
+        filter: QuestionFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        self.read(|data| {
+            Ok(paginate(
+                data.filtered_questions(&filter)
+                    .into_iter()
+                    .map(|row| data.question_with_author(row.question.clone())),
+                limit,
+                offset,
+            ))
+        })
+        .await
+    }
+
+    async fn get_questions_page(
+        &self,
+        filter: QuestionFilter,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Question>, Error> {
+        self.read(|data| {
+            Ok(keyset_page(
+                data.filtered_questions(&filter)
+                    .into_iter()
+                    .map(|row| (data.question_with_author(row.question.clone()), row.cursor())),
+                cursor,
+                limit,
+                filter.sort == QuestionSort::Newest,
+            ))
+        })
+        .await
+    }
+
+    async fn is_question_owner(
+        &self,
+        question_id: QuestionId,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        self.read(|data| {
+            Ok(data
+                .questions
+                .get(&question_id.0)
+                .is_some_and(|row| &row.account_id == account_id && row.deleted_at.is_none()))
+        })
+        .await
+    }
+
+    async fn add_question(
+        &self,
+        new_question: NewQuestion,
+        account_id: AccountId,
+    ) -> Result<Question, Error> {
+        self.write(|data| {
+            let tags = data.canonical_tags(new_question.tags)?;
+            data.register_tags(&tags);
+            let id = next_id(&mut data.question_seq);
+            let created_on = Utc::now();
+            let question = Question {
+                id: QuestionId(id),
+                title: new_question.title,
+                content: new_question.content,
+                tags,
+                score: 0,
+                accepted_answer_id: None,
+                author: Author {
+                    id: account_id.clone(),
+                    display_name: String::new(),
+                },
+                created_on,
+                updated_on: None,
+                version: 1,
+            };
+            data.questions.insert(
+                id,
+                QuestionRow {
+                    question: question.clone(),
+                    account_id,
+                    created_on,
+                    deleted_at: None,
+                },
+            );
+            Ok(data.question_with_author(question))
+        })
+        .await
+    }
+
+    async fn update_question(
+        &self,
+        question: Question,
+        id: QuestionId,
+        account_id: AccountId,
+    ) -> Result<Question, Error> {
+        self.write(|data| {
+            let tags = data.canonical_tags(question.tags)?;
+            let row = match data.questions.get_mut(&id.0) {
+                Some(row) if row.account_id == account_id && row.deleted_at.is_none() => row,
+                _ => return Err(not_found()),
+            };
+            let revision_id = next_id(&mut data.question_revision_seq);
+            row.question.updated_on = Some(Utc::now());
+            row.question.version += 1;
+            let revision = QuestionRevision {
+                id: RevisionId(revision_id),
+                question_id: id,
+                title: std::mem::replace(&mut row.question.title, question.title),
+                content: std::mem::replace(&mut row.question.content, question.content),
+                tags: std::mem::replace(&mut row.question.tags, tags),
+                editor: account_id,
+                created_on: Utc::now(),
+            };
+            let question = row.question.clone();
+            data.question_revisions.insert(revision_id, revision);
+            data.register_tags(&question.tags);
+            Ok(data.question_with_author(question))
+        })
+        .await
+    }
+
+    async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, Error> {
+        // The question's answers are deleted with the same timestamp, which
+        // is how `restore_question` finds them again.
+        let deleted_at = Utc::now();
+        self.write(|data| {
+            match data.questions.get_mut(&id.0) {
+                Some(row) if row.account_id == account_id && row.deleted_at.is_none() => {
+                    row.deleted_at = Some(deleted_at);
+                }
+                _ => return Ok(true),
+            }
+            for row in data.answers.values_mut() {
+                if row.answer.question_id == id && row.deleted_at.is_none() {
+                    row.deleted_at = Some(deleted_at);
+                }
+            }
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, Error> {
+        self.write(|data| {
+            let row = match data.questions.get_mut(&id.0) {
+                Some(row) if row.account_id == account_id && row.deleted_at.is_some() => row,
+                _ => return Err(not_found()),
+            };
+            let deleted_at = row.deleted_at.take();
+            let question = row.question.clone();
+            for row in data.answers.values_mut() {
+                if row.answer.question_id == id && row.deleted_at == deleted_at {
+                    row.deleted_at = None;
+                }
+            }
+            Ok(data.question_with_author(question))
+        })
+        .await
+    }
+
+    async fn get_answers(
+        &self,
+        question_id: QuestionId,
+        sort: AnswerSort,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Answer>, Error> {
+        self.read(|data| {
+            let mut rows: Vec<&AnswerRow> = data.live_answers(question_id).collect();
+            match sort {
+                AnswerSort::Oldest => rows.sort_by_key(|row| (row.created_on, row.answer.id.0)),
+                AnswerSort::Score => rows.sort_by_key(|row| {
+                    (Reverse(row.answer.score), row.created_on, row.answer.id.0)
+                }),
+            }
+            // The accepted answer is pinned first whatever the order.
+            let accepted = data.accepted_answer(question_id);
+            rows.sort_by_key(|row| Some(row.answer.id.0) != accepted);
+            Ok(paginate(
+                rows.into_iter().map(|row| data.answer_with_author(row.answer.clone())),
+                limit,
+                offset,
+            ))
+        })
+        .await
+    }
+
+    async fn get_answers_page(
+        &self,
+        question_id: QuestionId,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Answer>, Error> {
+        self.read(|data| {
+            // The accepted answer is left out of the walk and put on top of
+            // the first page instead.
+            let accepted = data.accepted_answer(question_id);
+            let pinned = accepted
+                .and_then(|id| data.answers.get(&id))
+                .filter(|row| cursor.is_none() && row.deleted_at.is_none())
+                .map(|row| data.answer_with_author(row.answer.clone()));
+            let rows = keyset_rows(
+                data.live_answers(question_id)
+                    .filter(|row| Some(row.answer.id.0) != accepted)
+                    .map(|row| (data.answer_with_author(row.answer.clone()), row.cursor())),
+                cursor,
+                limit.max(0) + i32::from(pinned.is_none()),
+                false,
+            );
+            Ok(match pinned {
+                Some(answer) => Page::pinned(answer, rows, limit),
+                None => Page::from_rows(rows, limit),
+            })
+        })
+        .await
+    }
+
+    async fn get_question(&self, id: QuestionId) -> Result<Question, Error> {
+        self.read(|data| match data.questions.get(&id.0) {
+            Some(row) if row.deleted_at.is_none() => Ok(data.question_with_author(row.question.clone())),
+            _ => Err(not_found()),
+        })
+        .await
+    }
+
+    async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, Error> {
+        self.read(|data| {
+            Ok(data
+                .question_revisions
+                .values()
+                .filter(|revision| revision.question_id == id)
+                .cloned()
+                .collect())
+        })
+        .await
+    }
+
+    async fn get_question_revision(
+        &self,
+        id: QuestionId,
+        revision_id: RevisionId,
+    ) -> Result<QuestionRevision, Error> {
+        self.read(|data| match data.question_revisions.get(&revision_id.0) {
+            Some(revision) if revision.question_id == id => Ok(revision.clone()),
+            _ => Err(not_found()),
+        })
+        .await
+    }
+
+    async fn count_answers(&self, question_id: QuestionId) -> Result<i64, Error> {
+        self.read(|data| {
+            Ok(data
+                .answers
+                .values()
+                .filter(|row| row.answer.question_id == question_id && row.deleted_at.is_none())
+                .count() as i64)
+        })
+        .await
+    }
+
+    async fn vote_question(
+        &self,
+        id: QuestionId,
+        account_id: AccountId,
+        vote: Option<Vote>,
+    ) -> Result<Question, Error> {
+        self.write(|data| {
+            let row = match data.questions.get_mut(&id.0) {
+                Some(row) if row.deleted_at.is_none() => row,
+                _ => return Err(not_found()),
+            };
+            row.question.score = cast_vote(&mut data.question_votes, id.0, &account_id, vote);
+            let question = row.question.clone();
+            Ok(data.question_with_author(question))
+        })
+        .await
+    }
+
+    async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, Error> {
+        self.write(|data| {
+            // Only a live answer to the question itself can be accepted.
+            if !data.live_answers(id).any(|row| row.answer.id == answer_id) {
+                return Err(not_found());
+            }
+            let question = match data.questions.get_mut(&id.0) {
+                Some(row) if row.deleted_at.is_none() => {
+                    row.question.accepted_answer_id = Some(answer_id);
+                    row.question.clone()
+                }
+                _ => return Err(not_found()),
+            };
+            Ok(data.question_with_author(question))
+        })
+        .await
+    }
+
+    async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, Error> {
+        self.write(|data| {
+            let question = match data.questions.get_mut(&id.0) {
+                Some(row) if row.deleted_at.is_none() => {
+                    row.question.accepted_answer_id = None;
+                    row.question.clone()
+                }
+                _ => return Err(not_found()),
+            };
+            Ok(data.question_with_author(question))
+        })
+        .await
+    }
+
+    async fn search(&self, query: String, limit: Option<i32>, offset: i32) -> Result<Vec<SearchResult>, Error> {
+        let terms = query_terms(&query);
+        self.read(|data| {
+            let questions = data.questions.values().filter(|row| row.deleted_at.is_none()).filter_map(|row| {
+                let question = &row.question;
+                let text = format!("{} {}", question.title, question.content);
+                naive_match(&terms, &text).map(|(rank, snippet)| SearchResult {
+                    question_id: question.id,
+                    answer_id: None,
+                    title: question.title.clone(),
+                    snippet,
+                    rank,
+                })
+            });
+            let answers = data.answers.values().filter(|row| row.deleted_at.is_none()).filter_map(|row| {
+                let answer = &row.answer;
+                let question = data
+                    .questions
+                    .get(&answer.question_id.0)
+                    .filter(|question| question.deleted_at.is_none())?;
+                naive_match(&terms, &answer.content).map(|(rank, snippet)| SearchResult {
+                    question_id: answer.question_id,
+                    answer_id: Some(answer.id.clone()),
+                    title: question.question.title.clone(),
+                    snippet,
+                    rank,
+                })
+            });
+            let mut results: Vec<_> = questions.chain(answers).collect();
+            sort_results(&mut results);
+            Ok(paginate(results.into_iter(), limit, offset))
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl TagStoreTrait for MemoryStore {
+    async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, Error> {
+        self.read(|data| {
+            let mut tags: Vec<Tag> = data.tags.iter().map(|slug| data.tag(slug)).collect();
+            tags.sort_by(|a, b| b.questions.cmp(&a.questions).then_with(|| a.slug.cmp(&b.slug)));
+            Ok(paginate(tags.into_iter(), limit, offset))
+        })
+        .await
+    }
+
+    async fn get_tag_questions(
+        &self,
+        slug: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        self.read(|data| {
+            let slug = data.tag_synonyms.get(&slug).cloned().unwrap_or(slug);
+            Ok(paginate(
+                data.live_questions_with_tag(&slug)
+                    .map(|row| data.question_with_author(row.question.clone())),
+                limit,
+                offset,
+            ))
+        })
+        .await
+    }
+
+    async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, Error> {
+        self.write(|data| {
+            let slug = data.tag_synonyms.get(&slug).cloned().unwrap_or(slug);
+            if synonym == slug {
+                return Err(Error::InvalidTag(synonym));
+            }
+            // Deleted questions still hold their tags until they are purged.
+            let in_use = data
+                .questions
+                .values()
+                .any(|row| row.question.tags.iter().flatten().any(|tag| *tag == synonym));
+            if in_use {
+                return Err(Error::TagInUse(synonym));
+            }
+            // An unused tag turns into the synonym, and its own synonyms
+            // move over to the canonical tag.
+            data.tags.remove(&synonym);
+            for tag in data.tag_synonyms.values_mut() {
+                if *tag == synonym {
+                    *tag = slug.clone();
+                }
+            }
+            data.tag_synonyms.insert(synonym, slug.clone());
+            data.tags.insert(slug.clone());
+            Ok(data.tag(&slug))
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl AnswerStoreTrait for MemoryStore {
+    async fn add_answer(&self, new_answer: NewAnswer, account_id: AccountId) -> Result<Answer, Error> {
+        self.write(|data| {
+            // `answers.corresponding_question` references a question that
+            // has not been deleted.
+            if !data.question_is_live(new_answer.question_id) {
+                return Err(not_found());
+            }
+            let id = next_id(&mut data.answer_seq);
+            let created_on = Utc::now();
+            let answer = Answer {
+                id: AnswerId(id),
+                content: new_answer.content,
+                question_id: new_answer.question_id,
+                score: 0,
+                author: Author {
+                    id: account_id.clone(),
+                    display_name: String::new(),
+                },
+                created_on,
+                updated_on: None,
+                version: 1,
+            };
+            data.answers.insert(
+                id,
+                AnswerRow {
+                    answer: answer.clone(),
+                    account_id,
+                    created_on,
+                    deleted_at: None,
+                },
+            );
+            Ok(data.answer_with_author(answer))
+        })
+        .await
+    }
+
+    async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, Error> {
+        self.read(|data| {
+            Ok(data
+                .answers
+                .get(&answer_id)
+                .is_some_and(|row| &row.account_id == account_id && row.deleted_at.is_none()))
+        })
+        .await
+    }
+
+    async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        self.write(|data| {
+            if !data.questions.contains_key(&answer.question_id.0) {
+                return Err(not_found());
+            }
+            let answer = match data.answers.get_mut(&id) {
+                Some(row) if row.account_id == account_id && row.deleted_at.is_none() => {
+                    let revision_id = next_id(&mut data.answer_revision_seq);
+                    data.answer_revisions.insert(
+                        revision_id,
+                        AnswerRevision {
+                            id: RevisionId(revision_id),
+                            answer_id: AnswerId(id),
+                            content: std::mem::replace(&mut row.answer.content, answer.content),
+                            editor: account_id,
+                            created_on: Utc::now(),
+                        },
+                    );
+                    row.answer.question_id = answer.question_id;
+                    row.answer.updated_on = Some(Utc::now());
+                    row.answer.version += 1;
+                    row.answer.clone()
+                }
+                _ => return Err(not_found()),
+            };
+            Ok(data.answer_with_author(answer))
+        })
+        .await
+    }
+
+    async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+        self.write(|data| {
+            if let Some(row) = data.answers.get_mut(&id) {
+                if row.account_id == account_id && row.deleted_at.is_none() {
+                    row.deleted_at = Some(Utc::now());
+                }
+            }
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn get_answer(&self, id: i32) -> Result<Answer, Error> {
+        self.read(|data| match data.answers.get(&id) {
+            Some(row) if row.deleted_at.is_none() => Ok(data.answer_with_author(row.answer.clone())),
+            _ => Err(not_found()),
+        })
+        .await
+    }
+
+    async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, Error> {
+        self.read(|data| {
+            Ok(data
+                .answer_revisions
+                .values()
+                .filter(|revision| revision.answer_id.0 == id)
+                .cloned()
+                .collect())
+        })
+        .await
+    }
+
+    async fn vote_answer(&self, id: i32, account_id: AccountId, vote: Option<Vote>) -> Result<Answer, Error> {
+        self.write(|data| {
+            let row = match data.answers.get_mut(&id) {
+                Some(row) if row.deleted_at.is_none() => row,
+                _ => return Err(not_found()),
+            };
+            row.answer.score = cast_vote(&mut data.answer_votes, id, &account_id, vote);
+            let answer = row.answer.clone();
+            Ok(data.answer_with_author(answer))
+        })
+        .await
+    }
+
+    async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, Error> {
+        self.read(|data| match data.answer_revisions.get(&revision_id.0) {
+            Some(revision) if revision.answer_id.0 == id => Ok(revision.clone()),
+            _ => Err(not_found()),
+        })
+        .await
+    }
+
+    async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        self.write(|data| {
+            let question_is_live = data
+                .answers
+                .get(&id)
+                .is_some_and(|row| data.question_is_live(row.answer.question_id));
+            let answer = match data.answers.get_mut(&id) {
+                // An answer of a deleted question comes back with the question.
+                Some(row)
+                    if row.account_id == account_id
+                        && row.deleted_at.is_some()
+                        && question_is_live =>
+                {
+                    row.deleted_at = None;
+                    row.answer.clone()
+                }
+                _ => return Err(not_found()),
+            };
+            Ok(data.answer_with_author(answer))
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl CommentStoreTrait for MemoryStore {
+    async fn get_comments(
+        &self,
+        parent: CommentParent,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Comment>, Error> {
+        self.read(|data| {
+            // Comments go away with their post while it is deleted.
+            if !data.comment_parent_is_live(&parent) {
+                return Ok(Vec::new());
+            }
+            let mut comments: Vec<&Comment> = data
+                .comments
+                .values()
+                .filter(|comment| match &parent {
+                    CommentParent::Question(id) => comment.question_id == Some(*id),
+                    CommentParent::Answer(id) => comment.answer_id.as_ref() == Some(id),
+                })
+                .collect();
+            comments.sort_by_key(|comment| (comment.created_on, comment.id.0));
+            Ok(paginate(comments.into_iter().cloned(), limit, offset))
+        })
+        .await
+    }
+
+    async fn add_comment(
+        &self,
+        parent: CommentParent,
+        new_comment: NewComment,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        self.write(|data| {
+            if !data.comment_parent_is_live(&parent) {
+                return Err(not_found());
+            }
+            let id = next_id(&mut data.comment_seq);
+            let (question_id, answer_id) = match parent {
+                CommentParent::Question(id) => (Some(id), None),
+                CommentParent::Answer(id) => (None, Some(id)),
+            };
+            let comment = Comment {
+                id: CommentId(id),
+                content: new_comment.content,
+                question_id,
+                answer_id,
+                account_id,
+                created_on: Utc::now(),
+            };
+            data.comments.insert(id, comment.clone());
+            Ok(comment)
+        })
+        .await
+    }
+
+    async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, Error> {
+        self.read(|data| {
+            Ok(data
+                .comments
+                .get(&comment_id.0)
+                .is_some_and(|comment| &comment.account_id == account_id))
+        })
+        .await
+    }
+
+    async fn update_comment(
+        &self,
+        comment: NewComment,
+        id: CommentId,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        self.write(|data| match data.comments.get_mut(&id.0) {
+            Some(row) if row.account_id == account_id => {
+                row.content = comment.content;
+                Ok(row.clone())
+            }
+            _ => Err(not_found()),
+        })
+        .await
+    }
+
+    async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, Error> {
+        self.write(|data| {
+            if data
+                .comments
+                .get(&id.0)
+                .is_some_and(|comment| comment.account_id == account_id)
+            {
+                data.comments.remove(&id.0);
+            }
+            Ok(true)
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl AuthStoreTrait for MemoryStore {
+    async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        self.write(|data| {
+            if data.email_taken(&account.email, None) {
+                return Err(Error::DatabaseQueryError(sqlx::Error::Database(Box::new(
+                    UniqueViolation("accounts_pkey"),
+                ))));
+            }
+            let id = next_id(&mut data.account_seq);
+            data.accounts.insert(
+                id,
+                Account {
+                    id: Some(AccountId(id)),
+                    email: account.email,
+                    password: account.password,
+                    display_name: account.display_name,
+                },
+            );
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn get_account(&self, email: String) -> Result<Account, Error> {
+        self.read(|data| {
+            data.accounts
+                .values()
+                .find(|account| account.email == email)
+                .cloned()
+                .ok_or_else(not_found)
+        })
+        .await
+    }
+
+    async fn update_account(
+        &self,
+        account_id: AccountId,
+        account: AccountUpdateRequest,
+    ) -> Result<AccountResponse, Error> {
+        self.write(|data| {
+            if data.email_taken(&account.email, Some(account_id.0)) {
+                return Err(Error::DatabaseQueryError(sqlx::Error::Database(Box::new(
+                    UniqueViolation("accounts_pkey"),
+                ))));
+            }
+            let row = data.accounts.get_mut(&account_id.0).ok_or_else(not_found)?;
+            // A new email has to be verified again.
+            if row.email != account.email {
+                data.verified_emails.remove(&account_id.0);
+            }
+            row.email = account.email;
+            Ok(AccountResponse {
+                email: row.email.clone(),
+                email_verified: data.verified_emails.contains(&account_id.0),
+                id: account_id,
+            })
+        })
+        .await
+    }
+
+    async fn update_password(
+        &self,
+        account_id: AccountId,
+        password: AccountUpdatePassword,
+    ) -> Result<bool, Error> {
+        self.write(|data| {
+            if let Some(row) = data.accounts.get_mut(&account_id.0) {
+                row.password = password.0;
+                // A new password logs the account out everywhere.
+                *data.token_generations.entry(account_id.0).or_default() += 1;
+                for session in data.sessions.values_mut() {
+                    if session.account_id == account_id {
+                        session.revoked = true;
+                    }
+                }
+            }
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, Error> {
+        self.read(|data| {
+            data.accounts
+                .get(&account_id.0)
+                .map(|account| AccountResponse {
+                    email: account.email.clone(),
+                    email_verified: data.verified_emails.contains(&account_id.0),
+                    id: account_id,
+                })
+                .ok_or_else(not_found)
+        })
+        .await
+    }
+
+    async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, Error> {
+        self.read(|data| {
+            if !data.accounts.contains_key(&account_id.0) {
+                return Err(not_found());
+            }
+            Ok(data.token_generations.get(&account_id.0).copied().unwrap_or(0))
+        })
+        .await
+    }
+
+    async fn add_session(
+        &self,
+        session: NewSession,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<SessionId, Error> {
+        self.write(|data| {
+            let id = next_id(&mut data.session_seq);
+            let now = Utc::now();
+            data.sessions.insert(
+                id,
+                SessionRow {
+                    session: ActiveSession {
+                        id: SessionId(id),
+                        created_on: now,
+                        last_used_on: now,
+                        user_agent: session.user_agent,
+                        ip: session.ip,
+                        current: false,
+                    },
+                    account_id: session.account_id,
+                    expires_on,
+                    revoked: false,
+                },
+            );
+            data.refresh_tokens.insert(
+                token_hash,
+                RefreshTokenRow {
+                    session_id: id,
+                    revoked: false,
+                },
+            );
+            Ok(SessionId(id))
+        })
+        .await
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        token_hash: String,
+        new_token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(AccountId, SessionId), Error> {
+        self.write(|data| {
+            let now = Utc::now();
+            let token = data
+                .refresh_tokens
+                .get_mut(&token_hash)
+                .filter(|token| !token.revoked)
+                .ok_or(Error::InvalidRefreshToken)?;
+            let session = data
+                .sessions
+                .get_mut(&token.session_id)
+                .filter(|session| session.is_active(now))
+                .ok_or(Error::InvalidRefreshToken)?;
+            token.revoked = true;
+            session.session.last_used_on = now;
+            session.expires_on = expires_on;
+            let session_id = session.session.id.clone();
+            let account_id = session.account_id.clone();
+            data.refresh_tokens.insert(
+                new_token_hash,
+                RefreshTokenRow {
+                    session_id: session_id.0,
+                    revoked: false,
+                },
+            );
+            Ok((account_id, session_id))
+        })
+        .await
+    }
+
+    async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, Error> {
+        self.write(|data| {
+            let now = Utc::now();
+            let session = data
+                .refresh_tokens
+                .get(&token_hash)
+                .filter(|token| !token.revoked)
+                .and_then(|token| data.sessions.get_mut(&token.session_id))
+                .filter(|session| session.is_active(now));
+            Ok(match session {
+                Some(session) => {
+                    session.revoked = true;
+                    true
+                }
+                None => false,
+            })
+        })
+        .await
+    }
+
+    async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        self.write(|data| {
+            let now = Utc::now();
+            Ok(match data.sessions.get_mut(&session_id.0) {
+                Some(session) if session.account_id == account_id && session.is_active(now) => {
+                    if now - session.session.last_used_on >= Duration::seconds(SESSION_TOUCH_SECONDS) {
+                        session.session.last_used_on = now;
+                    }
+                    true
+                }
+                _ => false,
+            })
+        })
+        .await
+    }
+
+    async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, Error> {
+        self.read(|data| {
+            let now = Utc::now();
+            let mut sessions: Vec<ActiveSession> = data
+                .sessions
+                .values()
+                .filter(|session| session.account_id == account_id && session.is_active(now))
+                .map(|session| session.session.clone())
+                .collect();
+            sessions.sort_by_key(|session| Reverse((session.last_used_on, session.id.0)));
+            Ok(sessions)
+        })
+        .await
+    }
+
+    async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        self.write(|data| {
+            Ok(match data.sessions.get_mut(&session_id.0) {
+                Some(session) if session.account_id == account_id && session.is_active(Utc::now()) => {
+                    session.revoked = true;
+                    true
+                }
+                _ => false,
+            })
+        })
+        .await
+    }
+
+    async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, Error> {
+        self.write(|data| {
+            let now = Utc::now();
+            let mut revoked = 0;
+            for session in data.sessions.values_mut() {
+                if session.account_id == account_id
+                    && session.session.id != session_id
+                    && session.is_active(now)
+                {
+                    session.revoked = true;
+                    revoked += 1;
+                }
+            }
+            Ok(revoked)
+        })
+        .await
+    }
+
+    async fn add_verification_token(
+        &self,
+        account_id: AccountId,
+        email: String,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        self.write(|data| {
+            data.verification_tokens.insert(
+                token_hash,
+                VerificationTokenRow {
+                    account_id,
+                    email,
+                    created_on: Utc::now(),
+                    expires_on,
+                    used: false,
+                },
+            );
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, Error> {
+        self.read(|data| {
+            Ok(data
+                .verification_tokens
+                .values()
+                .filter(|token| token.account_id == account_id && !token.used && token.expires_on > Utc::now())
+                .map(|token| token.created_on)
+                .max())
+        })
+        .await
+    }
+
+    async fn verify_email(&self, token_hash: String) -> Result<AccountId, Error> {
+        self.write(|data| {
+            let token = data
+                .verification_tokens
+                .get_mut(&token_hash)
+                .filter(|token| !token.used && token.expires_on > Utc::now())
+                .ok_or(Error::InvalidVerificationToken)?;
+            // A token does not verify an email the account changed since.
+            match data.accounts.get(&token.account_id.0) {
+                Some(account) if account.email == token.email => {
+                    token.used = true;
+                    data.verified_emails.insert(token.account_id.0);
+                    Ok(token.account_id.clone())
+                }
+                _ => Err(Error::InvalidVerificationToken),
+            }
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl ImportStoreTrait for MemoryStore {
+    async fn set_question_created_on(&self, id: QuestionId, created_on: DateTime<Utc>) -> Result<(), Error> {
+        self.write(|data| {
+            let row = data.questions.get_mut(&id.0).ok_or_else(not_found)?;
+            row.created_on = created_on;
+            row.question.created_on = created_on;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_answer_created_on(&self, id: AnswerId, created_on: DateTime<Utc>) -> Result<(), Error> {
+        self.write(|data| {
+            let row = data.answers.get_mut(&id.0).ok_or_else(not_found)?;
+            row.created_on = created_on;
+            row.answer.created_on = created_on;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_imported_post(&self, source_id: i64) -> Result<Option<ImportedPost>, Error> {
+        self.read(|data| Ok(data.imported_posts.get(&source_id).cloned())).await
+    }
+
+    async fn add_imported_post(&self, source_id: i64, post: ImportedPost) -> Result<(), Error> {
+        self.write(|data| {
+            if data.imported_posts.contains_key(&source_id) {
+                return Err(Error::DatabaseQueryError(sqlx::Error::Database(Box::new(
+                    UniqueViolation("imported_posts_pkey"),
+                ))));
+            }
+            data.imported_posts.insert(source_id, post);
+            Ok(())
+        })
+        .await
+    }
+
+    async fn set_display_name(&self, email: String, display_name: String) -> Result<bool, Error> {
+        self.write(|data| {
+            match data.accounts.values_mut().find(|account| account.email == email) {
+                Some(account) => {
+                    account.display_name = Some(display_name);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditStoreTrait for MemoryStore {
+    async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, Error> {
+        self.write(|data| {
+            let id = next_id(&mut data.audit_seq);
+            let record = AuditRecord {
+                id: AuditRecordId(id),
+                actor: record.actor,
+                action: record.action,
+                target: record.action.target(),
+                target_id: record.target_id,
+                before: record.before,
+                after: record.after,
+                request_id: record.request_id,
+                created_on: Utc::now(),
+            };
+            data.audit_log.insert(id, record.clone());
+            Ok(record)
+        })
+        .await
+    }
+
+    async fn get_audit_records(
+        &self,
+        filter: AuditFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<AuditRecord>, Error> {
+        self.read(|data| {
+            // Ids go up with time, so the newest record has the highest.
+            let records = data
+                .audit_log
+                .values()
+                .rev()
+                .filter(|record| filter.matches(record))
+                .cloned();
+            Ok(paginate(records, limit, offset))
+        })
+        .await
+    }
+}
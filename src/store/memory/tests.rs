@@ -0,0 +1,739 @@
+use super::*;
+use crate::store::fixtures::{new_question, new_session};
+
+fn new_account(email: &str) -> Account {
+    Account {
+        id: None,
+        email: email.to_string(),
+        password: "hashed".to_string(),
+        display_name: None,
+    }
+}
+
+#[tokio::test]
+async fn test_add_and_get_questions() {
+    let store = MemoryStore::new();
+    let first = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let second = store.add_question(new_question("Second"), AccountId(1)).await.unwrap();
+    assert_eq!(first.id, QuestionId(1));
+    assert_eq!(second.id, QuestionId(2));
+
+    let questions = store.get_questions(QuestionFilter::default(), None, 0).await.unwrap();
+    assert_eq!(questions, vec![first, second]);
+}
+
+#[tokio::test]
+async fn test_get_questions_pagination() {
+    let store = MemoryStore::new();
+    for title in ["One", "Two", "Three"] {
+        store.add_question(new_question(title), AccountId(1)).await.unwrap();
+    }
+
+    let page = store.get_questions(QuestionFilter::default(), Some(1), 1).await.unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].title, "Two");
+
+    let rest = store.get_questions(QuestionFilter::default(), None, 2).await.unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0].title, "Three");
+}
+
+#[tokio::test]
+async fn test_get_questions_page() {
+    let store = MemoryStore::new();
+    for title in ["One", "Two", "Three"] {
+        store.add_question(new_question(title), AccountId(1)).await.unwrap();
+    }
+
+    let first = store.get_questions_page(QuestionFilter::default(), None, 2).await.unwrap();
+    let titles: Vec<_> = first.items.iter().map(|q| q.title.as_str()).collect();
+    assert_eq!(titles, ["One", "Two"]);
+
+    // A question added mid-scroll neither shifts nor repeats the next page.
+    store.add_question(new_question("Four"), AccountId(1)).await.unwrap();
+    let second = store.get_questions_page(QuestionFilter::default(), first.next_cursor, 2).await.unwrap();
+    let titles: Vec<_> = second.items.iter().map(|q| q.title.as_str()).collect();
+    assert_eq!(titles, ["Three", "Four"]);
+    assert_eq!(second.next_cursor, None);
+}
+
+#[tokio::test]
+async fn test_search() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("Borrow checker errors"), AccountId(1)).await.unwrap();
+    store.add_question(new_question("Async traits"), AccountId(1)).await.unwrap();
+    let answer = store
+        .add_answer(
+            NewAnswer {
+                content: "Clone it to please the borrow checker".to_string(),
+                question_id: question.id,
+            },
+            AccountId(2),
+        )
+        .await
+        .unwrap();
+
+    let results = store.search("borrow checker".to_string(), None, 0).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result.question_id == question.id));
+    assert!(results.iter().any(|result| result.answer_id == Some(answer.id.clone())));
+    assert!(results[0].rank >= results[1].rank);
+    assert!(results[0].snippet.to_lowercase().contains("<b>borrow</b>"));
+
+    let page = store.search("borrow".to_string(), Some(1), 1).await.unwrap();
+    assert_eq!(page.len(), 1);
+
+    store.delete_question(question.id, AccountId(1)).await.unwrap();
+    assert!(store.search("borrow".to_string(), None, 0).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_question_ownership() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("Mine"), AccountId(1)).await.unwrap();
+
+    assert!(store.is_question_owner(question.id, &AccountId(1)).await.unwrap());
+    assert!(!store.is_question_owner(question.id, &AccountId(2)).await.unwrap());
+    assert!(!store.is_question_owner(QuestionId(42), &AccountId(1)).await.unwrap());
+
+    let mut update = question.clone();
+    update.title = "Theirs".to_string();
+    let result = store.update_question(update, question.id, AccountId(2)).await;
+    assert!(matches!(result, Err(Error::DatabaseQueryError(sqlx::Error::RowNotFound))));
+
+    store.delete_question(question.id, AccountId(2)).await.unwrap();
+    assert_eq!(store.get_questions(QuestionFilter::default(), None, 0).await.unwrap().len(), 1);
+
+    store.delete_question(question.id, AccountId(1)).await.unwrap();
+    assert!(store.get_questions(QuestionFilter::default(), None, 0).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_answers_belong_to_question() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("Q"), AccountId(1)).await.unwrap();
+    let answer = store
+        .add_answer(
+            NewAnswer {
+                content: "A".to_string(),
+                question_id: question.id,
+            },
+            AccountId(2),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(store.get_answers(question.id, AnswerSort::Oldest, None, 0).await.unwrap(), vec![answer.clone()]);
+    assert!(store.get_answers(QuestionId(42), AnswerSort::Oldest, None, 0).await.unwrap().is_empty());
+    assert!(store.is_answer_owner(answer.id.0, &AccountId(2)).await.unwrap());
+
+    let orphan = store
+        .add_answer(
+            NewAnswer {
+                content: "A".to_string(),
+                question_id: QuestionId(42),
+            },
+            AccountId(2),
+        )
+        .await;
+    assert!(orphan.is_err());
+}
+
+#[tokio::test]
+async fn test_accounts() {
+    let store = MemoryStore::new();
+    store.add_account(new_account("a@test.com")).await.unwrap();
+    store.add_account(new_account("b@test.com")).await.unwrap();
+
+    let duplicate = store.add_account(new_account("a@test.com")).await;
+    match duplicate {
+        Err(Error::DatabaseQueryError(sqlx::Error::Database(e))) => assert!(e.is_unique_violation()),
+        other => panic!("expected unique violation, got {:?}", other),
+    }
+
+    let account = store.get_account("b@test.com".to_string()).await.unwrap();
+    assert_eq!(account.id, Some(AccountId(2)));
+
+    let taken = store
+        .update_account(
+            AccountId(2),
+            AccountUpdateRequest {
+                email: "a@test.com".to_string(),
+            },
+        )
+        .await;
+    assert!(taken.is_err());
+
+    let updated = store
+        .update_account(
+            AccountId(2),
+            AccountUpdateRequest {
+                email: "c@test.com".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.email, "c@test.com");
+    assert_eq!(
+        store.get_account_information(AccountId(2)).await.unwrap(),
+        updated
+    );
+    assert!(store.get_account("b@test.com".to_string()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_refresh_tokens() {
+    let store = MemoryStore::new();
+    store.add_account(new_account("a@test.com")).await.unwrap();
+    let id = AccountId(1);
+    let expires_on = Utc::now() + chrono::Duration::days(1);
+    assert_eq!(store.get_token_generation(id.clone()).await.unwrap(), 0);
+
+    let session_id = store
+        .add_session(new_session(&id), "first".to_string(), expires_on)
+        .await
+        .unwrap();
+    let rotated = store
+        .rotate_refresh_token("first".to_string(), "second".to_string(), expires_on)
+        .await
+        .unwrap();
+    assert_eq!(rotated, (id.clone(), session_id));
+    // A token can only be used once.
+    assert!(matches!(
+        store.rotate_refresh_token("first".to_string(), "third".to_string(), expires_on).await,
+        Err(Error::InvalidRefreshToken)
+    ));
+
+    store
+        .add_session(new_session(&id), "expired".to_string(), Utc::now() - chrono::Duration::days(1))
+        .await
+        .unwrap();
+    assert!(!store.revoke_refresh_token("expired".to_string()).await.unwrap());
+
+    // A new password revokes every refresh token.
+    store
+        .update_password(id.clone(), AccountUpdatePassword("rehashed".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(store.get_token_generation(id).await.unwrap(), 1);
+    assert!(!store.revoke_refresh_token("second".to_string()).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_sessions() {
+    let store = MemoryStore::new();
+    store.add_account(new_account("a@test.com")).await.unwrap();
+    store.add_account(new_account("b@test.com")).await.unwrap();
+    let id = AccountId(1);
+    let expires_on = Utc::now() + chrono::Duration::days(1);
+    let first = store.add_session(new_session(&id), "first".to_string(), expires_on).await.unwrap();
+    let second = store.add_session(new_session(&id), "second".to_string(), expires_on).await.unwrap();
+    let third = store.add_session(new_session(&id), "third".to_string(), expires_on).await.unwrap();
+
+    // A session used within the interval is not written again.
+    let last_used_on = |sessions: &[ActiveSession], session_id: &SessionId| {
+        sessions.iter().find(|session| &session.id == session_id).unwrap().last_used_on
+    };
+    let before = last_used_on(&store.get_sessions(id.clone()).await.unwrap(), &first);
+    assert!(store.touch_session(id.clone(), first.clone()).await.unwrap());
+    assert_eq!(last_used_on(&store.get_sessions(id.clone()).await.unwrap(), &first), before);
+
+    // The most recently used session comes first.
+    store
+        .write(|data| {
+            data.sessions.get_mut(&first.0).unwrap().session.last_used_on -=
+                Duration::seconds(SESSION_TOUCH_SECONDS);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    assert!(store.touch_session(id.clone(), first.clone()).await.unwrap());
+    let sessions = store.get_sessions(id.clone()).await.unwrap();
+    assert!(last_used_on(&sessions, &first) > before);
+    assert_eq!(
+        sessions.iter().map(|session| session.id.clone()).collect::<Vec<_>>(),
+        vec![first.clone(), third.clone(), second.clone()]
+    );
+    assert_eq!(sessions[0].user_agent.as_deref(), Some("test"));
+
+    // Sessions of other accounts are out of reach.
+    assert!(!store.touch_session(AccountId(2), first.clone()).await.unwrap());
+    assert!(!store.revoke_session(AccountId(2), first.clone()).await.unwrap());
+
+    assert!(store.revoke_session(id.clone(), second.clone()).await.unwrap());
+    assert!(!store.touch_session(id.clone(), second.clone()).await.unwrap());
+    // Revoking the session revokes its refresh token.
+    assert!(matches!(
+        store.rotate_refresh_token("second".to_string(), "fourth".to_string(), expires_on).await,
+        Err(Error::InvalidRefreshToken)
+    ));
+
+    assert_eq!(store.revoke_other_sessions(id.clone(), first.clone()).await.unwrap(), 1);
+    assert!(!store.touch_session(id.clone(), third).await.unwrap());
+    assert_eq!(store.get_sessions(id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_email_verification() {
+    let store = MemoryStore::new();
+    store.add_account(new_account("a@test.com")).await.unwrap();
+    let id = AccountId(1);
+    let expires_on = Utc::now() + chrono::Duration::days(1);
+    assert!(!store.get_account_information(id.clone()).await.unwrap().email_verified);
+
+    store
+        .add_verification_token(id.clone(), "a@test.com".to_string(), "expired".to_string(), Utc::now() - chrono::Duration::days(1))
+        .await
+        .unwrap();
+    assert!(matches!(
+        store.verify_email("expired".to_string()).await,
+        Err(Error::InvalidVerificationToken)
+    ));
+    // Expired tokens do not count as sent.
+    assert_eq!(store.get_verification_sent_on(id.clone()).await.unwrap(), None);
+
+    store
+        .add_verification_token(id.clone(), "a@test.com".to_string(), "first".to_string(), expires_on)
+        .await
+        .unwrap();
+    assert!(store.get_verification_sent_on(id.clone()).await.unwrap().is_some());
+    assert_eq!(store.verify_email("first".to_string()).await.unwrap(), id);
+    assert!(store.get_account_information(id.clone()).await.unwrap().email_verified);
+    assert_eq!(store.get_verification_sent_on(id.clone()).await.unwrap(), None);
+    // A token can only be used once.
+    assert!(matches!(
+        store.verify_email("first".to_string()).await,
+        Err(Error::InvalidVerificationToken)
+    ));
+
+    // A new email has to be verified again, and tokens sent to the old one
+    // do not verify it.
+    store
+        .add_verification_token(id.clone(), "a@test.com".to_string(), "second".to_string(), expires_on)
+        .await
+        .unwrap();
+    let updated = store
+        .update_account(id.clone(), AccountUpdateRequest { email: "b@test.com".to_string() })
+        .await
+        .unwrap();
+    assert!(!updated.email_verified);
+    assert!(matches!(
+        store.verify_email("second".to_string()).await,
+        Err(Error::InvalidVerificationToken)
+    ));
+}
+
+#[tokio::test]
+async fn test_transaction_commit_and_rollback() {
+    let store = MemoryStore::new();
+
+    let tx = store.begin().await.unwrap();
+    tx.add_question(new_question("Dropped"), AccountId(1)).await.unwrap();
+    drop(tx);
+    assert!(store.get_questions(QuestionFilter::default(), None, 0).await.unwrap().is_empty());
+
+    let tx = store.begin().await.unwrap();
+    let nested = tx.begin().await.unwrap();
+    nested.add_question(new_question("Kept"), AccountId(1)).await.unwrap();
+    nested.commit().await.unwrap();
+    assert_eq!(tx.get_questions(QuestionFilter::default(), None, 0).await.unwrap().len(), 1);
+    tx.commit().await.unwrap();
+
+    let questions = store.get_questions(QuestionFilter::default(), None, 0).await.unwrap();
+    assert_eq!(questions.len(), 1);
+    assert_eq!(questions[0].title, "Kept");
+}
+
+#[tokio::test]
+async fn test_delete_question_cascades_to_answers() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("Q"), AccountId(1)).await.unwrap();
+    let new_answer = NewAnswer {
+        content: "A".to_string(),
+        question_id: question.id,
+    };
+    store.add_answer(new_answer, AccountId(2)).await.unwrap();
+    assert_eq!(store.count_answers(question.id).await.unwrap(), 1);
+
+    store.delete_question(question.id, AccountId(1)).await.unwrap();
+    assert_eq!(store.count_answers(question.id).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_soft_delete_restore_and_purge() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("Q"), AccountId(1)).await.unwrap();
+    let new_answer = NewAnswer {
+        content: "A".to_string(),
+        question_id: question.id,
+    };
+    let answer = store.add_answer(new_answer, AccountId(2)).await.unwrap();
+
+    store.delete_question(question.id, AccountId(1)).await.unwrap();
+    assert!(store.get_questions(QuestionFilter::default(), None, 0).await.unwrap().is_empty());
+    assert!(store.get_answers(question.id, AnswerSort::Oldest, None, 0).await.unwrap().is_empty());
+    // Deleted posts can only be restored, not changed by their owner.
+    assert!(!store.is_question_owner(question.id, &AccountId(1)).await.unwrap());
+    assert!(!store.is_answer_owner(answer.id.0, &AccountId(2)).await.unwrap());
+    assert!(store.restore_answer(answer.id.0, AccountId(2)).await.is_err());
+
+    assert_eq!(store.restore_question(question.id, AccountId(1)).await.unwrap(), question);
+    assert_eq!(store.get_answers(question.id, AnswerSort::Oldest, None, 0).await.unwrap(), vec![answer.clone()]);
+
+    store.delete_answer(answer.id.0, AccountId(2)).await.unwrap();
+    assert_eq!(store.restore_answer(answer.id.0, AccountId(2)).await.unwrap(), answer);
+
+    store.delete_question(question.id, AccountId(1)).await.unwrap();
+    assert_eq!(store.purge_deleted(Utc::now() - chrono::Duration::days(1)).await.unwrap(), 0);
+    assert_eq!(store.purge_deleted(Utc::now() + chrono::Duration::seconds(1)).await.unwrap(), 2);
+    assert!(store.restore_question(question.id, AccountId(1)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_edits_record_revisions() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let mut edit = question.clone();
+    edit.title = "Second".to_string();
+    edit.tags = None;
+    let edited = store.update_question(edit.clone(), question.id, AccountId(1)).await.unwrap();
+    assert!(store.update_question(edit.clone(), question.id, AccountId(2)).await.is_err());
+
+    let revisions = store.get_question_revisions(question.id).await.unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(
+        Question {
+            author: question.author.clone(),
+            created_on: question.created_on,
+            version: question.version,
+            ..revisions[0].to_question()
+        },
+        question
+    );
+    assert_eq!(revisions[0].editor, AccountId(1));
+    assert_eq!(
+        store.get_question_revision(question.id, revisions[0].id).await.unwrap(),
+        revisions[0]
+    );
+    assert!(edited.updated_on.is_some());
+    assert_eq!((question.version, edited.version), (1, 2));
+    assert_eq!(store.get_question(question.id).await.unwrap(), edited);
+
+    let new_answer = NewAnswer {
+        content: "Old".to_string(),
+        question_id: question.id,
+    };
+    let answer = store.add_answer(new_answer, AccountId(2)).await.unwrap();
+    let mut edit = answer.clone();
+    edit.content = "New".to_string();
+    let edited = store.update_answer(edit.clone(), answer.id.0, AccountId(2)).await.unwrap();
+    assert_eq!(Answer { updated_on: None, version: edit.version, ..edited.clone() }, edit);
+
+    let revisions = store.get_answer_revisions(answer.id.0).await.unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].content, "Old");
+    assert_eq!(revisions[0].answer_id, answer.id);
+    assert_eq!(store.get_answer(answer.id.0).await.unwrap(), edited);
+}
+
+#[tokio::test]
+async fn test_tags_and_synonyms() {
+    let store = MemoryStore::new();
+    let mut question = new_question("First");
+    question.tags = Some(vec!["Rust".to_string(), " rust".to_string(), "Async Await".to_string()]);
+    let first = store.add_question(question, AccountId(1)).await.unwrap();
+    assert_eq!(first.tags, Some(vec!["rust".to_string(), "async-await".to_string()]));
+    store.add_question(new_question("Second"), AccountId(1)).await.unwrap();
+
+    let tag = store.add_tag_synonym("rust".to_string(), "rust-lang".to_string()).await.unwrap();
+    assert_eq!(tag.synonyms, vec!["rust-lang".to_string()]);
+    assert!(matches!(
+        store.add_tag_synonym("rust".to_string(), "test".to_string()).await,
+        Err(Error::TagInUse(_))
+    ));
+
+    let mut question = new_question("Third");
+    question.tags = Some(vec!["Rust-Lang".to_string()]);
+    let third = store.add_question(question, AccountId(1)).await.unwrap();
+    assert_eq!(third.tags, Some(vec!["rust".to_string()]));
+
+    let tags = store.get_tags(None, 0).await.unwrap();
+    assert_eq!(tags.len(), 3);
+    assert_eq!((tags[0].slug.as_str(), tags[0].questions), ("rust", 2));
+    let questions = store.get_tag_questions("rust-lang".to_string(), None, 0).await.unwrap();
+    assert_eq!(questions, vec![first, third]);
+}
+
+#[tokio::test]
+async fn test_get_questions_filter_and_sort() {
+    let store = MemoryStore::new();
+    let mut question = new_question("First");
+    question.tags = Some(vec!["rust".to_string(), "async".to_string()]);
+    let first = store.add_question(question, AccountId(1)).await.unwrap();
+    let second = store.add_question(new_question("Second"), AccountId(2)).await.unwrap();
+    let new_answer = NewAnswer {
+        content: "Answer".to_string(),
+        question_id: first.id,
+    };
+    store.add_answer(new_answer, AccountId(2)).await.unwrap();
+
+    let tagged = QuestionFilter {
+        tags: vec!["rust".to_string(), "test".to_string()],
+        ..QuestionFilter::default()
+    };
+    assert_eq!(store.get_questions(tagged.clone(), None, 0).await.unwrap().len(), 2);
+    let all_tags = QuestionFilter {
+        tag_match: TagMatch::All,
+        ..tagged
+    };
+    assert!(store.get_questions(all_tags, None, 0).await.unwrap().is_empty());
+
+    let by_author = QuestionFilter {
+        author: Some(AccountId(2)),
+        ..QuestionFilter::default()
+    };
+    assert_eq!(store.get_questions(by_author, None, 0).await.unwrap(), vec![second.clone()]);
+    let unanswered = QuestionFilter {
+        unanswered: true,
+        ..QuestionFilter::default()
+    };
+    assert_eq!(store.get_questions(unanswered, None, 0).await.unwrap(), vec![second.clone()]);
+    let later = QuestionFilter {
+        created_after: Some(Utc::now() + chrono::Duration::days(1)),
+        ..QuestionFilter::default()
+    };
+    assert!(store.get_questions(later, None, 0).await.unwrap().is_empty());
+
+    let most_answers = QuestionFilter {
+        sort: QuestionSort::MostAnswers,
+        ..QuestionFilter::default()
+    };
+    assert_eq!(
+        store.get_questions(most_answers, None, 0).await.unwrap(),
+        vec![first.clone(), second.clone()]
+    );
+    let newest = QuestionFilter {
+        sort: QuestionSort::Newest,
+        ..QuestionFilter::default()
+    };
+    assert_eq!(
+        store.get_questions(newest.clone(), None, 0).await.unwrap(),
+        vec![second.clone(), first.clone()]
+    );
+    let page = store.get_questions_page(newest.clone(), None, 1).await.unwrap();
+    assert_eq!(page.items, vec![second]);
+    let page = store.get_questions_page(newest, page.next_cursor, 1).await.unwrap();
+    assert_eq!(page.items, vec![first]);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[tokio::test]
+async fn test_get_questions_recent_activity() {
+    let store = MemoryStore::new();
+    let first = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let second = store.add_question(new_question("Second"), AccountId(1)).await.unwrap();
+    let recent = QuestionFilter {
+        sort: QuestionSort::RecentActivity,
+        ..QuestionFilter::default()
+    };
+    assert_eq!(
+        store.get_questions(recent.clone(), None, 0).await.unwrap(),
+        vec![second.clone(), first.clone()]
+    );
+
+    let new_answer = NewAnswer {
+        content: "Answer".to_string(),
+        question_id: first.id,
+    };
+    store.add_answer(new_answer, AccountId(2)).await.unwrap();
+    assert_eq!(store.get_questions(recent, None, 0).await.unwrap(), vec![first, second]);
+}
+
+#[tokio::test]
+async fn test_votes_and_scores() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let voted = store.vote_question(question.id, AccountId(2), Some(Vote::Up)).await.unwrap();
+    assert_eq!(voted.score, 1);
+    store.vote_question(question.id, AccountId(3), Some(Vote::Up)).await.unwrap();
+    // Voting again changes the vote instead of adding one.
+    let voted = store.vote_question(question.id, AccountId(2), Some(Vote::Down)).await.unwrap();
+    assert_eq!(voted.score, 0);
+    let voted = store.vote_question(question.id, AccountId(2), None).await.unwrap();
+    assert_eq!(voted.score, 1);
+    assert_eq!(store.get_question(question.id).await.unwrap().score, 1);
+
+    let mut answers = Vec::new();
+    for _ in 0..2 {
+        let new_answer = NewAnswer {
+            content: "Answer".to_string(),
+            question_id: question.id,
+        };
+        answers.push(store.add_answer(new_answer, AccountId(2)).await.unwrap());
+    }
+    let voted = store.vote_answer(answers[1].id.0, AccountId(1), Some(Vote::Up)).await.unwrap();
+    assert_eq!(voted.score, 1);
+    let by_score = store.get_answers(question.id, AnswerSort::Score, None, 0).await.unwrap();
+    assert_eq!(by_score[0].id, answers[1].id);
+    assert_eq!(by_score[1].id, answers[0].id);
+
+    store.delete_answer(answers[0].id.0, AccountId(2)).await.unwrap();
+    assert!(store.vote_answer(answers[0].id.0, AccountId(1), Some(Vote::Up)).await.is_err());
+    assert!(store.vote_question(QuestionId(42), AccountId(1), Some(Vote::Up)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_accepted_answer() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let other = store.add_question(new_question("Second"), AccountId(1)).await.unwrap();
+    let mut answers = Vec::new();
+    for question_id in [question.id, question.id, question.id, other.id] {
+        let new_answer = NewAnswer {
+            content: "Answer".to_string(),
+            question_id,
+        };
+        answers.push(store.add_answer(new_answer, AccountId(2)).await.unwrap());
+    }
+    let ids = |answers: &[Answer]| answers.iter().map(|answer| answer.id.0).collect::<Vec<_>>();
+
+    // Only answers to the question itself can be accepted.
+    assert!(store.accept_answer(question.id, answers[3].id.clone()).await.is_err());
+    let accepted = store.accept_answer(question.id, answers[1].id.clone()).await.unwrap();
+    assert_eq!(accepted.accepted_answer_id, Some(answers[1].id.clone()));
+    assert_eq!(store.get_question(question.id).await.unwrap().accepted_answer_id, Some(answers[1].id.clone()));
+
+    // The accepted answer is pinned first.
+    let oldest = store.get_answers(question.id, AnswerSort::Oldest, None, 0).await.unwrap();
+    assert_eq!(ids(&oldest), [answers[1].id.0, answers[0].id.0, answers[2].id.0]);
+    let first = store.get_answers_page(question.id, None, 2).await.unwrap();
+    assert_eq!(ids(&first.items), [answers[1].id.0, answers[0].id.0]);
+    let next = store.get_answers_page(question.id, first.next_cursor, 2).await.unwrap();
+    assert_eq!(ids(&next.items), [answers[2].id.0]);
+    assert!(next.next_cursor.is_none());
+    // It counts toward the limit, even when it fills the page alone.
+    let mut cursor = None;
+    let mut walked = Vec::new();
+    loop {
+        let page = store.get_answers_page(question.id, cursor, 1).await.unwrap();
+        assert!(page.items.len() <= 1);
+        walked.extend(ids(&page.items));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(walked, ids(&oldest));
+
+    let unaccepted = store.unaccept_answer(question.id).await.unwrap();
+    assert_eq!(unaccepted.accepted_answer_id, None);
+    let oldest = store.get_answers(question.id, AnswerSort::Oldest, None, 0).await.unwrap();
+    assert_eq!(ids(&oldest), ids(&answers[..3]));
+
+    // Purging the accepted answer clears the mark.
+    store.accept_answer(question.id, answers[2].id.clone()).await.unwrap();
+    store.delete_answer(answers[2].id.0, AccountId(2)).await.unwrap();
+    store.purge_deleted(Utc::now() + chrono::Duration::seconds(1)).await.unwrap();
+    assert_eq!(store.get_question(question.id).await.unwrap().accepted_answer_id, None);
+}
+
+#[tokio::test]
+async fn test_comments() {
+    let store = MemoryStore::new();
+    let question = store.add_question(new_question("First"), AccountId(1)).await.unwrap();
+    let new_answer = NewAnswer {
+        content: "Answer".to_string(),
+        question_id: question.id,
+    };
+    let answer = store.add_answer(new_answer, AccountId(2)).await.unwrap();
+    let comment = |content: &str| NewComment {
+        content: content.to_string(),
+    };
+
+    let first = store
+        .add_comment(CommentParent::Question(question.id), comment("Which version?"), AccountId(2))
+        .await
+        .unwrap();
+    assert_eq!(first.question_id, Some(question.id));
+    assert_eq!(first.answer_id, None);
+    store
+        .add_comment(CommentParent::Question(question.id), comment("1.75"), AccountId(1))
+        .await
+        .unwrap();
+    let on_answer = store
+        .add_comment(CommentParent::Answer(answer.id.clone()), comment("Thanks"), AccountId(1))
+        .await
+        .unwrap();
+    assert_eq!(on_answer.answer_id, Some(answer.id.clone()));
+    assert!(store
+        .add_comment(CommentParent::Question(QuestionId(42)), comment("Lost"), AccountId(1))
+        .await
+        .is_err());
+
+    let comments = store.get_comments(CommentParent::Question(question.id), None, 0).await.unwrap();
+    let contents: Vec<&str> = comments.iter().map(|comment| comment.content.as_str()).collect();
+    assert_eq!(contents, vec!["Which version?", "1.75"]);
+    let comments = store.get_comments(CommentParent::Question(question.id), Some(1), 1).await.unwrap();
+    assert_eq!(comments[0].content, "1.75");
+
+    // Only the author can edit or delete a comment.
+    assert!(store.is_comment_owner(first.id, &AccountId(2)).await.unwrap());
+    assert!(!store.is_comment_owner(first.id, &AccountId(1)).await.unwrap());
+    let edited = store.update_comment(comment("Which edition?"), first.id, AccountId(2)).await.unwrap();
+    assert_eq!(edited.content, "Which edition?");
+    store.delete_comment(first.id, AccountId(2)).await.unwrap();
+    assert_eq!(store.get_comments(CommentParent::Question(question.id), None, 0).await.unwrap().len(), 1);
+
+    // Comments are hidden with their post.
+    store.delete_answer(answer.id.0, AccountId(2)).await.unwrap();
+    assert!(store.get_comments(CommentParent::Answer(answer.id.clone()), None, 0).await.unwrap().is_empty());
+    store.restore_answer(answer.id.0, AccountId(2)).await.unwrap();
+    assert_eq!(store.get_comments(CommentParent::Answer(answer.id), None, 0).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_posts_show_author_and_times() {
+    let store = MemoryStore::new();
+    store.add_account(new_account("jane.doe@example.com")).await.unwrap();
+    let account_id = store.get_account("jane.doe@example.com".to_string()).await.unwrap().id.unwrap();
+    let question = store.add_question(new_question("First"), account_id.clone()).await.unwrap();
+    assert_eq!(question.author.id, account_id);
+    assert_eq!(question.author.display_name, "jane.doe");
+    assert!(question.updated_on.is_none());
+    let answer = store
+        .add_answer(
+            NewAnswer {
+                content: "Answer".to_string(),
+                question_id: question.id,
+            },
+            account_id.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(answer.author.display_name, "jane.doe");
+    assert!(answer.created_on >= question.created_on);
+
+    let edited = store.update_answer(answer.clone(), answer.id.0, account_id.clone()).await.unwrap();
+    assert_eq!(edited.created_on, answer.created_on);
+    assert!(edited.updated_on.is_some_and(|updated_on| updated_on >= answer.created_on));
+    assert_eq!(edited.version, answer.version + 1);
+
+    // The name follows the account.
+    store
+        .update_account(account_id.clone(), AccountUpdateRequest { email: "jd@example.com".to_string() })
+        .await
+        .unwrap();
+    assert_eq!(store.get_question(question.id).await.unwrap().author.display_name, "jd");
+    store
+        .add_account(Account {
+            id: None,
+            email: "named@example.com".to_string(),
+            password: "hashed".to_string(),
+            display_name: Some("Jane".to_string()),
+        })
+        .await
+        .unwrap();
+    let named_id = store.get_account("named@example.com".to_string()).await.unwrap().id.unwrap();
+    let question = store.add_question(new_question("Second"), named_id).await.unwrap();
+    assert_eq!(question.author.display_name, "Jane");
+}
@@ -0,0 +1,1885 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use sqlx::{
+    sqlite::{Sqlite, SqlitePool, SqlitePoolOptions, SqliteRow},
+    types::Json,
+    QueryBuilder, Row,
+};
+
+use handle_errors::Error;
+
+use crate::routes::answer::store_trait::StoreTrait as AnswerStoreTrait;
+use crate::routes::audit::store_trait::StoreTrait as AuditStoreTrait;
+use crate::routes::authentication::{StoreTrait as AuthStoreTrait, SESSION_TOUCH_SECONDS};
+use crate::routes::comment::store_trait::StoreTrait as CommentStoreTrait;
+use crate::routes::question::store_trait::StoreTrait as QuestionStoreTrait;
+use crate::routes::tag::store_trait::StoreTrait as TagStoreTrait;
+use crate::routes::transaction::Transactional;
+use crate::stackexchange::store_trait::StoreTrait as ImportStoreTrait;
+use crate::stackexchange::ImportedPost;
+use crate::types::{
+    account::{
+        Account, AccountId, AccountResponse, AccountUpdatePassword, AccountUpdateRequest, ActiveSession, Author,
+        NewSession, SessionId,
+    },
+    answer::{Answer, AnswerId, NewAnswer},
+    audit::{AuditAction, AuditFilter, AuditRecord, AuditRecordId, NewAuditRecord},
+    comment::{Comment, CommentId, CommentParent, NewComment},
+    filter::{AnswerSort, QuestionFilter, QuestionSort, TagMatch},
+    pagination::{Cursor, Page},
+    question::{NewQuestion, Question, QuestionId},
+    revision::{AnswerRevision, QuestionRevision, RevisionId},
+    search::{naive_match, query_terms, sort_results, SearchResult},
+    tag::{dedup_tags, normalize_tags, Tag},
+    vote::Vote,
+};
+
+use super::connection::{self, Conn, TransactionHandle};
+
+#[cfg(test)]
+mod tests;
+
+/// A storage backend on top of a SQLite database (`SqlitePool`).
+///
+/// Mirrors `Store` query for query. Tags are kept as a JSON array since
+/// SQLite has no array type, and `deleted_at` as RFC 3339 text.
+///
+/// # Fields
+///
+/// * `connection`: A connection pool to a SQLite database.
+/// * `transaction`: The open transaction of a handle returned by
+///   `Transactional::begin`. Every query of such a handle runs inside it.
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    pub connection: SqlitePool,
+    transaction: Option<TransactionHandle<Sqlite>>,
+}
+
+// The columns of a question or an answer besides the name of its author.
+const QUESTION_COLUMNS: &str =
+    "id, title, content, tags, score, accepted_answer_id, account_id, created_on, updated_on, version";
+const ANSWER_COLUMNS: &str =
+    "id, content, corresponding_question, score, account_id, created_on, updated_on, version";
+
+// The name the author of a post goes by: the display name of the account, or
+// the part of its email before the `@`. `account_id` is the one of the post,
+// as `accounts` has no column of that name.
+const AUTHOR_NAME: &str = "(SELECT COALESCE(display_name, substr(email, 1, instr(email || '@', '@') - 1))
+    FROM accounts WHERE accounts.id = account_id) AS author_name";
+
+fn to_author(row: &SqliteRow) -> Author {
+    Author {
+        id: AccountId(row.get("account_id")),
+        display_name: row.get::<Option<String>, _>("author_name").unwrap_or_default(),
+    }
+}
+
+fn to_question(row: SqliteRow) -> Question {
+    Question {
+        id: QuestionId(row.get("id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        tags: row
+            .get::<Option<Json<Vec<String>>>, _>("tags")
+            .map(|tags| tags.0),
+        score: row.get("score"),
+        accepted_answer_id: row
+            .get::<Option<i32>, _>("accepted_answer_id")
+            .map(AnswerId),
+        author: to_author(&row),
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+        updated_on: row
+            .get::<Option<NaiveDateTime>, _>("updated_on")
+            .map(|time| time.and_utc()),
+        version: row.get("version"),
+    }
+}
+
+fn to_answer(row: SqliteRow) -> Answer {
+    Answer {
+        id: AnswerId(row.get("id")),
+        content: row.get("content"),
+        question_id: QuestionId(row.get("corresponding_question")),
+        score: row.get("score"),
+        author: to_author(&row),
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+        updated_on: row
+            .get::<Option<NaiveDateTime>, _>("updated_on")
+            .map(|time| time.and_utc()),
+        version: row.get("version"),
+    }
+}
+
+// `created_on` defaults to `CURRENT_TIMESTAMP`, UTC text without a zone.
+fn to_cursor(row: &SqliteRow) -> Cursor {
+    Cursor {
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+        id: row.get("id"),
+    }
+}
+
+// Formats a time like the stored `created_on`, so the two compare as text.
+fn timestamp_text(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+}
+
+fn cursor_created_on(cursor: &Option<Cursor>) -> Option<String> {
+    cursor.map(|cursor| timestamp_text(cursor.created_on))
+}
+
+fn to_tag(row: SqliteRow) -> Tag {
+    Tag {
+        slug: row.get("slug"),
+        questions: row.get("questions"),
+        synonyms: row.get::<Json<Vec<String>>, _>("synonyms").0,
+    }
+}
+
+fn to_question_revision(row: SqliteRow) -> QuestionRevision {
+    QuestionRevision {
+        id: RevisionId(row.get("id")),
+        question_id: QuestionId(row.get("question_id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        tags: row
+            .get::<Option<Json<Vec<String>>>, _>("tags")
+            .map(|tags| tags.0),
+        editor: AccountId(row.get("account_id")),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn to_answer_revision(row: SqliteRow) -> AnswerRevision {
+    AnswerRevision {
+        id: RevisionId(row.get("id")),
+        answer_id: AnswerId(row.get("answer_id")),
+        content: row.get("content"),
+        editor: AccountId(row.get("account_id")),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn to_comment(row: SqliteRow) -> Comment {
+    Comment {
+        id: CommentId(row.get("id")),
+        content: row.get("content"),
+        question_id: row.get::<Option<i32>, _>("question_id").map(QuestionId),
+        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+        account_id: AccountId(row.get("account_id")),
+        created_on: row.get("created_on"),
+    }
+}
+
+fn to_session(row: SqliteRow) -> ActiveSession {
+    ActiveSession {
+        id: SessionId(row.get("id")),
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+        last_used_on: row.get::<NaiveDateTime, _>("last_used_on").and_utc(),
+        user_agent: row.get("user_agent"),
+        ip: row.get("ip"),
+        current: false,
+    }
+}
+
+fn to_account_response(row: SqliteRow) -> AccountResponse {
+    AccountResponse {
+        email: row.get("email"),
+        id: AccountId(row.get("id")),
+        email_verified: row.get("email_verified"),
+    }
+}
+
+// The action is written by `add_audit_record`, so it always parses.
+fn to_audit_record(row: SqliteRow) -> Result<AuditRecord, sqlx::Error> {
+    let action = row
+        .get::<String, _>("action")
+        .parse::<AuditAction>()
+        .map_err(|e| sqlx::Error::Decode(e.to_string().into()))?;
+    Ok(AuditRecord {
+        id: AuditRecordId(row.get("id")),
+        actor: AccountId(row.get("actor")),
+        action,
+        target: action.target(),
+        target_id: row.get("target_id"),
+        before: row.get::<Option<Json<serde_json::Value>>, _>("row_before").map(|json| json.0),
+        after: row.get::<Option<Json<serde_json::Value>>, _>("row_after").map(|json| json.0),
+        request_id: row.get("request_id"),
+        created_on: row.get::<NaiveDateTime, _>("created_on").and_utc(),
+    })
+}
+
+impl SqliteStore {
+    /// Initializes a new `SqliteStore` instance with the provided database URL.
+    pub async fn new(db_url: &str) -> Result<Self, sqlx::Error> {
+        tracing::warn!("{}", db_url);
+        let db_pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect(db_url)
+            .await?;
+
+        Ok(SqliteStore {
+            connection: db_pool,
+            transaction: None,
+        })
+    }
+
+    /// Helper function to handle database errors consistently
+    fn handle_error<T>(result: Result<T, sqlx::Error>) -> Result<T, Error> {
+        result.map_err(|e| {
+            tracing::event!(tracing::Level::ERROR, "{:?}", e);
+            Error::DatabaseQueryError(e)
+        })
+    }
+
+    /// Helper function to borrow the connection a query runs on
+    async fn conn(&self) -> Result<Conn<'_, Sqlite>, Error> {
+        Self::handle_error(connection::acquire(&self.connection, self.transaction.as_ref()).await)
+    }
+
+    /// Helper function to check ownership of a resource.
+    ///
+    /// SQLite has no row locks; a transaction that reads and then writes
+    /// fails with `SQLITE_BUSY` instead of acting on a row another writer
+    /// changed in between. Deleted rows have no owner; restoring them
+    /// checks the owner itself.
+    async fn check_ownership(
+        &self,
+        table: &str,
+        id: i32,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        let query = format!(
+            "SELECT EXISTS(SELECT 1 FROM {} WHERE id = ? AND account_id = ?{})",
+            table,
+            live_filter(table)
+        );
+
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(id)
+                .bind(account_id.0)
+                .fetch_one(&mut *conn)
+                .await
+                .map(|row: SqliteRow| row.get(0)),
+        )
+    }
+
+    /// Normalizes the tags of a question and replaces synonyms with their
+    /// canonical tag.
+    async fn canonical_tags(&self, tags: Option<Vec<String>>) -> Result<Option<Vec<String>>, Error> {
+        match normalize_tags(tags)? {
+            Some(tags) => Ok(Some(self.resolve_synonyms(tags).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Replaces synonyms among normalized tags with their canonical tag.
+    async fn resolve_synonyms(&self, tags: Vec<String>) -> Result<Vec<String>, Error> {
+        if tags.is_empty() {
+            return Ok(tags);
+        }
+        let mut conn = self.conn().await?;
+        let tags = Self::handle_error(
+            sqlx::query(
+                "SELECT coalesce(t.slug, input.value) AS slug
+                FROM json_each(?) AS input
+                LEFT JOIN tag_synonyms s ON s.slug = input.value
+                LEFT JOIN tags t ON t.id = s.tag_id
+                ORDER BY input.key",
+            )
+                .bind(Json(tags))
+                .map(|row: SqliteRow| row.get("slug"))
+                .fetch_all(&mut *conn)
+                .await,
+        )?;
+        Ok(dedup_tags(tags))
+    }
+
+    /// Links a question to exactly its tags, creating the missing ones.
+    async fn set_question_tags(&self, id: QuestionId, tags: &Option<Vec<String>>) -> Result<(), Error> {
+        let tags = Json(tags.clone().unwrap_or_default());
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("INSERT OR IGNORE INTO tags (slug) SELECT value FROM json_each(?)")
+                .bind(&tags)
+                .execute(&mut *conn)
+                .await,
+        )?;
+        Self::handle_error(
+            sqlx::query("DELETE FROM question_tags WHERE question_id = ?")
+                .bind(id.0)
+                .execute(&mut *conn)
+                .await,
+        )?;
+        Self::handle_error(
+            sqlx::query(
+                "INSERT INTO question_tags (question_id, tag_id)
+                SELECT ?, id FROM tags WHERE slug IN (SELECT value FROM json_each(?))",
+            )
+                .bind(id.0)
+                .bind(&tags)
+                .execute(&mut *conn)
+                .await,
+        )?;
+        Ok(())
+    }
+
+    /// Permanently removes the questions and answers deleted before
+    /// `deleted_before`. Returns the number of rows removed.
+    pub async fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<u64, Error> {
+        let tx = self.begin().await?;
+        let purged = {
+            let mut conn = tx.conn().await?;
+            let answers = Self::handle_error(
+                sqlx::query("DELETE FROM answers WHERE julianday(deleted_at) < julianday(?)")
+                    .bind(deleted_before)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            let questions = Self::handle_error(
+                sqlx::query("DELETE FROM questions WHERE julianday(deleted_at) < julianday(?)")
+                    .bind(deleted_before)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            answers.rows_affected() + questions.rows_affected()
+        };
+        tx.commit().await?;
+        Ok(purged)
+    }
+}
+
+#[async_trait::async_trait]
+impl Transactional for SqliteStore {
+    async fn begin(&self) -> Result<Self, Error> {
+        let transaction = match &self.transaction {
+            Some(transaction) => transaction.join(),
+            None => Self::handle_error(TransactionHandle::begin(&self.connection).await)?,
+        };
+
+        Ok(SqliteStore {
+            connection: self.connection.clone(),
+            transaction: Some(transaction),
+        })
+    }
+
+    async fn commit(self) -> Result<(), Error> {
+        match self.transaction {
+            Some(transaction) => Self::handle_error(transaction.commit().await),
+            None => Ok(()),
+        }
+    }
+}
+
+// Live answers of the question `q`.
+const LIVE_ANSWERS: &str = "FROM answers a WHERE a.corresponding_question = q.id AND a.deleted_at IS NULL";
+
+/// Appends the conditions of `filter` to a query over `questions q`. `tags`
+/// are the tags of the filter with synonyms resolved.
+fn push_question_filter(query: &mut QueryBuilder<'_, Sqlite>, filter: &QuestionFilter, tags: Vec<String>) {
+    const HAS_TAG: &str = " AND EXISTS (
+        SELECT 1 FROM question_tags qt JOIN tags t ON t.id = qt.tag_id
+        WHERE qt.question_id = q.id AND t.slug";
+
+    query.push(" WHERE q.deleted_at IS NULL");
+    if !tags.is_empty() {
+        match filter.tag_match {
+            TagMatch::Any => {
+                query
+                    .push(HAS_TAG)
+                    .push(" IN (SELECT value FROM json_each(")
+                    .push_bind(Json(tags))
+                    .push(")))");
+            }
+            TagMatch::All => {
+                for tag in tags {
+                    query.push(HAS_TAG).push(" = ").push_bind(tag).push(")");
+                }
+            }
+        }
+    }
+    if let Some(author) = &filter.author {
+        query.push(" AND q.account_id = ").push_bind(author.0);
+    }
+    if let Some(after) = filter.created_after {
+        query.push(" AND q.created_on >= ").push_bind(timestamp_text(after));
+    }
+    if let Some(before) = filter.created_before {
+        query.push(" AND q.created_on < ").push_bind(timestamp_text(before));
+    }
+    if filter.unanswered {
+        query.push(format!(" AND NOT EXISTS (SELECT 1 {})", LIVE_ANSWERS));
+    }
+}
+
+fn push_question_order(query: &mut QueryBuilder<'_, Sqlite>, sort: QuestionSort) {
+    match sort {
+        QuestionSort::Oldest => query.push(" ORDER BY q.created_on, q.id"),
+        QuestionSort::Newest => query.push(" ORDER BY q.created_on DESC, q.id DESC"),
+        QuestionSort::MostAnswers => query.push(format!(
+            " ORDER BY (SELECT COUNT(*) {}) DESC, q.created_on DESC, q.id DESC",
+            LIVE_ANSWERS
+        )),
+        // The scalar max() is NULL if an argument is, so a question without
+        // answers falls back to its own creation.
+        QuestionSort::RecentActivity => query.push(format!(
+            " ORDER BY max(q.created_on, coalesce((SELECT MAX(a.created_on) {}), q.created_on)) DESC, q.id DESC",
+            LIVE_ANSWERS
+        )),
+    };
+}
+
+fn answer_order(sort: AnswerSort) -> &'static str {
+    match sort {
+        AnswerSort::Oldest => "created_on, id",
+        AnswerSort::Score => "score DESC, created_on, id",
+    }
+}
+
+// Comments are deleted for good; questions and answers keep deleted rows
+// until they are purged.
+fn live_filter(table: &str) -> &'static str {
+    match table {
+        "comments" => "",
+        _ => " AND deleted_at IS NULL",
+    }
+}
+
+#[async_trait::async_trait]
+impl QuestionStoreTrait for SqliteStore {
+    async fn get_questions(
+        &self,
+        filter: QuestionFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        let tags = self.resolve_synonyms(filter.tags.clone()).await?;
+        let mut query = QueryBuilder::new(format!("SELECT q.*, {} FROM questions q", AUTHOR_NAME));
+        push_question_filter(&mut query, &filter, tags);
+        push_question_order(&mut query, filter.sort);
+        // A negative LIMIT means "no limit" in SQLite.
+        query.push(" LIMIT ").push_bind(limit.unwrap_or(-1));
+        query.push(" OFFSET ").push_bind(offset);
+
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            query
+                .build()
+                .map(to_question)
+                .fetch_all(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn get_questions_page(
+        &self,
+        filter: QuestionFilter,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Question>, Error> {
+        let tags = self.resolve_synonyms(filter.tags.clone()).await?;
+        let newest_first = filter.sort == QuestionSort::Newest;
+        let mut query = QueryBuilder::new(format!("SELECT q.*, {} FROM questions q", AUTHOR_NAME));
+        push_question_filter(&mut query, &filter, tags);
+        if let Some(cursor) = cursor {
+            query
+                .push(if newest_first { " AND (q.created_on, q.id) < (" } else { " AND (q.created_on, q.id) > (" })
+                .push_bind(timestamp_text(cursor.created_on))
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        push_question_order(&mut query, if newest_first { QuestionSort::Newest } else { QuestionSort::Oldest });
+        query.push(" LIMIT ").push_bind(limit.max(0) + 1);
+
+        let mut conn = self.conn().await?;
+        let rows = Self::handle_error(
+            query
+                .build()
+                .map(|row: SqliteRow| {
+                    let cursor = to_cursor(&row);
+                    (to_question(row), cursor)
+                })
+                .fetch_all(&mut *conn)
+                .await,
+        )?;
+        Ok(Page::from_rows(rows, limit))
+    }
+
+    async fn is_question_owner(
+        &self,
+        question_id: QuestionId,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        self.check_ownership("questions", question_id.0, account_id).await
+    }
+
+    async fn add_question(
+        &self,
+        new_question: NewQuestion,
+        account_id: AccountId,
+    ) -> Result<Question, Error> {
+        let tx = self.begin().await?;
+        let tags = tx.canonical_tags(new_question.tags).await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "INSERT INTO questions (title, content, tags, account_id)
+                    VALUES (?, ?, ?, ?)
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(new_question.title)
+                .bind(new_question.content)
+                .bind(tags.map(Json))
+                .bind(account_id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await,
+            )?
+        };
+        tx.set_question_tags(question.id, &question.tags).await?;
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn update_question(
+        &self,
+        question: Question,
+        id: QuestionId,
+        account_id: AccountId,
+    ) -> Result<Question, Error> {
+        // The revision is rolled back with the transaction if the update
+        // finds nothing to change.
+        let tx = self.begin().await?;
+        let tags = tx.canonical_tags(question.tags).await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO question_revisions
+                    (question_id, title, content, tags, account_id, created_on)
+                    SELECT id, title, content, tags, ?2, ?3 FROM questions
+                    WHERE id = ?1 AND account_id = ?2 AND deleted_at IS NULL",
+                )
+                .bind(id.0)
+                .bind(account_id.0)
+                .bind(Utc::now())
+                .execute(&mut *conn)
+                .await,
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE questions
+                    SET title = ?, content = ?, tags = ?, updated_on = CURRENT_TIMESTAMP,
+                        version = version + 1
+                    WHERE id = ? AND account_id = ? AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(question.title)
+                .bind(question.content)
+                .bind(tags.map(Json))
+                .bind(id.0)
+                .bind(account_id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await,
+            )?
+        };
+        tx.set_question_tags(question.id, &question.tags).await?;
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn delete_question(&self, id: QuestionId, account_id: AccountId) -> Result<bool, Error> {
+        // The question's answers are deleted with the same timestamp, which
+        // is how `restore_question` finds them again.
+        let deleted_at = Utc::now();
+        let tx = self.begin().await?;
+        {
+            let mut conn = tx.conn().await?;
+            let deleted = Self::handle_error(
+                sqlx::query(
+                    "UPDATE questions SET deleted_at = ?
+                    WHERE id = ? AND account_id = ? AND deleted_at IS NULL",
+                )
+                .bind(deleted_at)
+                .bind(id.0)
+                .bind(account_id.0)
+                .execute(&mut *conn)
+                .await,
+            )?;
+            if deleted.rows_affected() > 0 {
+                Self::handle_error(
+                    sqlx::query(
+                        "UPDATE answers SET deleted_at = ?
+                        WHERE corresponding_question = ? AND deleted_at IS NULL",
+                    )
+                    .bind(deleted_at)
+                    .bind(id.0)
+                    .execute(&mut *conn)
+                    .await,
+                )?;
+            }
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn restore_question(&self, id: QuestionId, account_id: AccountId) -> Result<Question, Error> {
+        let tx = self.begin().await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            // Compared as stored, so the text round-trips unchanged.
+            let deleted_at: String = Self::handle_error(
+                sqlx::query(
+                    "SELECT deleted_at FROM questions
+                    WHERE id = ? AND account_id = ? AND deleted_at IS NOT NULL",
+                )
+                .bind(id.0)
+                .bind(account_id.0)
+                .fetch_one(&mut *conn)
+                .await
+                .map(|row: SqliteRow| row.get(0)),
+            )?;
+            Self::handle_error(
+                sqlx::query(
+                    "UPDATE answers SET deleted_at = NULL
+                    WHERE corresponding_question = ? AND deleted_at = ?",
+                )
+                .bind(id.0)
+                .bind(deleted_at)
+                .execute(&mut *conn)
+                .await,
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE questions SET deleted_at = NULL
+                    WHERE id = ?
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await,
+            )?
+        };
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn get_answers(
+        &self,
+        question_id: QuestionId,
+        sort: AnswerSort,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Answer>, Error> {
+        // The accepted answer is pinned first whatever the order.
+        let query = format!(
+            "SELECT *, {} FROM answers
+            WHERE corresponding_question = ?1 AND deleted_at IS NULL
+            ORDER BY id IS (SELECT accepted_answer_id FROM questions WHERE id = ?1) DESC, {}
+            LIMIT ?2 OFFSET ?3",
+            AUTHOR_NAME,
+            answer_order(sort)
+        );
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+            .bind(question_id.0)
+            .bind(limit.unwrap_or(-1))
+            .bind(offset)
+            .map(to_answer)
+            .fetch_all(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn get_answers_page(
+        &self,
+        question_id: QuestionId,
+        cursor: Option<Cursor>,
+        limit: i32,
+    ) -> Result<Page<Answer>, Error> {
+        let mut conn = self.conn().await?;
+        // The accepted answer is left out of the walk and put on top of the
+        // first page instead.
+        let accepted = match cursor {
+            Some(_) => None,
+            None => Self::handle_error(
+                sqlx::query(&format!(
+                    "SELECT *, {} FROM answers
+                    WHERE id = (SELECT accepted_answer_id FROM questions WHERE id = ?)
+                    AND deleted_at IS NULL",
+                    AUTHOR_NAME
+                ))
+                .bind(question_id.0)
+                .map(to_answer)
+                .fetch_optional(&mut *conn)
+                .await,
+            )?,
+        };
+        let rows = Self::handle_error(
+            sqlx::query(&format!(
+                "SELECT *, {} FROM answers
+                WHERE corresponding_question = ?1 AND deleted_at IS NULL
+                AND id IS NOT (SELECT accepted_answer_id FROM questions WHERE id = ?1)
+                AND (?2 IS NULL OR (created_on, id) > (?2, ?3))
+                ORDER BY created_on, id
+                LIMIT ?4",
+                AUTHOR_NAME
+            ))
+            .bind(question_id.0)
+            .bind(cursor_created_on(&cursor))
+            .bind(cursor.map(|cursor| cursor.id))
+            .bind(limit.max(0) + i32::from(accepted.is_none()))
+            .map(|row: SqliteRow| {
+                let cursor = to_cursor(&row);
+                (to_answer(row), cursor)
+            })
+            .fetch_all(&mut *conn)
+            .await,
+        )?;
+        Ok(match accepted {
+            Some(answer) => Page::pinned(answer, rows, limit),
+            None => Page::from_rows(rows, limit),
+        })
+    }
+
+    async fn get_question(&self, id: QuestionId) -> Result<Question, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!("SELECT *, {} FROM questions WHERE id = ? AND deleted_at IS NULL", AUTHOR_NAME))
+                .bind(id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn get_question_revisions(&self, id: QuestionId) -> Result<Vec<QuestionRevision>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM question_revisions WHERE question_id = ? ORDER BY id")
+                .bind(id.0)
+                .map(to_question_revision)
+                .fetch_all(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn get_question_revision(
+        &self,
+        id: QuestionId,
+        revision_id: RevisionId,
+    ) -> Result<QuestionRevision, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM question_revisions WHERE question_id = ? AND id = ?")
+                .bind(id.0)
+                .bind(revision_id.0)
+                .map(to_question_revision)
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn count_answers(&self, question_id: QuestionId) -> Result<i64, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "SELECT COUNT(*) FROM answers
+                WHERE corresponding_question = ? AND deleted_at IS NULL",
+            )
+                .bind(question_id.0)
+                .fetch_one(&mut *conn)
+                .await
+                .map(|row: SqliteRow| row.get(0)),
+        )
+    }
+
+    async fn vote_question(
+        &self,
+        id: QuestionId,
+        account_id: AccountId,
+        vote: Option<Vote>,
+    ) -> Result<Question, Error> {
+        let tx = self.begin().await?;
+        let question = {
+            let mut conn = tx.conn().await?;
+            // Only live posts take votes; for any other the update below
+            // finds no row.
+            let query = match vote {
+                Some(vote) => sqlx::query(
+                    "INSERT INTO question_votes (question_id, account_id, value)
+                    SELECT ?1, ?2, ?3 FROM questions WHERE id = ?1 AND deleted_at IS NULL
+                    ON CONFLICT (question_id, account_id) DO UPDATE SET value = excluded.value",
+                )
+                .bind(id.0)
+                .bind(account_id.0)
+                .bind(vote.value()),
+                None => sqlx::query("DELETE FROM question_votes WHERE question_id = ?1 AND account_id = ?2")
+                    .bind(id.0)
+                    .bind(account_id.0),
+            };
+            Self::handle_error(query.execute(&mut *conn).await)?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE questions
+                    SET score = (SELECT COALESCE(SUM(value), 0) FROM question_votes WHERE question_id = ?1)
+                    WHERE id = ?1 AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    QUESTION_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(id.0)
+                .map(to_question)
+                .fetch_one(&mut *conn)
+                .await,
+            )?
+        };
+        tx.commit().await?;
+        Ok(question)
+    }
+
+    async fn accept_answer(&self, id: QuestionId, answer_id: AnswerId) -> Result<Question, Error> {
+        let mut conn = self.conn().await?;
+        // Only a live answer to the question itself can be accepted.
+        Self::handle_error(
+            sqlx::query(&format!(
+                "UPDATE questions SET accepted_answer_id = ?2
+                WHERE id = ?1 AND deleted_at IS NULL
+                AND EXISTS (
+                    SELECT 1 FROM answers
+                    WHERE id = ?2 AND corresponding_question = ?1 AND deleted_at IS NULL
+                )
+                RETURNING {}, {}",
+                QUESTION_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(id.0)
+            .bind(answer_id.0)
+            .map(to_question)
+            .fetch_one(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn unaccept_answer(&self, id: QuestionId) -> Result<Question, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!(
+                "UPDATE questions SET accepted_answer_id = NULL
+                WHERE id = ? AND deleted_at IS NULL
+                RETURNING {}, {}",
+                QUESTION_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(id.0)
+            .map(to_question)
+            .fetch_one(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn search(&self, query: String, limit: Option<i32>, offset: i32) -> Result<Vec<SearchResult>, Error> {
+        let mut conn = self.conn().await?;
+        // SQLite has no tsvector, so the live rows are matched like in `MemoryStore`.
+        let rows = Self::handle_error(
+            sqlx::query(
+                "SELECT id AS question_id, NULL AS answer_id, title, title || ' ' || content AS body
+                FROM questions
+                WHERE deleted_at IS NULL
+                UNION ALL
+                SELECT q.id, a.id, q.title, a.content
+                FROM answers a JOIN questions q ON q.id = a.corresponding_question
+                WHERE a.deleted_at IS NULL AND q.deleted_at IS NULL",
+            )
+                .fetch_all(&mut *conn)
+                .await,
+        )?;
+        let terms = query_terms(&query);
+        let mut results: Vec<_> = rows
+            .iter()
+            .filter_map(|row| {
+                let body: String = row.get("body");
+                naive_match(&terms, &body).map(|(rank, snippet)| SearchResult {
+                    question_id: QuestionId(row.get("question_id")),
+                    answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+                    title: row.get("title"),
+                    snippet,
+                    rank,
+                })
+            })
+            .collect();
+        sort_results(&mut results);
+        let results = results.into_iter().skip(offset.max(0) as usize);
+        Ok(match limit {
+            Some(limit) => results.take(limit.max(0) as usize).collect(),
+            None => results.collect(),
+        })
+    }
+}
+
+// Every tag with the number of live questions using it and its synonyms.
+const TAGS_QUERY: &str = "SELECT t.slug, COUNT(q.id) AS questions,
+        (SELECT json_group_array(slug) FROM (
+            SELECT s.slug FROM tag_synonyms s WHERE s.tag_id = t.id ORDER BY s.slug
+        )) AS synonyms
+    FROM tags t
+    LEFT JOIN question_tags qt ON qt.tag_id = t.id
+    LEFT JOIN questions q ON q.id = qt.question_id AND q.deleted_at IS NULL";
+
+// The canonical slug of `?1`, which is `?1` itself unless it is a synonym.
+const CANONICAL_TAG: &str = "coalesce(
+        (SELECT t.slug FROM tag_synonyms s JOIN tags t ON t.id = s.tag_id WHERE s.slug = ?1),
+        ?1
+    )";
+
+#[async_trait::async_trait]
+impl TagStoreTrait for SqliteStore {
+    async fn get_tags(&self, limit: Option<i32>, offset: i32) -> Result<Vec<Tag>, Error> {
+        let query = format!(
+            "{} GROUP BY t.id ORDER BY questions DESC, t.slug LIMIT ? OFFSET ?",
+            TAGS_QUERY
+        );
+        let mut conn = self.conn().await?;
+        // A negative LIMIT means "no limit" in SQLite.
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(limit.unwrap_or(-1))
+                .bind(offset)
+                .map(to_tag)
+                .fetch_all(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn get_tag_questions(
+        &self,
+        slug: String,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Question>, Error> {
+        let query = format!(
+            "SELECT q.*, {} FROM questions q
+            JOIN question_tags qt ON qt.question_id = q.id
+            JOIN tags t ON t.id = qt.tag_id
+            WHERE t.slug = {} AND q.deleted_at IS NULL
+            ORDER BY q.id
+            LIMIT ?2 OFFSET ?3",
+            AUTHOR_NAME, CANONICAL_TAG
+        );
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(slug)
+                .bind(limit.unwrap_or(-1))
+                .bind(offset)
+                .map(to_question)
+                .fetch_all(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn add_tag_synonym(&self, slug: String, synonym: String) -> Result<Tag, Error> {
+        let tx = self.begin().await?;
+        let tag = {
+            let mut conn = tx.conn().await?;
+            let slug: String = Self::handle_error(
+                sqlx::query(&format!("SELECT {}", CANONICAL_TAG))
+                    .bind(slug)
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map(|row: SqliteRow| row.get(0)),
+            )?;
+            if synonym == slug {
+                return Err(Error::InvalidTag(synonym));
+            }
+            // Deleted questions still hold their tags until they are purged.
+            let in_use: bool = Self::handle_error(
+                sqlx::query(
+                    "SELECT EXISTS (
+                        SELECT 1 FROM question_tags qt JOIN tags t ON t.id = qt.tag_id
+                        WHERE t.slug = ?
+                    )",
+                )
+                    .bind(&synonym)
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map(|row: SqliteRow| row.get(0)),
+            )?;
+            if in_use {
+                return Err(Error::TagInUse(synonym));
+            }
+            Self::handle_error(
+                sqlx::query("INSERT OR IGNORE INTO tags (slug) VALUES (?)")
+                    .bind(&slug)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            // An unused tag turns into the synonym, and its own synonyms
+            // move over to the canonical tag.
+            Self::handle_error(
+                sqlx::query(
+                    "UPDATE tag_synonyms SET tag_id = (SELECT id FROM tags WHERE slug = ?2)
+                    WHERE tag_id = (SELECT id FROM tags WHERE slug = ?1)",
+                )
+                    .bind(&synonym)
+                    .bind(&slug)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            Self::handle_error(
+                sqlx::query("DELETE FROM tags WHERE slug = ?")
+                    .bind(&synonym)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO tag_synonyms (slug, tag_id)
+                    SELECT ?1, id FROM tags WHERE slug = ?2
+                    ON CONFLICT (slug) DO UPDATE SET tag_id = excluded.tag_id",
+                )
+                    .bind(&synonym)
+                    .bind(&slug)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!("{} WHERE t.slug = ? GROUP BY t.id", TAGS_QUERY))
+                    .bind(&slug)
+                    .map(to_tag)
+                    .fetch_one(&mut *conn)
+                    .await,
+            )?
+        };
+        tx.commit().await?;
+        Ok(tag)
+    }
+}
+
+#[async_trait::async_trait]
+impl AnswerStoreTrait for SqliteStore {
+    async fn add_answer(&self, new_answer: NewAnswer, account_id: AccountId) -> Result<Answer, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!(
+                "INSERT INTO answers (content, corresponding_question, account_id)
+                SELECT ?1, ?2, ?3
+                WHERE EXISTS (SELECT 1 FROM questions WHERE id = ?2 AND deleted_at IS NULL)
+                RETURNING {}, {}",
+                ANSWER_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(new_answer.content)
+            .bind(new_answer.question_id.0)
+            .bind(account_id.0)
+            .map(to_answer)
+            .fetch_one(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn is_answer_owner(&self, answer_id: i32, account_id: &AccountId) -> Result<bool, Error> {
+        self.check_ownership("answers", answer_id, account_id).await
+    }
+
+    async fn update_answer(&self, answer: Answer, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        let tx = self.begin().await?;
+        let answer = {
+            let mut conn = tx.conn().await?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO answer_revisions (answer_id, content, account_id, created_on)
+                    SELECT id, content, ?2, ?3 FROM answers
+                    WHERE id = ?1 AND account_id = ?2 AND deleted_at IS NULL",
+                )
+                .bind(id)
+                .bind(account_id.0)
+                .bind(Utc::now())
+                .execute(&mut *conn)
+                .await,
+            )?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE answers
+                    SET content = ?, corresponding_question = ?, updated_on = CURRENT_TIMESTAMP,
+                        version = version + 1
+                    WHERE id = ? AND account_id = ? AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    ANSWER_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(answer.content)
+                .bind(answer.question_id.0)
+                .bind(id)
+                .bind(account_id.0)
+                .map(to_answer)
+                .fetch_one(&mut *conn)
+                .await,
+            )?
+        };
+        tx.commit().await?;
+        Ok(answer)
+    }
+
+    async fn delete_answer(&self, id: i32, account_id: AccountId) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE answers SET deleted_at = ?
+                WHERE id = ? AND account_id = ? AND deleted_at IS NULL",
+            )
+            .bind(Utc::now())
+            .bind(id)
+            .bind(account_id.0)
+            .execute(&mut *conn)
+            .await
+            .map(|_| true),
+        )
+    }
+
+    async fn get_answer(&self, id: i32) -> Result<Answer, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&format!("SELECT *, {} FROM answers WHERE id = ? AND deleted_at IS NULL", AUTHOR_NAME))
+                .bind(id)
+                .map(to_answer)
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn get_answer_revisions(&self, id: i32) -> Result<Vec<AnswerRevision>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM answer_revisions WHERE answer_id = ? ORDER BY id")
+                .bind(id)
+                .map(to_answer_revision)
+                .fetch_all(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn vote_answer(
+        &self,
+        id: i32,
+        account_id: AccountId,
+        vote: Option<Vote>,
+    ) -> Result<Answer, Error> {
+        let tx = self.begin().await?;
+        let answer = {
+            let mut conn = tx.conn().await?;
+            // Only live posts take votes; for any other the update below
+            // finds no row.
+            let query = match vote {
+                Some(vote) => sqlx::query(
+                    "INSERT INTO answer_votes (answer_id, account_id, value)
+                    SELECT ?1, ?2, ?3 FROM answers WHERE id = ?1 AND deleted_at IS NULL
+                    ON CONFLICT (answer_id, account_id) DO UPDATE SET value = excluded.value",
+                )
+                .bind(id)
+                .bind(account_id.0)
+                .bind(vote.value()),
+                None => sqlx::query("DELETE FROM answer_votes WHERE answer_id = ?1 AND account_id = ?2")
+                    .bind(id)
+                    .bind(account_id.0),
+            };
+            Self::handle_error(query.execute(&mut *conn).await)?;
+            Self::handle_error(
+                sqlx::query(&format!(
+                    "UPDATE answers
+                    SET score = (SELECT COALESCE(SUM(value), 0) FROM answer_votes WHERE answer_id = ?1)
+                    WHERE id = ?1 AND deleted_at IS NULL
+                    RETURNING {}, {}",
+                    ANSWER_COLUMNS, AUTHOR_NAME
+                ))
+                .bind(id)
+                .map(to_answer)
+                .fetch_one(&mut *conn)
+                .await,
+            )?
+        };
+        tx.commit().await?;
+        Ok(answer)
+    }
+
+    async fn get_answer_revision(&self, id: i32, revision_id: RevisionId) -> Result<AnswerRevision, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM answer_revisions WHERE answer_id = ? AND id = ?")
+                .bind(id)
+                .bind(revision_id.0)
+                .map(to_answer_revision)
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn restore_answer(&self, id: i32, account_id: AccountId) -> Result<Answer, Error> {
+        let mut conn = self.conn().await?;
+        // An answer of a deleted question comes back with the question.
+        Self::handle_error(
+            sqlx::query(&format!(
+                "UPDATE answers SET deleted_at = NULL
+                WHERE id = ? AND account_id = ? AND deleted_at IS NOT NULL
+                AND EXISTS (
+                    SELECT 1 FROM questions
+                    WHERE questions.id = answers.corresponding_question
+                    AND questions.deleted_at IS NULL
+                )
+                RETURNING {}, {}",
+                ANSWER_COLUMNS, AUTHOR_NAME
+            ))
+            .bind(id)
+            .bind(account_id.0)
+            .map(to_answer)
+            .fetch_one(&mut *conn)
+            .await,
+        )
+    }
+}
+
+// The table of the post a comment is attached to, the column of
+// `comments` pointing to it and its id.
+fn comment_parent(parent: &CommentParent) -> (&'static str, &'static str, i32) {
+    match parent {
+        CommentParent::Question(id) => ("questions", "question_id", id.0),
+        CommentParent::Answer(id) => ("answers", "answer_id", id.0),
+    }
+}
+
+#[async_trait::async_trait]
+impl CommentStoreTrait for SqliteStore {
+    async fn get_comments(
+        &self,
+        parent: CommentParent,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<Comment>, Error> {
+        let (table, column, id) = comment_parent(&parent);
+        // Comments go away with their post while it is deleted.
+        let query = format!(
+            "SELECT c.* FROM comments c JOIN {} p ON p.id = c.{}
+            WHERE p.id = ? AND p.deleted_at IS NULL
+            ORDER BY c.created_on, c.id
+            LIMIT ? OFFSET ?",
+            table, column
+        );
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(id)
+                .bind(limit.unwrap_or(-1))
+                .bind(offset)
+                .map(to_comment)
+                .fetch_all(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn add_comment(
+        &self,
+        parent: CommentParent,
+        new_comment: NewComment,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        let (table, column, id) = comment_parent(&parent);
+        // Only live posts take comments; for any other no row is inserted.
+        let query = format!(
+            "INSERT INTO comments (content, {}, account_id, created_on)
+            SELECT ?1, id, ?3, ?4 FROM {} WHERE id = ?2 AND deleted_at IS NULL
+            RETURNING *",
+            column, table
+        );
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(&query)
+                .bind(new_comment.content)
+                .bind(id)
+                .bind(account_id.0)
+                .bind(Utc::now())
+                .map(to_comment)
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn is_comment_owner(&self, comment_id: CommentId, account_id: &AccountId) -> Result<bool, Error> {
+        self.check_ownership("comments", comment_id.0, account_id).await
+    }
+
+    async fn update_comment(
+        &self,
+        comment: NewComment,
+        id: CommentId,
+        account_id: AccountId,
+    ) -> Result<Comment, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE comments SET content = ?1
+                WHERE id = ?2 AND account_id = ?3
+                RETURNING *",
+            )
+            .bind(comment.content)
+            .bind(id.0)
+            .bind(account_id.0)
+            .map(to_comment)
+            .fetch_one(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn delete_comment(&self, id: CommentId, account_id: AccountId) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("DELETE FROM comments WHERE id = ? AND account_id = ?")
+                .bind(id.0)
+                .bind(account_id.0)
+                .execute(&mut *conn)
+                .await
+                .map(|_| true),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl AuthStoreTrait for SqliteStore {
+    async fn add_account(&self, account: Account) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("INSERT INTO accounts (email, password, display_name) VALUES (?, ?, ?)")
+                .bind(account.email)
+                .bind(account.password)
+                .bind(account.display_name)
+                .execute(&mut *conn)
+                .await
+                .map(|_| true),
+        )
+    }
+
+    async fn get_account(&self, email: String) -> Result<Account, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM accounts WHERE email = ?")
+                .bind(email)
+                .map(|row: SqliteRow| Account {
+                    id: Some(AccountId(row.get("id"))),
+                    email: row.get("email"),
+                    password: row.get("password"),
+                    display_name: row.get("display_name"),
+                })
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn update_account(
+        &self,
+        account_id: AccountId,
+        account: AccountUpdateRequest,
+    ) -> Result<AccountResponse, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            // A new email has to be verified again.
+            sqlx::query(
+                "UPDATE accounts
+                SET email = ?1, email_verified = email_verified AND email = ?1
+                WHERE id = ?2
+                RETURNING email, id, email_verified",
+            )
+            .bind(account.email)
+            .bind(account_id.0)
+            .map(to_account_response)
+            .fetch_one(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn update_password(
+        &self,
+        account_id: AccountId,
+        password: AccountUpdatePassword,
+    ) -> Result<bool, Error> {
+        // A new password starts a new token generation and revokes the
+        // refresh tokens, which logs the account out everywhere.
+        let tx = self.begin().await?;
+        {
+            let mut conn = tx.conn().await?;
+            Self::handle_error(
+                sqlx::query(
+                    "UPDATE accounts
+                    SET password = ?, token_generation = token_generation + 1
+                    WHERE id = ?",
+                )
+                .bind(password.0)
+                .bind(account_id.0)
+                .execute(&mut *conn)
+                .await,
+            )?;
+            Self::handle_error(
+                sqlx::query(
+                    "UPDATE sessions SET revoked_on = ?
+                    WHERE account_id = ? AND revoked_on IS NULL",
+                )
+                .bind(timestamp_text(Utc::now()))
+                .bind(account_id.0)
+                .execute(&mut *conn)
+                .await,
+            )?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn get_account_information(&self, account_id: AccountId) -> Result<AccountResponse, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT email, id, email_verified FROM accounts WHERE id = ?")
+                .bind(account_id.0)
+                .map(to_account_response)
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn get_token_generation(&self, account_id: AccountId) -> Result<i32, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT token_generation FROM accounts WHERE id = ?")
+                .bind(account_id.0)
+                .map(|row: SqliteRow| row.get("token_generation"))
+                .fetch_one(&mut *conn)
+                .await,
+        )
+    }
+
+    async fn add_session(
+        &self,
+        session: NewSession,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<SessionId, Error> {
+        let now = timestamp_text(Utc::now());
+        let tx = self.begin().await?;
+        let session_id = {
+            let mut conn = tx.conn().await?;
+            let session_id = Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO sessions
+                    (account_id, user_agent, ip, created_on, last_used_on, expires_on)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    RETURNING id",
+                )
+                .bind(session.account_id.0)
+                .bind(session.user_agent)
+                .bind(session.ip)
+                .bind(&now)
+                .bind(&now)
+                .bind(timestamp_text(expires_on))
+                .map(|row: SqliteRow| SessionId(row.get("id")))
+                .fetch_one(&mut *conn)
+                .await,
+            )?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO refresh_tokens (account_id, session_id, token_hash, created_on, expires_on)
+                    VALUES (?, ?, ?, ?, ?)",
+                )
+                .bind(session.account_id.0)
+                .bind(session_id.0)
+                .bind(token_hash)
+                .bind(&now)
+                .bind(timestamp_text(expires_on))
+                .execute(&mut *conn)
+                .await,
+            )?;
+            session_id
+        };
+        tx.commit().await?;
+        Ok(session_id)
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        token_hash: String,
+        new_token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<(AccountId, SessionId), Error> {
+        let now = timestamp_text(Utc::now());
+        let tx = self.begin().await?;
+        let ids = {
+            let mut conn = tx.conn().await?;
+            // Revoking first lets only one of two concurrent refreshes with
+            // a token succeed.
+            let (account_id, session_id) = Self::handle_error(
+                sqlx::query(
+                    "UPDATE refresh_tokens SET revoked_on = ?
+                    WHERE token_hash = ? AND revoked_on IS NULL AND session_id IN (
+                        SELECT id FROM sessions WHERE revoked_on IS NULL AND expires_on > ?
+                    )
+                    RETURNING account_id, session_id",
+                )
+                .bind(&now)
+                .bind(token_hash)
+                .bind(&now)
+                .map(|row: SqliteRow| (AccountId(row.get("account_id")), SessionId(row.get("session_id"))))
+                .fetch_optional(&mut *conn)
+                .await,
+            )?
+            .ok_or(Error::InvalidRefreshToken)?;
+            Self::handle_error(
+                sqlx::query(
+                    "INSERT INTO refresh_tokens (account_id, session_id, token_hash, created_on, expires_on)
+                    VALUES (?, ?, ?, ?, ?)",
+                )
+                .bind(account_id.0)
+                .bind(session_id.0)
+                .bind(new_token_hash)
+                .bind(&now)
+                .bind(timestamp_text(expires_on))
+                .execute(&mut *conn)
+                .await,
+            )?;
+            Self::handle_error(
+                sqlx::query("UPDATE sessions SET last_used_on = ?, expires_on = ? WHERE id = ?")
+                    .bind(&now)
+                    .bind(timestamp_text(expires_on))
+                    .bind(session_id.0)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            (account_id, session_id)
+        };
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    async fn revoke_refresh_token(&self, token_hash: String) -> Result<bool, Error> {
+        let now = timestamp_text(Utc::now());
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE sessions SET revoked_on = ?
+                WHERE revoked_on IS NULL AND expires_on > ? AND id IN (
+                    SELECT session_id FROM refresh_tokens
+                    WHERE token_hash = ? AND revoked_on IS NULL
+                )",
+            )
+            .bind(&now)
+            .bind(&now)
+            .bind(token_hash)
+            .execute(&mut *conn)
+            .await
+            .map(|result| result.rows_affected() > 0),
+        )
+    }
+
+    async fn touch_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        let now = Utc::now();
+        let mut conn = self.conn().await?;
+        // Checked on every request, so the row is only written when its last
+        // use is out of date.
+        let stale = Self::handle_error(
+            sqlx::query(
+                "SELECT last_used_on < ? FROM sessions
+                WHERE id = ? AND account_id = ? AND revoked_on IS NULL AND expires_on > ?",
+            )
+            .bind(timestamp_text(now - Duration::seconds(SESSION_TOUCH_SECONDS)))
+            .bind(session_id.0)
+            .bind(account_id.0)
+            .bind(timestamp_text(now))
+            .map(|row: SqliteRow| row.get::<bool, _>(0))
+            .fetch_optional(&mut *conn)
+            .await,
+        )?;
+        if stale == Some(true) {
+            Self::handle_error(
+                sqlx::query("UPDATE sessions SET last_used_on = ? WHERE id = ?")
+                    .bind(timestamp_text(now))
+                    .bind(session_id.0)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+        }
+        Ok(stale.is_some())
+    }
+
+    async fn get_sessions(&self, account_id: AccountId) -> Result<Vec<ActiveSession>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "SELECT id, user_agent, ip, created_on, last_used_on FROM sessions
+                WHERE account_id = ? AND revoked_on IS NULL AND expires_on > ?
+                ORDER BY last_used_on DESC, id DESC",
+            )
+            .bind(account_id.0)
+            .bind(timestamp_text(Utc::now()))
+            .map(to_session)
+            .fetch_all(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn revoke_session(&self, account_id: AccountId, session_id: SessionId) -> Result<bool, Error> {
+        let now = timestamp_text(Utc::now());
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE sessions SET revoked_on = ?
+                WHERE id = ? AND account_id = ? AND revoked_on IS NULL AND expires_on > ?",
+            )
+            .bind(&now)
+            .bind(session_id.0)
+            .bind(account_id.0)
+            .bind(&now)
+            .execute(&mut *conn)
+            .await
+            .map(|result| result.rows_affected() > 0),
+        )
+    }
+
+    async fn revoke_other_sessions(&self, account_id: AccountId, session_id: SessionId) -> Result<u64, Error> {
+        let now = timestamp_text(Utc::now());
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "UPDATE sessions SET revoked_on = ?
+                WHERE account_id = ? AND id != ? AND revoked_on IS NULL AND expires_on > ?",
+            )
+            .bind(&now)
+            .bind(account_id.0)
+            .bind(session_id.0)
+            .bind(&now)
+            .execute(&mut *conn)
+            .await
+            .map(|result| result.rows_affected()),
+        )
+    }
+
+    async fn add_verification_token(
+        &self,
+        account_id: AccountId,
+        email: String,
+        token_hash: String,
+        expires_on: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "INSERT INTO verification_tokens (account_id, email, token_hash, created_on, expires_on)
+                VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(account_id.0)
+            .bind(email)
+            .bind(token_hash)
+            .bind(timestamp_text(Utc::now()))
+            .bind(timestamp_text(expires_on))
+            .execute(&mut *conn)
+            .await
+            .map(|_| true),
+        )
+    }
+
+    async fn get_verification_sent_on(&self, account_id: AccountId) -> Result<Option<DateTime<Utc>>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "SELECT created_on FROM verification_tokens
+                WHERE account_id = ? AND used_on IS NULL AND expires_on > ?
+                ORDER BY created_on DESC
+                LIMIT 1",
+            )
+            .bind(account_id.0)
+            .bind(timestamp_text(Utc::now()))
+            .fetch_optional(&mut *conn)
+            .await
+            .map(|row: Option<SqliteRow>| row.map(|row| row.get::<NaiveDateTime, _>("created_on").and_utc())),
+        )
+    }
+
+    async fn verify_email(&self, token_hash: String) -> Result<AccountId, Error> {
+        let now = timestamp_text(Utc::now());
+        let tx = self.begin().await?;
+        let account_id = {
+            let mut conn = tx.conn().await?;
+            // A token does not verify an email the account changed since.
+            let account_id = Self::handle_error(
+                sqlx::query(
+                    "UPDATE accounts SET email_verified = TRUE
+                    WHERE EXISTS (
+                        SELECT 1 FROM verification_tokens t
+                        WHERE t.token_hash = ?1 AND t.used_on IS NULL AND t.expires_on > ?2
+                            AND t.account_id = accounts.id AND t.email = accounts.email
+                    )
+                    RETURNING id",
+                )
+                .bind(&token_hash)
+                .bind(&now)
+                .map(|row: SqliteRow| AccountId(row.get("id")))
+                .fetch_optional(&mut *conn)
+                .await,
+            )?
+            .ok_or(Error::InvalidVerificationToken)?;
+            Self::handle_error(
+                sqlx::query("UPDATE verification_tokens SET used_on = ? WHERE token_hash = ?")
+                    .bind(&now)
+                    .bind(token_hash)
+                    .execute(&mut *conn)
+                    .await,
+            )?;
+            account_id
+        };
+        tx.commit().await?;
+        Ok(account_id)
+    }
+}
+
+#[async_trait::async_trait]
+impl ImportStoreTrait for SqliteStore {
+    async fn set_question_created_on(&self, id: QuestionId, created_on: DateTime<Utc>) -> Result<(), Error> {
+        let mut conn = self.conn().await?;
+        let updated = Self::handle_error(
+            sqlx::query("UPDATE questions SET created_on = ? WHERE id = ?")
+                .bind(timestamp_text(created_on))
+                .bind(id.0)
+                .execute(&mut *conn)
+                .await,
+        )?;
+        match updated.rows_affected() {
+            0 => Err(Error::DatabaseQueryError(sqlx::Error::RowNotFound)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn set_answer_created_on(&self, id: AnswerId, created_on: DateTime<Utc>) -> Result<(), Error> {
+        let mut conn = self.conn().await?;
+        let updated = Self::handle_error(
+            sqlx::query("UPDATE answers SET created_on = ? WHERE id = ?")
+                .bind(timestamp_text(created_on))
+                .bind(id.0)
+                .execute(&mut *conn)
+                .await,
+        )?;
+        match updated.rows_affected() {
+            0 => Err(Error::DatabaseQueryError(sqlx::Error::RowNotFound)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn get_imported_post(&self, source_id: i64) -> Result<Option<ImportedPost>, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query("SELECT * FROM imported_posts WHERE source_id = ?")
+                .bind(source_id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map(|row: Option<SqliteRow>| {
+                    row.map(|row| ImportedPost {
+                        question_id: QuestionId(row.get("question_id")),
+                        answer_id: row.get::<Option<i32>, _>("answer_id").map(AnswerId),
+                        accepted_source_id: row.get("accepted_source_id"),
+                    })
+                }),
+        )
+    }
+
+    async fn add_imported_post(&self, source_id: i64, post: ImportedPost) -> Result<(), Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "INSERT INTO imported_posts (source_id, question_id, answer_id, accepted_source_id)
+                VALUES (?, ?, ?, ?)",
+            )
+            .bind(source_id)
+            .bind(post.question_id.0)
+            .bind(post.answer_id.map(|id| id.0))
+            .bind(post.accepted_source_id)
+            .execute(&mut *conn)
+            .await,
+        )?;
+        Ok(())
+    }
+
+    async fn set_display_name(&self, email: String, display_name: String) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        let updated = Self::handle_error(
+            sqlx::query("UPDATE accounts SET display_name = ? WHERE email = ?")
+                .bind(display_name)
+                .bind(email)
+                .execute(&mut *conn)
+                .await,
+        )?;
+        Ok(updated.rows_affected() > 0)
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditStoreTrait for SqliteStore {
+    async fn add_audit_record(&self, record: NewAuditRecord) -> Result<AuditRecord, Error> {
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            sqlx::query(
+                "INSERT INTO audit_log
+                (actor, action, target, target_id, row_before, row_after, request_id, created_on)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                RETURNING *",
+            )
+            .bind(record.actor.0)
+            .bind(record.action.as_str())
+            .bind(record.action.target().as_str())
+            .bind(record.target_id)
+            .bind(record.before.map(Json))
+            .bind(record.after.map(Json))
+            .bind(record.request_id)
+            .bind(timestamp_text(Utc::now()))
+            .try_map(to_audit_record)
+            .fetch_one(&mut *conn)
+            .await,
+        )
+    }
+
+    async fn get_audit_records(
+        &self,
+        filter: AuditFilter,
+        limit: Option<i32>,
+        offset: i32,
+    ) -> Result<Vec<AuditRecord>, Error> {
+        let mut query = QueryBuilder::new("SELECT * FROM audit_log WHERE 1");
+        if let Some(actor) = filter.actor {
+            query.push(" AND actor = ").push_bind(actor.0);
+        }
+        if let Some(action) = filter.action {
+            query.push(" AND action = ").push_bind(action.as_str());
+        }
+        if let Some(target) = filter.target {
+            query.push(" AND target = ").push_bind(target.as_str());
+        }
+        if let Some(target_id) = filter.target_id {
+            query.push(" AND target_id = ").push_bind(target_id);
+        }
+        if let Some(request_id) = filter.request_id {
+            query.push(" AND request_id = ").push_bind(request_id);
+        }
+        if let Some(after) = filter.created_after {
+            query.push(" AND created_on >= ").push_bind(timestamp_text(after));
+        }
+        if let Some(before) = filter.created_before {
+            query.push(" AND created_on < ").push_bind(timestamp_text(before));
+        }
+        query.push(" ORDER BY created_on DESC, id DESC");
+        query.push(" LIMIT ").push_bind(limit.unwrap_or(-1));
+        query.push(" OFFSET ").push_bind(offset);
+
+        let mut conn = self.conn().await?;
+        Self::handle_error(
+            query
+                .build()
+                .try_map(to_audit_record)
+                .fetch_all(&mut *conn)
+                .await,
+        )
+    }
+}
@@ -9,6 +9,64 @@ pub struct Session {
     pub account_id: AccountId,
     /// Time before which the session is not valid in UTC.
     pub nbf: DateTime<Utc>,
+    /// Token generation of the account when the token was issued. Changing
+    /// the password starts a new generation, which revokes older tokens.
+    #[serde(default)]
+    pub generation: i32,
+    /// ID of the login session the token belongs to.
+    pub session_id: SessionId,
+}
+
+/// Represents a unique identifier for a login session.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SessionId(pub i32);
+
+/// Used for starting a login session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewSession {
+    /// ID of the account which logged in.
+    pub account_id: AccountId,
+    /// The `User-Agent` header of the login request.
+    pub user_agent: Option<String>,
+    /// IP address the login request came from.
+    pub ip: Option<String>,
+}
+
+/// A login session which has not been revoked or expired, as listed by
+/// `/accounts/me/sessions`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ActiveSession {
+    /// ID of the session.
+    pub id: SessionId,
+    /// Time of the login in UTC.
+    pub created_on: DateTime<Utc>,
+    /// Time the session was last used in UTC.
+    pub last_used_on: DateTime<Utc>,
+    /// The `User-Agent` header of the login request.
+    pub user_agent: Option<String>,
+    /// IP address the login request came from.
+    pub ip: Option<String>,
+    /// Whether this is the session of the token the list was asked with.
+    #[serde(default)]
+    pub current: bool,
+}
+
+/// The tokens handed out by `/login` and `/token/refresh`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tokens {
+    /// Short-lived token sent as the `Authorization` header.
+    pub access_token: String,
+    /// Seconds until the access token expires.
+    pub expires_in: i64,
+    /// Single-use token which gets a new pair of tokens from
+    /// `/token/refresh`.
+    pub refresh_token: String,
+}
+
+/// Used for refreshing tokens and logging out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
 }
 
 /// Represents a user account with their credentials.
@@ -20,12 +78,25 @@ pub struct Account {
     pub email: String,
     /// Password for the account (stored securely in a production environment).
     pub password: String,
+    /// Name shown next to the posts of the account, optional at registration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
 }
 
 /// Represents a unique identifier for an account.
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct AccountId(pub i32);
 
+/// The account which wrote a question or an answer, as shown next to it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct Author {
+    /// ID of the account.
+    pub id: AccountId,
+    /// The display name of the account, or the part of its email before the
+    /// `@` if it has none.
+    pub display_name: String,
+}
+
 /// Used for requesting email updates.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -42,6 +113,16 @@ pub struct AccountResponse {
     pub email: String,
     /// ID of the account.
     pub id: AccountId,
+    /// Whether the account proved it owns `email`. Only verified accounts
+    /// can add questions and answers.
+    #[serde(default)]
+    pub email_verified: bool,
+}
+
+/// Used for verifying an email address with the token mailed to it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VerificationRequest {
+    pub token: String,
 }
 
 /// Used for requesting password updates.
@@ -1,4 +1,6 @@
+use crate::types::account::Author;
 use crate::types::question::QuestionId;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Represents an answer to a question.
@@ -10,6 +12,22 @@ pub struct Answer {
     pub content: String,
     /// ID of the question this answer is associated with.
     pub question_id: QuestionId,
+    /// Sum of the votes on the answer. Ignored when an answer is updated.
+    #[serde(default)]
+    pub score: i32,
+    /// The account which wrote the answer. Ignored when an answer is updated.
+    #[serde(default)]
+    pub author: Author,
+    /// When the answer was written. Ignored when an answer is updated.
+    #[serde(default)]
+    pub created_on: DateTime<Utc>,
+    /// When the answer was last edited, if ever. Ignored when an answer is updated.
+    #[serde(default)]
+    pub updated_on: Option<DateTime<Utc>>,
+    /// Starts at 1 and goes up with every edit; sent as the `ETag`. Ignored
+    /// when an answer is updated.
+    #[serde(default)]
+    pub version: i32,
 }
 
 /// Represents a unique identifier for an answer.
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use handle_errors::Error;
+
+use crate::types::account::AccountId;
+use crate::types::filter::parse_time;
+
+/// What an audited mutation changed.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditTarget {
+    Question,
+    Answer,
+    Account,
+}
+
+impl AuditTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditTarget::Question => "question",
+            AuditTarget::Answer => "answer",
+            AuditTarget::Account => "account",
+        }
+    }
+}
+
+impl FromStr for AuditTarget {
+    type Err = Error;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        match target {
+            "question" => Ok(AuditTarget::Question),
+            "answer" => Ok(AuditTarget::Answer),
+            "account" => Ok(AuditTarget::Account),
+            _ => Err(Error::InvalidParameter("target".to_string())),
+        }
+    }
+}
+
+/// The mutations written to the audit log.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    AddQuestion,
+    UpdateQuestion,
+    DeleteQuestion,
+    RestoreQuestion,
+    AddAnswer,
+    UpdateAnswer,
+    DeleteAnswer,
+    RestoreAnswer,
+    AddAccount,
+    UpdateAccount,
+    UpdatePassword,
+    VerifyEmail,
+}
+
+impl AuditAction {
+    const ALL: [AuditAction; 12] = [
+        AuditAction::AddQuestion,
+        AuditAction::UpdateQuestion,
+        AuditAction::DeleteQuestion,
+        AuditAction::RestoreQuestion,
+        AuditAction::AddAnswer,
+        AuditAction::UpdateAnswer,
+        AuditAction::DeleteAnswer,
+        AuditAction::RestoreAnswer,
+        AuditAction::AddAccount,
+        AuditAction::UpdateAccount,
+        AuditAction::UpdatePassword,
+        AuditAction::VerifyEmail,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::AddQuestion => "add_question",
+            AuditAction::UpdateQuestion => "update_question",
+            AuditAction::DeleteQuestion => "delete_question",
+            AuditAction::RestoreQuestion => "restore_question",
+            AuditAction::AddAnswer => "add_answer",
+            AuditAction::UpdateAnswer => "update_answer",
+            AuditAction::DeleteAnswer => "delete_answer",
+            AuditAction::RestoreAnswer => "restore_answer",
+            AuditAction::AddAccount => "add_account",
+            AuditAction::UpdateAccount => "update_account",
+            AuditAction::UpdatePassword => "update_password",
+            AuditAction::VerifyEmail => "verify_email",
+        }
+    }
+
+    /// The kind of row the action changes.
+    pub fn target(&self) -> AuditTarget {
+        match self {
+            AuditAction::AddQuestion
+            | AuditAction::UpdateQuestion
+            | AuditAction::DeleteQuestion
+            | AuditAction::RestoreQuestion => AuditTarget::Question,
+            AuditAction::AddAnswer
+            | AuditAction::UpdateAnswer
+            | AuditAction::DeleteAnswer
+            | AuditAction::RestoreAnswer => AuditTarget::Answer,
+            AuditAction::AddAccount
+            | AuditAction::UpdateAccount
+            | AuditAction::UpdatePassword
+            | AuditAction::VerifyEmail => AuditTarget::Account,
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = Error;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        AuditAction::ALL
+            .into_iter()
+            .find(|known| known.as_str() == action)
+            .ok_or_else(|| Error::InvalidParameter("action".to_string()))
+    }
+}
+
+/// Represents a unique identifier for an audit record.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AuditRecordId(pub i32);
+
+/// A mutation as it was written to the audit log.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub id: AuditRecordId,
+    /// The account which made the change.
+    pub actor: AccountId,
+    pub action: AuditAction,
+    pub target: AuditTarget,
+    /// Id of the question, answer or account changed.
+    pub target_id: i32,
+    /// The row before the change, if there was one. Passwords are never
+    /// part of it.
+    pub before: Option<Value>,
+    /// The row after the change, if there still is one.
+    pub after: Option<Value>,
+    /// Id of the request which made the change, from its `X-Request-Id`
+    /// header or generated. Changes made outside of a request have none.
+    pub request_id: Option<String>,
+    pub created_on: DateTime<Utc>,
+}
+
+/// Used for writing a record to the audit log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewAuditRecord {
+    pub actor: AccountId,
+    pub action: AuditAction,
+    pub target_id: i32,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub request_id: Option<String>,
+}
+
+/// Filter of the records listed by `/admin/audit`. The default lists every
+/// record, newest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditFilter {
+    pub actor: Option<AccountId>,
+    pub action: Option<AuditAction>,
+    pub target: Option<AuditTarget>,
+    pub target_id: Option<i32>,
+    pub request_id: Option<String>,
+    /// Only records written at or after this time.
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only records written before this time.
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    /// Whether `record` passes the filter.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.actor.as_ref().is_none_or(|actor| record.actor == *actor)
+            && self.action.is_none_or(|action| record.action == action)
+            && self.target.is_none_or(|target| record.target == target)
+            && self.target_id.is_none_or(|target_id| record.target_id == target_id)
+            && self
+                .request_id
+                .as_ref()
+                .is_none_or(|request_id| record.request_id.as_ref() == Some(request_id))
+            && self.created_after.is_none_or(|after| record.created_on >= after)
+            && self.created_before.is_none_or(|before| record.created_on < before)
+    }
+}
+
+/// Extract the filter from the query of the `/admin/audit` route
+/// # Example query
+/// Times are RFC 3339 or plain dates (midnight UTC).
+/// `/admin/audit?actor=3&action=update_question&target=question&target_id=7&created_after=2024-01-01`
+/// The parameters which are no filter, like the pagination, are returned.
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::audit::{self, AuditAction, AuditTarget};
+/// let mut query = HashMap::new();
+/// query.insert("action".to_string(), "delete_answer".to_string());
+/// query.insert("limit".to_string(), "10".to_string());
+/// let (f, rest) = audit::extract_audit_filter(query).unwrap();
+/// assert_eq!(f.action, Some(AuditAction::DeleteAnswer));
+/// assert_eq!(rest.get("limit").unwrap(), "10");
+/// ```
+pub fn extract_audit_filter(
+    params: HashMap<String, String>,
+) -> Result<(AuditFilter, HashMap<String, String>), Error> {
+    let mut filter = AuditFilter::default();
+    let mut rest = HashMap::new();
+
+    for (key, value) in params {
+        let invalid = || Error::InvalidParameter(key.clone());
+        match key.as_str() {
+            "actor" => filter.actor = Some(AccountId(value.parse().map_err(|_| invalid())?)),
+            "action" => filter.action = Some(value.parse()?),
+            "target" => filter.target = Some(value.parse()?),
+            "target_id" => filter.target_id = Some(value.parse().map_err(|_| invalid())?),
+            "request_id" => filter.request_id = Some(value),
+            "created_after" => filter.created_after = Some(parse_time(&value).ok_or_else(invalid)?),
+            "created_before" => filter.created_before = Some(parse_time(&value).ok_or_else(invalid)?),
+            _ => {
+                rest.insert(key, value);
+            }
+        }
+    }
+
+    Ok((filter, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_audit_filter() {
+        let (filter, rest) = extract_audit_filter(query(&[
+            ("actor", "3"),
+            ("action", "update_question"),
+            ("target", "question"),
+            ("target_id", "7"),
+            ("request_id", "abc"),
+            ("created_after", "2024-01-01"),
+            ("offset", "0"),
+        ]))
+        .unwrap();
+
+        assert_eq!(filter.actor, Some(AccountId(3)));
+        assert_eq!(filter.action, Some(AuditAction::UpdateQuestion));
+        assert_eq!(filter.target, Some(AuditTarget::Question));
+        assert_eq!(filter.target_id, Some(7));
+        assert_eq!(filter.request_id.as_deref(), Some("abc"));
+        assert_eq!(filter.created_after.unwrap().to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(rest, query(&[("offset", "0")]));
+
+        assert!(extract_audit_filter(query(&[("action", "drop_table")])).is_err());
+        assert!(extract_audit_filter(query(&[("target_id", "seven")])).is_err());
+    }
+
+    #[test]
+    fn test_action_names_round_trip() {
+        for action in AuditAction::ALL {
+            assert_eq!(action.as_str().parse::<AuditAction>().unwrap(), action);
+            assert_eq!(serde_json::to_value(action).unwrap(), action.as_str());
+            assert_eq!(action.target().as_str().parse::<AuditTarget>().unwrap(), action.target());
+        }
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use handle_errors::Error;
+
+use crate::types::tag::normalize_tags;
+
+/// File formats questions are imported from and exported to.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// One object mapping ids to questions, like `questions.json`. Arrays of
+    /// questions are imported as well.
+    #[default]
+    Json,
+    /// One question per line.
+    Ndjson,
+    /// One question or answer per row; see `CsvRow`.
+    Csv,
+}
+
+impl Format {
+    /// The format of a file, guessed from its extension.
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(Format::Json),
+            Some("ndjson") | Some("jsonl") => Ok(Format::Ndjson),
+            Some("csv") => Ok(Format::Csv),
+            _ => Err(Error::InvalidParameter("format".to_string())),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Ndjson => "application/x-ndjson",
+            Format::Csv => "text/csv",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "csv" => Ok(Format::Csv),
+            _ => Err(Error::InvalidParameter("format".to_string())),
+        }
+    }
+}
+
+/// A question with its answers, as imported and exported.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuestionRecord {
+    /// Id of the question in the file, a string or a number. Only used to
+    /// tie answers to their question in CSV; imported questions get new ids.
+    #[serde(default, deserialize_with = "deserialize_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub answers: Vec<AnswerRecord>,
+}
+
+/// An answer of a `QuestionRecord`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnswerRecord {
+    pub content: String,
+}
+
+/// A row of a CSV file. A row with a `question_id` is an answer to the
+/// question with that `id`; any other row is a question. `tags` are
+/// separated by `;`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct CsvRow {
+    pub id: String,
+    pub question_id: String,
+    pub title: String,
+    pub content: String,
+    pub tags: String,
+}
+
+/// A record of an import that could not be read or is invalid.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RowError {
+    /// The line of the record for NDJSON and CSV, its position for JSON,
+    /// counting from 1.
+    pub row: usize,
+    pub message: String,
+}
+
+/// Outcome of an import. Nothing is imported unless `errors` is empty.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    pub questions: usize,
+    pub answers: usize,
+    pub errors: Vec<RowError>,
+}
+
+// Ids are strings in `questions.json`, but numbers are just as likely.
+fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Text(String),
+        Number(i64),
+    }
+    Ok(Option::<Id>::deserialize(deserializer)?.map(|id| match id {
+        Id::Text(id) => id,
+        Id::Number(id) => id.to_string(),
+    }))
+}
+
+/// Extract the `format` query parameter of the `/admin/import` and
+/// `/admin/export` routes
+/// # Example query
+/// `json` (default), `ndjson` or `csv`
+/// `/admin/export?format=csv`
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::bulk::{self, Format};
+/// let mut query = HashMap::new();
+/// assert_eq!(bulk::extract_format(&query).unwrap(), Format::Json);
+/// query.insert("format".to_string(), "csv".to_string());
+/// assert_eq!(bulk::extract_format(&query).unwrap(), Format::Csv);
+/// ```
+pub fn extract_format(params: &HashMap<String, String>) -> Result<Format, Error> {
+    match params.get("format") {
+        None => Ok(Format::default()),
+        Some(format) => format.parse(),
+    }
+}
+
+/// Reads the questions of an import in `format` and validates every one of
+/// them. Returns the valid questions, tags normalized, and an error for each
+/// record that could not be read or is invalid.
+pub fn parse_records(input: &str, format: Format) -> (Vec<QuestionRecord>, Vec<RowError>) {
+    let rows = match format {
+        Format::Json => parse_json(input),
+        Format::Ndjson => parse_ndjson(input),
+        Format::Csv => parse_csv(input),
+    };
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for (row, record) in rows {
+        match record.and_then(validate_record) {
+            Ok(record) => records.push(record),
+            Err(message) => errors.push(RowError { row, message }),
+        }
+    }
+    (records, errors)
+}
+
+fn parse_json(input: &str) -> Vec<(usize, Result<QuestionRecord, String>)> {
+    let values = match serde_json::from_str::<serde_json::Value>(input) {
+        Ok(serde_json::Value::Object(map)) => map.into_iter().map(|(_, value)| value).collect(),
+        Ok(serde_json::Value::Array(values)) => values,
+        Ok(_) => return vec![(1, Err("expected an object or an array of questions".to_string()))],
+        Err(e) => return vec![(e.line(), Err(e.to_string()))],
+    };
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| (index + 1, serde_json::from_value(value).map_err(|e| e.to_string())))
+        .collect()
+}
+
+fn parse_ndjson(input: &str) -> Vec<(usize, Result<QuestionRecord, String>)> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index + 1, serde_json::from_str(line).map_err(|e| e.to_string())))
+        .collect()
+}
+
+fn parse_csv(input: &str) -> Vec<(usize, Result<QuestionRecord, String>)> {
+    let mut questions: Vec<(usize, Result<QuestionRecord, String>)> = Vec::new();
+    let mut answers = Vec::new();
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => return vec![(1, Err(e.to_string()))],
+    };
+    for result in reader.records() {
+        let (row, result) = match result {
+            Ok(record) => (
+                record.position().map_or(0, |position| position.line() as usize),
+                record.deserialize::<CsvRow>(Some(&headers)),
+            ),
+            Err(e) => (e.position().map_or(0, |position| position.line() as usize), Err(e)),
+        };
+        match result {
+            Err(e) => questions.push((row, Err(e.to_string()))),
+            Ok(csv_row) if !csv_row.question_id.is_empty() => answers.push((row, csv_row)),
+            Ok(csv_row) => {
+                let id = Some(csv_row.id).filter(|id| !id.is_empty());
+                let duplicate = id.is_some()
+                    && questions
+                        .iter()
+                        .any(|(_, question)| matches!(question, Ok(question) if question.id == id));
+                let tags = csv_row
+                    .tags
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                let question = QuestionRecord {
+                    id,
+                    title: csv_row.title,
+                    content: csv_row.content,
+                    tags: Some(tags).filter(|tags| !tags.is_empty()),
+                    answers: Vec::new(),
+                };
+                if duplicate {
+                    questions.push((row, Err(format!("question id {:?} is used twice", question.id.unwrap()))));
+                } else {
+                    questions.push((row, Ok(question)));
+                }
+            }
+        }
+    }
+    for (row, answer) in answers {
+        let question = questions.iter_mut().find_map(|(_, question)| match question {
+            Ok(question) if question.id.as_deref() == Some(&answer.question_id) => Some(question),
+            _ => None,
+        });
+        match question {
+            Some(question) => question.answers.push(AnswerRecord { content: answer.content }),
+            None => questions.push((row, Err(format!("no question with id {:?}", answer.question_id)))),
+        }
+    }
+    questions.sort_by_key(|(row, _)| *row);
+    questions
+}
+
+// Trims the texts of a record and normalizes its tags.
+fn validate_record(record: QuestionRecord) -> Result<QuestionRecord, String> {
+    let title = record.title.trim();
+    if title.is_empty() {
+        return Err("title must not be empty".to_string());
+    }
+    let content = record.content.trim();
+    if content.is_empty() {
+        return Err("content must not be empty".to_string());
+    }
+    let tags = normalize_tags(record.tags).map_err(|e| e.to_string())?;
+    let answers = record
+        .answers
+        .into_iter()
+        .enumerate()
+        .map(|(index, answer)| match answer.content.trim() {
+            "" => Err(format!("answer {}: content must not be empty", index + 1)),
+            content => Ok(AnswerRecord { content: content.to_string() }),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(QuestionRecord {
+        id: record.id,
+        title: title.to_string(),
+        content: content.to_string(),
+        tags,
+        answers,
+    })
+}
+
+/// What an export in `format` starts with, before its first record.
+pub fn export_header(format: Format) -> String {
+    match format {
+        Format::Json => "{\n".to_string(),
+        Format::Ndjson => String::new(),
+        Format::Csv => "id,question_id,title,content,tags\n".to_string(),
+    }
+}
+
+/// What an export in `format` ends with, after its last record.
+pub fn export_footer(format: Format) -> String {
+    match format {
+        Format::Json => "\n}\n".to_string(),
+        Format::Ndjson | Format::Csv => String::new(),
+    }
+}
+
+/// Writes `record` the way an export in `format` holds it. `first` tells
+/// whether it is the first record, which JSON writes without a leading comma.
+pub fn export_record(format: Format, record: &QuestionRecord, first: bool) -> String {
+    match format {
+        Format::Json => format!(
+            "{}{}: {}",
+            if first { "" } else { ",\n" },
+            serde_json::json!(record.id.clone().unwrap_or_default()),
+            serde_json::json!(record)
+        ),
+        Format::Ndjson => format!("{}\n", serde_json::json!(record)),
+        Format::Csv => {
+            let id = record.id.clone().unwrap_or_default();
+            let mut rows = vec![CsvRow {
+                id: id.clone(),
+                question_id: String::new(),
+                title: record.title.clone(),
+                content: record.content.clone(),
+                tags: record.tags.clone().unwrap_or_default().join(";"),
+            }];
+            rows.extend(record.answers.iter().map(|answer| CsvRow {
+                question_id: id.clone(),
+                content: answer.content.clone(),
+                ..CsvRow::default()
+            }));
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(Vec::new());
+            for row in rows {
+                // Writing into a `Vec` cannot fail.
+                writer.serialize(row).unwrap();
+            }
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, answers: &[&str]) -> QuestionRecord {
+        QuestionRecord {
+            id: Some(id.to_string()),
+            title: "How?".to_string(),
+            content: "Please help!".to_string(),
+            tags: Some(vec!["general".to_string(), "c#".to_string()]),
+            answers: answers
+                .iter()
+                .map(|content| AnswerRecord { content: content.to_string() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_sample_file() {
+        let (records, errors) = parse_records(include_str!("../../questions.json"), Format::Json);
+        assert!(errors.is_empty());
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id.as_deref(), Some("1"));
+        assert_eq!(records[0].tags, Some(vec!["general".to_string()]));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let records = vec![record("1", &["Like this", "Or, \"that\""]), record("2", &[])];
+        for format in [Format::Json, Format::Ndjson, Format::Csv] {
+            let mut export = export_header(format);
+            for (index, record) in records.iter().enumerate() {
+                export.push_str(&export_record(format, record, index == 0));
+            }
+            export.push_str(&export_footer(format));
+            assert_eq!(parse_records(&export, format), (records.clone(), vec![]), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_parse_reports_every_invalid_row() {
+        let input = concat!(
+            "{\"title\": \"How?\", \"content\": \"Please help!\", \"tags\": [\"Rust\"]}\n",
+            "{\"title\": \" \", \"content\": \"Please help!\"}\n",
+            "\n",
+            "not json\n",
+            "{\"title\": \"How?\", \"content\": \"x\", \"answers\": [{\"content\": \"\"}]}\n",
+        );
+        let (records, errors) = parse_records(input, Format::Ndjson);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tags, Some(vec!["rust".to_string()]));
+        let rows: Vec<usize> = errors.iter().map(|error| error.row).collect();
+        assert_eq!(rows, [2, 4, 5]);
+        assert_eq!(errors[2].message, "answer 1: content must not be empty");
+    }
+
+    #[test]
+    fn test_parse_csv_answers() {
+        let input = "id,question_id,title,content,tags\n\
+                     ,q1,,Answer before its question,\n\
+                     q1,,How?,Please help!,rust; async\n\
+                     q1,,Again?,Please help!,\n\
+                     ,q2,,Lost answer,\n";
+        let (records, errors) = parse_records(input, Format::Csv);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].answers, [AnswerRecord { content: "Answer before its question".to_string() }]);
+        assert_eq!(records[0].tags, Some(vec!["rust".to_string(), "async".to_string()]));
+        assert_eq!(
+            errors,
+            [
+                RowError { row: 4, message: "question id \"q1\" is used twice".to_string() },
+                RowError { row: 5, message: "no question with id \"q2\"".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path(Path::new("questions.json")).unwrap(), Format::Json);
+        assert_eq!(Format::from_path(Path::new("dump.jsonl")).unwrap(), Format::Ndjson);
+        assert!(Format::from_path(Path::new("questions.xml")).is_err());
+    }
+}
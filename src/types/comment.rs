@@ -0,0 +1,79 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use handle_errors::Error;
+
+use crate::types::account::AccountId;
+use crate::types::answer::AnswerId;
+use crate::types::question::QuestionId;
+
+/// Maximum length of a comment in characters.
+pub const MAX_COMMENT_LENGTH: usize = 600;
+
+/// A short remark on a question or an answer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// Unique identifier for the comment.
+    pub id: CommentId,
+    /// Content of the comment.
+    pub content: String,
+    /// The question commented on, if the comment is on a question.
+    pub question_id: Option<QuestionId>,
+    /// The answer commented on, if the comment is on an answer.
+    pub answer_id: Option<AnswerId>,
+    /// The account which wrote the comment.
+    pub account_id: AccountId,
+    /// When the comment was written.
+    pub created_on: DateTime<Utc>,
+}
+
+/// Represents a unique identifier for a comment.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CommentId(pub i32);
+
+/// Used for writing and editing comments.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NewComment {
+    /// Content of the comment.
+    pub content: String,
+}
+
+/// The post a comment is attached to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentParent {
+    Question(QuestionId),
+    Answer(AnswerId),
+}
+
+/// Trims the content of a comment and checks that it is neither empty nor
+/// longer than `MAX_COMMENT_LENGTH` characters.
+/// # Example usage
+/// ```rust
+/// use rust_hour::types::comment;
+/// assert_eq!(comment::validate_comment(" Which version? ").unwrap(), "Which version?");
+/// assert!(comment::validate_comment("  ").is_err());
+/// ```
+pub fn validate_comment(content: &str) -> Result<String, Error> {
+    let content = content.trim();
+    if content.is_empty() || content.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(Error::InvalidComment(MAX_COMMENT_LENGTH));
+    }
+    Ok(content.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_comment_length() {
+        let longest = "a".repeat(MAX_COMMENT_LENGTH);
+        assert_eq!(validate_comment(&longest).unwrap(), longest);
+        assert!(matches!(
+            validate_comment(&format!("{}a", longest)),
+            Err(Error::InvalidComment(MAX_COMMENT_LENGTH))
+        ));
+        // Length is counted in characters, not bytes.
+        assert!(validate_comment(&"é".repeat(MAX_COMMENT_LENGTH)).is_ok());
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use handle_errors::Error;
+
+/// The `ETag` of a question or an answer at `version`.
+/// # Example usage
+/// ```rust
+/// use rust_hour::types::etag;
+/// assert_eq!(etag::etag(3), "\"3\"");
+/// ```
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+/// The `ETag` of a list reply, derived from its JSON `body`. The same body
+/// always gets the same tag, so an unchanged list can be answered with 304.
+/// # Example usage
+/// ```rust
+/// use rust_hour::types::etag;
+/// assert_eq!(etag::body_etag(b"[]"), etag::body_etag(b"[]"));
+/// assert_ne!(etag::body_etag(b"[]"), etag::body_etag(b"[1]"));
+/// ```
+pub fn body_etag(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Whether the `If-None-Match` header of a read names `etag`, that is whether
+/// the client's copy is still current
+/// # Example header
+/// One or more tags the client holds, `If-None-Match: "1f2e", W/"3d4c"`, or
+/// `If-None-Match: *`. Weak tags match their strong counterpart.
+/// # Example usage
+/// ```rust
+/// use rust_hour::types::etag;
+/// assert!(etag::if_none_match(Some("W/\"3\", \"4\""), "\"4\""));
+/// assert!(!etag::if_none_match(None, "\"4\""));
+/// ```
+pub fn if_none_match(header: Option<&str>, etag: &str) -> bool {
+    header.is_some_and(|header| {
+        header
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    })
+}
+
+/// Extract the version the `If-Match` header of an update asks for
+/// # Example header
+/// The `ETag` of the post as it was read, `If-Match: "3"`. Without the header,
+/// or with `If-Match: *`, any version is updated.
+/// # Example usage
+/// ```rust
+/// use rust_hour::types::etag;
+/// assert_eq!(etag::extract_if_match(Some("\"3\"".to_string())).unwrap(), Some(3));
+/// assert_eq!(etag::extract_if_match(None).unwrap(), None);
+/// ```
+pub fn extract_if_match(header: Option<String>) -> Result<Option<i32>, Error> {
+    let header = match header {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let tag = header.trim();
+    if tag == "*" {
+        return Ok(None);
+    }
+    // Only tags handed out by `etag` can ever match; weak comparison does
+    // not apply to `If-Match`.
+    tag.strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .and_then(|version| version.parse::<i32>().ok())
+        .map(Some)
+        .ok_or(Error::PreconditionFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_if_match() {
+        assert_eq!(extract_if_match(Some("*".to_string())).unwrap(), None);
+        assert_eq!(extract_if_match(Some(" \"12\" ".to_string())).unwrap(), Some(12));
+        for header in ["W/\"3\"", "3", "\"three\"", "\"3\", \"4\""] {
+            assert!(matches!(
+                extract_if_match(Some(header.to_string())),
+                Err(Error::PreconditionFailed)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = body_etag(b"[]");
+        assert!(if_none_match(Some(&etag), &etag));
+        assert!(if_none_match(Some("*"), &etag));
+        assert!(if_none_match(Some(&format!("\"other\",{}", etag)), &etag));
+        assert!(!if_none_match(Some("\"other\""), &etag));
+        assert!(!if_none_match(Some(""), &etag));
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use handle_errors::Error;
+
+use crate::types::account::AccountId;
+use crate::types::tag::normalize_tag;
+
+/// Order of the questions listed by `/questions`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum QuestionSort {
+    /// Most recently asked first.
+    Newest,
+    /// Asked first comes first.
+    #[default]
+    Oldest,
+    /// Most live answers first, newest first among equals.
+    MostAnswers,
+    /// Most recently asked or answered first.
+    RecentActivity,
+}
+
+impl QuestionSort {
+    /// Whether the order is the `(created_on, id)` order cursors point into.
+    pub fn is_keyset(&self) -> bool {
+        matches!(self, QuestionSort::Newest | QuestionSort::Oldest)
+    }
+}
+
+impl FromStr for QuestionSort {
+    type Err = Error;
+
+    fn from_str(sort: &str) -> Result<Self, Self::Err> {
+        match sort {
+            "newest" => Ok(QuestionSort::Newest),
+            "oldest" => Ok(QuestionSort::Oldest),
+            "most_answers" => Ok(QuestionSort::MostAnswers),
+            "recent_activity" => Ok(QuestionSort::RecentActivity),
+            _ => Err(Error::InvalidParameter("sort".to_string())),
+        }
+    }
+}
+
+/// How the `tag` parameters of a filter combine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TagMatch {
+    /// A question needs one of the tags.
+    #[default]
+    Any,
+    /// A question needs every tag.
+    All,
+}
+
+impl FromStr for TagMatch {
+    type Err = Error;
+
+    fn from_str(tag_match: &str) -> Result<Self, Self::Err> {
+        match tag_match {
+            "any" => Ok(TagMatch::Any),
+            "all" => Ok(TagMatch::All),
+            _ => Err(Error::InvalidParameter("tag_match".to_string())),
+        }
+    }
+}
+
+/// Order of the answers listed by `/questions/{id}/answers`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AnswerSort {
+    /// Given first comes first.
+    #[default]
+    Oldest,
+    /// Highest score first, oldest first among equals.
+    Score,
+}
+
+impl FromStr for AnswerSort {
+    type Err = Error;
+
+    fn from_str(sort: &str) -> Result<Self, Self::Err> {
+        match sort {
+            "oldest" => Ok(AnswerSort::Oldest),
+            "score" => Ok(AnswerSort::Score),
+            _ => Err(Error::InvalidParameter("sort".to_string())),
+        }
+    }
+}
+
+/// Filter and order of the questions listed by `/questions`. The default
+/// lists every live question, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct QuestionFilter {
+    /// Normalized tags; synonyms are resolved by the store.
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    /// Only questions asked by this account.
+    pub author: Option<AccountId>,
+    /// Only questions asked at or after this time.
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only questions asked before this time.
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only questions without live answers.
+    pub unanswered: bool,
+    pub sort: QuestionSort,
+}
+
+/// Extract the filter from the query of the `/questions` route
+/// # Example query
+/// `tag` may be repeated and matches questions with any of the tags unless
+/// `tag_match=all` is given. Times are RFC 3339 or plain dates (midnight UTC).
+/// `/questions?tag=rust&tag=async&tag_match=all&author=3&unanswered=true&sort=newest`
+/// The parameters which are no filter, like the pagination, are returned as
+/// a map, the last value of a repeated parameter winning.
+/// # Example usage
+/// ```rust
+/// use rust_hour::types::filter::{self, QuestionSort};
+/// let query = vec![
+///     ("tag".to_string(), "Rust".to_string()),
+///     ("tag".to_string(), "async".to_string()),
+///     ("sort".to_string(), "newest".to_string()),
+///     ("limit".to_string(), "10".to_string()),
+/// ];
+/// let (f, rest) = filter::extract_question_filter(query).unwrap();
+/// assert_eq!(f.tags, vec!["rust", "async"]);
+/// assert_eq!(f.sort, QuestionSort::Newest);
+/// assert_eq!(rest.get("limit").unwrap(), "10");
+/// ```
+pub fn extract_question_filter(
+    params: Vec<(String, String)>,
+) -> Result<(QuestionFilter, HashMap<String, String>), Error> {
+    let mut filter = QuestionFilter::default();
+    let mut rest = HashMap::new();
+
+    for (key, value) in params {
+        let invalid = || Error::InvalidParameter(key.clone());
+        match key.as_str() {
+            "tag" => {
+                let tag = normalize_tag(&value)?;
+                if !filter.tags.contains(&tag) {
+                    filter.tags.push(tag);
+                }
+            }
+            "tag_match" => filter.tag_match = value.parse()?,
+            "author" => filter.author = Some(AccountId(value.parse().map_err(|_| invalid())?)),
+            "created_after" => filter.created_after = Some(parse_time(&value).ok_or_else(invalid)?),
+            "created_before" => filter.created_before = Some(parse_time(&value).ok_or_else(invalid)?),
+            "unanswered" => filter.unanswered = value.parse().map_err(|_| invalid())?,
+            "sort" => filter.sort = value.parse()?,
+            _ => {
+                rest.insert(key, value);
+            }
+        }
+    }
+
+    Ok((filter, rest))
+}
+
+/// Extract the `sort` parameter of the `/questions/{id}/answers` route and
+/// remove it from the query, which leaves the pagination
+/// # Example query
+/// `/questions/1/answers?sort=score&limit=10&offset=0`
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::filter::{self, AnswerSort};
+/// let mut query = HashMap::new();
+/// assert_eq!(filter::extract_answer_sort(&mut query).unwrap(), AnswerSort::Oldest);
+/// query.insert("sort".to_string(), "score".to_string());
+/// assert_eq!(filter::extract_answer_sort(&mut query).unwrap(), AnswerSort::Score);
+/// assert!(query.is_empty());
+/// ```
+pub fn extract_answer_sort(params: &mut HashMap<String, String>) -> Result<AnswerSort, Error> {
+    match params.remove("sort") {
+        Some(sort) => sort.parse(),
+        None => Ok(AnswerSort::default()),
+    }
+}
+
+pub(crate) fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(time, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_question_filter() {
+        let (filter, rest) = extract_question_filter(query(&[
+            ("tag", "Rust"),
+            ("tag", "rust"),
+            ("tag_match", "all"),
+            ("author", "3"),
+            ("created_after", "2024-01-01"),
+            ("created_before", "2024-02-01T12:00:00+01:00"),
+            ("unanswered", "true"),
+            ("sort", "most_answers"),
+            ("offset", "0"),
+        ]))
+        .unwrap();
+
+        assert_eq!(filter.tags, vec!["rust"]);
+        assert_eq!(filter.tag_match, TagMatch::All);
+        assert_eq!(filter.author, Some(AccountId(3)));
+        assert_eq!(filter.created_after.unwrap().to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(filter.created_before.unwrap().to_rfc3339(), "2024-02-01T11:00:00+00:00");
+        assert!(filter.unanswered);
+        assert_eq!(filter.sort, QuestionSort::MostAnswers);
+        assert_eq!(rest.len(), 1);
+    }
+
+    #[test]
+    fn test_extract_question_filter_defaults() {
+        let (filter, rest) = extract_question_filter(Vec::new()).unwrap();
+        assert_eq!(filter, QuestionFilter::default());
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_extract_question_filter_invalid() {
+        for (key, value) in [
+            ("sort", "popular"),
+            ("tag_match", "none"),
+            ("author", "me"),
+            ("created_after", "yesterday"),
+            ("unanswered", "yes"),
+        ] {
+            let result = extract_question_filter(query(&[(key, value)]));
+            assert!(
+                matches!(result, Err(Error::InvalidParameter(ref name)) if name == key),
+                "{}={}",
+                key,
+                value
+            );
+        }
+        assert!(matches!(
+            extract_question_filter(query(&[("tag", "a/b")])),
+            Err(Error::InvalidTag(_))
+        ));
+    }
+}
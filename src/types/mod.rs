@@ -1,4 +1,13 @@
 pub mod account;
+pub mod audit;
 pub mod answer;
+pub mod bulk;
+pub mod comment;
+pub mod etag;
+pub mod filter;
 pub mod pagination;
 pub mod question;
+pub mod revision;
+pub mod search;
+pub mod tag;
+pub mod vote;
@@ -1,7 +1,13 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Serialize, Serializer};
+
 use handle_errors::Error;
 
+/// Page size of a cursor paginated list when no `limit` is given
+pub const DEFAULT_PAGE_SIZE: i32 = 20;
+
 /// Pagination struct which is getting extract
 /// from query params
 #[derive(Default, Debug, PartialEq)]
@@ -52,9 +58,158 @@ pub fn extract_pagination(params: HashMap<String, String>) -> Result<Pagination,
     Err(Error::MissingParameters)
 }
 
+/// Position in a list ordered by `(created_on, id)`, pointing at the last
+/// item of a page. The next page starts right after it, so rows added in the
+/// meantime neither shift nor repeat the items a client has already seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cursor {
+    pub created_on: DateTime<Utc>,
+    pub id: i32,
+}
+
+impl Cursor {
+    /// Encodes the cursor as the opaque string handed out to clients.
+    pub fn encode(&self) -> String {
+        format!(
+            "{:016x}{:08x}",
+            self.created_on.timestamp_micros(),
+            self.id as u32
+        )
+    }
+
+    /// Decodes a string produced by `Cursor::encode`.
+    pub fn decode(cursor: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidParameter("cursor".to_string());
+        if cursor.len() != 24 || !cursor.is_ascii() {
+            return Err(invalid());
+        }
+        let micros = u64::from_str_radix(&cursor[..16], 16).map_err(|_| invalid())? as i64;
+        let id = u32::from_str_radix(&cursor[16..], 16).map_err(|_| invalid())? as i32;
+        let created_on = Utc.timestamp_micros(micros).single().ok_or_else(invalid)?;
+        Ok(Cursor { created_on, id })
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+/// Cursor pagination which is getting extract from query params
+#[derive(Debug, PartialEq)]
+pub struct KeysetPagination {
+    /// The last item of the previous page, `None` for the first page
+    pub cursor: Option<Cursor>,
+    /// The number of items which have to be returned
+    pub limit: i32,
+}
+
+/// One page of a cursor paginated list
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page, `None` on the last page
+    pub next_cursor: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from rows fetched with `limit + 1`: the extra row only
+    /// tells that there is a next page and is dropped.
+    pub fn from_rows(mut rows: Vec<(T, Cursor)>, limit: i32) -> Self {
+        let limit = limit.max(0) as usize;
+        let has_next = rows.len() > limit;
+        rows.truncate(limit);
+        let next_cursor = if has_next {
+            rows.last().map(|(_, cursor)| *cursor)
+        } else {
+            None
+        };
+        Page {
+            items: rows.into_iter().map(|(item, _)| item).collect(),
+            next_cursor,
+        }
+    }
+
+    /// Builds a first page with `pinned` on top from rows fetched with
+    /// `limit`: the pinned item takes the place of one row, so the page
+    /// still holds at most `limit` items.
+    pub fn pinned(pinned: T, mut rows: Vec<(T, Cursor)>, limit: i32) -> Self {
+        if limit <= 0 {
+            return Page::from_rows(Vec::new(), limit);
+        }
+        let limit = limit as usize;
+        let next_cursor = if rows.len() < limit {
+            None
+        } else if limit == 1 {
+            // Only the pinned item fits: the next page starts right before
+            // the first row.
+            Some(Cursor {
+                created_on: rows[0].1.created_on,
+                id: rows[0].1.id - 1,
+            })
+        } else {
+            Some(rows[limit - 2].1)
+        };
+        rows.truncate(limit - 1);
+        Page {
+            items: std::iter::once(pinned)
+                .chain(rows.into_iter().map(|(item, _)| item))
+                .collect(),
+            next_cursor,
+        }
+    }
+}
+
+/// Extract cursor pagination from the query of a list route
+/// # Example query
+/// A `cursor`, or a `limit` without an `offset`, asks for cursor pagination;
+/// the first page is requested without a cursor and every page returns the
+/// cursor of the next one
+/// `/questions?limit=20&cursor=...`
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::pagination;
+/// let mut query = HashMap::new();
+/// query.insert("limit".to_string(), "20".to_string());
+/// let p = pagination::extract_keyset_pagination(&query).unwrap().unwrap();
+/// assert_eq!(p.limit, 20);
+/// assert_eq!(p.cursor, None);
+/// query.insert("offset".to_string(), "40".to_string());
+/// assert!(pagination::extract_keyset_pagination(&query).unwrap().is_none());
+/// ```
+pub fn extract_keyset_pagination(
+    params: &HashMap<String, String>,
+) -> Result<Option<KeysetPagination>, Error> {
+    let cursor = params.get("cursor");
+    if cursor.is_none() && (params.contains_key("offset") || !params.contains_key("limit")) {
+        return Ok(None);
+    }
+    let limit = match params.get("limit") {
+        Some(limit) => limit.parse().map_err(Error::ParseError)?,
+        None => DEFAULT_PAGE_SIZE,
+    };
+    let cursor = match cursor {
+        Some(cursor) if !cursor.is_empty() => Some(Cursor::decode(cursor)?),
+        _ => None,
+    };
+    Ok(Some(KeysetPagination { cursor, limit }))
+}
+
 #[cfg(test)]
 mod pagination_tests {
-    use super::{extract_pagination, Error, HashMap, Pagination};
+    use super::{
+        extract_keyset_pagination, extract_pagination, Cursor, Error, HashMap, Page, Pagination,
+    };
+    use chrono::{TimeZone, Utc};
+
+    fn cursor(id: i32) -> Cursor {
+        Cursor {
+            created_on: Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap(),
+            id,
+        }
+    }
 
     #[test]
     fn valid_pagination() {
@@ -114,4 +269,60 @@ mod pagination_tests {
 
         assert_eq!(pagination_result, expected);
     }
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = Cursor {
+            created_on: Utc.timestamp_micros(1_792_238_400_123_456).unwrap(),
+            id: 42,
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn invalid_cursor() {
+        let mut params = HashMap::new();
+        params.insert(String::from("cursor"), String::from("NOT_A_CURSOR"));
+        let pagination_result = format!("{}", extract_keyset_pagination(&params).unwrap_err());
+
+        assert_eq!(pagination_result, "Invalid parameter: cursor");
+    }
+
+    #[test]
+    fn cursor_without_limit() {
+        let mut params = HashMap::new();
+        params.insert(String::from("cursor"), cursor(7).encode());
+        let pagination = extract_keyset_pagination(&params).unwrap().unwrap();
+
+        assert_eq!(pagination.cursor, Some(cursor(7)));
+        assert_eq!(pagination.limit, super::DEFAULT_PAGE_SIZE);
+    }
+
+    #[test]
+    fn page_from_rows() {
+        let rows = vec![("a", cursor(1)), ("b", cursor(2)), ("c", cursor(3))];
+        let page = Page::from_rows(rows.clone(), 2);
+        assert_eq!(page.items, vec!["a", "b"]);
+        assert_eq!(page.next_cursor, Some(cursor(2)));
+
+        let last = Page::from_rows(rows, 3);
+        assert_eq!(last.items, vec!["a", "b", "c"]);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn page_pinned() {
+        let rows = vec![("a", cursor(1)), ("b", cursor(2))];
+        let page = Page::pinned("p", rows.clone(), 2);
+        assert_eq!(page.items, vec!["p", "a"]);
+        assert_eq!(page.next_cursor, Some(cursor(1)));
+
+        let page = Page::pinned("p", rows.clone(), 1);
+        assert_eq!(page.items, vec!["p"]);
+        assert_eq!(page.next_cursor, Some(cursor(0)));
+
+        let last = Page::pinned("p", rows, 3);
+        assert_eq!(last.items, vec!["p", "a", "b"]);
+        assert_eq!(last.next_cursor, None);
+    }
 }
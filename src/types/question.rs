@@ -1,6 +1,16 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
 
+use handle_errors::Error;
+
+use crate::types::account::Author;
+use crate::types::answer::{Answer, AnswerId};
+use crate::types::comment::Comment;
+use crate::types::pagination::Page;
+use crate::types::revision::QuestionRevision;
+
 /// Represents a question in the system.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Question {
@@ -12,6 +22,25 @@ pub struct Question {
     pub content: String,
     /// Optional tags associated with the question.
     pub tags: Option<Vec<String>>,
+    /// Sum of the votes on the question. Ignored when a question is updated.
+    #[serde(default)]
+    pub score: i32,
+    /// The answer the owner accepted. Ignored when a question is updated.
+    #[serde(default)]
+    pub accepted_answer_id: Option<AnswerId>,
+    /// The account which asked the question. Ignored when a question is updated.
+    #[serde(default)]
+    pub author: Author,
+    /// When the question was asked. Ignored when a question is updated.
+    #[serde(default)]
+    pub created_on: DateTime<Utc>,
+    /// When the question was last edited, if ever. Ignored when a question is updated.
+    #[serde(default)]
+    pub updated_on: Option<DateTime<Utc>>,
+    /// Starts at 1 and goes up with every edit; sent as the `ETag`. Ignored
+    /// when a question is updated.
+    #[serde(default)]
+    pub version: i32,
 }
 /// Represents a unique identifier for a question.
 #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -36,10 +65,98 @@ pub struct NewQuestion {
     pub tags: Option<Vec<String>>,
 }
 
+/// A question with its metadata and the first page of its answers, as
+/// returned by `GET /questions/{id}`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct QuestionDetail {
+    #[serde(flatten)]
+    pub question: Question,
+    /// Number of live answers.
+    pub answer_count: i64,
+    /// First page of the answers, the accepted answer on top.
+    pub answers: Page<Answer>,
+    /// Only with `include=comments`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<Vec<Comment>>,
+    /// Only with `include=revisions`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revisions: Option<Vec<QuestionRevision>>,
+}
+
+/// What `GET /questions/{id}` embeds besides the answers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuestionInclude {
+    pub comments: bool,
+    pub revisions: bool,
+}
+
+/// Extract the `include` query parameter of the `GET /questions/{id}` route
+/// # Example query
+/// A comma separated list of `comments` and `revisions`
+/// `/questions/1?include=comments,revisions`
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::question;
+/// let mut query = HashMap::new();
+/// query.insert("include".to_string(), "comments".to_string());
+/// let include = question::extract_include(&query).unwrap();
+/// assert!(include.comments);
+/// assert!(!include.revisions);
+/// ```
+pub fn extract_include(params: &HashMap<String, String>) -> Result<QuestionInclude, Error> {
+    let mut include = QuestionInclude::default();
+    for item in params.get("include").into_iter().flat_map(|value| value.split(',')) {
+        match item.trim() {
+            "comments" => include.comments = true,
+            "revisions" => include.revisions = true,
+            "" => {}
+            _ => return Err(Error::InvalidParameter("include".to_string())),
+        }
+    }
+    Ok(include)
+}
+
+/// Extract the `cascade` query parameter of the `DELETE /questions/{id}` route
+/// # Example query
+/// Deleting a question that still has answers is refused unless the answers
+/// should be deleted along with it
+/// `/questions/1?cascade=true`
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::question;
+/// let mut query = HashMap::new();
+/// assert!(!question::extract_cascade(&query).unwrap());
+/// query.insert("cascade".to_string(), "true".to_string());
+/// assert!(question::extract_cascade(&query).unwrap());
+/// ```
+pub fn extract_cascade(params: &HashMap<String, String>) -> Result<bool, Error> {
+    match params.get("cascade") {
+        None => Ok(false),
+        Some(value) => value
+            .parse()
+            .map_err(|_| Error::InvalidParameter("cascade".to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extract_include() {
+        let mut params = HashMap::new();
+        assert_eq!(extract_include(&params).unwrap(), QuestionInclude::default());
+        params.insert("include".to_string(), "revisions, comments".to_string());
+        assert_eq!(
+            extract_include(&params).unwrap(),
+            QuestionInclude { comments: true, revisions: true }
+        );
+        params.insert("include".to_string(), "answers".to_string());
+        assert!(matches!(extract_include(&params), Err(Error::InvalidParameter(_))));
+    }
+
     #[test]
     fn test_question_id_from_str_valid() {
         let id_str = "42";
@@ -101,6 +218,12 @@ mod tests {
             title: "Test Question".to_string(),
             content: "Test Content".to_string(),
             tags: Some(vec!["test".to_string()]),
+            score: 0,
+            accepted_answer_id: None,
+            author: Author::default(),
+            created_on: Utc::now(),
+            updated_on: None,
+            version: 1,
         };
 
         assert_eq!(question.id, QuestionId(1));
@@ -121,4 +244,22 @@ mod tests {
         assert_eq!(new_question.content, "Test Content");
         assert_eq!(new_question.tags, Some(vec!["test".to_string()]));
     }
+
+    #[test]
+    fn test_extract_cascade() {
+        let mut params = HashMap::new();
+        assert!(!extract_cascade(&params).unwrap());
+
+        params.insert("cascade".to_string(), "true".to_string());
+        assert!(extract_cascade(&params).unwrap());
+
+        params.insert("cascade".to_string(), "false".to_string());
+        assert!(!extract_cascade(&params).unwrap());
+
+        params.insert("cascade".to_string(), "yes".to_string());
+        assert!(matches!(
+            extract_cascade(&params),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
 }
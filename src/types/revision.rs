@@ -0,0 +1,237 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use handle_errors::Error;
+
+use crate::types::account::{AccountId, Author};
+use crate::types::answer::AnswerId;
+use crate::types::question::{Question, QuestionId};
+
+/// Represents a unique identifier for a revision.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RevisionId(pub i32);
+
+/// The state of a question before one of its edits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuestionRevision {
+    /// Unique identifier for the revision.
+    pub id: RevisionId,
+    /// ID of the edited question.
+    pub question_id: QuestionId,
+    /// Title before the edit.
+    pub title: String,
+    /// Content before the edit.
+    pub content: String,
+    /// Tags before the edit.
+    pub tags: Option<Vec<String>>,
+    /// Account that made the edit.
+    pub editor: AccountId,
+    /// When the edit was made.
+    pub created_on: DateTime<Utc>,
+}
+
+impl QuestionRevision {
+    /// The question as it was in this revision. Votes, the accepted answer,
+    /// the author and the timestamps are not part of a revision, so the
+    /// score is 0, no answer is accepted and the rest is left at defaults.
+    pub fn to_question(&self) -> Question {
+        Question {
+            id: self.question_id,
+            title: self.title.clone(),
+            content: self.content.clone(),
+            tags: self.tags.clone(),
+            score: 0,
+            accepted_answer_id: None,
+            author: Author::default(),
+            created_on: DateTime::default(),
+            updated_on: None,
+            version: 0,
+        }
+    }
+}
+
+/// The state of an answer before one of its edits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnswerRevision {
+    /// Unique identifier for the revision.
+    pub id: RevisionId,
+    /// ID of the edited answer.
+    pub answer_id: AnswerId,
+    /// Content before the edit.
+    pub content: String,
+    /// Account that made the edit.
+    pub editor: AccountId,
+    /// When the edit was made.
+    pub created_on: DateTime<Utc>,
+}
+
+/// One line of a line-level diff.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", content = "line", rename_all = "lowercase")]
+pub enum DiffLine {
+    /// The line is in both versions.
+    Same(String),
+    /// The line is only in the newer version.
+    Added(String),
+    /// The line is only in the older version.
+    Removed(String),
+}
+
+/// Computes a line-level diff from `old` to `new`, keeping as many lines
+/// unchanged as possible.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of
+    // old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line.to_string())));
+    diff.extend(new[j..].iter().map(|line| DiffLine::Added(line.to_string())));
+    diff
+}
+
+/// The changes between two versions of a question.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuestionDiff {
+    /// The older revision.
+    pub from: RevisionId,
+    /// The newer revision, `None` for the current version.
+    pub to: Option<RevisionId>,
+    pub title: Vec<DiffLine>,
+    pub content: Vec<DiffLine>,
+    /// One tag per line.
+    pub tags: Vec<DiffLine>,
+}
+
+impl QuestionDiff {
+    pub fn new(from: RevisionId, to: Option<RevisionId>, old: &Question, new: &Question) -> Self {
+        let tags = |question: &Question| question.tags.clone().unwrap_or_default().join("\n");
+        QuestionDiff {
+            from,
+            to,
+            title: diff_lines(&old.title, &new.title),
+            content: diff_lines(&old.content, &new.content),
+            tags: diff_lines(&tags(old), &tags(new)),
+        }
+    }
+}
+
+/// The changes between two versions of an answer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnswerDiff {
+    /// The older revision.
+    pub from: RevisionId,
+    /// The newer revision, `None` for the current version.
+    pub to: Option<RevisionId>,
+    pub content: Vec<DiffLine>,
+}
+
+impl AnswerDiff {
+    pub fn new(from: RevisionId, to: Option<RevisionId>, old: &str, new: &str) -> Self {
+        AnswerDiff {
+            from,
+            to,
+            content: diff_lines(old, new),
+        }
+    }
+}
+
+/// Extract the revisions to compare from the query of a `/revisions/diff` route
+/// # Example query
+/// `from` is required; without `to` the diff goes up to the current version
+/// `/questions/1/revisions/diff?from=2&to=5`
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::revision::{self, RevisionId};
+/// let mut query = HashMap::new();
+/// query.insert("from".to_string(), "2".to_string());
+/// assert_eq!(revision::extract_diff_range(&query).unwrap(), (RevisionId(2), None));
+/// query.insert("to".to_string(), "5".to_string());
+/// assert_eq!(
+///     revision::extract_diff_range(&query).unwrap(),
+///     (RevisionId(2), Some(RevisionId(5)))
+/// );
+/// ```
+pub fn extract_diff_range(
+    params: &HashMap<String, String>,
+) -> Result<(RevisionId, Option<RevisionId>), Error> {
+    let from = params
+        .get("from")
+        .ok_or(Error::MissingParameters)?
+        .parse()
+        .map_err(Error::ParseError)?;
+    let to = params
+        .get("to")
+        .map(|to| to.parse().map_err(Error::ParseError))
+        .transpose()?;
+    Ok((RevisionId(from), to.map(RevisionId)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nc\nd");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Same("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_empty() {
+        assert!(diff_lines("", "").is_empty());
+        assert_eq!(diff_lines("", "a"), vec![DiffLine::Added("a".to_string())]);
+        assert_eq!(diff_lines("a", ""), vec![DiffLine::Removed("a".to_string())]);
+    }
+
+    #[test]
+    fn test_diff_line_json() {
+        let json = serde_json::to_value(DiffLine::Added("x".to_string())).unwrap();
+        assert_eq!(json, serde_json::json!({ "op": "added", "line": "x" }));
+    }
+
+    #[test]
+    fn test_extract_diff_range_missing_from() {
+        let params = HashMap::new();
+        assert!(matches!(
+            extract_diff_range(&params),
+            Err(Error::MissingParameters)
+        ));
+    }
+}
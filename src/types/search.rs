@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use handle_errors::Error;
+
+use crate::types::answer::AnswerId;
+use crate::types::question::QuestionId;
+
+/// Number of words around the first match kept in a snippet.
+const SNIPPET_WORDS: usize = 20;
+
+/// A question or answer matching a search query.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// The matching question, or the question of the matching answer.
+    pub question_id: QuestionId,
+    /// The matching answer, `None` when the question itself matched.
+    pub answer_id: Option<AnswerId>,
+    /// Title of the question.
+    pub title: String,
+    /// Excerpt of the matching text with the matches wrapped in `<b>` tags.
+    pub snippet: String,
+    /// Relevance of the match, higher is better.
+    pub rank: f32,
+}
+
+/// Extract the search query from the query of the `/search` route
+/// # Example query
+/// `q` is required and may be paginated like `/questions`
+/// `/search?q=rust+lifetimes&limit=10&offset=0`
+/// # Example usage
+/// ```rust
+/// use std::collections::HashMap;
+/// use rust_hour::types::search;
+/// let mut query = HashMap::new();
+/// query.insert("q".to_string(), "rust lifetimes".to_string());
+/// assert_eq!(search::extract_query(&query).unwrap(), "rust lifetimes");
+/// ```
+pub fn extract_query(params: &HashMap<String, String>) -> Result<String, Error> {
+    match params.get("q").map(|q| q.trim()) {
+        Some(q) if !q.is_empty() => Ok(q.to_string()),
+        _ => Err(Error::MissingParameters),
+    }
+}
+
+/// Splits a query into the lowercase words a text has to contain.
+pub fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Matches `text` against `terms` without a search index, for stores that
+/// have none. Every term has to occur in the text. Returns the rank, the
+/// share of matching words, and a snippet around the first match.
+pub fn naive_match(terms: &[String], text: &str) -> Option<(f32, String)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let matches = |word: &str| {
+        let word = word.to_lowercase();
+        terms.iter().any(|term| word.contains(term.as_str()))
+    };
+    let text = text.to_lowercase();
+    if terms.is_empty() || !terms.iter().all(|term| text.contains(term.as_str())) {
+        return None;
+    }
+
+    let hits: Vec<bool> = words.iter().map(|word| matches(word)).collect();
+    let first = hits.iter().position(|hit| *hit).unwrap_or(0);
+    let start = first.saturating_sub(SNIPPET_WORDS / 4);
+    let end = (start + SNIPPET_WORDS).min(words.len());
+    let snippet = words[start..end]
+        .iter()
+        .zip(&hits[start..end])
+        .map(|(word, hit)| if *hit { format!("<b>{}</b>", word) } else { word.to_string() })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let rank = hits.iter().filter(|hit| **hit).count() as f32 / words.len().max(1) as f32;
+    Some((rank, snippet))
+}
+
+/// Orders results by rank, best first; ties keep the question before its answers.
+pub fn sort_results(results: &mut [SearchResult]) {
+    results.sort_by(|a, b| {
+        b.rank
+            .total_cmp(&a.rank)
+            .then(a.question_id.0.cmp(&b.question_id.0))
+            .then(a.answer_id.as_ref().map(|id| id.0).cmp(&b.answer_id.as_ref().map(|id| id.0)))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_query_missing() {
+        let mut params = HashMap::new();
+        assert!(matches!(extract_query(&params), Err(Error::MissingParameters)));
+        params.insert("q".to_string(), "  ".to_string());
+        assert!(matches!(extract_query(&params), Err(Error::MissingParameters)));
+    }
+
+    #[test]
+    fn test_naive_match() {
+        let terms = query_terms("Borrow checker!");
+        let (rank, snippet) = naive_match(&terms, "Fighting the borrow checker again").unwrap();
+        assert_eq!(snippet, "Fighting the <b>borrow</b> <b>checker</b> again");
+        assert_eq!(rank, 0.4);
+    }
+
+    #[test]
+    fn test_naive_match_requires_every_term() {
+        let terms = query_terms("borrow lifetime");
+        assert_eq!(naive_match(&terms, "Fighting the borrow checker"), None);
+        assert_eq!(naive_match(&[], "Fighting the borrow checker"), None);
+    }
+}
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+use handle_errors::Error;
+
+/// Maximum number of tags on a question.
+pub const MAX_TAGS: usize = 5;
+/// Maximum length of a tag slug.
+pub const MAX_TAG_LENGTH: usize = 35;
+
+/// A canonical tag with its usage.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tag {
+    /// Canonical lowercase slug of the tag.
+    pub slug: String,
+    /// Number of questions with the tag.
+    pub questions: i64,
+    /// Slugs that are replaced by this tag when a question is saved.
+    pub synonyms: Vec<String>,
+}
+
+/// Used for adding a synonym to a tag.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NewSynonym {
+    /// The slug to map onto the tag.
+    pub synonym: String,
+}
+
+/// Turns a tag as typed by a user into its slug: trimmed, lowercase and with
+/// inner whitespace replaced by `-`. A slug is made of letters, digits and
+/// `+`, `#`, `.` and `-`, and is at most `MAX_TAG_LENGTH` characters long.
+/// # Example usage
+/// ```rust
+/// use rust_hour::types::tag;
+/// assert_eq!(tag::normalize_tag(" Rust ").unwrap(), "rust");
+/// assert_eq!(tag::normalize_tag("Async Await").unwrap(), "async-await");
+/// assert!(tag::normalize_tag("rust!").is_err());
+/// ```
+pub fn normalize_tag(tag: &str) -> Result<String, Error> {
+    let slug = tag
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    let allowed = |c: char| c.is_alphanumeric() || matches!(c, '+' | '#' | '.' | '-');
+    if slug.is_empty() || slug.chars().count() > MAX_TAG_LENGTH || !slug.chars().all(allowed) {
+        return Err(Error::InvalidTag(tag.to_string()));
+    }
+    Ok(slug)
+}
+
+/// Normalizes the tags of a question and drops duplicates, keeping the
+/// first occurrence. Fails if a tag is invalid or there are more than
+/// `MAX_TAGS` distinct tags.
+pub fn normalize_tags(tags: Option<Vec<String>>) -> Result<Option<Vec<String>>, Error> {
+    tags.map(|tags| {
+        let slugs = tags
+            .iter()
+            .map(|tag| normalize_tag(tag))
+            .collect::<Result<Vec<_>, _>>()?;
+        let slugs = dedup_tags(slugs);
+        if slugs.len() > MAX_TAGS {
+            return Err(Error::TooManyTags(MAX_TAGS));
+        }
+        Ok(slugs)
+    })
+    .transpose()
+}
+
+/// Drops repeated slugs, keeping the first occurrence.
+pub fn dedup_tags(slugs: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(slugs.len());
+    for slug in slugs {
+        if !unique.contains(&slug) {
+            unique.push(slug);
+        }
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Option<Vec<String>> {
+        Some(tags.iter().map(|tag| tag.to_string()).collect())
+    }
+
+    #[test]
+    fn test_normalize_tags_dedups_variants() {
+        assert_eq!(
+            normalize_tags(tags(&["Rust", "rust", " rust", "C#"])).unwrap(),
+            tags(&["rust", "c#"])
+        );
+        assert_eq!(normalize_tags(None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_normalize_tag_invalid() {
+        assert!(matches!(normalize_tag("  "), Err(Error::InvalidTag(_))));
+        assert!(matches!(normalize_tag("a/b"), Err(Error::InvalidTag(_))));
+        assert!(normalize_tag(&"a".repeat(MAX_TAG_LENGTH)).is_ok());
+        assert!(normalize_tag(&"a".repeat(MAX_TAG_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_tags_too_many() {
+        let result = normalize_tags(tags(&["a", "b", "c", "d", "e", "f"]));
+        assert!(matches!(result, Err(Error::TooManyTags(MAX_TAGS))));
+        // Duplicates do not count against the limit.
+        assert!(normalize_tags(tags(&["a", "b", "c", "d", "e", "E"])).is_ok());
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// An up or down vote on a question or answer.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Vote {
+    Up,
+    Down,
+}
+
+impl Vote {
+    /// What the vote adds to the score of a post.
+    pub fn value(&self) -> i32 {
+        match self {
+            Vote::Up => 1,
+            Vote::Down => -1,
+        }
+    }
+}
+
+/// Used for casting or changing a vote.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NewVote {
+    /// `"up"` or `"down"`.
+    pub vote: Vote,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_vote_from_json() {
+        let new_vote: NewVote = serde_json::from_str(r#"{"vote": "down"}"#).unwrap();
+        assert_eq!(new_vote.vote, Vote::Down);
+        assert_eq!(new_vote.vote.value(), -1);
+        assert!(serde_json::from_str::<NewVote>(r#"{"vote": "sideways"}"#).is_err());
+    }
+}